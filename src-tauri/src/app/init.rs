@@ -2,15 +2,20 @@ use sea_orm::DbConn;
 use tauri::{AppHandle, Manager};
 use tracing::{error, info};
 
-use crate::core::chat::receipt::ReceiptHandler;
-use crate::core::chat::receiver::MessageReceiver;
+use crate::core::chat::PollService;
 use crate::core::contact::start_discovery;
+use crate::core::file::handler::DbFileSource;
+use crate::core::file::rate_limit::restore_transfer_limits;
+use crate::core::file::resume::resume_interrupted_transfers;
+use crate::core::file::storage::{collect_garbage, set_download_dir};
+use crate::core::group::GroupService;
 use crate::database::init_database;
 use crate::database::handler::UserHandler;
 use crate::database::model::user;
 use crate::event::bus::EVENT_RECEIVER;
 use crate::event::handlers::{handle_network_event, handle_ui_event};
 use crate::event::model::AppEvent;
+use crate::network::tcp::start_tcp_listener;
 use crate::network::udp::{init_udp_socket, start_udp_receiver};
 
 pub async fn init_app(app_handle: &AppHandle) -> Result<DbConn, Box<dyn std::error::Error>> {
@@ -28,6 +33,8 @@ pub async fn init_app(app_handle: &AppHandle) -> Result<DbConn, Box<dyn std::err
         return Err(format!("应用数据目录创建后仍不存在: {:?}", app_data_dir).into());
     }
 
+    set_download_dir(app_data_dir.join("downloads")).map_err(|e| format!("创建下载目录失败: {}", e))?;
+
     let db_path = app_data_dir.join("feiqiu.db");
     info!("数据库文件路径: {:?}", db_path);
 
@@ -121,26 +128,45 @@ async fn start_background_services(app_handle: AppHandle, db: DbConn) {
 
     let db_clone = db.clone();
     let app_handle_clone = app_handle.clone();
-    tokio::spawn(async move {
-        event_loop(app_handle_clone, db_clone).await;
+    tokio::task::spawn_blocking(move || {
+        event_loop(app_handle_clone, db_clone);
     });
 
-    let db_clone = std::sync::Arc::new(db.clone());
     tokio::spawn(async move {
-        MessageReceiver::new(db_clone).start();
+        if let Err(e) = start_udp_receiver().await {
+            error!("UDP 接收器启动失败: {}", e);
+        }
     });
 
-    let db_clone = std::sync::Arc::new(db.clone());
+    let file_source = std::sync::Arc::new(DbFileSource::new(db.clone()));
     tokio::spawn(async move {
-        ReceiptHandler::new(db_clone).start();
+        if let Err(e) = start_tcp_listener(file_source).await {
+            error!("TCP 文件通道启动失败: {}", e);
+        }
     });
 
+    // 等待网络服务就绪后继续上次中断的传输
+    let db_clone = db.clone();
     tokio::spawn(async move {
-        if let Err(e) = start_udp_receiver().await {
-            error!("UDP 接收器启动失败: {}", e);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        // 先恢复单个传输的限速，续传的数据块从一开始就受约束
+        if let Err(e) = restore_transfer_limits(&db_clone).await {
+            error!("恢复传输限速失败: {}", e);
+        }
+        if let Err(e) = resume_interrupted_transfers(&db_clone, None).await {
+            error!("恢复中断的传输失败: {}", e);
+        }
+        // 清理不再被引用的接收文件
+        if let Err(e) = collect_garbage(&db_clone).await {
+            error!("文件垃圾回收失败: {}", e);
         }
     });
 
+    // 定期处理过期的群邀请和入群申请
+    tokio::spawn(GroupService::run_request_expiry(db.clone()));
+    // 到达截止时间的投票自动结束并广播最终结果
+    tokio::spawn(PollService::run_deadline_close(db.clone()));
+
     tokio::spawn(async move {
         if let Err(e) = start_discovery().await {
             error!("用户发现服务启动失败: {}", e);
@@ -148,18 +174,25 @@ async fn start_background_services(app_handle: AppHandle, db: DbConn) {
     });
 }
 
-async fn event_loop(_app_handle: AppHandle, db: DbConn) {
+/// 事件分发循环
+///
+/// 事件总线上的事件只由这里取出并分发给各模块，其他模块不直接从总线接收。
+/// 总线接收是阻塞调用，在独立线程上运行，不占用运行时工作线程；
+/// 每个事件在单独的任务中处理，文件数据块的读写不会拖慢聊天事件；UI 事件转发给前端窗口
+fn event_loop(app_handle: AppHandle, db: DbConn) {
+    let runtime = tokio::runtime::Handle::current();
     loop {
         match EVENT_RECEIVER.recv() {
             Ok(event) => {
                 let db_clone = db.clone();
-                tokio::spawn(async move {
+                let app_handle = app_handle.clone();
+                runtime.spawn(async move {
                     match event {
                         AppEvent::Network(net_event) => {
                             handle_network_event(net_event, &db_clone).await;
                         }
                         AppEvent::Ui(ui_event) => {
-                            handle_ui_event(ui_event).await;
+                            handle_ui_event(&app_handle, ui_event).await;
                         }
                         _ => {}
                     }
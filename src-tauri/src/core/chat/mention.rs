@@ -0,0 +1,200 @@
+// src-tauri/src/core/chat/mention.rs
+//
+//! 群消息 @ 提及
+//!
+//! 提及以成员机器标识随群消息信封发送，收发双方都按消息保存到提及表，
+//! 会话据此记录提及本机用户的未读消息数。@所有人 只有群主和管理员可以使用，
+//! 收到其他成员发出的 @所有人 时忽略。
+
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::sync::ROLE_ADMIN;
+use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
+use crate::database::handler::{ChatMessageHandler, ChatMessageMentionHandler, UserHandler};
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::model::MENTION_ALL;
+use crate::types::MentionInfo;
+use sea_orm::DbConn;
+use std::collections::HashSet;
+use tracing::warn;
+
+/// 整理提及列表：去重，@所有人（None）排在最前
+pub fn normalize_mentions(mention_uids: &[i64], mention_all: bool) -> Vec<Option<i64>> {
+    let mut uids = mention_uids.to_vec();
+    uids.sort_unstable();
+    uids.dedup();
+
+    let all = mention_all.then_some(None);
+    all.into_iter().chain(uids.into_iter().map(Some)).collect()
+}
+
+/// 提及列表中是否包含指定用户（包括 @所有人）
+pub fn mentions_user(mentions: &[Option<i64>], uid: i64) -> bool {
+    mentions.iter().any(|m| m.is_none_or(|m| m == uid))
+}
+
+/// 提及服务
+pub struct MentionService;
+
+impl MentionService {
+    /// 检查要发送的提及
+    ///
+    /// # 参数
+    /// - `gid`: 群组ID
+    /// - `sender_uid`: 发送者用户ID（@所有人 需要群主或管理员权限）
+    /// - `mention_uids`: 被提及的成员
+    /// - `mention_all`: 是否 @所有人
+    ///
+    /// # 返回
+    /// 整理后的提及列表（@所有人 为 None）
+    pub async fn validate(
+        db: &DbConn,
+        gid: i64,
+        sender_uid: i64,
+        mention_uids: &[i64],
+        mention_all: bool,
+    ) -> AppResult<Vec<Option<i64>>> {
+        if mention_all && !Self::is_admin(db, gid, sender_uid).await? {
+            return Err(AppError::Permission("只有群主和管理员可以 @所有人".to_string()));
+        }
+
+        let mentions = normalize_mentions(mention_uids, mention_all);
+        for uid in mentions.iter().flatten() {
+            if GroupMemberHandler::find_by_group_and_member(db, gid, *uid).await?.is_none() {
+                return Err(AppError::Business(format!("用户 {} 不是群成员", uid)));
+            }
+        }
+
+        Ok(mentions)
+    }
+
+    /// 保存消息中的提及
+    pub async fn record(db: &DbConn, mid: i64, gid: i64, mentions: &[Option<i64>]) -> AppResult<()> {
+        for mentioned_uid in mentions {
+            ChatMessageMentionHandler::create(db, mid, gid, *mentioned_uid).await?;
+        }
+        Ok(())
+    }
+
+    /// 获取群消息信封中的提及（成员机器标识，@所有人 为 `MENTION_ALL`）
+    pub async fn envelope_mentions(db: &DbConn, mid: i64) -> AppResult<Vec<String>> {
+        let mut result = Vec::new();
+        for mention in ChatMessageMentionHandler::list_by_message(db, mid).await? {
+            match mention.mentioned_uid {
+                Some(uid) => result.push(UserHandler::find_by_id(db, uid).await?.feiq_machine_id),
+                None => result.push(MENTION_ALL.to_string()),
+            }
+        }
+        Ok(result)
+    }
+
+    /// 保存收到的群消息中的提及
+    ///
+    /// # 参数
+    /// - `mid`: 本地消息 ID
+    /// - `gid`: 本地群组 ID
+    /// - `sender_uid`: 发送者用户ID
+    /// - `mentions`: 信封中的提及
+    ///
+    /// # 返回
+    /// 保存的提及列表（@所有人 为 None）
+    pub async fn record_incoming(
+        db: &DbConn,
+        mid: i64,
+        gid: i64,
+        sender_uid: i64,
+        mentions: &[String],
+    ) -> AppResult<Vec<Option<i64>>> {
+        let mut mention_uids = Vec::new();
+        let mut mention_all = false;
+        for mention in mentions {
+            if mention == MENTION_ALL {
+                mention_all = true;
+            } else {
+                mention_uids.push(member_uid_for(db, mention).await?);
+            }
+        }
+
+        // 按本地的群状态检查 @所有人 权限
+        if mention_all && !Self::is_admin(db, gid, sender_uid).await? {
+            warn!("忽略非管理员的 @所有人: gid={}, sender_uid={}", gid, sender_uid);
+            mention_all = false;
+        }
+
+        let mentions = normalize_mentions(&mention_uids, mention_all);
+        Self::record(db, mid, gid, &mentions).await?;
+        Ok(mentions)
+    }
+
+    /// 获取提及指定用户的群消息（按时间倒序）
+    ///
+    /// 不包括用户自己发出的消息，@所有人 只包括用户仍在其中的群组
+    pub async fn list_mentions(db: &DbConn, uid: i64) -> AppResult<Vec<MentionInfo>> {
+        let mut result = Vec::new();
+        let mut seen = HashSet::new();
+
+        for mention in ChatMessageMentionHandler::list_for_user(db, uid).await? {
+            if !seen.insert(mention.mid) {
+                continue;
+            }
+            let message = match ChatMessageHandler::find_by_id(db, mention.mid).await {
+                Ok(message) if message.sender_uid != uid => message,
+                _ => continue,
+            };
+            if mention.mentioned_uid.is_none()
+                && GroupMemberHandler::find_by_group_and_member(db, mention.gid, uid)
+                    .await?
+                    .is_none()
+            {
+                continue;
+            }
+
+            let group_name = match GroupHandler::find_by_id(db, mention.gid).await {
+                Ok(group) => group.group_name,
+                _ => format!("Group{}", mention.gid),
+            };
+            let sender_nickname = match UserHandler::find_by_id(db, message.sender_uid).await {
+                Ok(user) => user.nickname,
+                _ => format!("User{}", message.sender_uid),
+            };
+
+            result.push(MentionInfo {
+                mid: message.mid,
+                gid: mention.gid,
+                group_name,
+                sender_uid: message.sender_uid,
+                sender_nickname,
+                content: message.content,
+                mention_all: mention.mentioned_uid.is_none(),
+                send_time: message.send_time,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// 成员是否为群主或管理员
+    async fn is_admin(db: &DbConn, gid: i64, uid: i64) -> AppResult<bool> {
+        let member = GroupMemberHandler::find_by_group_and_member(db, gid, uid).await?;
+        Ok(member.is_some_and(|m| m.role >= ROLE_ADMIN))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_mentions() {
+        assert_eq!(normalize_mentions(&[3, 2, 3], false), vec![Some(2), Some(3)]);
+        assert_eq!(normalize_mentions(&[2], true), vec![None, Some(2)]);
+        assert!(normalize_mentions(&[], false).is_empty());
+    }
+
+    #[test]
+    fn test_mentions_user() {
+        assert!(mentions_user(&[Some(2), Some(3)], 3));
+        assert!(!mentions_user(&[Some(2)], 3));
+        assert!(mentions_user(&[None], 3), "@all mentions everyone");
+        assert!(!mentions_user(&[], 3));
+    }
+}
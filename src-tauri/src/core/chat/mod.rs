@@ -13,11 +13,28 @@
 /// - sender: 消息发送处理器
 /// - receipt: 已读回执处理器
 /// - manager: 会话管理器
+/// - mention: 群消息 @ 提及
+/// - poll: 群投票
+/// - reaction: 消息表情回应
+/// - reply: 引用回复
+/// - revision: 消息撤回与编辑
 /// - service: 聊天业务逻辑服务层
+/// - typing: 正在输入提示
 pub mod manager;
+pub mod mention;
+pub mod poll;
+pub mod reaction;
 pub mod receipt;
 pub mod receiver;
+pub mod reply;
+pub mod revision;
 pub mod sender;
 pub mod service;
+pub mod typing;
 
+pub use mention::MentionService;
+pub use poll::PollService;
+pub use reaction::ReactionService;
+pub use revision::MessageRevisionService;
 pub use service::ChatService;
+pub use typing::TypingService;
@@ -0,0 +1,624 @@
+// src-tauri/src/core/chat/poll.rs
+//
+//! 群投票
+//!
+//! 投票以带 `FEIQ_FLAG_POLL` 标志的群消息发出，各成员按消息保存投票。
+//! 成员的投票发给投票创建者，由创建者汇总后把结果广播给群成员；
+//! 匿名投票的结果只有票数，不带投票人。截止时间之后创建者不再接受投票，
+//! 创建者也可以提前结束投票。
+//!
+//! 创建者收到投票后回复确认；投票人未收到确认时按间隔重发，重发次数有限。
+//! 到达截止时间后创建者自动结束投票并广播最终结果。
+
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::GroupBroadcaster;
+use crate::database::handler::group::GroupMemberHandler;
+use crate::database::handler::{GroupPollHandler, GroupPollVoteHandler, UserHandler};
+use crate::database::model::group_poll;
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_GROUP;
+use crate::network::feiq::model::{FeiQPacket, PollResult, PollSpec};
+use crate::network::udp::sender;
+use crate::types::{ChatMessage, MessageType, PollInfo, PollOptionInfo};
+use chrono::NaiveDateTime;
+use sea_orm::DbConn;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// 投票选项的最大数量
+pub const MAX_POLL_OPTIONS: usize = 20;
+
+/// 未收到确认时重发投票的间隔
+const VOTE_RETRY_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 投票最多发送的次数（含首次）
+const VOTE_MAX_ATTEMPTS: u32 = 5;
+
+/// 检查到期投票的间隔
+const DEADLINE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 等待创建者确认的投票
+///
+/// key: 投票编号，value: (创建者 machine_id, 所选选项)；重新投票时覆盖
+type PendingVotes = Mutex<HashMap<String, (String, Vec<usize>)>>;
+
+fn pending_votes() -> &'static PendingVotes {
+    static PENDING: OnceLock<PendingVotes> = OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 投票是否仍在等待确认（已被新的选择覆盖时也视为不再等待）
+fn is_vote_pending(poll_no: &str, choices: &[usize]) -> bool {
+    let pending = pending_votes().lock().unwrap_or_else(|e| e.into_inner());
+    pending.get(poll_no).is_some_and(|(_, pending_choices)| pending_choices == choices)
+}
+
+/// 生成投票编号
+pub fn make_poll_no(machine_id: &str, now: NaiveDateTime) -> String {
+    let machine_id: String = machine_id.split_whitespace().collect();
+    format!("{}-{}", machine_id, now.and_utc().timestamp_millis())
+}
+
+/// 检查并整理所选选项：去重排序，单选只能选一个
+pub fn normalize_choices(choices: &[usize], option_count: usize, multiple: bool) -> Result<Vec<usize>, &'static str> {
+    let mut choices = choices.to_vec();
+    choices.sort_unstable();
+    choices.dedup();
+
+    if choices.is_empty() {
+        return Err("至少需要选择一个选项");
+    }
+    if choices.iter().any(|c| *c >= option_count) {
+        return Err("选项不存在");
+    }
+    if !multiple && choices.len() > 1 {
+        return Err("单选投票只能选择一个选项");
+    }
+    Ok(choices)
+}
+
+/// 统计每个选项的票数
+pub fn tally(option_count: usize, votes: &[Vec<usize>]) -> Vec<i64> {
+    let mut counts = vec![0; option_count];
+    for choice in votes.iter().flatten() {
+        if let Some(count) = counts.get_mut(*choice) {
+            *count += 1;
+        }
+    }
+    counts
+}
+
+/// 投票是否仍可投票
+pub fn is_open(closed: bool, deadline: Option<NaiveDateTime>, now: NaiveDateTime) -> bool {
+    !closed && deadline.is_none_or(|deadline| now < deadline)
+}
+
+fn format_choices(choices: &[usize]) -> String {
+    let choices: Vec<String> = choices.iter().map(|c| c.to_string()).collect();
+    choices.join(",")
+}
+
+fn parse_choices(choices: &str) -> Vec<usize> {
+    choices.split(',').filter_map(|c| c.parse().ok()).collect()
+}
+
+fn poll_options(poll: &group_poll::Model) -> Vec<String> {
+    serde_json::from_str(&poll.options).unwrap_or_default()
+}
+
+/// 投票服务
+pub struct PollService;
+
+impl PollService {
+    /// 检查要发起的投票
+    ///
+    /// # 参数
+    /// - `poll_no`: 投票编号
+    /// - `question`: 问题
+    /// - `options`: 选项（至少两个）
+    /// - `multiple`: 是否多选
+    /// - `anonymous`: 是否匿名
+    /// - `deadline`: 截止时间（Unix 时间戳，秒）
+    ///
+    /// # 返回
+    /// 整理后的投票
+    pub fn build_spec(
+        poll_no: String,
+        question: &str,
+        options: &[String],
+        multiple: bool,
+        anonymous: bool,
+        deadline: Option<i64>,
+    ) -> AppResult<PollSpec> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(AppError::Business("投票问题不能为空".to_string()));
+        }
+        let options: Vec<String> = options.iter().map(|o| o.trim().to_string()).collect();
+        if options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+            return Err(AppError::Business(format!("投票需要 2 到 {} 个选项", MAX_POLL_OPTIONS)));
+        }
+        if options.iter().any(|o| o.is_empty()) {
+            return Err(AppError::Business("投票选项不能为空".to_string()));
+        }
+        if deadline.is_some_and(|deadline| deadline <= chrono::Utc::now().timestamp()) {
+            return Err(AppError::Business("截止时间必须晚于当前时间".to_string()));
+        }
+
+        Ok(PollSpec {
+            poll_no,
+            question: question.to_string(),
+            options,
+            multiple,
+            anonymous,
+            deadline,
+        })
+    }
+
+    /// 保存投票消息中的投票
+    ///
+    /// # 参数
+    /// - `mid`: 投票消息 ID
+    /// - `gid`: 群组 ID
+    /// - `creator_uid`: 创建者用户 ID
+    /// - `spec`: 投票
+    pub async fn record(
+        db: &DbConn,
+        mid: i64,
+        gid: i64,
+        creator_uid: i64,
+        spec: &PollSpec,
+    ) -> AppResult<group_poll::Model> {
+        let deadline = spec
+            .deadline
+            .and_then(|deadline| chrono::DateTime::from_timestamp(deadline, 0))
+            .map(|deadline| deadline.naive_utc());
+        let options = serde_json::to_string(&spec.options).unwrap_or_default();
+        let counts = serde_json::to_string(&vec![0; spec.options.len()]).unwrap_or_default();
+
+        GroupPollHandler::create(
+            db,
+            mid,
+            gid,
+            &spec.poll_no,
+            creator_uid,
+            &spec.question,
+            options,
+            spec.multiple,
+            spec.anonymous,
+            deadline,
+            counts,
+        )
+        .await
+    }
+
+    /// 保存收到的投票消息中的投票（重发的投票不重复保存）
+    ///
+    /// # 参数
+    /// - `mid`: 本地消息 ID
+    /// - `gid`: 本地群组 ID
+    /// - `creator_uid`: 创建者（投票消息发送者）用户 ID
+    /// - `spec`: 投票
+    pub async fn record_incoming(db: &DbConn, mid: i64, gid: i64, creator_uid: i64, spec: &PollSpec) -> AppResult<()> {
+        if GroupPollHandler::find_by_poll_no(db, &spec.poll_no).await?.is_some() {
+            return Ok(());
+        }
+
+        Self::record(db, mid, gid, creator_uid, spec).await?;
+        info!("已保存收到的投票: mid={}, poll_no={}", mid, spec.poll_no);
+        Ok(())
+    }
+
+    /// 投票（重新投票时覆盖之前的选择）
+    ///
+    /// 创建者自己投票时直接汇总，其他成员的投票发给创建者
+    ///
+    /// # 参数
+    /// - `poll_id`: 投票ID
+    /// - `voter_uid`: 投票人用户ID
+    /// - `choices`: 所选选项序号
+    pub async fn vote(db: &DbConn, poll_id: i64, voter_uid: i64, choices: &[usize]) -> AppResult<()> {
+        let poll = GroupPollHandler::find_by_id(db, poll_id).await?;
+        if GroupMemberHandler::find_by_group_and_member(db, poll.gid, voter_uid)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::Permission("不是群成员".to_string()));
+        }
+        if !is_open(poll.closed != 0, poll.deadline, chrono::Utc::now().naive_utc()) {
+            return Err(AppError::Business("投票已结束".to_string()));
+        }
+        let choices = normalize_choices(choices, poll_options(&poll).len(), poll.multiple != 0)
+            .map_err(|reason| AppError::Business(reason.to_string()))?;
+
+        if poll.creator_uid == voter_uid {
+            GroupPollVoteHandler::upsert(db, poll.id, voter_uid, &format_choices(&choices)).await?;
+            return Self::publish_result(db, poll).await;
+        }
+
+        let creator = UserHandler::find_by_id(db, poll.creator_uid).await?;
+        if creator.status != 1 || !peer_supports(&creator.feiq_ip, FEIQ_CAP_GROUP) {
+            return Err(AppError::Business("投票创建者不在线".to_string()));
+        }
+        let packet = FeiQPacket::make_feiq_poll_vote_packet(&poll.poll_no, &choices, None);
+        let addr = format!("{}:{}", creator.feiq_ip, creator.feiq_port);
+        sender::send_packet(&addr, &packet)
+            .await
+            .map_err(|e| AppError::Network(format!("发送投票失败: {}", e)))?;
+
+        pending_votes()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(poll.poll_no.clone(), (creator.feiq_machine_id.clone(), choices.clone()));
+        tokio::spawn(Self::retry_vote(poll.poll_no.clone(), addr, packet, choices.clone()));
+
+        GroupPollVoteHandler::upsert(db, poll.id, voter_uid, &format_choices(&choices)).await?;
+        info!("投票已发送: poll_id={}, voter={}", poll.id, voter_uid);
+        Self::notify(&poll);
+        Ok(())
+    }
+
+    /// 未收到创建者确认时重发投票
+    async fn retry_vote(poll_no: String, addr: String, packet: FeiQPacket, choices: Vec<usize>) {
+        for _ in 1..VOTE_MAX_ATTEMPTS {
+            tokio::time::sleep(VOTE_RETRY_INTERVAL).await;
+            if !is_vote_pending(&poll_no, &choices) {
+                return;
+            }
+            info!("投票未确认，重发: poll_no={}", poll_no);
+            if let Err(e) = sender::send_packet(&addr, &packet).await {
+                warn!("重发投票失败: poll_no={}, {}", poll_no, e);
+            }
+        }
+
+        tokio::time::sleep(VOTE_RETRY_INTERVAL).await;
+        if is_vote_pending(&poll_no, &choices) {
+            pending_votes().lock().unwrap_or_else(|e| e.into_inner()).remove(&poll_no);
+            warn!("投票始终未得到创建者确认: poll_no={}", poll_no);
+        }
+    }
+
+    /// 处理创建者的投票确认
+    ///
+    /// 只接受投票创建者对当前选择的确认，旧选择的确认不影响新选择的重发
+    pub fn handle_vote_ack(sender_ip: &str, sender_port: u16, poll_no: &str, accepted: bool, choices: &[usize]) {
+        let mut pending = pending_votes().lock().unwrap_or_else(|e| e.into_inner());
+        let matches = pending.get(poll_no).is_some_and(|(creator, pending_choices)| {
+            *creator == format!("{}:{}", sender_ip, sender_port) && pending_choices == choices
+        });
+        if !matches {
+            return;
+        }
+
+        pending.remove(poll_no);
+        if accepted {
+            info!("投票已被创建者确认: poll_no={}", poll_no);
+        } else {
+            warn!("投票未被计入（投票已结束或选择无效）: poll_no={}", poll_no);
+        }
+    }
+
+    /// 提前结束投票（只有创建者可以结束）
+    pub async fn close(db: &DbConn, poll_id: i64, operator_uid: i64) -> AppResult<()> {
+        let poll = GroupPollHandler::find_by_id(db, poll_id).await?;
+        if poll.creator_uid != operator_uid {
+            return Err(AppError::Permission("只有投票创建者可以结束投票".to_string()));
+        }
+        if poll.closed != 0 {
+            return Ok(());
+        }
+
+        let poll = GroupPollHandler::update_result(db, poll.id, poll.counts, poll.voter_count, true).await?;
+        info!("投票已结束: poll_id={}", poll.id);
+        Self::publish_result(db, poll).await
+    }
+
+    /// 处理其他成员发来的投票（本机是投票创建者）
+    ///
+    /// 非群成员、已结束的投票和无效的选择都被忽略
+    pub async fn handle_vote(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        poll_no: &str,
+        choices: &[usize],
+    ) -> AppResult<()> {
+        let poll = match GroupPollHandler::find_by_poll_no(db, poll_no).await? {
+            Some(poll) => poll,
+            None => {
+                warn!("收到未知投票的投票: poll_no={}", poll_no);
+                return Ok(());
+            }
+        };
+        if poll.creator_uid != UserHandler::get_current_user_id(db).await? {
+            warn!("忽略发给非创建者的投票: poll_no={}", poll_no);
+            return Ok(());
+        }
+
+        let voter_uid = member_uid_for(db, &format!("{}:{}", sender_ip, sender_port)).await?;
+        if GroupMemberHandler::find_by_group_and_member(db, poll.gid, voter_uid)
+            .await?
+            .is_none()
+        {
+            warn!(
+                "忽略非群成员的投票: poll_no={}, from={}:{}",
+                poll_no, sender_ip, sender_port
+            );
+            return Ok(());
+        }
+        if !is_open(poll.closed != 0, poll.deadline, chrono::Utc::now().naive_utc()) {
+            info!("投票已结束，忽略投票: poll_no={}, voter={}", poll_no, voter_uid);
+            Self::ack_vote(sender_ip, sender_port, poll_no, false, choices).await;
+            return Ok(());
+        }
+        let normalized = match normalize_choices(choices, poll_options(&poll).len(), poll.multiple != 0) {
+            Ok(normalized) => normalized,
+            Err(reason) => {
+                warn!("忽略无效的投票: poll_no={}, voter={}, {}", poll_no, voter_uid, reason);
+                Self::ack_vote(sender_ip, sender_port, poll_no, false, choices).await;
+                return Ok(());
+            }
+        };
+
+        // 重发的投票同样覆盖保存并确认，确认丢失时投票人会再次重发
+        GroupPollVoteHandler::upsert(db, poll.id, voter_uid, &format_choices(&normalized)).await?;
+        Self::ack_vote(sender_ip, sender_port, poll_no, true, choices).await;
+        Self::publish_result(db, poll).await
+    }
+
+    /// 回复投票确认（按投票人发来的选项原样确认）
+    async fn ack_vote(sender_ip: &str, sender_port: u16, poll_no: &str, accepted: bool, choices: &[usize]) {
+        let packet = FeiQPacket::make_feiq_poll_vote_ack_packet(poll_no, accepted, choices, None);
+        if let Err(e) = sender::send_packet(&format!("{}:{}", sender_ip, sender_port), &packet).await {
+            warn!("发送投票确认失败: poll_no={}, {}", poll_no, e);
+        }
+    }
+
+    /// 结束已到截止时间的投票并广播最终结果（本机是投票创建者）
+    ///
+    /// # 返回
+    /// 结束的投票数量
+    pub async fn close_due(db: &DbConn) -> AppResult<usize> {
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        let due = GroupPollHandler::list_due(db, current_uid, chrono::Utc::now().naive_utc()).await?;
+        let count = due.len();
+        for poll in due {
+            info!("投票已到截止时间: poll_id={}", poll.id);
+            Self::publish_result(db, poll).await?;
+        }
+        Ok(count)
+    }
+
+    /// 定期结束到期的投票，成员无需再投票即可收到最终结果
+    pub async fn run_deadline_close(db: DbConn) {
+        let mut ticker = tokio::time::interval(DEADLINE_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = Self::close_due(&db).await {
+                error!("结束到期投票失败: {}", e);
+            }
+        }
+    }
+
+    /// 应用创建者广播的投票结果
+    ///
+    /// 只接受投票创建者发来的结果；非匿名投票按结果更新投票人
+    pub async fn apply_result(db: &DbConn, sender_ip: &str, sender_port: u16, result: PollResult) -> AppResult<()> {
+        let poll = match GroupPollHandler::find_by_poll_no(db, &result.poll_no).await? {
+            Some(poll) => poll,
+            None => {
+                info!("忽略未知投票的结果: poll_no={}", result.poll_no);
+                return Ok(());
+            }
+        };
+        let creator = UserHandler::find_by_id(db, poll.creator_uid).await?;
+        if creator.feiq_machine_id != format!("{}:{}", sender_ip, sender_port) {
+            warn!("忽略非创建者发来的投票结果: poll_no={}", result.poll_no);
+            return Ok(());
+        }
+        if result.counts.len() != poll_options(&poll).len() {
+            warn!("投票结果的选项数量不一致: poll_no={}", result.poll_no);
+            return Ok(());
+        }
+
+        let counts = serde_json::to_string(&result.counts).unwrap_or_default();
+        let poll = GroupPollHandler::update_result(db, poll.id, counts, result.voter_count, result.closed).await?;
+
+        if poll.anonymous == 0 {
+            let mut choices: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+            for (i, voters) in result.voters.iter().enumerate() {
+                for voter in voters {
+                    choices.entry(member_uid_for(db, voter).await?).or_default().push(i);
+                }
+            }
+
+            // 自己刚发出的投票可能还没有出现在结果中，保留本地记录
+            let current_uid = UserHandler::get_current_user_id(db).await?;
+            for vote in GroupPollVoteHandler::list_by_poll(db, poll.id).await? {
+                if vote.voter_uid != current_uid && !choices.contains_key(&vote.voter_uid) {
+                    GroupPollVoteHandler::delete(db, vote.id).await?;
+                }
+            }
+            for (voter_uid, voter_choices) in choices {
+                GroupPollVoteHandler::upsert(db, poll.id, voter_uid, &format_choices(&voter_choices)).await?;
+            }
+        }
+
+        Self::notify(&poll);
+        Ok(())
+    }
+
+    /// 获取投票
+    pub async fn get_poll(db: &DbConn, poll_id: i64) -> AppResult<PollInfo> {
+        let poll = GroupPollHandler::find_by_id(db, poll_id).await?;
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        Self::to_info(db, poll, current_uid).await
+    }
+
+    /// 为投票消息附上投票
+    pub async fn attach(db: &DbConn, messages: &mut [ChatMessage]) -> AppResult<()> {
+        let mids: Vec<i64> = messages
+            .iter()
+            .filter(|m| m.msg_type == MessageType::Poll)
+            .map(|m| m.mid)
+            .collect();
+        if mids.is_empty() {
+            return Ok(());
+        }
+
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        for poll in GroupPollHandler::find_by_messages(db, mids).await? {
+            let mid = poll.mid;
+            let info = Self::to_info(db, poll, current_uid).await?;
+            if let Some(message) = messages.iter_mut().find(|m| m.mid == mid) {
+                message.poll = Some(info);
+            }
+        }
+        Ok(())
+    }
+
+    /// 汇总投票并把结果广播给群成员（本机是投票创建者）
+    async fn publish_result(db: &DbConn, poll: group_poll::Model) -> AppResult<()> {
+        let votes = GroupPollVoteHandler::list_by_poll(db, poll.id).await?;
+        let option_count = poll_options(&poll).len();
+        let choices: Vec<Vec<usize>> = votes.iter().map(|vote| parse_choices(&vote.choices)).collect();
+        let counts = tally(option_count, &choices);
+        let closed = !is_open(poll.closed != 0, poll.deadline, chrono::Utc::now().naive_utc());
+
+        let mut voters = vec![Vec::new(); option_count];
+        if poll.anonymous == 0 {
+            for (vote, vote_choices) in votes.iter().zip(&choices) {
+                let machine_id = UserHandler::find_by_id(db, vote.voter_uid).await?.feiq_machine_id;
+                for choice in vote_choices {
+                    if let Some(option_voters) = voters.get_mut(*choice) {
+                        option_voters.push(machine_id.clone());
+                    }
+                }
+            }
+        }
+
+        let counts_json = serde_json::to_string(&counts).unwrap_or_default();
+        let poll = GroupPollHandler::update_result(db, poll.id, counts_json, votes.len() as i64, closed).await?;
+
+        let result = PollResult {
+            poll_no: poll.poll_no.clone(),
+            closed,
+            voter_count: poll.voter_count,
+            counts,
+            voters,
+        };
+        let packet = FeiQPacket::make_feiq_poll_result_packet(&result, None);
+        let sent_count =
+            GroupBroadcaster::broadcast_by_capability(db, poll.gid, FEIQ_CAP_GROUP, &packet, None, poll.creator_uid)
+                .await?;
+        info!("投票结果已广播到 {} 个成员: poll_id={}", sent_count, poll.id);
+
+        Self::notify(&poll);
+        Ok(())
+    }
+
+    fn notify(poll: &group_poll::Model) {
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::PollUpdated {
+            gid: poll.gid,
+            msg_id: poll.mid,
+            poll_id: poll.id,
+        }));
+    }
+
+    async fn to_info(db: &DbConn, poll: group_poll::Model, current_uid: i64) -> AppResult<PollInfo> {
+        let options = poll_options(&poll);
+        let counts: Vec<i64> = serde_json::from_str(&poll.counts).unwrap_or_default();
+        let votes = GroupPollVoteHandler::list_by_poll(db, poll.id).await?;
+
+        let mut voters = vec![Vec::new(); options.len()];
+        let mut my_choices = Vec::new();
+        for vote in &votes {
+            let choices = parse_choices(&vote.choices);
+            if vote.voter_uid == current_uid {
+                my_choices = choices.clone();
+            }
+            if poll.anonymous == 0 {
+                for choice in choices {
+                    if let Some(option_voters) = voters.get_mut(choice) {
+                        option_voters.push(vote.voter_uid);
+                    }
+                }
+            }
+        }
+
+        let options = options
+            .into_iter()
+            .zip(voters)
+            .enumerate()
+            .map(|(i, (text, voters))| PollOptionInfo {
+                text,
+                count: counts.get(i).copied().unwrap_or(0),
+                voters,
+            })
+            .collect();
+
+        Ok(PollInfo {
+            poll_id: poll.id,
+            mid: poll.mid,
+            gid: poll.gid,
+            creator_uid: poll.creator_uid,
+            question: poll.question,
+            options,
+            multiple: poll.multiple != 0,
+            anonymous: poll.anonymous != 0,
+            deadline: poll.deadline.map(|deadline| deadline.to_string()),
+            closed: !is_open(poll.closed != 0, poll.deadline, chrono::Utc::now().naive_utc()),
+            voter_count: poll.voter_count,
+            my_choices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_choices() {
+        assert_eq!(normalize_choices(&[2, 0, 2], 3, true), Ok(vec![0, 2]));
+        assert_eq!(normalize_choices(&[1, 1], 3, false), Ok(vec![1]));
+        assert!(normalize_choices(&[0, 1], 3, false).is_err());
+        assert!(normalize_choices(&[3], 3, true).is_err());
+        assert!(normalize_choices(&[], 3, true).is_err());
+    }
+
+    #[test]
+    fn test_tally_and_deadline() {
+        assert_eq!(tally(3, &[vec![0], vec![0, 2], vec![5]]), vec![2, 0, 1]);
+        assert_eq!(parse_choices(&format_choices(&[0, 2])), vec![0, 2]);
+
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().naive_utc();
+        let later = now + chrono::Duration::hours(1);
+        assert!(is_open(false, None, now));
+        assert!(is_open(false, Some(later), now));
+        assert!(!is_open(false, Some(now), now));
+        assert!(!is_open(true, Some(later), now));
+    }
+
+    #[test]
+    fn test_vote_ack_matches_current_choice() {
+        let poll_no = "192.168.1.2:2425-1700000000000";
+        pending_votes()
+            .lock()
+            .unwrap()
+            .insert(poll_no.to_string(), ("192.168.1.2:2425".to_string(), vec![1]));
+
+        // 旧选择的确认和其他主机的确认都不结束等待
+        PollService::handle_vote_ack("192.168.1.2", 2425, poll_no, true, &[0]);
+        PollService::handle_vote_ack("192.168.1.3", 2425, poll_no, true, &[1]);
+        assert!(is_vote_pending(poll_no, &[1]));
+
+        PollService::handle_vote_ack("192.168.1.2", 2425, poll_no, true, &[1]);
+        assert!(!is_vote_pending(poll_no, &[1]));
+    }
+}
@@ -0,0 +1,326 @@
+// src-tauri/src/core/chat/reaction.rs
+//
+//! 消息表情回应
+//!
+//! 单聊和群聊中的消息都可以用表情回应，每个回应者对一条消息只保留一个表情，
+//! 重新回应时覆盖之前的表情。回应包携带原消息编号（群消息另带原消息发送者），只发给声明了
+//! `FEIQ_CAP_REACTION` 的对端，其他客户端不会收到任何提示。
+//! 回应者记为对端的用户 ID（按机器标识查找），与群成员、消息发送者使用同一身份。
+
+use crate::core::chat::receiver::sender_uid_for;
+use crate::core::chat::revision::EDIT_STATE_RECALLED;
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::{GroupBroadcaster, GroupEnvelopeService};
+use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
+use crate::database::handler::{ChatMessageHandler, MessageReactionHandler, UserHandler};
+use crate::database::model::chat_message;
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_REACTION;
+use crate::network::feiq::model::{FeiQPacket, Reaction};
+use crate::network::udp::sender;
+use crate::types::{ChatMessage, ReactionSummary};
+use sea_orm::DbConn;
+use tracing::{info, warn};
+
+/// 表情的最大字符数（组合表情由多个字符组成）
+pub const MAX_EMOJI_CHARS: usize = 16;
+
+/// 检查表情：不能为空、不能含空白，"-" 在协议中表示取消回应
+pub fn validate_emoji(emoji: &str) -> Result<&str, &'static str> {
+    let emoji = emoji.trim();
+    if emoji.is_empty() || emoji == "-" {
+        return Err("表情不能为空");
+    }
+    if emoji.chars().any(char::is_whitespace) || emoji.chars().count() > MAX_EMOJI_CHARS {
+        return Err("无效的表情");
+    }
+    Ok(emoji)
+}
+
+/// 按表情汇总回应（按表情首次出现的顺序）
+///
+/// # 参数
+/// - `reactions`: (回应者, 表情)，按回应时间正序
+/// - `current_uid`: 当前用户ID
+pub fn summarize(reactions: &[(i64, String)], current_uid: i64) -> Vec<ReactionSummary> {
+    let mut summaries: Vec<ReactionSummary> = Vec::new();
+    for (reactor_uid, emoji) in reactions {
+        let index = match summaries.iter().position(|s| &s.emoji == emoji) {
+            Some(index) => index,
+            None => {
+                summaries.push(ReactionSummary {
+                    emoji: emoji.clone(),
+                    count: 0,
+                    reactors: Vec::new(),
+                    reacted_by_me: false,
+                });
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[index];
+        summary.count += 1;
+        summary.reactors.push(*reactor_uid);
+        summary.reacted_by_me |= *reactor_uid == current_uid;
+    }
+    summaries
+}
+
+/// 表情回应服务
+pub struct ReactionService;
+
+impl ReactionService {
+    /// 回应消息（`emoji` 为 None 时取消回应）
+    ///
+    /// # 参数
+    /// - `mid`: 消息ID
+    /// - `reactor_uid`: 回应者用户ID
+    /// - `emoji`: 表情
+    ///
+    /// # 返回
+    /// 消息最新的回应汇总
+    pub async fn react(
+        db: &DbConn,
+        mid: i64,
+        reactor_uid: i64,
+        emoji: Option<String>,
+    ) -> AppResult<Vec<ReactionSummary>> {
+        let emoji = match &emoji {
+            Some(emoji) => Some(
+                validate_emoji(emoji)
+                    .map_err(|reason| AppError::Business(reason.to_string()))?
+                    .to_string(),
+            ),
+            None => None,
+        };
+
+        let message = ChatMessageHandler::find_by_id(db, mid).await?;
+        if message.edit_state == EDIT_STATE_RECALLED {
+            return Err(AppError::Business("不能回应已撤回的消息".to_string()));
+        }
+        let msg_no = match &message.msg_no {
+            Some(msg_no) => msg_no.clone(),
+            None => return Err(AppError::Business("消息缺少消息编号，对方无法定位该消息".to_string())),
+        };
+        if message.session_type == 1
+            && GroupMemberHandler::find_by_group_and_member(db, message.target_id, reactor_uid)
+                .await?
+                .is_none()
+        {
+            return Err(AppError::Permission("不是群成员".to_string()));
+        }
+
+        let (group_no, msg_sender) = match message.session_type {
+            1 => (
+                Some(GroupEnvelopeService::ensure_group_no(db, message.target_id).await?),
+                Some(UserHandler::find_by_id(db, message.sender_uid).await?.feiq_machine_id),
+            ),
+            _ => (None, None),
+        };
+        let reaction = Reaction {
+            msg_no,
+            emoji: emoji.clone(),
+            group_no,
+            msg_sender,
+        };
+        Self::notify_peers(db, &message, reactor_uid, &reaction).await?;
+
+        match &emoji {
+            Some(emoji) => MessageReactionHandler::upsert(db, mid, reactor_uid, emoji).await?,
+            None => MessageReactionHandler::delete(db, mid, reactor_uid).await?,
+        }
+        info!(
+            "消息回应已更新: mid={}, reactor={}, emoji={:?}",
+            mid, reactor_uid, emoji
+        );
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::ReactionUpdated { msg_id: mid }));
+
+        Self::get_reactions(db, mid, reactor_uid).await
+    }
+
+    /// 应用对端发来的表情回应
+    ///
+    /// 群回应只接受群成员发来的，并按原消息发送者和消息编号定位消息；找不到消息时只记录日志
+    pub async fn apply_remote(db: &DbConn, sender_ip: &str, sender_port: u16, reaction: Reaction) -> AppResult<()> {
+        let reactor_uid = member_uid_for(db, &format!("{}:{}", sender_ip, sender_port)).await?;
+        let message = match &reaction.group_no {
+            Some(group_no) => {
+                let group = match GroupHandler::find_by_group_no(db, group_no).await? {
+                    Some(group) => group,
+                    None => {
+                        warn!("忽略未知群组的消息回应: group_no={}", group_no);
+                        return Ok(());
+                    }
+                };
+                if GroupMemberHandler::find_by_group_and_member(db, group.gid, reactor_uid)
+                    .await?
+                    .is_none()
+                {
+                    warn!(
+                        "忽略非群成员的消息回应: gid={}, from={}:{}",
+                        group.gid, sender_ip, sender_port
+                    );
+                    return Ok(());
+                }
+                let msg_sender = match &reaction.msg_sender {
+                    Some(msg_sender) => UserHandler::find_by_machine_id(db, msg_sender).await?,
+                    None => None,
+                };
+                match msg_sender {
+                    Some(msg_sender) => {
+                        ChatMessageHandler::find_by_sender_and_msg_no(db, msg_sender.uid, &reaction.msg_no)
+                            .await?
+                            .filter(|m| m.session_type == 1 && m.target_id == group.gid)
+                    }
+                    None => None,
+                }
+            }
+            None => Self::find_single_message(db, sender_ip, sender_port, &reaction.msg_no).await?,
+        };
+
+        let message = match message {
+            Some(message) if message.edit_state != EDIT_STATE_RECALLED => message,
+            Some(_) => return Ok(()),
+            None => {
+                warn!("找不到被回应的消息: from={}, msg_no={}", sender_ip, reaction.msg_no);
+                return Ok(());
+            }
+        };
+
+        match &reaction.emoji {
+            Some(emoji) => match validate_emoji(emoji) {
+                Ok(emoji) => MessageReactionHandler::upsert(db, message.mid, reactor_uid, emoji).await?,
+                Err(reason) => {
+                    warn!("忽略无效的消息回应: msg_no={}, {}", reaction.msg_no, reason);
+                    return Ok(());
+                }
+            },
+            None => MessageReactionHandler::delete(db, message.mid, reactor_uid).await?,
+        }
+        info!("收到消息回应: mid={}, reactor={}", message.mid, reactor_uid);
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::ReactionUpdated { msg_id: message.mid }));
+        Ok(())
+    }
+
+    /// 获取消息的回应汇总
+    pub async fn get_reactions(db: &DbConn, mid: i64, current_uid: i64) -> AppResult<Vec<ReactionSummary>> {
+        let reactions: Vec<(i64, String)> = MessageReactionHandler::list_by_messages(db, vec![mid])
+            .await?
+            .into_iter()
+            .map(|r| (r.reactor_uid, r.emoji))
+            .collect();
+        Ok(summarize(&reactions, current_uid))
+    }
+
+    /// 为消息附上回应汇总
+    pub async fn attach(db: &DbConn, messages: &mut [ChatMessage]) -> AppResult<()> {
+        let mids: Vec<i64> = messages.iter().map(|m| m.mid).collect();
+        let reactions = MessageReactionHandler::list_by_messages(db, mids).await?;
+        if reactions.is_empty() {
+            return Ok(());
+        }
+
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        for message in messages.iter_mut() {
+            let message_reactions: Vec<(i64, String)> = reactions
+                .iter()
+                .filter(|r| r.mid == message.mid)
+                .map(|r| (r.reactor_uid, r.emoji.clone()))
+                .collect();
+            message.reactions = summarize(&message_reactions, current_uid);
+        }
+        Ok(())
+    }
+
+    /// 查找单聊中被回应的消息
+    ///
+    /// 对端发来的消息以对端地址派生的 ID 存储会话，自己发出的消息可能存于联系人的会话中
+    async fn find_single_message(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        msg_no: &str,
+    ) -> AppResult<Option<chat_message::Model>> {
+        let peer_uid = sender_uid_for(sender_ip, sender_port);
+        if let Some(message) = ChatMessageHandler::find_in_session(db, 0, peer_uid, msg_no).await? {
+            return Ok(Some(message));
+        }
+
+        match UserHandler::find_by_machine_id(db, &format!("{}:{}", sender_ip, sender_port)).await? {
+            Some(user) if user.uid != peer_uid => ChatMessageHandler::find_in_session(db, 0, user.uid, msg_no).await,
+            _ => Ok(None),
+        }
+    }
+
+    /// 通知会话对端（只发给支持表情回应的对端）
+    async fn notify_peers(
+        db: &DbConn,
+        message: &chat_message::Model,
+        reactor_uid: i64,
+        reaction: &Reaction,
+    ) -> AppResult<()> {
+        let packet = FeiQPacket::make_feiq_reaction_packet(reaction, None);
+
+        if message.session_type == 0 {
+            let target_user = UserHandler::find_by_id(db, message.target_id)
+                .await
+                .map_err(|_| AppError::NotFound(format!("目标用户 {} 不存在", message.target_id)))?;
+            if target_user.status != 1 {
+                return Err(AppError::Business("目标用户不在线".to_string()));
+            }
+            if !peer_supports(&target_user.feiq_ip, FEIQ_CAP_REACTION) {
+                info!("对端不支持表情回应，只在本地记录: mid={}", message.mid);
+                return Ok(());
+            }
+
+            let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
+            sender::send_packet(&addr, &packet)
+                .await
+                .map_err(|e| AppError::Network(format!("发送消息回应失败: {}", e)))?;
+        } else {
+            let sent_count = GroupBroadcaster::broadcast_by_capability(
+                db,
+                message.target_id,
+                FEIQ_CAP_REACTION,
+                &packet,
+                None,
+                reactor_uid,
+            )
+            .await?;
+            info!("消息回应已广播到 {} 个成员", sent_count);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_emoji() {
+        assert_eq!(validate_emoji(" 👍 "), Ok("👍"));
+        assert_eq!(validate_emoji("👨‍👩‍👧"), Ok("👨‍👩‍👧"));
+        assert!(validate_emoji("").is_err());
+        assert!(validate_emoji("-").is_err());
+        assert!(validate_emoji("👍 👍").is_err());
+        assert!(validate_emoji(&"👍".repeat(MAX_EMOJI_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let reactions = vec![(2, "👍".to_string()), (1, "🎉".to_string()), (3, "👍".to_string())];
+        let summaries = summarize(&reactions, 3);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].emoji, "👍");
+        assert_eq!(summaries[0].count, 2);
+        assert_eq!(summaries[0].reactors, vec![2, 3]);
+        assert!(summaries[0].reacted_by_me);
+        assert_eq!(summaries[1].reactors, vec![1]);
+        assert!(!summaries[1].reacted_by_me);
+        assert!(summarize(&[], 1).is_empty());
+    }
+}
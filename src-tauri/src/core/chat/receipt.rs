@@ -7,65 +7,55 @@
 /// - 发送已读回执
 /// - 更新消息已读状态
 /// - 处理 READMSG 命令
-use crate::database::handler::ChatMessageHandler;
-use crate::event::bus::EVENT_RECEIVER;
-use crate::event::model::{AppEvent, NetworkEvent, UiEvent};
+/// - 记录群消息每个接收成员的送达（RECVMSG）和已读（READMSG）
+use crate::database::handler::{ChatMessageHandler, GroupMessageReceiptHandler, UserHandler};
+use crate::database::model::group_message_receipt;
+use crate::error::AppResult;
+use crate::event::model::{AppEvent, UiEvent};
 use crate::network::feiq::model::FeiQPacket;
 use crate::network::udp::sender;
+use crate::types::{GroupReadMembers, GroupReadSummary, GroupReceiptMember};
 use sea_orm::DbConn;
-use std::sync::Arc;
 use tracing::{error, info, warn};
 
-/// 已读回执处理器
-pub struct ReceiptHandler {
-    db: Arc<DbConn>,
+/// 群消息回执状态：已送达
+pub const GROUP_RECEIPT_DELIVERED: i8 = 1;
+
+/// 群消息回执状态：已读
+pub const GROUP_RECEIPT_READ: i8 = 2;
+
+/// 统计群消息回执
+///
+/// # 返回
+/// (接收成员数, 已送达数, 已读数)，已读的成员也计入已送达
+pub fn count_receipts(receipts: &[group_message_receipt::Model]) -> (usize, usize, usize) {
+    let delivered = receipts.iter().filter(|r| r.status >= GROUP_RECEIPT_DELIVERED).count();
+    let read = receipts.iter().filter(|r| r.status >= GROUP_RECEIPT_READ).count();
+    (receipts.len(), delivered, read)
 }
 
-impl ReceiptHandler {
-    /// 创建新的已读回执处理器
-    pub fn new(db: Arc<DbConn>) -> Self {
-        Self { db }
-    }
+/// 已读回执处理器
+///
+/// 由事件分发循环调用，总线上的每个事件只会被分发循环取走一次
+pub struct ReceiptHandler;
 
-    /// 启动已读回执处理
+impl ReceiptHandler {
+    /// 处理接收确认（RECVMSG）
     ///
-    /// 在后台任务中监听事件总线，处理已读回执相关的网络事件
-    pub fn start(&self) {
-        let db = self.db.clone();
-        let receiver = EVENT_RECEIVER.clone();
-
-        tokio::spawn(async move {
-            info!("已读回执处理器已启动");
-            Self::event_loop(db, receiver).await;
-        });
-    }
+    /// 群消息记录该成员已送达，单聊消息只记录日志
+    pub async fn handle_recvmsg(db: &DbConn, sender_ip: &str, sender_port: u16, msg_no: &str) {
+        info!("收到消息确认: {} from {}:{}", msg_no, sender_ip, sender_port);
 
-    /// 事件循环
-    async fn event_loop(db: Arc<DbConn>, receiver: crossbeam_channel::Receiver<AppEvent>) {
-        loop {
-            match receiver.recv() {
-                Ok(event) => {
-                    // 处理消息已读事件（IPMSG_READMSG）
-                    if let AppEvent::Network(NetworkEvent::MessageRead { msg_no }) = &event {
-                        Self::handle_readmsg(db.clone(), msg_no.clone()).await;
-                    }
-                    // 处理消息删除事件（IPMSG_DELMSG）
-                    else if let AppEvent::Network(NetworkEvent::MessageDeleted { msg_no }) = &event {
-                        Self::handle_delmsg(db.clone(), msg_no.clone()).await;
-                    }
-                }
-                Err(e) => {
-                    error!("事件接收失败: {}", e);
-                }
-            }
+        if let Err(e) = Self::record_group_receipt(db, sender_ip, sender_port, msg_no, GROUP_RECEIPT_DELIVERED).await {
+            error!("记录群消息送达失败: {}", e);
         }
     }
 
     /// 处理消息已读请求（READMSG）
     ///
     /// 对方阅读了我们发送的消息后，会发送 READMSG 请求
-    /// 我们需要回复 ANSREADMSG 确认
-    async fn handle_readmsg(db: Arc<DbConn>, msg_no: String) {
+    /// 我们需要回复 ANSREADMSG 确认。群消息记录该成员已读，所有成员都已读后消息标记为已读
+    pub async fn handle_readmsg(db: &DbConn, sender_ip: &str, sender_port: u16, msg_no: &str) {
         info!("收到消息已读请求");
 
         if msg_no.is_empty() {
@@ -73,11 +63,21 @@ impl ReceiptHandler {
             return;
         }
 
+        // 群消息按成员记录
+        match Self::record_group_receipt(db, sender_ip, sender_port, msg_no, GROUP_RECEIPT_READ).await {
+            Ok(true) => {
+                Self::send_ansreadmsg(msg_no).await;
+                return;
+            }
+            Ok(false) => {}
+            Err(e) => error!("记录群消息已读失败: {}", e),
+        }
+
         // 通过 msg_no 查找消息并更新状态
-        match ChatMessageHandler::find_by_msg_no(&db, &msg_no).await {
+        match ChatMessageHandler::find_by_msg_no(db, msg_no).await {
             Ok(Some(message)) => {
                 // 更新消息状态为已读（2）
-                if let Err(e) = ChatMessageHandler::update_status(&db, message.mid, 2).await {
+                if let Err(e) = ChatMessageHandler::update_status(db, message.mid, 2).await {
                     error!("更新消息已读状态失败: {}", e);
                 } else {
                     info!("消息已标记为已读: mid={}", message.mid);
@@ -90,7 +90,7 @@ impl ReceiptHandler {
                 }
 
                 // 发送 ANSREADMSG 确认
-                Self::send_ansreadmsg(&msg_no).await;
+                Self::send_ansreadmsg(msg_no).await;
             }
             Ok(None) => {
                 warn!("找不到对应的消息: msg_no={}", msg_no);
@@ -101,41 +101,99 @@ impl ReceiptHandler {
         }
     }
 
-    /// 处理消息删除请求（DELMSG）
+    /// 记录群消息成员的送达或已读
     ///
-    /// 对方删除了消息后，会发送 DELMSG 请求
-    /// 我们需要在本地也删除该消息
-    async fn handle_delmsg(db: Arc<DbConn>, msg_no: String) {
-        info!("收到消息删除请求");
+    /// # 返回
+    /// 消息是本机发出的群消息时返回 true
+    async fn record_group_receipt(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        msg_no: &str,
+        status: i8,
+    ) -> AppResult<bool> {
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        let message = match ChatMessageHandler::find_by_sender_and_msg_no(db, current_uid, msg_no).await? {
+            Some(message) if message.session_type == 1 => message,
+            _ => return Ok(false),
+        };
+        let member = match UserHandler::find_by_machine_id(db, &format!("{}:{}", sender_ip, sender_port)).await? {
+            Some(member) => member,
+            None => {
+                warn!("收到未知成员的群消息回执: {}:{}", sender_ip, sender_port);
+                return Ok(true);
+            }
+        };
 
-        if msg_no.is_empty() {
-            warn!("删除请求缺少消息编号");
-            return;
+        if GroupMessageReceiptHandler::advance(db, message.mid, member.uid, status)
+            .await?
+            .is_none()
+        {
+            return Ok(true);
         }
 
-        // 通过 msg_no 查找消息并删除
-        match ChatMessageHandler::find_by_msg_no(&db, &msg_no).await {
-            Ok(Some(message)) => {
-                // 删除消息
-                if let Err(e) = ChatMessageHandler::delete(&db, message.mid).await {
-                    error!("删除消息失败: {}", e);
-                } else {
-                    info!("消息已删除: mid={}", message.mid);
-
-                    // 触发 UI 事件：删除消息
-                    let _ = crate::event::bus::EVENT_SENDER.send(AppEvent::Ui(UiEvent::UpdateMessageStatus {
-                        msg_id: message.mid,
-                        status: 3, // 3 表示已删除
-                    }));
-                }
+        let _ = crate::event::bus::EVENT_SENDER.send(AppEvent::Ui(UiEvent::GroupReceiptUpdated {
+            msg_id: message.mid,
+            member_uid: member.uid,
+            status,
+        }));
+
+        // 所有成员都已读后消息标记为已读
+        if status == GROUP_RECEIPT_READ {
+            let receipts = GroupMessageReceiptHandler::list_by_message(db, message.mid).await?;
+            let (total, _, read) = count_receipts(&receipts);
+            if read == total && message.status != 2 {
+                ChatMessageHandler::update_status(db, message.mid, 2).await?;
+                let _ = crate::event::bus::EVENT_SENDER.send(AppEvent::Ui(UiEvent::UpdateMessageStatus {
+                    msg_id: message.mid,
+                    status: 2,
+                }));
             }
-            Ok(None) => {
-                warn!("找不到对应的消息: msg_no={}", msg_no);
-            }
-            Err(e) => {
-                error!("查找消息失败: {}", e);
+        }
+
+        Ok(true)
+    }
+
+    /// 获取群消息的已读统计（"已读 5/12"）
+    pub async fn group_read_summary(db: &DbConn, mid: i64) -> AppResult<GroupReadSummary> {
+        let receipts = GroupMessageReceiptHandler::list_by_message(db, mid).await?;
+        let (total, delivered, read) = count_receipts(&receipts);
+
+        Ok(GroupReadSummary {
+            mid,
+            total,
+            delivered,
+            read,
+        })
+    }
+
+    /// 获取群消息已读和未读的成员列表
+    pub async fn group_read_members(db: &DbConn, mid: i64) -> AppResult<GroupReadMembers> {
+        let mut result = GroupReadMembers {
+            mid,
+            read: Vec::new(),
+            unread: Vec::new(),
+        };
+
+        for receipt in GroupMessageReceiptHandler::list_by_message(db, mid).await? {
+            let nickname = match UserHandler::find_by_id(db, receipt.member_uid).await {
+                Ok(user) => user.nickname,
+                _ => format!("User{}", receipt.member_uid),
+            };
+            let member = GroupReceiptMember {
+                member_uid: receipt.member_uid,
+                nickname,
+                status: receipt.status,
+                read_time: receipt.read_time.map(|t| t.to_string()),
+            };
+            if receipt.status >= GROUP_RECEIPT_READ {
+                result.read.push(member);
+            } else {
+                result.unread.push(member);
             }
         }
+
+        Ok(result)
     }
 
     /// 发送已读回执（ANSREADMSG）
@@ -236,6 +294,25 @@ mod tests {
     #[test]
     fn test_receipt_handler_module_exists() {
         // 这是一个简单的存在性测试
-        assert_eq!(std::mem::size_of::<ReceiptHandler>(), 8); // Arc<DbConn> 的大小
+        assert_eq!(std::mem::size_of::<ReceiptHandler>(), 0); // 无状态的处理器
+    }
+
+    #[test]
+    fn test_count_receipts() {
+        let now = chrono::Utc::now().naive_utc();
+        let receipt = |member_uid, status| group_message_receipt::Model {
+            id: member_uid,
+            mid: 1,
+            gid: 1,
+            member_uid,
+            status,
+            deliver_time: None,
+            read_time: None,
+            create_time: now,
+        };
+
+        let receipts = vec![receipt(2, 0), receipt(3, GROUP_RECEIPT_DELIVERED), receipt(4, GROUP_RECEIPT_READ)];
+        assert_eq!(count_receipts(&receipts), (3, 2, 1), "Read members count as delivered");
+        assert_eq!(count_receipts(&[]), (0, 0, 0));
     }
 }
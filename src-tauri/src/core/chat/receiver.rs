@@ -3,89 +3,59 @@
 /// 消息接收处理器
 ///
 /// 负责处理从网络层接收到的消息：
-/// - 监听事件总线的网络数据包事件
 /// - 解析 SENDMSG 命令
-/// - 存储接收到的消息到数据库
+/// - 存储接收到的消息到数据库（群消息按信封存入对应的群会话）
 /// - 触发 UI 更新事件
 /// - 发送 RECVMSG 确认（如果消息需要确认）
 /// - 更新会话未读计数
+use crate::core::chat::mention::{mentions_user, MentionService};
+use crate::core::chat::poll::PollService;
+use crate::core::group::GroupEnvelopeService;
 use crate::database::handler::{ChatMessageHandler, ChatSessionHandler, UserHandler};
-use crate::event::bus::EVENT_RECEIVER;
-use crate::event::model::{AppEvent, NetworkEvent, UiEvent};
-use crate::network::feiq::model::FeiQPacket;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::model::{FeiQPacket, GroupEnvelope, PollSpec};
+use crate::types::MessageType;
 use sea_orm::DbConn;
-use std::sync::Arc;
 use tracing::{error, info, warn};
 
 // 注意：保留 FeiQPacket 用于 RECVMSG 确认发送
 
-/// 消息接收器
-pub struct MessageReceiver {
-    db: Arc<DbConn>,
+/// 根据发送者地址得到存储消息时使用的发送者用户 ID
+///
+/// 撤回/编辑等引用已收消息的包需要用同样的方式定位发送者
+pub(crate) fn sender_uid_for(ip: &str, port: u16) -> i64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let machine_id = format!("{}:{}", ip, port);
+    let mut hasher = DefaultHasher::new();
+    machine_id.hash(&mut hasher);
+    (hasher.finish() % 9000000000000000000 + 1000000000000000000) as i64
 }
 
-impl MessageReceiver {
-    /// 创建新的消息接收器
-    pub fn new(db: Arc<DbConn>) -> Self {
-        Self { db }
-    }
-
-    /// 启动消息接收处理
-    ///
-    /// 在后台任务中监听事件总线，处理接收到的消息
-    pub fn start(&self) {
-        let db = self.db.clone();
-        let receiver = EVENT_RECEIVER.clone();
-
-        tokio::spawn(async move {
-            info!("消息接收器已启动");
-            Self::event_loop(db, receiver).await;
-        });
-    }
-
-    /// 事件循环
-    async fn event_loop(db: Arc<DbConn>, receiver: crossbeam_channel::Receiver<AppEvent>) {
-        loop {
-            match receiver.recv() {
-                Ok(event) => {
-                    // 处理细粒度的MessageReceived事件
-                    if let AppEvent::Network(NetworkEvent::MessageReceived {
-                        sender_ip,
-                        sender_port,
-                        sender_nickname,
-                        content,
-                        msg_no,
-                        needs_receipt,
-                    }) = event
-                    {
-                        Self::handle_message_received(
-                            db.clone(),
-                            sender_ip,
-                            sender_port,
-                            sender_nickname,
-                            content,
-                            msg_no,
-                            needs_receipt,
-                        )
-                        .await;
-                    }
-                }
-                Err(e) => {
-                    error!("事件接收失败: {}", e);
-                }
-            }
-        }
-    }
+/// 消息接收器
+///
+/// 由事件分发循环调用，总线上的每个事件只会被分发循环取走一次
+pub struct MessageReceiver;
 
+impl MessageReceiver {
     /// 处理接收到的消息
-    async fn handle_message_received(
-        db: Arc<DbConn>,
+    ///
+    /// 带群消息信封（`group`）的消息存入对应的群会话，其余消息存入与发送者的单聊会话；
+    /// 群消息中的投票（`poll`）随消息一起保存
+    #[allow(clippy::too_many_arguments)]
+    pub async fn handle_message_received(
+        db: &DbConn,
         sender_ip: String,
         sender_port: u16,
         sender_nickname: String,
         content: String,
         msg_no: String,
         needs_receipt: bool,
+        reply_to: Option<String>,
+        quote: Option<String>,
+        group: Option<GroupEnvelope>,
+        poll: Option<PollSpec>,
     ) {
         info!("收到消息包 from {}:{}", sender_ip, sender_port);
 
@@ -99,26 +69,50 @@ impl MessageReceiver {
                 }
             };
 
-        // 查找或创建发送者用户记录
-        let sender_uid = match Self::get_or_create_sender_user(&db, &sender_ip, sender_port, &sender_nickname).await {
-            Ok(uid) => uid,
-            Err(e) => {
-                error!("获取发送者用户信息失败: {}", e);
-                return;
-            }
+        let (session_type, target_id, sender_uid) = match &group {
+            // 群聊：target_id 是本地群组 ID
+            Some(envelope) => match GroupEnvelopeService::resolve_incoming(db, envelope, &sender_ip, sender_port).await
+            {
+                Ok((gid, sender_uid)) => (1, gid, sender_uid),
+                Err(e) => {
+                    error!("解析群消息信封失败: group_no={}, {}", envelope.group_no, e);
+                    return;
+                }
+            },
+            // 单聊：对于接收方，target_id 是发送者的 uid
+            None => match Self::get_or_create_sender_user(db, &sender_ip, sender_port, &sender_nickname).await {
+                Ok(uid) => (0, uid, uid),
+                Err(e) => {
+                    error!("获取发送者用户信息失败: {}", e);
+                    return;
+                }
+            },
         };
 
-        // 存储消息到数据库（单聊类型 = 0）
-        let session_type = 0; // 单聊
-        let target_id = sender_uid; // 对于接收方，target_id 是发送者的 uid
+        // 重发的群消息已经存储过，只需再次确认
+        if let Some(envelope) = &group {
+            if let Ok(Some(existing)) =
+                ChatMessageHandler::find_by_group_msg_no(db, target_id, sender_uid, envelope.group_msg_no).await
+            {
+                info!("忽略重复的群消息: mid={}, group_msg_no={}", existing.mid, envelope.group_msg_no);
+                if needs_receipt {
+                    Self::send_recv_confirmation(&format!("{}:{}", sender_ip, sender_port), &msg_no).await;
+                }
+                return;
+            }
+        }
 
+        let msg_type = match poll {
+            Some(_) => MessageType::Poll as i8,
+            None => MessageType::Text as i8,
+        };
         match ChatMessageHandler::create_with_msg_no(
-            &db,
+            db,
             session_type,
             target_id,
             sender_uid,
             content,
-            0, // 文本消息类型
+            msg_type,
             Some(msg_no.clone()),
         )
         .await
@@ -126,9 +120,53 @@ impl MessageReceiver {
             Ok(message) => {
                 info!("消息已保存到数据库: mid={}, sender={}", message.mid, sender_nickname);
 
+                let message = match &group {
+                    Some(envelope) => ChatMessageHandler::set_group_msg_no(db, message.mid, envelope.group_msg_no)
+                        .await
+                        .unwrap_or(message),
+                    None => message,
+                };
+
+                // 引用回复：关联本地的原消息，找不到原消息时仍保留引用摘要
+                let message = match reply_to {
+                    Some(reply_msg_no) => {
+                        let reply_to_mid =
+                            ChatMessageHandler::find_reply_target(db, session_type, target_id, &reply_msg_no)
+                                .await
+                                .ok()
+                                .flatten()
+                                .map(|original| original.mid);
+                        let quote = quote.unwrap_or_default();
+                        ChatMessageHandler::set_reply(db, message.mid, reply_to_mid, quote)
+                            .await
+                            .unwrap_or(message)
+                    }
+                    None => message,
+                };
+
+                // 群消息中的投票，创建者即消息发送者
+                if let (Some(_), Some(spec)) = (&group, &poll) {
+                    if let Err(e) = PollService::record_incoming(db, message.mid, target_id, sender_uid, spec).await {
+                        error!("保存投票失败: mid={}, {}", message.mid, e);
+                    }
+                }
+
+                // 群消息中的提及
+                let mentions = match &group {
+                    Some(envelope) if !envelope.mentions.is_empty() => {
+                        MentionService::record_incoming(db, message.mid, target_id, sender_uid, &envelope.mentions)
+                            .await
+                            .unwrap_or_else(|e| {
+                                error!("保存消息提及失败: mid={}, {}", message.mid, e);
+                                Vec::new()
+                            })
+                    }
+                    _ => Vec::new(),
+                };
+
                 // 获取或创建会话
                 // 获取当前登录用户的 uid
-                let current_user_uid = match UserHandler::get_current_user_id(&db).await {
+                let current_user_uid = match UserHandler::get_current_user_id(db).await {
                     Ok(uid) => uid,
                     Err(_) => {
                         // 如果没有当前用户，尝试创建或使用默认值
@@ -138,13 +176,25 @@ impl MessageReceiver {
                 };
 
                 if let Ok(session) =
-                    ChatSessionHandler::get_or_create(&db, current_user_uid, session_type, target_id).await
+                    ChatSessionHandler::get_or_create(db, current_user_uid, session_type, target_id).await
                 {
                     // 更新会话的最后消息
-                    let _ = ChatSessionHandler::update_last_message(&db, session.sid, message.mid).await;
+                    let _ = ChatSessionHandler::update_last_message(db, session.sid, message.mid).await;
 
                     // 增加未读计数
-                    let _ = ChatSessionHandler::increment_unread(&db, session.sid).await;
+                    let _ = ChatSessionHandler::increment_unread(db, session.sid).await;
+
+                    // 提及了本机用户时另外计数并通知
+                    if mentions_user(&mentions, current_user_uid) {
+                        let _ = ChatSessionHandler::increment_mention(db, session.sid).await;
+                        crate::event::bus::EVENT_SENDER
+                            .send(AppEvent::Ui(UiEvent::Mentioned {
+                                gid: target_id,
+                                msg_id: message.mid,
+                                mention_all: !mentions.contains(&Some(current_user_uid)),
+                            }))
+                            .unwrap_or_else(|e| error!("发送提及事件失败: {}", e));
+                    }
                 }
 
                 // 如果需要发送确认
@@ -196,13 +246,7 @@ impl MessageReceiver {
         // 尝试通过 IP 查找用户
         // 这里简化处理，实际可能需要更复杂的查询逻辑
         // 暂时生成一个基于 IP:port 的用户 ID
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-
-        let machine_id = format!("{}:{}", ip, port);
-        let mut hasher = DefaultHasher::new();
-        machine_id.hash(&mut hasher);
-        let uid = (hasher.finish() % 9000000000000000000 + 1000000000000000000) as i64;
+        let uid = sender_uid_for(ip, port);
 
         // 检查用户是否存在，不存在则创建
         match UserHandler::find_by_id(db, uid).await {
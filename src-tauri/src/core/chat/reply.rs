@@ -0,0 +1,134 @@
+// src-tauri/src/core/chat/reply.rs
+//
+//! 引用回复
+//!
+//! 回复消息通过原消息编号引用原消息，并附带一段引用摘要，
+//! 没有原消息的客户端（包括不识别引用的客户端）仍能看到上下文。
+//! 返回给前端的消息会把引用解析为本地的原消息。
+
+use crate::core::chat::revision::EDIT_STATE_RECALLED;
+use crate::core::chat::ChatService;
+use crate::database::handler::ChatMessageHandler;
+use crate::database::model::chat_message;
+use crate::error::AppResult;
+use crate::types::{ChatMessage, QuotedMessage};
+use sea_orm::DbConn;
+use std::collections::HashMap;
+
+/// 引用摘要的最大字符数
+pub const QUOTE_MAX_CHARS: usize = 30;
+
+/// 生成引用摘要：合并为单行，超长时截断并加省略号
+pub fn quote_excerpt(content: &str) -> String {
+    let line = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() <= QUOTE_MAX_CHARS {
+        line
+    } else {
+        let mut excerpt: String = line.chars().take(QUOTE_MAX_CHARS).collect();
+        excerpt.push('…');
+        excerpt
+    }
+}
+
+/// 构造引用信息
+///
+/// # 参数
+/// - `message`: 回复消息
+/// - `original`: 本地的原消息（找不到时为 None）
+pub fn quoted_message(message: &chat_message::Model, original: Option<&chat_message::Model>) -> Option<QuotedMessage> {
+    match original {
+        Some(original) => Some(QuotedMessage {
+            mid: Some(original.mid),
+            sender_uid: Some(original.sender_uid),
+            content: original.content.clone(),
+            is_revoked: original.edit_state == EDIT_STATE_RECALLED,
+        }),
+        None => message.reply_quote.as_ref().map(|quote| QuotedMessage {
+            mid: None,
+            sender_uid: None,
+            content: quote.clone(),
+            is_revoked: false,
+        }),
+    }
+}
+
+/// 转换为前端消息，并把引用解析为本地的原消息
+pub async fn resolve_replies(db: &DbConn, messages: Vec<chat_message::Model>) -> AppResult<Vec<ChatMessage>> {
+    let reply_mids: Vec<i64> = messages.iter().filter_map(|m| m.reply_to_mid).collect();
+    let originals: HashMap<i64, chat_message::Model> = ChatMessageHandler::find_by_ids(db, reply_mids)
+        .await?
+        .into_iter()
+        .map(|m| (m.mid, m))
+        .collect();
+
+    Ok(messages
+        .into_iter()
+        .map(|m| {
+            let original = m.reply_to_mid.and_then(|mid| originals.get(&mid));
+            let reply_to = quoted_message(&m, original);
+            ChatMessage {
+                reply_to,
+                ..ChatService::to_frontend_message(m)
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(mid: i64, content: &str) -> chat_message::Model {
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 3, 3)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        chat_message::Model {
+            mid,
+            session_type: 0,
+            target_id: 2,
+            sender_uid: 1,
+            msg_type: 0,
+            content: content.to_string(),
+            send_time: now.to_string(),
+            status: 1,
+            msg_no: None,
+            edit_state: 0,
+            reply_to_mid: None,
+            reply_quote: None,
+            group_msg_no: None,
+            create_time: now,
+            update_time: now,
+        }
+    }
+
+    #[test]
+    fn test_quote_excerpt() {
+        assert_eq!(quote_excerpt("明天\n  10 点开会"), "明天 10 点开会");
+
+        let long = "很".repeat(QUOTE_MAX_CHARS + 5);
+        let excerpt = quote_excerpt(&long);
+        assert_eq!(excerpt.chars().count(), QUOTE_MAX_CHARS + 1);
+        assert!(excerpt.ends_with('…'));
+    }
+
+    #[test]
+    fn test_quoted_message() {
+        let mut reply = message(2, "好的");
+        reply.reply_to_mid = Some(1);
+        reply.reply_quote = Some("明天开会".to_string());
+
+        let mut original = message(1, "");
+        original.edit_state = EDIT_STATE_RECALLED;
+        let quoted = quoted_message(&reply, Some(&original)).unwrap();
+        assert_eq!(quoted.mid, Some(1));
+        assert!(quoted.is_revoked);
+
+        // 原消息不在本地时使用引用摘要
+        let quoted = quoted_message(&reply, None).unwrap();
+        assert_eq!(quoted.mid, None);
+        assert_eq!(quoted.content, "明天开会");
+
+        assert!(quoted_message(&message(3, "普通消息"), None).is_none());
+    }
+}
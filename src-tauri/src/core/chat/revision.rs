@@ -0,0 +1,445 @@
+// src-tauri/src/core/chat/revision.rs
+//
+//! 消息撤回与编辑
+//!
+//! 发送者可以在撤回时限内撤回自己发出的消息，也可以修改消息内容。
+//! 声明了 `FEIQ_CAP_MSGEDIT` 的对端收到携带原消息编号的撤回/编辑包后就地修订消息，
+//! 其他客户端只会收到一条普通文本提示。每次修订都记入消息修订历史。
+//!
+//! 修订总是先在本地生效再通知对端。单聊对端离线或发送失败时，通知存入待补发表，
+//! 对方上线时补发；补发的撤回包保留撤回时的时间戳。
+//!
+//! 撤回包携带发送方的撤回时限，接收方按撤回时刻与该时限判断撤回是否有效；
+//! 不携带时限的旧版本撤回包按协议默认时限 [`DEFAULT_RECALL_WINDOW_SECS`] 判断。
+
+use crate::core::chat::receiver::sender_uid_for;
+use crate::core::group::GroupBroadcaster;
+use crate::database::handler::{ChatMessageHandler, ChatMessageRevisionHandler, PendingRevisionHandler, UserHandler};
+use crate::database::model::{chat_message, chat_message_revision, pending_revision};
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_MSGEDIT;
+use crate::network::feiq::model::FeiQPacket;
+use crate::network::feiq::utils::timestamp_to_local;
+use crate::network::udp::sender;
+use chrono::NaiveDateTime;
+use sea_orm::DbConn;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{error, info, warn};
+
+/// 默认撤回时限（秒），也是对端撤回包未携带时限时使用的协议默认值
+pub const DEFAULT_RECALL_WINDOW_SECS: u64 = 120;
+
+/// 修订状态：已编辑
+pub const EDIT_STATE_EDITED: i8 = 1;
+
+/// 修订状态：已撤回
+pub const EDIT_STATE_RECALLED: i8 = 2;
+
+static RECALL_WINDOW_SECS: AtomicU64 = AtomicU64::new(DEFAULT_RECALL_WINDOW_SECS);
+
+/// 当前撤回时限（秒，0 表示不限制）
+pub fn recall_window_secs() -> u64 {
+    RECALL_WINDOW_SECS.load(Ordering::Relaxed)
+}
+
+/// 设置撤回时限（秒，0 表示不限制）
+pub fn set_recall_window_secs(secs: u64) {
+    RECALL_WINDOW_SECS.store(secs, Ordering::Relaxed);
+}
+
+/// 消息是否仍在撤回时限内
+///
+/// # 参数
+/// - `sent_at`: 消息创建时间
+/// - `now`: 当前时间
+/// - `window_secs`: 撤回时限（秒，0 表示不限制）
+pub fn within_recall_window(sent_at: NaiveDateTime, now: NaiveDateTime, window_secs: u64) -> bool {
+    window_secs == 0 || (now - sent_at).num_seconds() <= window_secs as i64
+}
+
+/// 修订通知
+struct RevisionNotice<'a> {
+    msg_no: &'a str,
+    edit_state: i8,
+    content: &'a str,
+    /// 撤回时限（秒，0 表示不限制）
+    window_secs: u64,
+    /// 修订时间（秒级时间戳）
+    revised_at: i64,
+}
+
+impl RevisionNotice<'_> {
+    /// 构造撤回/编辑包及不支持扩展的对端收到的文本提示包
+    ///
+    /// 两个包都使用修订时间作为时间戳，补发时对端仍按修订时刻判断撤回时限
+    fn packets(&self) -> (FeiQPacket, FeiQPacket) {
+        let mut packet = if self.edit_state == EDIT_STATE_RECALLED {
+            FeiQPacket::make_feiq_recall_packet(self.msg_no, self.window_secs, None)
+        } else {
+            FeiQPacket::make_feiq_edit_packet(self.msg_no, self.content, None)
+        };
+        let mut fallback = FeiQPacket::make_feiq_message_packet(&fallback_notice(self.edit_state, self.content), None);
+        for packet in [&mut packet, &mut fallback] {
+            packet.ext_info.timestamp = self.revised_at;
+            packet.ext_info.timestamp_local = timestamp_to_local(self.revised_at);
+        }
+        (packet, fallback)
+    }
+}
+
+impl<'a> From<&'a pending_revision::Model> for RevisionNotice<'a> {
+    fn from(pending: &'a pending_revision::Model) -> Self {
+        RevisionNotice {
+            msg_no: &pending.msg_no,
+            edit_state: pending.action,
+            content: &pending.content,
+            window_secs: pending.window_secs.max(0) as u64,
+            revised_at: pending.revise_time.and_utc().timestamp(),
+        }
+    }
+}
+
+/// 不支持撤回/编辑的对端收到的文本提示
+pub fn fallback_notice(edit_state: i8, content: &str) -> String {
+    if edit_state == EDIT_STATE_RECALLED {
+        "[撤回了一条消息]".to_string()
+    } else {
+        format!("[修改了一条消息] {}", content)
+    }
+}
+
+/// 消息修订服务
+pub struct MessageRevisionService;
+
+impl MessageRevisionService {
+    /// 撤回自己发送的消息
+    ///
+    /// # 参数
+    /// - `mid`: 消息 ID
+    /// - `operator_uid`: 操作者用户 ID（必须是消息发送者）
+    ///
+    /// # 返回
+    /// 撤回后的消息
+    pub async fn recall_message(db: &DbConn, mid: i64, operator_uid: i64) -> AppResult<chat_message::Model> {
+        let message = Self::own_message(db, mid, operator_uid).await?;
+
+        let window = recall_window_secs();
+        if !within_recall_window(message.create_time, chrono::Utc::now().naive_utc(), window) {
+            return Err(AppError::Business(format!("消息发送已超过 {} 秒，无法撤回", window)));
+        }
+
+        let revised = Self::apply(db, message, EDIT_STATE_RECALLED, String::new(), operator_uid).await?;
+        Self::notify_peers(db, &revised, EDIT_STATE_RECALLED, "", window).await;
+        Ok(revised)
+    }
+
+    /// 编辑自己发送的文本消息
+    ///
+    /// # 参数
+    /// - `mid`: 消息 ID
+    /// - `operator_uid`: 操作者用户 ID（必须是消息发送者）
+    /// - `content`: 新内容
+    ///
+    /// # 返回
+    /// 编辑后的消息
+    pub async fn edit_message(
+        db: &DbConn,
+        mid: i64,
+        operator_uid: i64,
+        content: String,
+    ) -> AppResult<chat_message::Model> {
+        if content.trim().is_empty() {
+            return Err(AppError::Business("消息内容不能为空".to_string()));
+        }
+
+        let message = Self::own_message(db, mid, operator_uid).await?;
+        if message.msg_type != 0 {
+            return Err(AppError::Business("只能编辑文本消息".to_string()));
+        }
+        if message.content == content {
+            return Ok(message);
+        }
+
+        let revised = Self::apply(db, message, EDIT_STATE_EDITED, content.clone(), operator_uid).await?;
+        Self::notify_peers(db, &revised, EDIT_STATE_EDITED, &content, 0).await;
+        Ok(revised)
+    }
+
+    /// 获取消息的修订历史
+    pub async fn get_history(db: &DbConn, mid: i64) -> AppResult<Vec<chat_message_revision::Model>> {
+        ChatMessageRevisionHandler::list_by_message(db, mid).await
+    }
+
+    /// 应用对端发来的撤回/编辑
+    ///
+    /// 只修订该发送者自己发出的消息，撤回同样受撤回时限限制；找不到消息时只记录日志
+    ///
+    /// # 参数
+    /// - `sender_ip` / `sender_port`: 对端地址
+    /// - `msg_no`: 原消息编号
+    /// - `edit_state`: 修订状态（已编辑/已撤回）
+    /// - `content`: 新内容（撤回时为空）
+    /// - `sent_at`: 修订包的发送时间（秒级时间戳；0 或晚于当前时间时按当前时间计）
+    /// - `window_secs`: 发送方的撤回时限（秒，0 表示不限制；None 时按 [`DEFAULT_RECALL_WINDOW_SECS`]）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn apply_remote(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        msg_no: &str,
+        edit_state: i8,
+        content: String,
+        sent_at: i64,
+        window_secs: Option<u64>,
+    ) {
+        if msg_no.is_empty() || (edit_state == EDIT_STATE_EDITED && content.trim().is_empty()) {
+            warn!("无效的消息修订包: from={}, msg_no={}", sender_ip, msg_no);
+            return;
+        }
+
+        match Self::find_remote(db, sender_ip, sender_port, msg_no).await {
+            Ok(Some(message)) => {
+                if edit_state == EDIT_STATE_RECALLED {
+                    let now = chrono::Utc::now().naive_utc();
+                    let recalled_at = chrono::DateTime::from_timestamp(sent_at, 0)
+                        .map(|t| t.naive_utc())
+                        .filter(|t| sent_at > 0 && *t <= now)
+                        .unwrap_or(now);
+                    let window = window_secs.unwrap_or(DEFAULT_RECALL_WINDOW_SECS);
+                    if !within_recall_window(message.create_time, recalled_at, window) {
+                        warn!("撤回已超过撤回时限，忽略: from={}, msg_no={}", sender_ip, msg_no);
+                        return;
+                    }
+                }
+                let sender_uid = message.sender_uid;
+                if let Err(e) = Self::apply(db, message, edit_state, content, sender_uid).await {
+                    warn!("应用消息修订失败: msg_no={}, {}", msg_no, e);
+                }
+            }
+            Ok(None) => warn!("找不到被修订的消息: from={}, msg_no={}", sender_ip, msg_no),
+            Err(e) => error!("查找被修订的消息失败: {}", e),
+        }
+    }
+
+    /// 查找对端发送的消息
+    ///
+    /// 单聊消息以对端地址派生的 ID 记录发送者，群消息以发送者的用户 ID 记录
+    async fn find_remote(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        msg_no: &str,
+    ) -> AppResult<Option<chat_message::Model>> {
+        let peer_uid = sender_uid_for(sender_ip, sender_port);
+        if let Some(message) = ChatMessageHandler::find_by_sender_and_msg_no(db, peer_uid, msg_no).await? {
+            return Ok(Some(message));
+        }
+
+        match UserHandler::find_by_machine_id(db, &format!("{}:{}", sender_ip, sender_port)).await? {
+            Some(user) if user.uid != peer_uid => {
+                ChatMessageHandler::find_by_sender_and_msg_no(db, user.uid, msg_no).await
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// 查找操作者自己发送的、可修订的消息
+    async fn own_message(db: &DbConn, mid: i64, operator_uid: i64) -> AppResult<chat_message::Model> {
+        let message = ChatMessageHandler::find_by_id(db, mid).await?;
+
+        if message.sender_uid != operator_uid {
+            return Err(AppError::Business("只能修改自己发送的消息".to_string()));
+        }
+        if message.edit_state == EDIT_STATE_RECALLED {
+            return Err(AppError::Business("消息已撤回".to_string()));
+        }
+        if message.msg_no.is_none() {
+            return Err(AppError::Business("消息缺少消息编号，对方无法定位该消息".to_string()));
+        }
+
+        Ok(message)
+    }
+
+    /// 修订本地消息并记录历史
+    async fn apply(
+        db: &DbConn,
+        message: chat_message::Model,
+        edit_state: i8,
+        content: String,
+        actor_uid: i64,
+    ) -> AppResult<chat_message::Model> {
+        if message.edit_state == EDIT_STATE_RECALLED {
+            return Err(AppError::Business("消息已撤回".to_string()));
+        }
+
+        let new_content = (edit_state == EDIT_STATE_EDITED).then(|| content.clone());
+        ChatMessageRevisionHandler::create(db, message.mid, edit_state, message.content, new_content, actor_uid)
+            .await?;
+        let revised = ChatMessageHandler::revise(db, message.mid, content, edit_state).await?;
+        info!("消息已修订: mid={}, edit_state={}", revised.mid, edit_state);
+
+        let event = if edit_state == EDIT_STATE_RECALLED {
+            UiEvent::MessageRecalled { msg_id: revised.mid }
+        } else {
+            UiEvent::MessageEdited {
+                msg_id: revised.mid,
+                content: revised.content.clone(),
+            }
+        };
+        let _ = EVENT_SENDER.send(AppEvent::Ui(event));
+
+        Ok(revised)
+    }
+
+    /// 通知会话对端
+    ///
+    /// 支持扩展的对端收到撤回/编辑包，其余对端收到文本提示。修订已在本地生效，
+    /// 通知失败只记录日志；单聊对端离线或发送失败时通知存入待补发表
+    async fn notify_peers(db: &DbConn, message: &chat_message::Model, edit_state: i8, content: &str, window_secs: u64) {
+        let notice = RevisionNotice {
+            msg_no: message.msg_no.as_deref().unwrap_or_default(),
+            edit_state,
+            content,
+            window_secs,
+            revised_at: chrono::Utc::now().timestamp(),
+        };
+
+        if message.session_type != 0 {
+            let (packet, fallback) = notice.packets();
+            match GroupBroadcaster::broadcast_by_capability(
+                db,
+                message.target_id,
+                FEIQ_CAP_MSGEDIT,
+                &packet,
+                Some(&fallback),
+                message.sender_uid,
+            )
+            .await
+            {
+                Ok(sent_count) => info!("消息修订已广播到 {} 个成员", sent_count),
+                Err(e) => warn!("广播消息修订失败: msg_no={}, {}", notice.msg_no, e),
+            }
+            return;
+        }
+
+        let target_user = match UserHandler::find_by_id(db, message.target_id).await {
+            Ok(user) => user,
+            Err(e) => {
+                warn!("找不到修订通知的目标用户 {}: {}", message.target_id, e);
+                return;
+            }
+        };
+        if target_user.status == 1 {
+            match Self::send_notice(&target_user.feiq_ip, target_user.feiq_port, &notice).await {
+                Ok(()) => return,
+                Err(e) => warn!("{}，待对方上线后重发: msg_no={}", e, notice.msg_no),
+            }
+        } else {
+            info!("对方不在线，修订通知待对方上线后发送: msg_no={}", notice.msg_no);
+        }
+
+        if let Err(e) = PendingRevisionHandler::upsert(
+            db,
+            target_user.uid,
+            notice.msg_no,
+            edit_state,
+            content,
+            window_secs as i64,
+            chrono::DateTime::from_timestamp(notice.revised_at, 0)
+                .map(|t| t.naive_utc())
+                .unwrap_or_else(|| chrono::Utc::now().naive_utc()),
+        )
+        .await
+        {
+            error!("保存待补发的修订通知失败: msg_no={}, {}", notice.msg_no, e);
+        }
+    }
+
+    /// 向单聊对端发送修订通知
+    async fn send_notice(ip: &str, port: u16, notice: &RevisionNotice<'_>) -> AppResult<()> {
+        let (packet, fallback) = notice.packets();
+        let packet = if peer_supports(ip, FEIQ_CAP_MSGEDIT) {
+            &packet
+        } else {
+            &fallback
+        };
+        sender::send_packet(&format!("{}:{}", ip, port), packet)
+            .await
+            .map_err(|e| AppError::Network(format!("发送消息修订失败: {}", e)))
+    }
+
+    /// 用户上线时补发其离线期间的修订通知
+    ///
+    /// 发送成功的通知从待补发表删除，发送失败时保留剩余通知，下次上线再试
+    ///
+    /// # 参数
+    /// - `machine_id`: 上线用户的机器标识 "ip:port"
+    pub async fn send_pending(db: &DbConn, machine_id: &str) -> AppResult<()> {
+        let user = match UserHandler::find_by_machine_id(db, machine_id).await? {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+        let pending = PendingRevisionHandler::list_by_target(db, user.uid).await?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        for revision in &pending {
+            Self::send_notice(&user.feiq_ip, user.feiq_port, &RevisionNotice::from(revision)).await?;
+            PendingRevisionHandler::delete(db, revision.id).await?;
+        }
+
+        info!("已补发 {} 条消息修订通知: {}", pending.len(), machine_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_recall_window() {
+        let sent_at = chrono::NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        assert!(within_recall_window(
+            sent_at,
+            sent_at + chrono::Duration::seconds(120),
+            120
+        ));
+        assert!(!within_recall_window(
+            sent_at,
+            sent_at + chrono::Duration::seconds(121),
+            120
+        ));
+        assert!(within_recall_window(sent_at, sent_at + chrono::Duration::days(30), 0));
+    }
+
+    #[test]
+    fn test_revision_notice_packets_keep_revise_time() {
+        let notice = RevisionNotice {
+            msg_no: "T0000000042",
+            edit_state: EDIT_STATE_RECALLED,
+            content: "",
+            window_secs: 300,
+            revised_at: 1700000000,
+        };
+        let (packet, fallback) = notice.packets();
+        assert_eq!(packet.recall_info(), ("T0000000042".to_string(), Some(300)));
+        assert_eq!(packet.ext_info.timestamp, 1700000000);
+        assert_eq!(fallback.ext_info.timestamp, 1700000000);
+        assert_eq!(fallback.ext_info.remark, "[撤回了一条消息]");
+    }
+
+    #[test]
+    fn test_fallback_notice() {
+        assert_eq!(fallback_notice(EDIT_STATE_RECALLED, ""), "[撤回了一条消息]");
+        assert_eq!(fallback_notice(EDIT_STATE_EDITED, "明天见"), "[修改了一条消息] 明天见");
+    }
+}
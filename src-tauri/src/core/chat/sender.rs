@@ -79,6 +79,7 @@ impl MessageSender {
 
         // 5. 构造消息包
         let packet = FeiQPacket::make_feiq_message_packet(&content, None);
+        ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
         let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
 
         // 6. 发送 UDP 消息
@@ -138,10 +139,11 @@ impl MessageSender {
 
         // 4. 构造消息包
         let packet = FeiQPacket::make_feiq_message_packet(&content, None);
+        ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
 
-        // 5. 使用 GroupBroadcaster 广播消息
-        use crate::core::group::GroupBroadcaster;
-        let sent_count = GroupBroadcaster::broadcast_message(db, group_id, &packet, sender_uid).await?;
+        // 5. 加上群消息信封并广播给群成员
+        use crate::core::group::GroupEnvelopeService;
+        let sent_count = GroupEnvelopeService::broadcast(db, mid, &packet).await?;
 
         info!("群消息已广播到 {} 个成员", sent_count);
 
@@ -179,6 +181,7 @@ impl MessageSender {
             // 单聊
             let target_user = UserHandler::find_by_id(db, message.target_id).await?;
             let packet = FeiQPacket::make_feiq_message_packet(&message.content, None);
+            ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
             let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
 
             sender::send_packet(&addr, &packet)
@@ -190,8 +193,9 @@ impl MessageSender {
         } else {
             // 群聊
             let packet = FeiQPacket::make_feiq_message_packet(&message.content, None);
-            use crate::core::group::GroupBroadcaster;
-            GroupBroadcaster::broadcast_message(db, message.target_id, &packet, message.sender_uid).await?;
+            ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
+            use crate::core::group::GroupEnvelopeService;
+            GroupEnvelopeService::broadcast(db, mid, &packet).await?;
 
             // 更新状态为已发送
             ChatMessageHandler::update_status(db, mid, 1).await?;
@@ -7,7 +7,13 @@
 //! - 删除消息
 //! - 管理聊天会话
 
+use crate::core::chat::mention::MentionService;
+use crate::core::chat::poll::{make_poll_no, PollService};
+use crate::core::chat::reaction::ReactionService;
+use crate::core::chat::reply::{quote_excerpt, resolve_replies};
+use crate::core::chat::revision::{EDIT_STATE_EDITED, EDIT_STATE_RECALLED};
 use crate::database::handler::{ChatMessageHandler, ChatSessionHandler, UserHandler};
+use crate::database::model::chat_message;
 use crate::error::{AppError, AppResult};
 use crate::network::feiq::model::FeiQPacket;
 use crate::network::udp::sender;
@@ -38,6 +44,132 @@ impl ChatService {
         sender_uid: i64,
         content: String,
         msg_type: i8,
+    ) -> AppResult<i64> {
+        Self::send(db, session_type, target_id, sender_uid, content, msg_type, None, &[]).await
+    }
+
+    /// 发送带 @ 提及的群消息
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `sender_uid`: 发送者用户ID
+    /// - `content`: 消息内容
+    /// - `mention_uids`: 被提及的成员
+    /// - `mention_all`: 是否 @所有人（需要群主或管理员权限）
+    ///
+    /// # 返回
+    /// 返回新创建的消息ID
+    pub async fn send_with_mentions(
+        db: &DbConn,
+        gid: i64,
+        sender_uid: i64,
+        content: String,
+        mention_uids: Vec<i64>,
+        mention_all: bool,
+    ) -> AppResult<i64> {
+        let mentions = MentionService::validate(db, gid, sender_uid, &mention_uids, mention_all).await?;
+        Self::send(db, 1, gid, sender_uid, content, 0, None, &mentions).await
+    }
+
+    /// 发送引用回复
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `session_type`: 会话类型（0=单聊, 1=群聊）
+    /// - `target_id`: 目标ID（用户ID或群组ID）
+    /// - `sender_uid`: 发送者用户ID
+    /// - `content`: 回复内容
+    /// - `reply_to_mid`: 被回复的消息ID
+    ///
+    /// # 返回
+    /// 返回新创建的消息ID
+    pub async fn send_reply(
+        db: &DbConn,
+        session_type: i8,
+        target_id: i64,
+        sender_uid: i64,
+        content: String,
+        reply_to_mid: i64,
+    ) -> AppResult<i64> {
+        let original = ChatMessageHandler::find_by_id(db, reply_to_mid).await?;
+        if original.edit_state == EDIT_STATE_RECALLED {
+            return Err(AppError::Business("不能回复已撤回的消息".to_string()));
+        }
+
+        Self::send(db, session_type, target_id, sender_uid, content, 0, Some(original), &[]).await
+    }
+
+    /// 在群聊中发起投票
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `sender_uid`: 发起者用户ID
+    /// - `question`: 投票问题
+    /// - `options`: 投票选项
+    /// - `multiple`: 是否多选
+    /// - `anonymous`: 是否匿名
+    /// - `deadline`: 截止时间（Unix 时间戳，秒）
+    ///
+    /// # 返回
+    /// 返回新创建的消息ID
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_poll(
+        db: &DbConn,
+        gid: i64,
+        sender_uid: i64,
+        question: String,
+        options: Vec<String>,
+        multiple: bool,
+        anonymous: bool,
+        deadline: Option<i64>,
+    ) -> AppResult<i64> {
+        use crate::core::group::GroupEnvelopeService;
+        use crate::database::handler::group::GroupMemberHandler;
+
+        if GroupMemberHandler::find_by_group_and_member(db, gid, sender_uid)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::Permission("不是群成员".to_string()));
+        }
+
+        let sender = UserHandler::find_by_id(db, sender_uid).await?;
+        let poll_no = make_poll_no(&sender.feiq_machine_id, chrono::Utc::now().naive_utc());
+        let spec = PollService::build_spec(poll_no, &question, &options, multiple, anonymous, deadline)?;
+
+        // 1. 创建投票消息记录，内容为投票问题
+        let message =
+            ChatMessageHandler::create(db, 1, gid, sender_uid, spec.question.clone(), MessageType::Poll as i8).await?;
+        let mid = message.mid;
+        PollService::record(db, mid, gid, sender_uid, &spec).await?;
+
+        // 2. 更新会话的最后消息
+        let session = ChatSessionHandler::get_or_create(db, sender_uid, 1, gid).await?;
+        ChatSessionHandler::update_last_message(db, session.sid, mid).await?;
+
+        // 3. 广播投票
+        let packet = FeiQPacket::make_feiq_poll_packet(&spec, None);
+        ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
+        let sent_count = GroupEnvelopeService::broadcast(db, mid, &packet).await?;
+        info!("投票已广播到 {} 个成员: mid={}, poll_no={}", sent_count, mid, spec.poll_no);
+
+        ChatMessageHandler::update_status(db, mid, 1).await?;
+        Ok(mid)
+    }
+
+    /// 创建消息记录并发送（`reply_to` 为被回复的原消息，`mentions` 为群消息中的提及）
+    #[allow(clippy::too_many_arguments)]
+    async fn send(
+        db: &DbConn,
+        session_type: i8,
+        target_id: i64,
+        sender_uid: i64,
+        content: String,
+        msg_type: i8,
+        reply_to: Option<chat_message::Model>,
+        mentions: &[Option<i64>],
     ) -> AppResult<i64> {
         // 1. 创建消息记录（状态：发送中 = 0）
         let message = ChatMessageHandler::create(db, session_type, target_id, sender_uid, content.clone(), msg_type)
@@ -50,6 +182,13 @@ impl ChatService {
         let mid = message.mid;
         info!("消息记录已创建: mid={}", mid);
 
+        if let Some(original) = &reply_to {
+            ChatMessageHandler::set_reply(db, mid, Some(original.mid), quote_excerpt(&original.content)).await?;
+        }
+        if !mentions.is_empty() {
+            MentionService::record(db, mid, target_id, mentions).await?;
+        }
+
         // 2. 获取或创建会话
         let session = ChatSessionHandler::get_or_create(db, sender_uid, session_type, target_id)
             .await
@@ -81,8 +220,9 @@ impl ChatService {
                 return Err(AppError::Business("目标用户不在线".to_string()));
             }
 
-            // 构造消息包
-            let packet = FeiQPacket::make_feiq_message_packet(&content, None);
+            // 构造消息包，记录消息编号以便之后撤回/编辑
+            let packet = Self::make_packet(&content, reply_to.as_ref());
+            ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
             let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
 
             // 发送 UDP 消息
@@ -102,10 +242,11 @@ impl ChatService {
             }
         } else {
             // 群聊
-            let packet = FeiQPacket::make_feiq_message_packet(&content, None);
+            let packet = Self::make_packet(&content, reply_to.as_ref());
+            ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id).await?;
 
-            use crate::core::group::GroupBroadcaster;
-            let sent_count = GroupBroadcaster::broadcast_message(db, target_id, &packet, sender_uid).await?;
+            use crate::core::group::GroupEnvelopeService;
+            let sent_count = GroupEnvelopeService::broadcast(db, mid, &packet).await?;
 
             info!("群消息已广播到 {} 个成员", sent_count);
 
@@ -116,6 +257,19 @@ impl ChatService {
         }
     }
 
+    /// 构造消息包，回复消息带上原消息编号和引用摘要
+    fn make_packet(content: &str, reply_to: Option<&chat_message::Model>) -> FeiQPacket {
+        match reply_to {
+            Some(original) => FeiQPacket::make_feiq_reply_packet(
+                content,
+                original.msg_no.as_deref().unwrap_or_default(),
+                &quote_excerpt(&original.content),
+                None,
+            ),
+            None => FeiQPacket::make_feiq_message_packet(content, None),
+        }
+    }
+
     /// 获取会话的消息列表
     ///
     /// # 参数
@@ -136,37 +290,56 @@ impl ChatService {
     ) -> AppResult<Vec<ChatMessage>> {
         let messages = ChatMessageHandler::find_by_session_paged(db, session_type, target_id, page, page_size).await?;
 
-        // 转换为前端类型
-        let result: Vec<ChatMessage> = messages
-            .into_iter()
-            .map(|m| ChatMessage {
-                mid: m.mid,
-                session_type: if m.session_type == 0 {
-                    SessionType::Single
-                } else {
-                    SessionType::Group
-                },
-                target_id: m.target_id,
-                sender_uid: m.sender_uid,
-                msg_type: match m.msg_type {
-                    0 => MessageType::Text,
-                    1 => MessageType::File,
-                    2 => MessageType::Emoji,
-                    _ => MessageType::Text,
-                },
-                content: m.content,
-                send_time: m.send_time,
-                status: match m.status {
-                    -1 => MessageStatus::Failed,
-                    0 => MessageStatus::Sending,
-                    1 => MessageStatus::Sent,
-                    2 => MessageStatus::Read,
-                    _ => MessageStatus::Sending,
-                },
-            })
-            .collect();
+        // 转换为前端类型，并解析引用回复、投票和表情回应
+        let mut messages = resolve_replies(db, messages).await?;
+        PollService::attach(db, &mut messages).await?;
+        ReactionService::attach(db, &mut messages).await?;
+        Ok(messages)
+    }
 
-        Ok(result)
+    /// 获取回复了指定消息的所有消息（按时间正序）
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `mid`: 被回复的消息ID
+    pub async fn get_replies(db: &DbConn, mid: i64) -> AppResult<Vec<ChatMessage>> {
+        let replies = ChatMessageHandler::find_replies(db, mid).await?;
+        resolve_replies(db, replies).await
+    }
+
+    /// 转换为前端消息类型（不解析引用回复）
+    pub fn to_frontend_message(m: chat_message::Model) -> ChatMessage {
+        ChatMessage {
+            mid: m.mid,
+            session_type: if m.session_type == 0 {
+                SessionType::Single
+            } else {
+                SessionType::Group
+            },
+            target_id: m.target_id,
+            sender_uid: m.sender_uid,
+            msg_type: match m.msg_type {
+                0 => MessageType::Text,
+                1 => MessageType::File,
+                2 => MessageType::Emoji,
+                6 => MessageType::Poll,
+                _ => MessageType::Text,
+            },
+            content: m.content,
+            send_time: m.send_time,
+            status: match m.status {
+                -1 => MessageStatus::Failed,
+                0 => MessageStatus::Sending,
+                1 => MessageStatus::Sent,
+                2 => MessageStatus::Read,
+                _ => MessageStatus::Sending,
+            },
+            edited: m.edit_state == EDIT_STATE_EDITED,
+            is_revoked: m.edit_state == EDIT_STATE_RECALLED,
+            reply_to: None,
+            poll: None,
+            reactions: Vec::new(),
+        }
     }
 
     /// 标记消息已读
@@ -228,6 +401,7 @@ impl ChatService {
                 target_id: s.target_id,
                 last_msg_id: s.last_msg_id,
                 unread_count: s.unread_count,
+                mention_count: s.mention_count,
                 update_time: s.update_time.format("%Y-%m-%d %H:%M:%S").to_string(),
             })
             .collect();
@@ -0,0 +1,340 @@
+// src-tauri/src/core/chat/typing.rs
+//
+//! 正在输入提示
+//!
+//! 输入状态只发给声明了 `FEIQ_CAP_TYPING` 的对端，不产生消息记录，也不影响未读计数。
+//! 发送端对"正在输入"限流，同一会话每隔 `TYPING_RESEND_INTERVAL` 才重发一次；
+//! 接收端丢弃同一来源在 `TYPING_RECV_MIN_INTERVAL` 内重复的状态，
+//! 在 `TYPING_EXPIRE` 内没有收到刷新时自动变为停止输入（每个输入者只有一个过期检查任务）。
+
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::{GroupBroadcaster, GroupEnvelopeService};
+use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
+use crate::database::handler::UserHandler;
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_TYPING;
+use crate::network::feiq::model::FeiQPacket;
+use crate::network::udp::sender;
+use sea_orm::DbConn;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// 同一会话重发"正在输入"的最小间隔
+pub const TYPING_RESEND_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 没有收到刷新时"正在输入"的有效期
+pub const TYPING_EXPIRE: Duration = Duration::from_secs(8);
+
+/// 接收端处理同一来源相同输入状态的最小间隔
+pub const TYPING_RECV_MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 是否需要发送输入状态
+///
+/// # 参数
+/// - `last`: 该会话上次发送的时间和状态
+/// - `typing`: 当前输入状态
+/// - `now`: 当前时间
+pub fn should_send(last: Option<SentState>, typing: bool, now: Instant) -> bool {
+    match last {
+        Some((sent_at, true)) if typing => now.duration_since(sent_at) >= TYPING_RESEND_INTERVAL,
+        Some((_, last_typing)) => typing || last_typing,
+        None => typing,
+    }
+}
+
+/// 是否处理收到的输入状态
+///
+/// 状态变化时总是处理；相同状态在 `TYPING_RECV_MIN_INTERVAL` 内重复到达时丢弃
+///
+/// # 参数
+/// - `last`: 该来源上次处理的时间和状态
+/// - `typing`: 收到的输入状态
+/// - `now`: 当前时间
+pub fn should_accept(last: Option<SentState>, typing: bool, now: Instant) -> bool {
+    match last {
+        Some((at, last_typing)) if last_typing == typing => now.duration_since(at) >= TYPING_RECV_MIN_INTERVAL,
+        _ => true,
+    }
+}
+
+/// 输入状态的键：(会话类型, 目标ID, 输入者ID)
+pub type TypingKey = (i8, i64, i64);
+
+/// 某会话上次发送（或某来源上次收到）的输入状态：(时间, 是否正在输入)
+type SentState = (Instant, bool);
+
+/// 输入状态的来源：(IP:端口, 群编号)
+type TypingSource = (String, Option<String>);
+
+/// 过期检查的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryCheck {
+    /// 仍在有效期内，等待指定时间后再检查
+    Pending(Duration),
+    /// 已过期，变为停止输入
+    Expired,
+    /// 已停止输入或已被新的一轮输入取代，检查任务结束
+    Stale,
+}
+
+/// 对端的输入状态
+///
+/// 每轮输入（从开始到停止或过期）有一个代号和一个过期检查任务，
+/// 期间的刷新只推迟过期时间，不再产生新的任务
+#[derive(Debug, Default)]
+pub struct TypingTracker {
+    next_generation: u64,
+    typing: HashMap<TypingKey, (u64, Instant)>,
+}
+
+impl TypingTracker {
+    /// 记录"正在输入"并推迟过期时间
+    ///
+    /// # 返回
+    /// 新的一轮输入开始时返回其代号（调用方通知界面并启动过期检查），刷新时返回 None
+    pub fn start(&mut self, key: TypingKey, now: Instant) -> Option<u64> {
+        let expires_at = now + TYPING_EXPIRE;
+        if let Some(entry) = self.typing.get_mut(&key) {
+            entry.1 = expires_at;
+            return None;
+        }
+
+        self.next_generation += 1;
+        self.typing.insert(key, (self.next_generation, expires_at));
+        Some(self.next_generation)
+    }
+
+    /// 记录"停止输入"，返回状态是否变化
+    pub fn stop(&mut self, key: TypingKey) -> bool {
+        self.typing.remove(&key).is_some()
+    }
+
+    /// 过期检查：到期时变为停止输入
+    pub fn check_expiry(&mut self, key: TypingKey, generation: u64, now: Instant) -> ExpiryCheck {
+        match self.typing.get(&key) {
+            Some((current, expires_at)) if *current == generation => {
+                if now >= *expires_at {
+                    self.typing.remove(&key);
+                    ExpiryCheck::Expired
+                } else {
+                    ExpiryCheck::Pending(*expires_at - now)
+                }
+            }
+            _ => ExpiryCheck::Stale,
+        }
+    }
+}
+
+fn sent_states() -> &'static Mutex<HashMap<(i8, i64), SentState>> {
+    static SENT: OnceLock<Mutex<HashMap<(i8, i64), SentState>>> = OnceLock::new();
+    SENT.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 每个来源 (IP:端口, 群编号) 上次处理的输入状态
+fn received_states() -> &'static Mutex<HashMap<TypingSource, SentState>> {
+    static RECEIVED: OnceLock<Mutex<HashMap<TypingSource, SentState>>> = OnceLock::new();
+    RECEIVED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn tracker() -> &'static Mutex<TypingTracker> {
+    static TRACKER: OnceLock<Mutex<TypingTracker>> = OnceLock::new();
+    TRACKER.get_or_init(|| Mutex::new(TypingTracker::default()))
+}
+
+fn notify((session_type, target_id, uid): TypingKey, typing: bool) {
+    let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::TypingChanged {
+        session_type,
+        target_id,
+        uid,
+        typing,
+    }));
+}
+
+/// 输入状态服务
+pub struct TypingService;
+
+impl TypingService {
+    /// 通知会话对端本端的输入状态（限流，重复的状态不发送）
+    ///
+    /// # 参数
+    /// - `session_type`: 会话类型（0=单聊, 1=群聊）
+    /// - `target_id`: 目标ID（用户ID或群组ID）
+    /// - `owner_uid`: 当前用户ID
+    /// - `typing`: 是否正在输入
+    pub async fn set_typing(
+        db: &DbConn,
+        session_type: i8,
+        target_id: i64,
+        owner_uid: i64,
+        typing: bool,
+    ) -> AppResult<()> {
+        {
+            let mut sent = sent_states().lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            if !should_send(sent.get(&(session_type, target_id)).copied(), typing, now) {
+                return Ok(());
+            }
+            sent.insert((session_type, target_id), (now, typing));
+        }
+
+        if session_type == 0 {
+            let target_user = UserHandler::find_by_id(db, target_id).await?;
+            if target_user.status != 1 || !peer_supports(&target_user.feiq_ip, FEIQ_CAP_TYPING) {
+                return Ok(());
+            }
+
+            let packet = FeiQPacket::make_feiq_typing_packet(typing, None, None);
+            let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
+            sender::send_packet(&addr, &packet)
+                .await
+                .map_err(|e| AppError::Network(format!("发送输入状态失败: {}", e)))?;
+        } else {
+            let group_no = GroupEnvelopeService::ensure_group_no(db, target_id).await?;
+            let packet = FeiQPacket::make_feiq_typing_packet(typing, Some(&group_no), None);
+            GroupBroadcaster::broadcast_by_capability(db, target_id, FEIQ_CAP_TYPING, &packet, None, owner_uid).await?;
+        }
+
+        debug!(
+            "输入状态已发送: session_type={}, target_id={}, typing={}",
+            session_type, target_id, typing
+        );
+        Ok(())
+    }
+
+    /// 处理对端发来的输入状态
+    ///
+    /// 群输入状态只接受群成员发来的；"正在输入"在有效期内没有刷新时自动变为停止输入。
+    /// 输入者按用户记录（与群成员相同）标识
+    pub async fn handle_remote(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        typing: bool,
+        group_no: Option<String>,
+    ) -> AppResult<()> {
+        let machine_id = format!("{}:{}", sender_ip, sender_port);
+        {
+            let mut received = received_states().lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let source = (machine_id.clone(), group_no.clone());
+            if !should_accept(received.get(&source).copied(), typing, now) {
+                return Ok(());
+            }
+            received.retain(|_, (at, _)| now.duration_since(*at) < TYPING_EXPIRE);
+            received.insert(source, (now, typing));
+        }
+
+        let uid = member_uid_for(db, &machine_id).await?;
+        let key = match group_no {
+            Some(group_no) => {
+                let group = match GroupHandler::find_by_group_no(db, &group_no).await? {
+                    Some(group) => group,
+                    None => return Ok(()),
+                };
+                if GroupMemberHandler::find_by_group_and_member(db, group.gid, uid)
+                    .await?
+                    .is_none()
+                {
+                    warn!(
+                        "忽略非群成员的输入状态: gid={}, from={}:{}",
+                        group.gid, sender_ip, sender_port
+                    );
+                    return Ok(());
+                }
+                (1, group.gid, uid)
+            }
+            None => (0, uid, uid),
+        };
+
+        if !typing {
+            if tracker().lock().unwrap_or_else(|e| e.into_inner()).stop(key) {
+                notify(key, false);
+            }
+            return Ok(());
+        }
+
+        let started = tracker().lock().unwrap_or_else(|e| e.into_inner()).start(key, Instant::now());
+        if let Some(generation) = started {
+            notify(key, true);
+            tokio::spawn(Self::expire_typing(key, generation));
+        }
+        Ok(())
+    }
+
+    /// 一轮输入的过期检查，刷新推迟过期时间时继续等待
+    async fn expire_typing(key: TypingKey, generation: u64) {
+        loop {
+            let check = tracker()
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .check_expiry(key, generation, Instant::now());
+            match check {
+                ExpiryCheck::Pending(wait) => tokio::time::sleep(wait).await,
+                ExpiryCheck::Expired => {
+                    notify(key, false);
+                    return;
+                }
+                ExpiryCheck::Stale => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_send() {
+        let now = Instant::now();
+        assert!(should_send(None, true, now));
+        assert!(!should_send(None, false, now));
+        assert!(!should_send(Some((now, true)), true, now + Duration::from_secs(1)));
+        assert!(should_send(Some((now, true)), true, now + TYPING_RESEND_INTERVAL));
+        assert!(should_send(Some((now, true)), false, now));
+        assert!(!should_send(Some((now, false)), false, now));
+        assert!(should_send(Some((now, false)), true, now));
+    }
+
+    #[test]
+    fn test_should_accept() {
+        let now = Instant::now();
+        assert!(should_accept(None, true, now));
+        assert!(!should_accept(Some((now, true)), true, now + Duration::from_millis(200)));
+        assert!(should_accept(Some((now, true)), true, now + TYPING_RECV_MIN_INTERVAL));
+        assert!(should_accept(Some((now, true)), false, now));
+    }
+
+    #[test]
+    fn test_typing_tracker() {
+        let mut tracker = TypingTracker::default();
+        let key = (1, 10, 20);
+        let now = Instant::now();
+
+        let first = tracker.start(key, now).unwrap();
+        assert_eq!(tracker.start(key, now + Duration::from_secs(5)), None, "Refreshing typing should not re-emit");
+
+        // 刷新推迟过期时间，同一轮输入只有一个过期检查
+        assert_eq!(
+            tracker.check_expiry(key, first, now + TYPING_EXPIRE),
+            ExpiryCheck::Pending(Duration::from_secs(5))
+        );
+        assert_eq!(
+            tracker.check_expiry(key, first, now + TYPING_EXPIRE + Duration::from_secs(5)),
+            ExpiryCheck::Expired
+        );
+        assert!(!tracker.stop(key));
+
+        // 停止后重新开始的一轮使旧的检查失效
+        let second = tracker.start(key, now).unwrap();
+        assert!(tracker.stop(key));
+        let third = tracker.start(key, now).unwrap();
+        assert_eq!(tracker.check_expiry(key, second, now), ExpiryCheck::Stale);
+        assert_ne!(tracker.check_expiry(key, third, now), ExpiryCheck::Stale);
+    }
+}
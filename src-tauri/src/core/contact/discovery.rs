@@ -4,12 +4,11 @@
 ///
 /// 功能:
 /// - 启动时广播 BR_ENTRY 包（支持 IPMsg 和 FeiQ 格式）
-/// - 监听其他用户的 BR_ENTRY 并回复 ANSENTRY
 /// - 维护在线用户列表
-/// - 处理用户离线事件
+///
+/// BR_ENTRY/ANSENTRY/BR_EXIT 事件由事件分发循环交给 `event::handlers`，
+/// 再调用这里的 `mark_online`、`send_ansentry` 和 `remove_online_user`
 use crate::error::AppResult;
-use crate::event::bus::EVENT_RECEIVER;
-use crate::event::model::{AppEvent, NetworkEvent};
 use crate::network::feiq::{
     constants::*,
     model::FeiQPacket,
@@ -19,7 +18,7 @@ use crate::network::utils::subnet::detect_subnet_broadcast;
 use crate::types::UserInfo;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tracing::{error, info};
+use tracing::info;
 
 /// 在线用户列表
 ///
@@ -65,6 +64,20 @@ pub fn add_online_user(user: UserInfo) {
     }
 }
 
+/// 将收到 BR_ENTRY/ANSENTRY 的用户登记为在线
+pub fn mark_online(ip: &str, port: u16, nickname: &str) {
+    let machine_id = format!("{}:{}", ip, port);
+    add_online_user(UserInfo {
+        uid: generate_user_id(&machine_id),
+        nickname: nickname.to_string(),
+        feiq_ip: ip.to_string(),
+        feiq_port: port,
+        feiq_machine_id: machine_id,
+        avatar: None,
+        status: 1, // 在线
+    });
+}
+
 /// 移除在线用户
 pub fn remove_online_user(ip: &str) {
     let users = get_online_users();
@@ -100,24 +113,13 @@ pub fn find_user_by_ip(ip: &str) -> Option<UserInfo> {
 
 /// 启动用户发现服务
 ///
-/// 流程:
-/// 1. 广播 BR_ENTRY 包（上线通知，支持 IPMsg 和 FeiQ 格式）
-/// 2. 监听事件总线，处理网络事件
-/// 3. 收到 BR_ENTRY 时回复 ANSENTRY
-/// 4. 收到 ANSENTRY 时添加到在线列表
-/// 5. 收到 BR_EXIT 时从在线列表移除
+/// 广播 BR_ENTRY 包（上线通知）。其他用户的上线、应答和下线事件
+/// 只由事件分发循环处理，本模块不再单独从事件总线接收
 pub async fn start_discovery() -> AppResult<()> {
     info!("用户发现服务启动中...");
 
-    // 1. 广播上线（同时发送 IPMsg 和 FeiQ 格式）
     broadcast_entry().await?;
 
-    // 2. 监听事件总线
-    let receiver = EVENT_RECEIVER.clone();
-    tokio::spawn(async move {
-        discovery_event_loop(receiver).await;
-    });
-
     info!("用户发现服务已启动");
     Ok(())
 }
@@ -143,7 +145,7 @@ async fn broadcast_entry() -> AppResult<()> {
 }
 
 /// 发送在线响应
-async fn send_ansentry(addr: &str) -> AppResult<()> {
+pub async fn send_ansentry(addr: &str) -> AppResult<()> {
     info!("回复 ANSENTRY to {}", addr);
 
     let packet = FeiQPacket::make_feiq_ansentry_packet(None);
@@ -154,89 +156,6 @@ async fn send_ansentry(addr: &str) -> AppResult<()> {
     Ok(())
 }
 
-/// 用户发现事件循环
-async fn discovery_event_loop(receiver: crossbeam_channel::Receiver<AppEvent>) {
-    info!("用户发现事件循环启动");
-
-    loop {
-        match receiver.recv() {
-            Ok(event) => {
-                if let AppEvent::Network(net_event) = event {
-                    match net_event {
-                        // 用户上线（IPMSG_BR_ENTRY）
-                        NetworkEvent::UserOnline {
-                            ip,
-                            port,
-                            nickname,
-                            hostname: _,
-                            mac_addr,
-                        } => {
-                            info!("收到 BR_ENTRY from {} ({}:{})", nickname, ip, port);
-
-                            let machine_id = format!("{}:{}", ip, port);
-                            let user = UserInfo {
-                                uid: generate_user_id(&machine_id),
-                                nickname: nickname.clone(),
-                                feiq_ip: ip.clone(),
-                                feiq_port: port,
-                                feiq_machine_id: machine_id,
-                                avatar: None,
-                                status: 1,
-                            };
-
-                            add_online_user(user);
-
-                            let addr = format!("{}:{}", ip, port);
-                            if let Err(e) = send_ansentry(&addr).await {
-                                error!("发送 ANSENTRY 失败: {}", e);
-                            }
-
-                            if let Some(mac) = mac_addr {
-                                info!("  ├─ MAC: {}", mac);
-                            }
-                        }
-
-                        // 用户在线应答（IPMSG_ANSENTRY）
-                        NetworkEvent::UserPresenceResponse {
-                            ip,
-                            port,
-                            nickname,
-                            hostname: _,
-                        } => {
-                            info!("收到 ANSENTRY from {} ({}:{})", nickname, ip, port);
-
-                            let machine_id = format!("{}:{}", ip, port);
-                            let user = UserInfo {
-                                uid: generate_user_id(&machine_id),
-                                nickname: nickname.clone(),
-                                feiq_ip: ip.clone(),
-                                feiq_port: port,
-                                feiq_machine_id: machine_id,
-                                avatar: None,
-                                status: 1, // 在线
-                            };
-
-                            add_online_user(user);
-                        }
-
-                        // 用户下线（IPMSG_BR_EXIT）
-                        NetworkEvent::UserOffline { ip } => {
-                            info!("收到 BR_EXIT from {}", ip);
-                            remove_online_user(&ip);
-                        }
-
-                        // 其他网络事件忽略（消息处理在聊天模块）
-                        _ => {}
-                    }
-                }
-            }
-            Err(e) => {
-                error!("事件接收失败: {}", e);
-            }
-        }
-    }
-}
-
 /// 解析发送者信息
 ///
 /// 根据 IPMsg 协议格式解析发送者信息
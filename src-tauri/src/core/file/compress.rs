@@ -0,0 +1,152 @@
+// src-tauri/src/core/file/compress.rs
+//
+//! 文件数据块压缩
+//!
+//! 双方都声明 `FEIQ_CAP_COMPRESS` 能力时，发送方对每个数据块做 deflate 压缩，
+//! 并在数据包附加标志位上标记 `FEIQ_FLAG_COMPRESSED`；压缩后不变小的数据块按原样发送。
+//! 图片、音视频、压缩包等本身已压缩的文件类型直接跳过。
+//!
+//! 压缩只用于 UDP 滑动窗口推送的数据块。TCP 通道（GETFILEDATA/GETDIRFILES 拉取）
+//! 按 IPMsg 原协议传输原始字节，以便与其他飞秋/IPMsg 客户端互通，其进度的压缩率固定为 1.0。
+
+use crate::core::file::window::MAX_CHUNK_SIZE;
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_COMPRESS;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// 未压缩传输的压缩率（TCP 通道始终如此）
+pub const UNCOMPRESSED_RATIO: f64 = 1.0;
+
+/// 已压缩格式的 MIME 类型（application/*）
+const COMPRESSED_APPLICATION_TYPES: &[&str] = &[
+    "zip",
+    "gzip",
+    "x-gzip",
+    "x-bzip2",
+    "x-xz",
+    "x-7z-compressed",
+    "x-rar-compressed",
+    "vnd.rar",
+    "zstd",
+    "java-archive",
+    "epub+zip",
+    "pdf",
+    "vnd.android.package-archive",
+];
+
+/// 未压缩的媒体类型（image/*, audio/* 中的例外）
+const UNCOMPRESSED_MEDIA_TYPES: &[&str] = &["bmp", "svg+xml", "tiff", "x-icon", "wav", "x-wav"];
+
+/// 根据扩展名判断文件是否已经是压缩格式
+pub fn is_precompressed(path: &Path) -> bool {
+    let mime = match mime_guess::from_path(path).first() {
+        Some(mime) => mime,
+        None => return false,
+    };
+    let subtype = mime.subtype().as_str();
+    let suffix = mime.suffix().map(|s| s.as_str());
+
+    match mime.type_().as_str() {
+        "image" | "audio" | "video" => !UNCOMPRESSED_MEDIA_TYPES.contains(&subtype),
+        "application" => {
+            // Office Open XML / OpenDocument 本质上都是 zip
+            COMPRESSED_APPLICATION_TYPES.contains(&subtype)
+                || suffix == Some("zip")
+                || subtype.starts_with("vnd.openxmlformats-officedocument")
+                || subtype.starts_with("vnd.oasis.opendocument")
+        }
+        _ => false,
+    }
+}
+
+/// 发送给对端的文件是否应当压缩
+///
+/// # 参数
+/// * `peer_ip` - 接收方 IP
+/// * `path` - 本地文件路径
+pub fn should_compress(peer_ip: &str, path: &Path) -> bool {
+    peer_supports(peer_ip, FEIQ_CAP_COMPRESS) && !is_precompressed(path)
+}
+
+/// 压缩一个数据块
+///
+/// # 返回
+/// 压缩后确实变小时返回压缩数据，否则返回 None（按原样发送）
+pub fn compress_chunk(data: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(data.len() / 2), Compression::fast());
+    encoder.write_all(data).ok()?;
+    let compressed = encoder.finish().ok()?;
+    (compressed.len() < data.len()).then_some(compressed)
+}
+
+/// 解压一个数据块
+///
+/// 解压后的大小不能超过最大数据块大小，防止恶意数据耗尽内存
+pub fn decompress_chunk(data: &[u8]) -> AppResult<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(data.len() * 2);
+    DeflateDecoder::new(data)
+        .take(MAX_CHUNK_SIZE as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| AppError::Protocol(format!("数据块解压失败: {}", e)))?;
+
+    if decoded.len() > MAX_CHUNK_SIZE {
+        return Err(AppError::Protocol(format!(
+            "解压后的数据块超过上限: > {} 字节",
+            MAX_CHUNK_SIZE
+        )));
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let data = "2026-01-01 12:00:00,INFO,request ok\n".repeat(200);
+        let compressed = compress_chunk(data.as_bytes()).expect("text should compress");
+        assert!(compressed.len() < data.len() / 4);
+        assert_eq!(decompress_chunk(&compressed).unwrap(), data.as_bytes());
+    }
+
+    #[test]
+    fn test_incompressible_chunk_is_sent_raw() {
+        // xorshift 伪随机数据，无法压缩
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect();
+        assert!(compress_chunk(&data).is_none());
+    }
+
+    #[test]
+    fn test_decompress_rejects_oversized_chunk() {
+        let data = vec![0u8; MAX_CHUNK_SIZE * 2];
+        let compressed = compress_chunk(&data).unwrap();
+        assert!(decompress_chunk(&compressed).is_err());
+    }
+
+    #[test]
+    fn test_precompressed_types() {
+        assert!(is_precompressed(Path::new("photo.jpg")));
+        assert!(is_precompressed(Path::new("archive.zip")));
+        assert!(is_precompressed(Path::new("report.docx")));
+        assert!(is_precompressed(Path::new("movie.mp4")));
+        assert!(!is_precompressed(Path::new("server.log")));
+        assert!(!is_precompressed(Path::new("export.csv")));
+        assert!(!is_precompressed(Path::new("scan.bmp")));
+        assert!(!is_precompressed(Path::new("no_extension")));
+    }
+}
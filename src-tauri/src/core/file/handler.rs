@@ -5,33 +5,74 @@
 //! 处理来自网络层的文件传输事件：
 //! - FileDataRequest: 对方请求文件数据块
 //! - FileDataReceived: 接收到文件数据块
+//! - FileDataAck: 对方确认已收到数据块
 //! - FileRelease: 文件传输释放/取消
-
-use crate::core::file::transfer::FileReceiver;
+//!
+//! 同时提供 TCP 文件通道的数据来源 (`DbFileSource`，含共享文件夹) 和下载逻辑
+
+use crate::core::file::compress::UNCOMPRESSED_RATIO;
+use crate::core::file::history;
+use crate::core::file::rate_limit::{rate_limiter, release_transfer_limit};
+use crate::core::file::resume::{format_checkpoint, record_download_checkpoint};
+use crate::core::file::scheduler::{self, transfer_scheduler};
+use crate::core::file::share::{parse_share_packet_no, ShareService};
+use crate::core::file::storage::{download_path, register_received_file, remember_hash};
+use crate::core::file::thumbnail::prepare_image;
+use crate::core::file::transfer::{dispatch_chunk_ack, estimate_eta, FileReceiver, IncrementalHash, RateMeter};
 use crate::database::handler::{FileStorageHandler, TransferStateHandler};
 use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, FileEvent};
 use crate::network::feiq::model::FeiQPacket;
+use crate::network::tcp::protocol::{DirEntryHeader, TcpFileRequest};
+use crate::network::tcp::FileSource;
 use crate::network::udp::sender;
+use crate::types::TransferStatus;
 use sea_orm::DbConn;
-use std::sync::OnceLock;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
 use tracing::info;
 
+/// TCP 下载读缓冲区大小
+const TCP_BUFFER_SIZE: usize = 64 * 1024;
+
+/// TCP 下载时每接收多少字节落库一次进度
+const TCP_PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// TCP 下载单次读取的超时时间（超时视为连接中断，释放调度名额）
+const TCP_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// 文件传输事件处理器
 pub struct FileTransferHandler;
 
 /// 文件接收器缓存 (用于分块接收)
 ///
 /// key: (packet_no, file_id)
-/// value: FileReceiver
-fn file_receivers() -> &'static std::sync::Mutex<std::collections::HashMap<(String, u64), FileReceiver>> {
-    static RECEIVERS: OnceLock<std::sync::Mutex<std::collections::HashMap<(String, u64), FileReceiver>>> = OnceLock::new();
-    RECEIVERS.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()))
+/// value: FileReceiver（写文件是异步的，每个接收器单独加锁，不在全局锁内等待 I/O）
+type FileReceivers = std::sync::Mutex<HashMap<(String, u64), Arc<tokio::sync::Mutex<FileReceiver>>>>;
+
+fn file_receivers() -> &'static FileReceivers {
+    static RECEIVERS: OnceLock<FileReceivers> = OnceLock::new();
+    RECEIVERS.get_or_init(|| std::sync::Mutex::new(HashMap::new()))
+}
+
+/// TCP 上传中途断开的次数
+///
+/// key: (tid, 对端 IP)，上传完成时取出作为重试次数
+fn interrupted_serves() -> &'static std::sync::Mutex<HashMap<(i64, String), u32>> {
+    static SERVES: OnceLock<std::sync::Mutex<HashMap<(i64, String), u32>>> = OnceLock::new();
+    SERVES.get_or_init(|| std::sync::Mutex::new(HashMap::new()))
 }
 
 impl FileTransferHandler {
     /// 处理文件数据请求事件
     ///
-    /// 当远程用户请求文件数据块时，读取本地文件并发送
+    /// 远程用户请求文件数据（UDP 回退）时，把该对端的上传记录交给调度器，
+    /// 由 `FileSender` 从请求的偏移量起按滑动窗口推送（自适应分块，逐块确认）。
+    /// 传输已在队列中时忽略重复的请求
     ///
     /// # 参数
     /// - `db`: 数据库连接
@@ -53,33 +94,34 @@ impl FileTransferHandler {
 
         let transfer_states = TransferStateHandler::find_by_packet_no(db, packet_no).await?;
 
-        let transfer_state = transfer_states
-            .iter()
-            .find(|t| t.file_id as u64 == file_id)
-            .ok_or_else(|| {
-                AppError::NotFound(format!(
-                    "找不到传输记录: packet_no={}, file_id={}",
-                    packet_no, file_id
-                ))
-            })?;
-
-        let file_storage = FileStorageHandler::find_by_id(db, transfer_state.file_id).await?;
-
-        let chunk = Self::read_file_chunk(&file_storage.file_path, offset).await?;
+        let transfer_state = select_for_peer(
+            transfer_states
+                .into_iter()
+                .filter(|t| t.file_id as u64 == file_id && t.direction == 1),
+            from_ip,
+        )
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "找不到传输记录: packet_no={}, file_id={}",
+                packet_no, file_id
+            ))
+        })?;
+
+        let scheduler = transfer_scheduler();
+        if scheduler.is_active(transfer_state.tid) {
+            tracing::debug!("传输已在进行，忽略重复的数据请求: tid={}", transfer_state.tid);
+            return Ok(());
+        }
 
-        let packet = FeiQPacket::make_feiq_file_data_packet(packet_no, file_id, offset, &chunk, None);
-        let packet_str = packet.to_feiq_string();
-
-        let addr = format!("{}:{}", from_ip, 2425);
-        sender::send_packet_data(&addr, &packet_str)
-            .await
-            .map_err(|e| AppError::Network(format!("发送文件数据块失败: {}", e)))?;
+        let offset = offset.min(transfer_state.file_size as u64);
+        TransferStateHandler::update_progress(db, transfer_state.tid, offset as i64, TransferStatus::Pending as i8)
+            .await?;
+        scheduler.enqueue(transfer_state.tid, 1, 0, offset);
+        scheduler::pump(db);
 
         info!(
-            "已发送文件数据块: file_id={}, offset={}, size={}",
-            file_id,
-            offset,
-            chunk.len()
+            "文件数据请求已排队推送: tid={}, file_id={}, offset={}",
+            transfer_state.tid, file_id, offset
         );
 
         Ok(())
@@ -96,6 +138,7 @@ impl FileTransferHandler {
     /// - `file_id`: 文件ID
     /// - `offset`: 偏移量
     /// - `data`: Base64编码的文件数据
+    /// - `compressed`: 数据块是否经过压缩
     pub async fn handle_file_data_received(
         db: &DbConn,
         from_ip: &str,
@@ -103,6 +146,7 @@ impl FileTransferHandler {
         file_id: u64,
         offset: u64,
         data: &str,
+        compressed: bool,
     ) -> AppResult<()> {
         info!(
             "收到文件数据: from_ip={}, packet_no={}, file_id={}, offset={}, size={}",
@@ -120,57 +164,130 @@ impl FileTransferHandler {
 
         let transfer_states = TransferStateHandler::find_by_packet_no(db, packet_no).await?;
 
-        let transfer_state = transfer_states
-            .iter()
-            .find(|t| t.file_id as u64 == file_id)
-            .ok_or_else(|| {
-                AppError::NotFound(format!(
-                    "找不到传输记录: packet_no={}, file_id={}",
-                    packet_no, file_id
-                ))
-            })?;
+        let transfer_state = select_for_peer(
+            transfer_states
+                .into_iter()
+                .filter(|t| t.file_id as u64 == file_id && t.direction == 0),
+            from_ip,
+        )
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "找不到传输记录: packet_no={}, file_id={}",
+                packet_no, file_id
+            ))
+        })?;
+
+        // 已取消、暂停或结束的下载不再写入
+        if !accepts_data(&transfer_state) {
+            return Err(AppError::Business(format!(
+                "传输不在进行中，丢弃数据块: tid={}, status={}",
+                transfer_state.tid, transfer_state.status
+            )));
+        }
 
         let key = (packet_no.to_string(), file_id);
 
-        let is_complete = {
+        // 推迟确认以限制 UDP 接收速率，发送方的滑动窗口随之放缓
+        rate_limiter().acquire(from_ip, &key, chunk.len()).await;
+
+        let receiver = {
             let mut receivers = file_receivers().lock().map_err(|e| {
                 AppError::Business(format!("获取文件接收器缓存失败: {}", e))
             })?;
 
-            let receiver = receivers.entry(key.clone()).or_insert_with(|| {
-                FileReceiver::new(
-                    transfer_state.get_save_path(),
-                    file_id,
-                    transfer_state.file_size as u64,
-                )
-            });
+            receivers
+                .entry(key.clone())
+                .or_insert_with(|| {
+                    Arc::new(tokio::sync::Mutex::new(
+                        FileReceiver::new(transfer_state.get_save_path(), file_id, transfer_state.file_size as u64)
+                            .starting_at(transfer_state.transferred as u64),
+                    ))
+                })
+                .clone()
+        };
+
+        // 按收到的字节区间判断完成：乱序到达时文件长度可能先于数据到齐
+        let (is_complete, received_through, content_hash, stats) = {
+            let mut receiver = receiver.lock().await;
+            receiver.receive_encoded_chunk(offset, &chunk, compressed).await?;
 
-            receiver.receive_chunk(offset, &chunk)?;
+            let is_complete = receiver.is_complete();
+            let content_hash = if is_complete {
+                receiver.incremental_checksum()
+            } else {
+                None
+            };
 
-            let current_size = receiver.current_size()?;
-            let is_complete = current_size >= transfer_state.file_size as u64;
+            (is_complete, receiver.received_through(), content_hash, receiver.stats())
+        };
 
-            if is_complete {
-                info!(
-                    "文件接收完成: file_id={}, size={}",
-                    file_id, transfer_state.file_size
-                );
+        if is_complete {
+            info!(
+                "文件接收完成: file_id={}, size={}",
+                file_id, transfer_state.file_size
+            );
+            if let Ok(mut receivers) = file_receivers().lock() {
                 receivers.remove(&key);
             }
+        }
 
-            is_complete
-        };
+        // 回复数据块确认，推进发送方的滑动窗口
+        let ack = FeiQPacket::make_feiq_file_data_ack_packet(packet_no, file_id, offset, None);
+        let addr = format!("{}:{}", from_ip, 2425);
+        sender::send_packet_data(&addr, &ack.to_feiq_string())
+            .await
+            .map_err(|e| AppError::Network(format!("发送数据块确认失败: {}", e)))?;
 
-        let new_offset = offset + chunk.len() as u64;
-        TransferStateHandler::update_progress(db, transfer_state.tid, new_offset as i64, 1).await?;
+        let total = transfer_state.file_size as u64;
+        let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::TransferProgress {
+            file_id: file_id as i64,
+            progress: stats.progress,
+            total,
+            rate: stats.rate,
+            compression_ratio: stats.compression_ratio,
+            eta_secs: estimate_eta(total.saturating_sub(stats.progress), stats.rate),
+        }));
+
+        // 只持久化连续收到的部分，断点续传不会跳过空洞
+        TransferStateHandler::update_progress(db, transfer_state.tid, received_through as i64, 1).await?;
 
         if is_complete {
             TransferStateHandler::update_status(db, transfer_state.tid, 2, None).await?;
+            release_transfer_limit(db, packet_no, file_id).await;
+            history::record_completed(db, &transfer_state, stats.received, stats.elapsed, stats.duplicates).await;
+            // 数据块按顺序到达时哈希已随接收算好，登记时不再整文件读取
+            if let Some(hash) = content_hash {
+                remember_hash(Path::new(&transfer_state.get_save_path()), hash).await?;
+            }
+            if let Err(e) = register_received_file(db, &transfer_state).await {
+                tracing::warn!("登记接收文件失败: file_id={}, {}", file_id, e);
+            }
         }
 
         Ok(())
     }
 
+    /// 处理数据块确认事件
+    ///
+    /// 将确认转交给正在发送该文件的滑动窗口
+    ///
+    /// # 参数
+    /// - `from_ip`: 确认方IP
+    /// - `packet_no`: 数据包编号
+    /// - `file_id`: 文件ID
+    /// - `offset`: 已确认数据块的偏移量
+    pub fn handle_file_data_ack(from_ip: &str, packet_no: &str, file_id: u64, offset: u64) {
+        if !dispatch_chunk_ack(from_ip, packet_no, file_id, offset) {
+            tracing::debug!(
+                "收到无对应发送器的数据块确认: from_ip={}, packet_no={}, file_id={}, offset={}",
+                from_ip,
+                packet_no,
+                file_id,
+                offset
+            );
+        }
+    }
+
     /// 处理文件释放事件
     ///
     /// 当文件传输被取消或释放时，清理相关资源
@@ -179,22 +296,39 @@ impl FileTransferHandler {
     /// - `db`: 数据库连接
     /// - `from_ip`: 发送者IP
     /// - `packet_no`: 数据包编号
-    pub async fn handle_file_release(db: &DbConn, from_ip: &str, packet_no: &str) -> AppResult<()> {
+    /// - `reason`: 对方附带的拒绝原因
+    pub async fn handle_file_release(
+        db: &DbConn,
+        from_ip: &str,
+        packet_no: &str,
+        reason: Option<String>,
+    ) -> AppResult<()> {
         info!(
             "收到文件释放: from_ip={}, packet_no={}",
             from_ip, packet_no
         );
 
-        let transfer_states = TransferStateHandler::find_by_packet_no(db, packet_no).await?;
+        // 同一批文件发给了多个接收方时，只取消该对端的传输；没有该对端的记录时不做处理
+        let peer_states: Vec<_> = TransferStateHandler::find_by_packet_no(db, packet_no)
+            .await?
+            .into_iter()
+            .filter(|t| t.target_ip == from_ip)
+            .collect();
+
+        let message = match reason {
+            Some(reason) => format!("对方拒绝: {}", reason),
+            None => "对方取消传输".to_string(),
+        };
 
-        for transfer_state in transfer_states {
-            TransferStateHandler::update_status(db, transfer_state.tid, -2, Some("对方取消传输".to_string())).await?;
+        for transfer_state in peer_states {
+            TransferStateHandler::update_status(db, transfer_state.tid, -2, Some(message.clone())).await?;
+            release_transfer_limit(db, packet_no, transfer_state.file_id as u64).await;
 
             let mut receivers = file_receivers().lock().map_err(|e| {
                 AppError::Business(format!("获取文件接收器缓存失败: {}", e))
             })?;
 
-            receivers.retain(|p, _| p.0.as_str() != packet_no);
+            receivers.remove(&(packet_no.to_string(), transfer_state.file_id as u64));
         }
 
         info!("文件传输已清理: packet_no={}", packet_no);
@@ -202,44 +336,298 @@ impl FileTransferHandler {
         Ok(())
     }
 
-    /// 读取文件数据块
+    /// 通过 TCP 通道下载文件数据
+    ///
+    /// 从已发送 GETFILEDATA 请求的连接中读取原始字节，写入本地文件并更新进度
     ///
     /// # 参数
-    /// - `file_path`: 文件路径
-    /// - `offset`: 偏移量
+    /// - `db`: 数据库连接
+    /// - `from_ip`: 发送方IP（用于按对端限速）
+    /// - `stream`: 已发送请求的 TCP 连接
+    /// - `packet_no`: 数据包编号
+    /// - `file_id`: 文件ID
+    /// - `offset`: 起始偏移量
     ///
     /// # 返回
-    /// 返回文件数据块 (最大 4KB)
-    async fn read_file_chunk(file_path: &str, offset: u64) -> AppResult<Vec<u8>> {
-        use tokio::fs::File;
-        use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+    /// 返回本次接收的字节数
+    pub async fn download_via_tcp(
+        db: &DbConn,
+        from_ip: &str,
+        mut stream: TcpStream,
+        packet_no: &str,
+        file_id: u64,
+        offset: u64,
+    ) -> AppResult<u64> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+
+        // 只接收本地记录过的、来自该对端的下载
+        let transfer_state = TransferStateHandler::find_by_packet_no(db, packet_no)
+            .await?
+            .into_iter()
+            .find(|t| t.file_id as u64 == file_id && t.direction == 0 && t.target_ip == from_ip)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("没有对应的下载记录: packet_no={}, file_id={}", packet_no, file_id))
+            })?;
 
-        let mut file = File::open(file_path)
-            .await
-            .map_err(AppError::Io)?;
+        let save_path = transfer_state.get_save_path();
 
-        file.seek(SeekFrom::Start(offset))
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&save_path)
             .await
             .map_err(AppError::Io)?;
+        file.seek(SeekFrom::Start(offset)).await.map_err(AppError::Io)?;
 
-        let mut buffer = vec![0u8; 4096];
-        let n = file
-            .read(&mut buffer)
-            .await
-            .map_err(AppError::Io)?;
+        TransferStateHandler::update_progress(db, transfer_state.tid, offset as i64, 1).await?;
+
+        let total = transfer_state.file_size as u64;
+        let mut buffer = vec![0u8; TCP_BUFFER_SIZE];
+        let mut position = offset;
+        let mut last_reported = offset;
+        let mut meter = RateMeter::new();
+        let mut hash = IncrementalHash::starting_at(offset);
+        let started = Instant::now();
+        let key = (packet_no.to_string(), file_id);
+
+        // 最多读取到广告的文件大小，多余的数据不会写入保存路径
+        let mut limited = (&mut stream).take(total.saturating_sub(offset));
+        let mut interrupted = None;
+        loop {
+            let n = match tokio::time::timeout(TCP_READ_TIMEOUT, limited.read(&mut buffer)).await {
+                Ok(Ok(n)) => n,
+                Ok(Err(e)) => {
+                    interrupted = Some(e.to_string());
+                    break;
+                }
+                Err(_) => {
+                    interrupted = Some("读取超时".to_string());
+                    break;
+                }
+            };
+            if n == 0 {
+                break;
+            }
+
+            file.write_all(&buffer[..n]).await.map_err(AppError::Io)?;
+            hash.update(position, &buffer[..n]);
+            position += n as u64;
+            meter.record(n as u64);
+
+            // 接收端限速：延迟下一次读取，由 TCP 流控把速率传导给发送方
+            rate_limiter().acquire(from_ip, &key, n).await;
+
+            if position - last_reported >= TCP_PROGRESS_STEP {
+                last_reported = position;
+                TransferStateHandler::update_progress(db, transfer_state.tid, position as i64, 1).await?;
+                // 从头顺序接收时顺带记录前缀校验点，中断后恢复时用来确认部分文件
+                if let Some((len, prefix)) = hash.prefix() {
+                    file.flush().await.map_err(AppError::Io)?;
+                    TransferStateHandler::set_checksum(db, transfer_state.tid, format_checkpoint(len, &prefix)).await?;
+                }
+                let rate = meter.rate();
+                let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::TransferProgress {
+                    file_id: file_id as i64,
+                    progress: position,
+                    total,
+                    rate,
+                    compression_ratio: UNCOMPRESSED_RATIO,
+                    eta_secs: estimate_eta(total.saturating_sub(position), rate),
+                }));
+            }
+        }
+
+        file.flush().await.map_err(AppError::Io)?;
+
+        TransferStateHandler::update_progress(db, transfer_state.tid, position as i64, 1).await?;
+        if position < total {
+            match hash.prefix() {
+                Some((len, prefix)) => {
+                    TransferStateHandler::set_checksum(db, transfer_state.tid, format_checkpoint(len, &prefix)).await?;
+                }
+                None => record_download_checkpoint(db, transfer_state.tid, &save_path, position).await?,
+            }
+            let message = format!(
+                "TCP 连接提前关闭: file_id={}, {}/{}{}",
+                file_id,
+                position,
+                total,
+                interrupted.map(|e| format!(", {}", e)).unwrap_or_default()
+            );
+            let status = TransferStatus::Failed as i8;
+            TransferStateHandler::update_status(db, transfer_state.tid, status, Some(message.clone())).await?;
+            return Err(AppError::Network(message));
+        }
+        TransferStateHandler::update_status(db, transfer_state.tid, 2, None).await?;
+        release_transfer_limit(db, &transfer_state.packet_no, file_id).await;
+        // TCP 自身负责重传，这里记录连接中断后重新请求的次数
+        let retries = transfer_scheduler().retries(transfer_state.tid);
+        history::record_completed(db, &transfer_state, position - offset, started.elapsed(), retries).await;
+
+        if let Some(content_hash) = hash.finish(position) {
+            remember_hash(Path::new(&save_path), content_hash).await?;
+        }
+
+        // 相同内容已接收过时，新副本被删除，改用已有文件路径
+        let final_path = register_received_file(db, &transfer_state).await.unwrap_or_else(|e| {
+            tracing::warn!("登记接收文件失败: file_id={}, {}", file_id, e);
+            save_path.clone()
+        });
+
+        let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::DownloadCompleted {
+            file_id: file_id as i64,
+            path: final_path,
+        }));
+
+        info!(
+            "TCP 文件接收完成: file_id={}, bytes={}",
+            file_id,
+            position - offset
+        );
+
+        Ok(position - offset)
+    }
+}
+
+/// 基于数据库的 TCP 文件来源
+///
+/// 根据 packet_no 和 file_id 查找上传记录对应的本地文件
+pub struct DbFileSource {
+    db: DbConn,
+}
+
+impl DbFileSource {
+    pub fn new(db: DbConn) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl FileSource for DbFileSource {
+    async fn resolve(&self, peer_ip: &str, packet_no: &str, file_id: u64) -> AppResult<PathBuf> {
+        if let Some(share_path) = parse_share_packet_no(packet_no) {
+            return ShareService::resolve_file_for_peer(&self.db, peer_ip, &share_path).await;
+        }
+
+        let transfer_states = TransferStateHandler::find_by_packet_no(&self.db, packet_no).await?;
+        let transfer_state = select_for_peer(
+            transfer_states.into_iter().filter(|t| t.file_id as u64 == file_id && t.direction == 1),
+            peer_ip,
+        )
+        .ok_or_else(|| {
+            AppError::NotFound(format!(
+                "找不到传输记录: packet_no={}, file_id={}",
+                packet_no, file_id
+            ))
+        })?;
+
+        if transfer_state.status == -2 {
+            return Err(AppError::Business(format!("传输已取消: packet_no={}", packet_no)));
+        }
+        if transfer_state.status == TransferStatus::Paused as i8 {
+            return Err(AppError::Business(format!("传输已暂停: packet_no={}", packet_no)));
+        }
+
+        TransferStateHandler::update_status(&self.db, transfer_state.tid, 1, None).await?;
+
+        let file_storage = FileStorageHandler::find_by_id(&self.db, transfer_state.file_id).await?;
+        Ok(PathBuf::from(file_storage.file_path))
+    }
+
+    async fn list_shares(&self, peer_ip: &str) -> AppResult<Vec<DirEntryHeader>> {
+        ShareService::list_shares_for_peer(&self.db, peer_ip).await
+    }
+
+    async fn list_dir(&self, peer_ip: &str, path: &str) -> AppResult<Vec<DirEntryHeader>> {
+        ShareService::list_dir_for_peer(&self.db, peer_ip, path).await
+    }
+
+    async fn thumbnail(&self, peer_ip: &str, packet_no: &str, file_id: u64) -> AppResult<Vec<u8>> {
+        let transfer_states = TransferStateHandler::find_by_packet_no(&self.db, packet_no).await?;
+        let transfer_state = select_for_peer(
+            transfer_states.into_iter().filter(|t| t.file_id as u64 == file_id && t.direction == 1),
+            peer_ip,
+        )
+        .ok_or_else(|| AppError::NotFound(format!("找不到传输记录: packet_no={}, file_id={}", packet_no, file_id)))?;
+
+        let file_storage = FileStorageHandler::find_by_id(&self.db, transfer_state.file_id).await?;
+        let file_storage = prepare_image(&self.db, file_storage).await?;
+        let thumbnail_path = file_storage
+            .thumbnail_path
+            .ok_or_else(|| AppError::NotFound(format!("文件没有缩略图: file_id={}", file_id)))?;
+
+        tokio::fs::read(&thumbnail_path).await.map_err(AppError::Io)
+    }
 
-        buffer.truncate(n);
-        Ok(buffer)
+    async fn throttle(&self, peer_ip: &str, request: &TcpFileRequest, bytes: usize) {
+        let key = (request.packet_no.clone(), request.file_id);
+        rate_limiter().acquire(peer_ip, &key, bytes).await;
+    }
+
+    async fn on_finished(&self, peer_ip: &str, request: &TcpFileRequest, sent: u64, elapsed: Duration) {
+        let transfer_state = match TransferStateHandler::find_by_packet_no(&self.db, &request.packet_no).await {
+            Ok(states) => select_for_peer(
+                states
+                    .into_iter()
+                    .filter(|t| t.file_id as u64 == request.file_id && t.direction == 1),
+                peer_ip,
+            ),
+            Err(e) => {
+                tracing::error!("查询传输记录失败: {}", e);
+                return;
+            }
+        };
+
+        if let Some(t) = transfer_state {
+            let transferred = request.offset + sent;
+            let status = if transferred >= t.file_size as u64 { 2 } else { 1 };
+            if let Err(e) = TransferStateHandler::update_progress(&self.db, t.tid, transferred as i64, status).await {
+                tracing::error!("更新上传进度失败: {}", e);
+            }
+            // TCP 自身负责重传，这里记录连接提前断开、对方从断点重新请求的次数
+            let retries = {
+                let mut interrupted = interrupted_serves().lock().unwrap_or_else(|e| e.into_inner());
+                let key = (t.tid, peer_ip.to_string());
+                if status == 2 {
+                    interrupted.remove(&key).unwrap_or(0)
+                } else {
+                    *interrupted.entry(key).or_insert(0) += 1;
+                    0
+                }
+            };
+            if status == 2 {
+                release_transfer_limit(&self.db, &request.packet_no, request.file_id).await;
+                history::record_completed(&self.db, &t, sent, elapsed, retries).await;
+            }
+        }
     }
 }
 
-trait TransferStateExt {
+/// 从同一文件的传输记录中选出属于指定对端的一条
+///
+/// 一次发给多个接收方时，每个接收方各有一条记录，按对端 IP 区分；
+/// 没有发给该对端的记录时返回 None，其他主机不能凭猜测的包编号取走文件
+fn select_for_peer<I>(states: I, peer_ip: &str) -> Option<crate::database::model::transfer_state::Model>
+where
+    I: IntoIterator<Item = crate::database::model::transfer_state::Model>,
+{
+    states.into_iter().find(|state| state.target_ip == peer_ip)
+}
+
+/// 下载是否接受数据块（等待中或传输中）
+fn accepts_data(state: &crate::database::model::transfer_state::Model) -> bool {
+    state.status == TransferStatus::Pending as i8 || state.status == TransferStatus::Transferring as i8
+}
+
+/// 传输记录的本地辅助方法
+pub(crate) trait TransferStateExt {
     fn get_save_path(&self) -> String;
 }
 
 impl TransferStateExt for crate::database::model::transfer_state::Model {
     fn get_save_path(&self) -> String {
-        format!("/tmp/feiqiu_download_{}", self.file_id)
+        download_path(self.tid)
     }
 }
 
@@ -247,8 +635,34 @@ impl TransferStateExt for crate::database::model::transfer_state::Model {
 mod tests {
     use super::*;
 
+    fn download(status: TransferStatus) -> crate::database::model::transfer_state::Model {
+        crate::database::model::transfer_state::Model {
+            tid: 1,
+            file_id: 1,
+            session_type: 0,
+            target_id: 2,
+            direction: 0,
+            transferred: 0,
+            file_size: 100,
+            status: status as i8,
+            packet_no: "1700000000".to_string(),
+            target_ip: "192.168.1.10".to_string(),
+            target_port: 2425,
+            checksum: String::new(),
+            error_message: None,
+            rate_limit: 0,
+            update_time: String::new(),
+            create_time: chrono::Utc::now().naive_utc(),
+        }
+    }
+
     #[test]
-    fn test_handler_structure() {
-        assert!(true);
+    fn test_accepts_data_only_while_active() {
+        assert!(accepts_data(&download(TransferStatus::Pending)));
+        assert!(accepts_data(&download(TransferStatus::Transferring)));
+        assert!(!accepts_data(&download(TransferStatus::Cancelled)));
+        assert!(!accepts_data(&download(TransferStatus::Paused)));
+        assert!(!accepts_data(&download(TransferStatus::Completed)));
+        assert!(!accepts_data(&download(TransferStatus::Failed)));
     }
 }
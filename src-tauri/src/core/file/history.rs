@@ -0,0 +1,250 @@
+// src-tauri/src/core/file/history.rs
+//
+//! 传输历史统计
+//!
+//! 每次完成的传输记录耗时、平均速率、重传次数和对端，
+//! 用于查看与谁之间传过哪些文件，以及按联系人汇总的收发量
+
+use crate::database::handler::{FileOfferHandler, FileStorageHandler, TransferHistoryHandler, UserHandler};
+use crate::database::model::{transfer_history, transfer_state};
+use crate::error::AppResult;
+use crate::types::PeerTransferTotals;
+use sea_orm::{ActiveValue, DbConn};
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+
+/// 默认返回的历史条数
+pub const DEFAULT_HISTORY_LIMIT: u64 = 200;
+
+/// 平均速率（字节/秒），耗时不足 1 毫秒时按 1 毫秒计
+pub fn average_speed(bytes: u64, elapsed: Duration) -> u64 {
+    let millis = elapsed.as_millis().max(1) as u64;
+    bytes.saturating_mul(1000) / millis
+}
+
+/// 记录一次完成的传输
+///
+/// 统计只是附带信息，失败时只记录日志，不影响传输结果
+///
+/// # 参数
+/// - `transfer`: 传输记录
+/// - `bytes`: 本次实际传输的字节数
+/// - `elapsed`: 本次传输耗时
+/// - `retries`: 重传次数（UDP 为重传的数据块数，TCP 为连接中断后重新请求的次数）
+pub async fn record_completed(
+    db: &DbConn,
+    transfer: &transfer_state::Model,
+    bytes: u64,
+    elapsed: Duration,
+    retries: u32,
+) {
+    if let Err(e) = try_record_completed(db, transfer, bytes, elapsed, retries).await {
+        warn!("记录传输历史失败: tid={}, {}", transfer.tid, e);
+    }
+}
+
+async fn try_record_completed(
+    db: &DbConn,
+    transfer: &transfer_state::Model,
+    bytes: u64,
+    elapsed: Duration,
+    retries: u32,
+) -> AppResult<()> {
+    let file_name = if transfer.direction == 1 {
+        FileStorageHandler::find_by_id(db, transfer.file_id)
+            .await
+            .ok()
+            .map(|f| f.file_name)
+    } else {
+        FileOfferHandler::find(db, &transfer.packet_no, transfer.file_id)
+            .await?
+            .map(|offer| offer.file_name)
+    }
+    .unwrap_or_else(|| format!("file_{}", transfer.file_id));
+
+    let peer_uid = if transfer.target_id > 0 {
+        transfer.target_id
+    } else {
+        UserHandler::find_by_ip_port(db, &transfer.target_ip, transfer.target_port)
+            .await?
+            .map(|u| u.uid)
+            .unwrap_or(0)
+    };
+
+    let finished_at = chrono::Utc::now().naive_utc();
+    let started_at = finished_at - chrono::Duration::milliseconds(elapsed.as_millis() as i64);
+
+    TransferHistoryHandler::create(
+        db,
+        transfer_history::ActiveModel {
+            id: ActiveValue::NotSet,
+            tid: ActiveValue::Set(transfer.tid),
+            file_id: ActiveValue::Set(transfer.file_id),
+            file_name: ActiveValue::Set(file_name),
+            file_size: ActiveValue::Set(transfer.file_size),
+            direction: ActiveValue::Set(transfer.direction),
+            peer_uid: ActiveValue::Set(peer_uid),
+            peer_ip: ActiveValue::Set(transfer.target_ip.clone()),
+            bytes: ActiveValue::Set(bytes as i64),
+            duration_ms: ActiveValue::Set(elapsed.as_millis() as i64),
+            avg_speed: ActiveValue::Set(average_speed(bytes, elapsed) as i64),
+            retries: ActiveValue::Set(retries as i32),
+            started_at: ActiveValue::Set(started_at),
+            finished_at: ActiveValue::Set(finished_at),
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// 按对端汇总传输历史
+///
+/// 已知用户按用户ID汇总，未知用户按 IP 汇总；结果按最近传输时间倒序
+pub fn aggregate_totals(records: &[transfer_history::Model]) -> Vec<PeerTransferTotals> {
+    let mut totals: HashMap<(i64, String), PeerTransferTotals> = HashMap::new();
+
+    for record in records {
+        let key = if record.peer_uid > 0 {
+            (record.peer_uid, String::new())
+        } else {
+            (0, record.peer_ip.clone())
+        };
+        let entry = totals.entry(key).or_insert_with(|| PeerTransferTotals {
+            peer_uid: record.peer_uid,
+            peer_ip: record.peer_ip.clone(),
+            nickname: None,
+            sent_count: 0,
+            sent_bytes: 0,
+            received_count: 0,
+            received_bytes: 0,
+            total_duration_ms: 0,
+            avg_speed: 0,
+            last_transfer_time: record.finished_at.to_string(),
+        });
+
+        if record.direction == 1 {
+            entry.sent_count += 1;
+            entry.sent_bytes += record.bytes.max(0) as u64;
+        } else {
+            entry.received_count += 1;
+            entry.received_bytes += record.bytes.max(0) as u64;
+        }
+        entry.total_duration_ms += record.duration_ms.max(0) as u64;
+
+        let finished_at = record.finished_at.to_string();
+        if finished_at > entry.last_transfer_time {
+            entry.last_transfer_time = finished_at;
+            entry.peer_ip = record.peer_ip.clone();
+        }
+    }
+
+    let mut totals: Vec<PeerTransferTotals> = totals
+        .into_values()
+        .map(|mut t| {
+            t.avg_speed = average_speed(
+                t.sent_bytes + t.received_bytes,
+                Duration::from_millis(t.total_duration_ms),
+            );
+            t
+        })
+        .collect();
+    totals.sort_by(|a, b| b.last_transfer_time.cmp(&a.last_transfer_time));
+    totals
+}
+
+/// 传输历史服务
+pub struct TransferHistoryService;
+
+impl TransferHistoryService {
+    /// 获取传输历史（按完成时间倒序）
+    ///
+    /// # 参数
+    /// - `peer_uid`: 只返回与该用户之间的传输（None 表示全部）
+    /// - `limit`: 最多返回的条数（None 表示默认条数）
+    pub async fn list(
+        db: &DbConn,
+        peer_uid: Option<i64>,
+        limit: Option<u64>,
+    ) -> AppResult<Vec<transfer_history::Model>> {
+        TransferHistoryHandler::list(db, peer_uid, limit.unwrap_or(DEFAULT_HISTORY_LIMIT)).await
+    }
+
+    /// 按联系人汇总收发量
+    pub async fn peer_totals(db: &DbConn) -> AppResult<Vec<PeerTransferTotals>> {
+        let records = TransferHistoryHandler::list_all(db).await?;
+        let mut totals = aggregate_totals(&records);
+
+        for total in totals.iter_mut().filter(|t| t.peer_uid > 0) {
+            if let Ok(user) = UserHandler::find_by_id(db, total.peer_uid).await {
+                total.nickname = Some(user.nickname);
+            }
+        }
+
+        Ok(totals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        peer_uid: i64,
+        peer_ip: &str,
+        direction: i8,
+        bytes: i64,
+        duration_ms: i64,
+        day: u32,
+    ) -> transfer_history::Model {
+        let finished_at = chrono::NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        transfer_history::Model {
+            id: 0,
+            tid: 0,
+            file_id: 0,
+            file_name: "a.txt".to_string(),
+            file_size: bytes,
+            direction,
+            peer_uid,
+            peer_ip: peer_ip.to_string(),
+            bytes,
+            duration_ms,
+            avg_speed: 0,
+            retries: 0,
+            started_at: finished_at,
+            finished_at,
+        }
+    }
+
+    #[test]
+    fn test_average_speed() {
+        assert_eq!(average_speed(1000, Duration::from_millis(500)), 2000);
+        assert_eq!(average_speed(10, Duration::ZERO), 10_000);
+    }
+
+    #[test]
+    fn test_aggregate_totals() {
+        let records = vec![
+            record(7, "192.168.1.7", 1, 1000, 1000, 1),
+            record(7, "192.168.1.8", 0, 3000, 1000, 3),
+            record(0, "192.168.1.9", 0, 500, 500, 2),
+        ];
+
+        let totals = aggregate_totals(&records);
+        assert_eq!(totals.len(), 2);
+
+        let first = &totals[0];
+        assert_eq!(first.peer_uid, 7);
+        assert_eq!(first.peer_ip, "192.168.1.8");
+        assert_eq!((first.sent_count, first.sent_bytes), (1, 1000));
+        assert_eq!((first.received_count, first.received_bytes), (1, 3000));
+        assert_eq!(first.avg_speed, 2000);
+
+        assert_eq!(totals[1].peer_uid, 0);
+        assert_eq!(totals[1].peer_ip, "192.168.1.9");
+    }
+}
@@ -2,11 +2,20 @@
 //
 //! 文件传输核心业务逻辑
 
+pub mod compress;
 pub mod handler;
+pub mod history;
+pub mod policy;
+pub mod rate_limit;
 pub mod request;
 pub mod resume;
+pub mod scheduler;
 pub mod service;
+pub mod share;
+pub mod storage;
+pub mod thumbnail;
 pub mod transfer;
+pub mod window;
 
 pub use handler::FileTransferHandler;
 pub use service::FileService;
@@ -0,0 +1,485 @@
+// src-tauri/src/core/file/policy.rs
+//
+//! 文件接收策略
+//!
+//! 收到文件请求时按规则决定自动接收、自动拒绝还是交给用户确认：
+//! - 规则按优先级依次匹配，条件为发送方联系人分组、扩展名和文件大小范围，未设置的条件不参与匹配
+//! - 任一文件被拒绝时拒绝整个请求，拒绝原因随释放包告知发送方
+//! - 全部文件命中自动接收规则时直接开始下载，否则等待用户确认
+//! - 文件名可疑（双扩展名、方向控制字符等）的文件不会被自动接收，下载完成后移入隔离目录；
+//!   内容与扩展名不符（如伪装成文档的可执行文件）的文件同样被隔离
+
+use crate::core::file::resume::create_transfer_state;
+use crate::core::file::thumbnail::sniff_image_size;
+use crate::core::file::FileService;
+use crate::database::handler::{ContactHandler, FileOfferHandler, FilePolicyHandler, UserHandler};
+use crate::database::model::{file_accept_policy, file_offer, transfer_state};
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::model::FileAttachment;
+use crate::types::FilePolicyAction;
+use sea_orm::DbConn;
+use std::io::Read;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 隔离目录
+pub const QUARANTINE_DIR: &str = "/tmp/feiqiu_quarantine";
+
+/// 检查文件内容时读取的头部长度
+const CONTENT_SNIFF_LEN: usize = 4096;
+
+/// 可执行文件和脚本的扩展名
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "exe", "com", "scr", "msi", "bat", "cmd", "ps1", "vbs", "vbe", "js", "wsf", "jar", "lnk", "sh", "pif", "cpl",
+];
+
+/// 图片扩展名（内容必须能识别为图片）
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+/// 方向控制字符（常用于把 "txt.exe" 显示成 "exe.txt"）
+const BIDI_CONTROLS: &[char] = &[
+    '\u{202A}', '\u{202B}', '\u{202D}', '\u{202E}', '\u{2066}', '\u{2067}', '\u{2068}',
+];
+
+/// 小写扩展名
+pub fn extension_of(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+}
+
+fn is_executable_extension(ext: &str) -> bool {
+    EXECUTABLE_EXTENSIONS.contains(&ext)
+}
+
+/// 规则的条件是否全部满足
+///
+/// # 参数
+/// - `rule`: 接收策略
+/// - `sender_tag`: 发送方在联系人中的分组
+/// - `file`: 文件信息
+pub fn rule_matches(rule: &file_accept_policy::Model, sender_tag: Option<&str>, file: &FileAttachment) -> bool {
+    if let Some(tag) = &rule.contact_tag {
+        if sender_tag != Some(tag.as_str()) {
+            return false;
+        }
+    }
+
+    if let Some(extensions) = &rule.extensions {
+        let ext = extension_of(&file.file_name).unwrap_or_default();
+        let listed = extensions
+            .split(',')
+            .map(|e| e.trim().trim_start_matches('.').to_ascii_lowercase())
+            .any(|e| !e.is_empty() && e == ext);
+        if !listed {
+            return false;
+        }
+    }
+
+    let size = file.file_size;
+    if rule.min_size.is_some_and(|min| size < min) || rule.max_size.is_some_and(|max| size > max) {
+        return false;
+    }
+
+    true
+}
+
+/// 按优先级找出第一条命中的规则
+///
+/// # 返回
+/// 命中规则的动作和规则本身；没有规则命中时为询问用户
+pub fn evaluate<'a>(
+    rules: &'a [file_accept_policy::Model],
+    sender_tag: Option<&str>,
+    file: &FileAttachment,
+) -> (FilePolicyAction, Option<&'a file_accept_policy::Model>) {
+    let mut enabled: Vec<&file_accept_policy::Model> = rules.iter().filter(|r| r.enabled != 0).collect();
+    enabled.sort_by_key(|r| (r.priority, r.id));
+
+    enabled
+        .into_iter()
+        .find(|r| rule_matches(r, sender_tag, file))
+        .map(|r| (FilePolicyAction::from_code(r.action), Some(r)))
+        .unwrap_or((FilePolicyAction::Ask, None))
+}
+
+/// 检查文件名是否可疑
+///
+/// # 返回
+/// 可疑原因；文件名正常时返回 None
+pub fn suspicious_name(name: &str) -> Option<String> {
+    if name.contains(BIDI_CONTROLS) {
+        return Some("文件名包含方向控制字符".to_string());
+    }
+
+    let ext = extension_of(name)?;
+    let stem = &name[..name.len() - ext.len() - 1];
+
+    if stem.ends_with(char::is_whitespace) {
+        return Some("扩展名前有空白字符".to_string());
+    }
+
+    if is_executable_extension(&ext) {
+        if let Some(inner) = extension_of(stem) {
+            if !inner.is_empty() && inner.chars().all(|c| c.is_ascii_alphanumeric()) && inner.len() <= 4 {
+                return Some(format!("双扩展名 .{}.{}", inner, ext));
+            }
+        }
+    }
+
+    None
+}
+
+/// 检查文件内容与扩展名是否相符
+///
+/// # 参数
+/// - `name`: 文件名
+/// - `header`: 文件头部内容
+///
+/// # 返回
+/// 不符的原因；相符或无法判断时返回 None
+pub fn content_mismatch(name: &str, header: &[u8]) -> Option<String> {
+    let ext = extension_of(name).unwrap_or_default();
+    if is_executable_extension(&ext) {
+        return None;
+    }
+
+    let executable = header.starts_with(b"MZ") || header.starts_with(b"\x7fELF") || header.starts_with(b"#!");
+    if executable {
+        return Some(format!("内容为可执行文件，与扩展名 .{} 不符", ext));
+    }
+
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) && !header.is_empty() && sniff_image_size(header).is_none() {
+        return Some(format!("内容不是 .{} 图片", ext));
+    }
+
+    None
+}
+
+/// 隔离文件名：去掉路径分隔符和控制字符
+fn quarantine_file_name(file_id: i64, name: &str) -> String {
+    let safe: String = name
+        .chars()
+        .map(|c| {
+            if c == '/' || c == '\\' || c.is_control() || BIDI_CONTROLS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("{}/{}_{}", QUARANTINE_DIR, file_id, safe)
+}
+
+/// 文件接收策略服务
+pub struct FilePolicyService;
+
+impl FilePolicyService {
+    /// 按接收策略处理收到的文件请求
+    ///
+    /// # 参数
+    /// - `from_ip`: 发送方IP
+    /// - `from_port`: 发送方端口
+    /// - `packet_no`: 文件请求的包编号
+    /// - `files`: 文件列表
+    ///
+    /// # 返回
+    /// 对整个请求采取的动作
+    pub async fn handle_offer(
+        db: &DbConn,
+        from_ip: &str,
+        from_port: u16,
+        packet_no: &str,
+        files: Vec<FileAttachment>,
+    ) -> AppResult<FilePolicyAction> {
+        let sender = UserHandler::find_by_ip_port(db, from_ip, from_port).await?;
+        let sender_tag = match (&sender, UserHandler::get_current_user_id(db).await) {
+            (Some(user), Ok(owner_uid)) => ContactHandler::find_by_owner_and_contact(db, owner_uid, user.uid)
+                .await?
+                .and_then(|c| c.tag),
+            _ => None,
+        };
+        let rules = FilePolicyHandler::list_enabled(db).await?;
+
+        // 断点续传时对方会以相同包编号重新发出请求，已记录的文件不再处理
+        let mut new_files = Vec::new();
+        for file in files {
+            if FileOfferHandler::find(db, packet_no, file.file_id as i64).await?.is_none() {
+                new_files.push(file);
+            }
+        }
+        if new_files.is_empty() {
+            info!("重复的文件请求，已忽略: packet_no={}", packet_no);
+            return Ok(FilePolicyAction::Ask);
+        }
+
+        let mut actions = Vec::with_capacity(new_files.len());
+        let mut rejection = None;
+        let mut suspicious = false;
+        for file in &new_files {
+            let quarantine_reason = suspicious_name(&file.file_name);
+            suspicious |= quarantine_reason.is_some();
+            FileOfferHandler::create(
+                db,
+                packet_no,
+                file.file_id as i64,
+                from_ip,
+                file.file_name.clone(),
+                file.file_size,
+                quarantine_reason,
+            )
+            .await?;
+
+            let (action, rule) = evaluate(&rules, sender_tag.as_deref(), file);
+            if action == FilePolicyAction::Reject && rejection.is_none() {
+                let rule_name = rule.map(|r| r.name.as_str()).unwrap_or_default();
+                rejection = Some(format!("{} ({})", rule_name, file.file_name));
+            }
+            actions.push(action);
+        }
+
+        if let Some(reason) = rejection {
+            FileService::reject_file(db, packet_no.to_string(), from_ip.to_string(), Some(reason.clone())).await?;
+            info!("文件请求已按策略拒绝: packet_no={}, reason={}", packet_no, reason);
+            let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::ShowNotification {
+                title: "已拒绝文件".to_string(),
+                body: format!("来自 {} 的文件: {}", from_ip, reason),
+            }));
+            return Ok(FilePolicyAction::Reject);
+        }
+
+        let sender_uid = sender.map(|u| u.uid).unwrap_or(0);
+        for file in &new_files {
+            create_transfer_state(
+                db,
+                file.file_id as i64,
+                0,
+                sender_uid,
+                0,
+                file.file_size,
+                packet_no,
+                from_ip,
+                from_port,
+                "",
+            )
+            .await?;
+        }
+
+        let auto_accepted = !suspicious && actions.iter().all(|a| *a == FilePolicyAction::Accept);
+        if auto_accepted {
+            for file in &new_files {
+                FileService::accept_file(db, packet_no.to_string(), file.file_id, 0, from_ip.to_string()).await?;
+            }
+            info!("文件请求已按策略自动接收: packet_no={}", packet_no);
+        }
+
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::FileOfferReceived {
+            from_ip: from_ip.to_string(),
+            packet_no: packet_no.to_string(),
+            files: serde_json::to_string(&new_files).unwrap_or_default(),
+            auto_accepted,
+        }));
+
+        Ok(if auto_accepted {
+            FilePolicyAction::Accept
+        } else {
+            FilePolicyAction::Ask
+        })
+    }
+
+    /// 下载完成后检查是否需要隔离
+    ///
+    /// 请求时文件名可疑，或内容与扩展名不符时，把文件移入隔离目录
+    ///
+    /// # 参数
+    /// - `transfer`: 已完成的下载记录
+    /// - `path`: 下载文件路径
+    ///
+    /// # 返回
+    /// 隔离后的文件路径；无需隔离时返回 None
+    pub async fn quarantine_if_needed(
+        db: &DbConn,
+        transfer: &transfer_state::Model,
+        path: &str,
+    ) -> AppResult<Option<String>> {
+        let Some(offer) = FileOfferHandler::find(db, &transfer.packet_no, transfer.file_id).await? else {
+            return Ok(None);
+        };
+
+        let reason = match offer.quarantine_reason.clone() {
+            Some(reason) => reason,
+            None => {
+                let owned = path.to_string();
+                let header = tokio::task::spawn_blocking(move || -> std::io::Result<Vec<u8>> {
+                    let mut header = Vec::with_capacity(CONTENT_SNIFF_LEN);
+                    std::fs::File::open(owned)?
+                        .take(CONTENT_SNIFF_LEN as u64)
+                        .read_to_end(&mut header)?;
+                    Ok(header)
+                })
+                .await
+                .map_err(|e| AppError::Business(format!("读取文件头失败: {}", e)))?
+                .map_err(AppError::Io)?;
+
+                match content_mismatch(&offer.file_name, &header) {
+                    Some(reason) => reason,
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        tokio::fs::create_dir_all(QUARANTINE_DIR).await.map_err(AppError::Io)?;
+        let target = quarantine_file_name(transfer.file_id, &offer.file_name);
+        tokio::fs::rename(path, &target).await.map_err(AppError::Io)?;
+        FileOfferHandler::set_quarantine(db, offer.id, reason.clone()).await?;
+
+        warn!(
+            "接收的文件已隔离: file_id={}, path={}, reason={}",
+            transfer.file_id, target, reason
+        );
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::FileQuarantined {
+            file_id: transfer.file_id,
+            path: target.clone(),
+            reason,
+        }));
+
+        Ok(Some(target))
+    }
+
+    /// 添加接收策略
+    ///
+    /// # 参数
+    /// - `action`: 0=询问, 1=自动接收, 2=自动拒绝
+    /// - `extensions`: 逗号分隔的扩展名列表
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_policy(
+        db: &DbConn,
+        name: String,
+        priority: i32,
+        action: i8,
+        contact_tag: Option<String>,
+        extensions: Option<String>,
+        min_size: Option<i64>,
+        max_size: Option<i64>,
+    ) -> AppResult<file_accept_policy::Model> {
+        if !(0..=2).contains(&action) {
+            return Err(AppError::Business(format!("无效的策略动作: {}", action)));
+        }
+        if let (Some(min), Some(max)) = (min_size, max_size) {
+            if min > max {
+                return Err(AppError::Business("最小文件大小不能超过最大文件大小".to_string()));
+            }
+        }
+
+        let extensions = extensions
+            .map(|list| {
+                list.split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_ascii_lowercase())
+                    .filter(|e| !e.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .filter(|list| !list.is_empty());
+        let contact_tag = contact_tag.filter(|t| !t.is_empty());
+
+        FilePolicyHandler::create(db, name, priority, action, contact_tag, extensions, min_size, max_size).await
+    }
+
+    /// 获取所有接收策略
+    pub async fn list_policies(db: &DbConn) -> AppResult<Vec<file_accept_policy::Model>> {
+        FilePolicyHandler::list_all(db).await
+    }
+
+    /// 启用或停用接收策略
+    pub async fn set_policy_enabled(db: &DbConn, id: i64, enabled: bool) -> AppResult<()> {
+        FilePolicyHandler::set_enabled(db, id, enabled).await
+    }
+
+    /// 删除接收策略
+    pub async fn remove_policy(db: &DbConn, id: i64) -> AppResult<()> {
+        FilePolicyHandler::delete(db, id).await
+    }
+
+    /// 获取被隔离的文件
+    pub async fn list_quarantined(db: &DbConn) -> AppResult<Vec<file_offer::Model>> {
+        FileOfferHandler::list_quarantined(db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i64, priority: i32, action: FilePolicyAction) -> file_accept_policy::Model {
+        file_accept_policy::Model {
+            id,
+            name: format!("rule{}", id),
+            priority,
+            action: action as i8,
+            contact_tag: None,
+            extensions: None,
+            min_size: None,
+            max_size: None,
+            enabled: 1,
+            create_time: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    fn file(name: &str, size: i64) -> FileAttachment {
+        FileAttachment {
+            file_name: name.to_string(),
+            file_size: size,
+            mtime: 0,
+            attr: 1,
+            image_size: None,
+            file_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_priority_and_conditions() {
+        let mut reject_exe = rule(1, 0, FilePolicyAction::Reject);
+        reject_exe.extensions = Some("exe,bat".to_string());
+        let mut accept_team = rule(2, 10, FilePolicyAction::Accept);
+        accept_team.contact_tag = Some("同事".to_string());
+        accept_team.max_size = Some(1024);
+
+        let rules = vec![accept_team, reject_exe];
+
+        let (action, hit) = evaluate(&rules, Some("同事"), &file("Setup.EXE", 10));
+        assert_eq!(action, FilePolicyAction::Reject);
+        assert_eq!(hit.map(|r| r.id), Some(1));
+
+        assert_eq!(
+            evaluate(&rules, Some("同事"), &file("a.txt", 10)).0,
+            FilePolicyAction::Accept
+        );
+        assert_eq!(
+            evaluate(&rules, Some("同事"), &file("a.txt", 4096)).0,
+            FilePolicyAction::Ask
+        );
+        assert_eq!(evaluate(&rules, None, &file("a.txt", 10)).0, FilePolicyAction::Ask);
+
+        let mut disabled = rules.clone();
+        disabled[1].enabled = 0;
+        assert_eq!(evaluate(&disabled, None, &file("a.exe", 10)).0, FilePolicyAction::Ask);
+    }
+
+    #[test]
+    fn test_suspicious_name_and_content() {
+        assert!(suspicious_name("report.pdf.exe").is_some());
+        assert!(suspicious_name("photo\u{202E}gpj.exe").is_some());
+        assert!(suspicious_name("invoice   .scr").is_some());
+        assert!(suspicious_name("setup.exe").is_none());
+        assert!(suspicious_name("archive.tar.gz").is_none());
+        assert!(suspicious_name("README").is_none());
+
+        assert!(content_mismatch("notes.txt", b"MZ\x90\x00").is_some());
+        assert!(content_mismatch("run.sh", b"#!/bin/sh").is_none());
+        assert!(content_mismatch("cat.png", b"not an image").is_some());
+        assert!(content_mismatch("notes.txt", b"hello").is_none());
+    }
+}
@@ -0,0 +1,294 @@
+// src-tauri/src/core/file/rate_limit.rs
+//
+//! 文件传输限速
+//!
+//! 基于令牌桶，分三级生效：全局、按对端 IP、按单个传输 (packet_no, file_id)。
+//! 一次发送/接收需要同时满足三级限制，等待时间取三者最大值。
+//! 所有限制均可在运行时通过 IPC 调整，速率为 0 表示不限速。
+//! 单个传输的限速随传输记录持久化，启动时恢复，传输完成或取消后才移除。
+
+use crate::database::handler::TransferStateHandler;
+use crate::error::AppResult;
+use crate::types::{PeerRateLimit, RateLimitSettings, TransferRateLimit};
+use sea_orm::DbConn;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// 令牌桶最小容量（允许的突发字节数）
+const MIN_BURST: f64 = 64.0 * 1024.0;
+
+/// 传输标识: (packet_no, file_id)
+pub type TransferKey = (String, u64);
+
+/// 令牌桶
+///
+/// 容量为 1 秒的配额（不少于 `MIN_BURST`），允许透支：
+/// 预留后令牌为负时，调用方需等待令牌补齐
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: u64) -> Self {
+        let mut bucket = Self {
+            rate,
+            tokens: 0.0,
+            last_refill: Instant::now(),
+        };
+        bucket.tokens = bucket.capacity();
+        bucket
+    }
+
+    /// 当前速率（字节/秒，0 表示不限速）
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// 调整速率，已积累的令牌不超过新容量
+    pub fn set_rate(&mut self, rate: u64) {
+        self.refill();
+        self.rate = rate;
+        self.tokens = self.tokens.min(self.capacity());
+    }
+
+    fn capacity(&self) -> f64 {
+        (self.rate as f64).max(MIN_BURST)
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.capacity());
+    }
+
+    /// 预留指定字节数的令牌
+    ///
+    /// # 返回
+    /// 返回调用方需要等待的时间
+    pub fn reserve(&mut self, bytes: usize) -> Duration {
+        if self.rate == 0 {
+            return Duration::ZERO;
+        }
+
+        self.refill();
+        self.tokens -= bytes as f64;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate as f64)
+        }
+    }
+}
+
+#[derive(Default)]
+struct LimiterState {
+    global: Option<TokenBucket>,
+    peers: HashMap<String, TokenBucket>,
+    transfers: HashMap<TransferKey, TokenBucket>,
+}
+
+/// 三级限速器
+#[derive(Default)]
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+/// 获取全局限速器
+pub fn rate_limiter() -> &'static RateLimiter {
+    static LIMITER: OnceLock<RateLimiter> = OnceLock::new();
+    LIMITER.get_or_init(RateLimiter::default)
+}
+
+impl RateLimiter {
+    fn lock(&self) -> std::sync::MutexGuard<'_, LimiterState> {
+        // 限速状态只包含数值，锁中毒后继续使用即可
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 设置全局限速（0 表示不限速）
+    pub fn set_global_limit(&self, bytes_per_sec: u64) {
+        let mut state = self.lock();
+        state.global = Self::update_bucket(state.global.take(), bytes_per_sec);
+    }
+
+    /// 设置对端限速（0 表示取消）
+    pub fn set_peer_limit(&self, peer_ip: &str, bytes_per_sec: u64) {
+        let mut state = self.lock();
+        let bucket = Self::update_bucket(state.peers.remove(peer_ip), bytes_per_sec);
+        if let Some(bucket) = bucket {
+            state.peers.insert(peer_ip.to_string(), bucket);
+        }
+    }
+
+    /// 设置单个传输限速（0 表示取消）
+    pub fn set_transfer_limit(&self, key: TransferKey, bytes_per_sec: u64) {
+        let mut state = self.lock();
+        let bucket = Self::update_bucket(state.transfers.remove(&key), bytes_per_sec);
+        if let Some(bucket) = bucket {
+            state.transfers.insert(key, bucket);
+        }
+    }
+
+    /// 传输结束后移除其限速配置
+    pub fn clear_transfer(&self, key: &TransferKey) {
+        self.lock().transfers.remove(key);
+    }
+
+    /// 获取当前限速设置
+    pub fn settings(&self) -> RateLimitSettings {
+        let state = self.lock();
+        RateLimitSettings {
+            global: state.global.as_ref().map(|b| b.rate()).unwrap_or(0),
+            per_peer: state
+                .peers
+                .iter()
+                .map(|(peer_ip, b)| PeerRateLimit {
+                    peer_ip: peer_ip.clone(),
+                    bytes_per_sec: b.rate(),
+                })
+                .collect(),
+            per_transfer: state
+                .transfers
+                .iter()
+                .map(|((packet_no, file_id), b)| TransferRateLimit {
+                    packet_no: packet_no.clone(),
+                    file_id: *file_id,
+                    bytes_per_sec: b.rate(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 计算发送/接收指定字节数需要等待的时间（同时预留令牌）
+    pub fn reserve(&self, peer_ip: &str, key: &TransferKey, bytes: usize) -> Duration {
+        let mut state = self.lock();
+        let mut wait = Duration::ZERO;
+
+        if let Some(bucket) = state.global.as_mut() {
+            wait = wait.max(bucket.reserve(bytes));
+        }
+        if let Some(bucket) = state.peers.get_mut(peer_ip) {
+            wait = wait.max(bucket.reserve(bytes));
+        }
+        if let Some(bucket) = state.transfers.get_mut(key) {
+            wait = wait.max(bucket.reserve(bytes));
+        }
+
+        wait
+    }
+
+    /// 等待直到允许发送/接收指定字节数
+    pub async fn acquire(&self, peer_ip: &str, key: &TransferKey, bytes: usize) {
+        let wait = self.reserve(peer_ip, key, bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    fn update_bucket(existing: Option<TokenBucket>, bytes_per_sec: u64) -> Option<TokenBucket> {
+        if bytes_per_sec == 0 {
+            return None;
+        }
+        Some(match existing {
+            Some(mut bucket) => {
+                bucket.set_rate(bytes_per_sec);
+                bucket
+            }
+            None => TokenBucket::new(bytes_per_sec),
+        })
+    }
+}
+
+/// 从传输记录恢复单个传输的限速配置
+///
+/// 未结束（包括失败、暂停）的传输在重启后重试时继续受原限速约束
+pub async fn restore_transfer_limits(db: &DbConn) -> AppResult<usize> {
+    let transfers = TransferStateHandler::find_rate_limited(db).await?;
+    for t in &transfers {
+        rate_limiter().set_transfer_limit((t.packet_no.clone(), t.file_id as u64), t.rate_limit as u64);
+    }
+    Ok(transfers.len())
+}
+
+/// 传输进入终止状态（完成/取消）后释放其限速配置
+///
+/// 同一文件发给多个接收方时共用一个限速配置，全部结束后才移除；
+/// 失败或暂停的传输保留配置，重试时继续生效
+pub async fn release_transfer_limit(db: &DbConn, packet_no: &str, file_id: u64) {
+    let transfers = match TransferStateHandler::find_by_packet_no(db, packet_no).await {
+        Ok(transfers) => transfers,
+        Err(e) => {
+            tracing::warn!("查询传输记录失败，保留限速配置: packet_no={}, {}", packet_no, e);
+            return;
+        }
+    };
+
+    let finished = transfers
+        .iter()
+        .filter(|t| t.file_id as u64 == file_id)
+        .all(|t| t.status == 2 || t.status == -2);
+    if finished {
+        rate_limiter().clear_transfer(&(packet_no.to_string(), file_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_bucket_never_waits() {
+        let mut bucket = TokenBucket::new(0);
+        assert_eq!(bucket.reserve(10 * 1024 * 1024), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_bucket_waits_after_burst() {
+        let mut bucket = TokenBucket::new(100 * 1024);
+        // 初始容量为 1 秒配额
+        assert_eq!(bucket.reserve(100 * 1024), Duration::ZERO);
+
+        let wait = bucket.reserve(50 * 1024);
+        assert!(wait >= Duration::from_millis(490) && wait <= Duration::from_millis(510));
+    }
+
+    #[test]
+    fn test_limiter_takes_strictest_level() {
+        let limiter = RateLimiter::default();
+        let key = ("1700000000".to_string(), 1);
+        limiter.set_global_limit(10 * 1024 * 1024);
+        limiter.set_peer_limit("192.168.1.10", 100 * 1024);
+
+        limiter.reserve("192.168.1.10", &key, 100 * 1024);
+        let wait = limiter.reserve("192.168.1.10", &key, 100 * 1024);
+        assert!(wait >= Duration::from_millis(990));
+
+        // 其他对端只受全局限制
+        assert_eq!(limiter.reserve("192.168.1.11", &key, 100 * 1024), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_limiter_settings_and_clear() {
+        let limiter = RateLimiter::default();
+        let key = ("1700000000".to_string(), 2);
+        limiter.set_transfer_limit(key.clone(), 4096);
+        limiter.set_peer_limit("192.168.1.10", 8192);
+
+        let settings = limiter.settings();
+        assert_eq!(settings.global, 0);
+        assert_eq!(settings.per_peer.len(), 1);
+        assert_eq!(settings.per_transfer[0].bytes_per_sec, 4096);
+
+        limiter.set_peer_limit("192.168.1.10", 0);
+        limiter.clear_transfer(&key);
+        let settings = limiter.settings();
+        assert!(settings.per_peer.is_empty());
+        assert!(settings.per_transfer.is_empty());
+    }
+}
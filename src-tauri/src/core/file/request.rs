@@ -45,12 +45,16 @@ pub fn handle_file_attach_request(packet: &FeiQPacket) -> AppResult<Vec<FileAtta
             .parse::<u32>()
             .map_err(|_| AppError::Protocol("Invalid attr".to_string()))?;
 
-        files.push(FileAttachment {
+        let mut file = FileAttachment {
             file_name,
             file_size,
             mtime,
             attr,
-        });
+            image_size: None,
+            file_id: 0,
+        };
+        file.apply_extensions(&parts[4..]);
+        files.push(file);
     }
 
     Ok(files)
@@ -64,6 +68,15 @@ pub fn create_file_attach_request(files: &[FileAttachment], _receiver_ip: &str,
     FeiQPacket::make_feiq_file_attach_packet(files, None)
 }
 
+/// 创建沿用指定 packet_no 的文件附件请求包
+///
+/// 接收方依据 packet_no 关联传输记录，重新提供文件（断点续传）时必须保持不变
+pub fn create_file_attach_request_with_packet_no(files: &[FileAttachment], packet_no: &str) -> FeiQPacket {
+    let mut packet = FeiQPacket::make_feiq_file_attach_packet(files, None);
+    packet.ext_info.unique_id = packet_no.to_string();
+    packet
+}
+
 /// 创建文件数据请求包
 ///
 /// 用于接收方请求文件数据
@@ -78,10 +91,31 @@ pub fn create_file_release(packet_no: &str) -> FeiQPacket {
     FeiQPacket::make_feiq_release_files_packet(packet_no, None)
 }
 
+/// 创建带拒绝原因的文件释放包
+///
+/// remark 格式: "packet_no:原因"，不认识原因字段的客户端只取 packet_no
+pub fn create_file_release_with_reason(packet_no: &str, reason: &str) -> FeiQPacket {
+    let mut packet = FeiQPacket::make_feiq_release_files_packet(packet_no, None);
+    packet.ext_info.remark = format!("{}:{}", packet_no, reason);
+    packet
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_file_release_reason_roundtrip() {
+        let packet = create_file_release_with_reason("1700000000", "文件类型被禁止: setup.exe");
+        assert_eq!(
+            packet.release_info(),
+            ("1700000000".to_string(), Some("文件类型被禁止: setup.exe".to_string()))
+        );
+
+        let packet = create_file_release("1700000000");
+        assert_eq!(packet.release_info(), ("1700000000".to_string(), None));
+    }
+
     // TODO: FeiQ file transfer not implemented yet - tests disabled
     // #[test]
     // fn test_handle_file_attach_request() {
@@ -1,10 +1,55 @@
 // src-tauri/src/core/file/resume.rs
 //
 //! 文件传输恢复逻辑
+//!
+//! 启动时以及对方重新上线时，继续未完成的传输：
+//! - 下载：校验本地部分文件后，从保存的偏移量重新请求（经由传输调度器）
+//! - 上传：沿用原 packet_no 重新向接收方提供文件，由接收方从其偏移量继续拉取
+//!
+//! 未完成的下载在 transfer_state.checksum 中保存前缀校验点 `偏移量:前缀SHA256`，
+//! 恢复时重新计算部分文件的前缀哈希，与校验点不一致时从头下载；下载完成后该字段改存整文件哈希
 
+use crate::core::file::handler::TransferStateExt;
+use crate::core::file::rate_limit::release_transfer_limit;
+use crate::core::file::request::create_file_attach_request_with_packet_no;
+use crate::core::file::scheduler::{self, transfer_scheduler};
+use crate::core::file::storage::hash_prefix;
 use crate::database::handler::{transfer_state::TransferStateHandler, FileStorageHandler};
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::model::FileAttachment;
+use crate::network::udp::sender;
+use crate::types::TransferStatus;
 use sea_orm::DbConn;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 同一上传向同一接收方重新提供的最小间隔
+///
+/// 对方每次上线广播 (BR_ENTRY/ANSENTRY) 都会触发恢复，间隔内不重复发送文件请求
+const REOFFER_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 最近重新提供过的上传 ((packet_no, 目标地址) -> 发送时间)
+fn reoffered() -> &'static Mutex<HashMap<(String, String), Instant>> {
+    static REOFFERED: OnceLock<Mutex<HashMap<(String, String), Instant>>> = OnceLock::new();
+    REOFFERED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 记录一次重新提供，间隔内已提供过时返回 false
+fn should_reoffer(packet_no: &str, target_addr: &str) -> bool {
+    let mut reoffered = reoffered().lock().unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    reoffered.retain(|_, at| now.duration_since(*at) < REOFFER_INTERVAL);
+
+    let key = (packet_no.to_string(), target_addr.to_string());
+    if reoffered.contains_key(&key) {
+        return false;
+    }
+    reoffered.insert(key, now);
+    true
+}
 
 /// 恢复传输
 pub async fn resume_transfers(db: &DbConn) -> AppResult<Vec<ResumeInfo>> {
@@ -12,12 +57,16 @@ pub async fn resume_transfers(db: &DbConn) -> AppResult<Vec<ResumeInfo>> {
     let mut resume_infos = Vec::new();
 
     for state in pending {
-        // 从 file_storage 表查询文件路径
-        let file_path = match FileStorageHandler::find_by_id(db, state.file_id).await {
-            Ok(file) => file.file_path,
-            Err(_) => {
-                tracing::warn!("文件 {} 不存在于 file_storage 表", state.file_id);
-                String::new()
+        // 下载使用本地部分文件；上传从 file_storage 表查询文件路径
+        let file_path = if state.direction == 0 {
+            state.get_save_path()
+        } else {
+            match FileStorageHandler::find_by_id(db, state.file_id).await {
+                Ok(file) => file.file_path,
+                Err(_) => {
+                    tracing::warn!("文件 {} 不存在于 file_storage 表", state.file_id);
+                    String::new()
+                }
             }
         };
 
@@ -51,7 +100,163 @@ pub struct ResumeInfo {
     pub direction: i8, // 0=下载, 1=上传
 }
 
+impl ResumeInfo {
+    /// 目标地址中的 IP 部分
+    pub fn target_ip(&self) -> &str {
+        self.target_addr
+            .rsplit_once(':')
+            .map(|(ip, _)| ip)
+            .unwrap_or(&self.target_addr)
+    }
+}
+
+/// 继续未完成的传输
+///
+/// # 参数
+/// * `db` - 数据库连接
+/// * `peer_ip` - 只恢复与该对端的传输；为 None 时恢复全部（启动时）
+///
+/// # 返回
+/// 返回已恢复的传输数
+pub async fn resume_interrupted_transfers(db: &DbConn, peer_ip: Option<&str>) -> AppResult<usize> {
+    let queued: Vec<i64> = transfer_scheduler().snapshot().iter().map(|e| e.tid).collect();
+    let infos: Vec<ResumeInfo> = resume_transfers(db)
+        .await?
+        .into_iter()
+        .filter(|info| peer_ip.is_none_or(|ip| info.target_ip() == ip))
+        .filter(|info| !queued.contains(&info.tid))
+        .collect();
+
+    let mut resumed = 0;
+    let mut offers: BTreeMap<(String, String), Vec<ResumeInfo>> = BTreeMap::new();
+
+    for info in infos {
+        if info.direction == 1 {
+            offers
+                .entry((info.packet_no.clone(), info.target_addr.clone()))
+                .or_default()
+                .push(info);
+            continue;
+        }
+
+        let offset = verify_partial_download(&info.file_path, info.offset, info.total, &info.checksum).await?;
+        if offset != info.offset {
+            warn!(
+                "部分文件与传输记录不一致，从 {} 继续: tid={}, 记录偏移={}",
+                offset, info.tid, info.offset
+            );
+        }
+        TransferStateHandler::update_progress(db, info.tid, offset as i64, TransferStatus::Pending as i8).await?;
+        transfer_scheduler().enqueue(info.tid, info.direction, 0, offset);
+        resumed += 1;
+    }
+    scheduler::pump(db);
+
+    for ((packet_no, target_addr), infos) in offers {
+        if !should_reoffer(&packet_no, &target_addr) {
+            continue;
+        }
+        match reoffer_upload(&packet_no, &target_addr, &infos).await {
+            Ok(()) => resumed += infos.len(),
+            Err(e) => warn!("重新提供文件失败: packet_no={}, {}", packet_no, e),
+        }
+    }
+
+    if resumed > 0 {
+        info!("已恢复 {} 个中断的传输", resumed);
+    }
+    Ok(resumed)
+}
+
+/// 校验下载的部分文件，得出可以继续的偏移量
+///
+/// 文件缺失时从头开始；文件短于记录时以文件长度为准；
+/// 有前缀校验点时重新计算该前缀的哈希，一致则从校验点继续，不一致（内容被改动）则从头开始；
+/// 继续位置之后的数据截掉
+///
+/// # 参数
+/// * `checkpoint` - 传输记录中保存的前缀校验点，没有时只按长度校验
+pub async fn verify_partial_download(path: &str, transferred: u64, total: u64, checkpoint: &str) -> AppResult<u64> {
+    let len = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => return Ok(0),
+    };
+
+    let mut offset = transferred.min(len).min(total);
+    if let Some((verified, expected)) = parse_checkpoint(checkpoint) {
+        offset = if verified <= offset && hash_prefix(Path::new(path), verified).await? == expected {
+            verified
+        } else {
+            warn!("部分文件前缀校验失败，从头下载: {}", path);
+            0
+        };
+    }
+
+    if len > offset {
+        let file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(path)
+            .await
+            .map_err(AppError::Io)?;
+        file.set_len(offset).await.map_err(AppError::Io)?;
+    }
+
+    Ok(offset)
+}
+
+/// 生成前缀校验点
+pub fn format_checkpoint(offset: u64, hash: &str) -> String {
+    format!("{}:{}", offset, hash)
+}
+
+/// 解析前缀校验点（整文件哈希或空值返回 None）
+fn parse_checkpoint(checkpoint: &str) -> Option<(u64, &str)> {
+    let (offset, hash) = checkpoint.split_once(':')?;
+    Some((offset.parse().ok()?, hash))
+}
+
+/// 计算下载部分文件的前缀哈希，作为校验点写入传输记录
+pub async fn record_download_checkpoint(db: &DbConn, tid: i64, path: &str, offset: u64) -> AppResult<()> {
+    let hash = hash_prefix(Path::new(path), offset).await?;
+    TransferStateHandler::set_checksum(db, tid, format_checkpoint(offset, &hash)).await?;
+    Ok(())
+}
+
+/// 沿用原 packet_no 重新提供上传的文件
+async fn reoffer_upload(packet_no: &str, target_addr: &str, infos: &[ResumeInfo]) -> AppResult<()> {
+    let mut files = Vec::new();
+    for info in infos {
+        let path = Path::new(&info.file_path);
+        let metadata = tokio::fs::metadata(path).await.map_err(AppError::Io)?;
+        files.push(FileAttachment {
+            file_name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            file_size: info.total as i64,
+            mtime: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            attr: if metadata.is_dir() { 2 } else { 1 },
+            image_size: None,
+            file_id: info.file_id as u64,
+        });
+    }
+
+    let packet = create_file_attach_request_with_packet_no(&files, packet_no);
+    sender::send_packet_data(target_addr, &packet.to_feiq_string())
+        .await
+        .map_err(|e| AppError::Network(format!("发送文件请求失败: {}", e)))?;
+
+    info!("已重新提供文件: packet_no={}, files={}", packet_no, files.len());
+    Ok(())
+}
+
 /// 创建文件传输状态记录
+#[allow(clippy::too_many_arguments)]
 pub async fn create_transfer_state(
     db: &DbConn,
     file_id: i64,
@@ -81,6 +286,7 @@ pub async fn create_transfer_state(
         target_port: ActiveValue::Set(target_port),
         checksum: ActiveValue::Set(checksum.to_string()),
         error_message: ActiveValue::NotSet,
+        rate_limit: ActiveValue::Set(0),
         update_time: ActiveValue::Set(chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()),
         create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
     };
@@ -97,7 +303,8 @@ pub async fn update_transfer_progress(db: &DbConn, tid: i64, transferred: i64, s
 
 /// 完成传输
 pub async fn complete_transfer(db: &DbConn, tid: i64) -> AppResult<()> {
-    TransferStateHandler::update_status(db, tid, 2, None).await?;
+    let t = TransferStateHandler::update_status(db, tid, 2, None).await?;
+    release_transfer_limit(db, &t.packet_no, t.file_id as u64).await;
     Ok(())
 }
 
@@ -109,7 +316,8 @@ pub async fn fail_transfer(db: &DbConn, tid: i64, error: &str) -> AppResult<()>
 
 /// 取消传输
 pub async fn cancel_transfer(db: &DbConn, tid: i64) -> AppResult<()> {
-    TransferStateHandler::update_status(db, tid, -2, Some("Cancelled by user".to_string())).await?;
+    let t = TransferStateHandler::update_status(db, tid, -2, Some("Cancelled by user".to_string())).await?;
+    release_transfer_limit(db, &t.packet_no, t.file_id as u64).await;
     Ok(())
 }
 
@@ -134,5 +342,52 @@ mod tests {
         assert_eq!(info.tid, 1);
         assert_eq!(info.offset, 1024);
         assert_eq!(info.total, 10240);
+        assert_eq!(info.target_ip(), "192.168.1.100");
+    }
+
+    #[tokio::test]
+    async fn test_verify_partial_download() {
+        let path = std::env::temp_dir().join(format!("feiqiu_resume_test_{}", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+
+        assert_eq!(verify_partial_download(&path_str, 512, 4096, "").await.unwrap(), 0);
+
+        std::fs::write(&path, vec![0u8; 1024]).unwrap();
+        // 文件长于记录：截掉未确认的尾部
+        assert_eq!(verify_partial_download(&path_str, 512, 4096, "").await.unwrap(), 512);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 512);
+
+        // 文件短于记录：以文件长度为准
+        assert_eq!(verify_partial_download(&path_str, 2048, 4096, "").await.unwrap(), 512);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_partial_download_checkpoint() {
+        let path = std::env::temp_dir().join(format!("feiqiu_resume_checkpoint_{}", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, vec![7u8; 1024]).unwrap();
+
+        let hash = hash_prefix(&path, 256).await.unwrap();
+        let checkpoint = format_checkpoint(256, &hash);
+        // 前缀一致：从校验点继续
+        assert_eq!(verify_partial_download(&path_str, 1024, 4096, &checkpoint).await.unwrap(), 256);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 256);
+
+        // 前缀被改动：从头下载
+        std::fs::write(&path, vec![8u8; 512]).unwrap();
+        assert_eq!(verify_partial_download(&path_str, 512, 4096, &checkpoint).await.unwrap(), 0);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_reoffer_debounced() {
+        let packet_no = format!("reoffer-test-{}", std::process::id());
+        assert!(should_reoffer(&packet_no, "192.168.1.100:2425"));
+        assert!(!should_reoffer(&packet_no, "192.168.1.100:2425"));
+        assert!(should_reoffer(&packet_no, "192.168.1.101:2425"));
     }
 }
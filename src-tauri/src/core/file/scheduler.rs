@@ -0,0 +1,549 @@
+// src-tauri/src/core/file/scheduler.rs
+//
+//! 文件传输调度
+//!
+//! 维护上传/下载队列：按优先级（数值大者优先）和入队顺序排队，
+//! 同时运行的传输数不超过上限。暂停会中止正在运行的任务，并把已确认的偏移量
+//! 写回 transfer_state，恢复时从该偏移量继续；失败的传输保留在队列中，可手动重试。
+//!
+//! 只调度本端主动驱动的传输：下载（TCP 拉取，对方不支持时 UDP 请求）和上传推送（FileSender，
+//! 包括对方通过 UDP GETFILEDATA 请求的上传）。
+//! 对方通过 TCP 通道主动拉取的上传由 `DbFileSource` 直接服务，不占用并发名额。
+
+use crate::core::file::compress::should_compress;
+use crate::core::file::handler::{FileTransferHandler, TransferStateExt};
+use crate::core::file::history;
+use crate::core::file::rate_limit::release_transfer_limit;
+use crate::core::file::request::create_file_data_request;
+use crate::core::file::resume::record_download_checkpoint;
+use crate::core::file::storage::record_sent_hash;
+use crate::core::file::transfer::FileSender;
+use crate::database::handler::{FileStorageHandler, TransferStateHandler};
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, FileEvent};
+use crate::network::tcp::TcpFileClient;
+use crate::network::udp::sender;
+use crate::types::{QueueItemState, TransferStatus};
+use sea_orm::DbConn;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::task::AbortHandle;
+use tokio::time::{interval, Duration, Instant};
+use tracing::{error, info, warn};
+
+/// 默认最大并发传输数
+pub const DEFAULT_MAX_CONCURRENT: usize = 3;
+
+/// 上传推送过程中落库进度的间隔
+const PROGRESS_PERSIST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// UDP 回退下载检查进度的间隔
+const UDP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// UDP 回退下载没有进展的最长时间
+const UDP_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 队列中的一项传输
+struct QueueEntry {
+    direction: i8,
+    priority: i32,
+    seq: u64,
+    state: QueueItemState,
+    retries: u32,
+    /// 已确认的偏移量（上传推送时由发送回调更新）
+    acked: Arc<AtomicU64>,
+    task: Option<AbortHandle>,
+}
+
+/// 暂停时被移出运行的传输
+pub struct PausedTransfer {
+    pub direction: i8,
+    pub was_running: bool,
+    pub acked: u64,
+}
+
+/// 队列快照中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueueSnapshotEntry {
+    pub tid: i64,
+    pub state: QueueItemState,
+    pub priority: i32,
+    pub position: Option<usize>,
+    pub retries: u32,
+}
+
+struct SchedulerState {
+    max_concurrent: usize,
+    entries: HashMap<i64, QueueEntry>,
+    next_seq: u64,
+}
+
+/// 传输调度器
+pub struct TransferScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+/// 获取全局传输调度器
+pub fn transfer_scheduler() -> &'static TransferScheduler {
+    static SCHEDULER: OnceLock<TransferScheduler> = OnceLock::new();
+    SCHEDULER.get_or_init(|| TransferScheduler::new(DEFAULT_MAX_CONCURRENT))
+}
+
+impl TransferScheduler {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(SchedulerState {
+                max_concurrent: max_concurrent.max(1),
+                entries: HashMap::new(),
+                next_seq: 0,
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SchedulerState> {
+        // 队列状态在锁内总是一次性更新完成，锁中毒后继续使用即可
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 加入队列
+    ///
+    /// 已在队列中的传输只更新优先级；已暂停或失败的传输重新排队，失败后重新排队计为一次重试
+    ///
+    /// # 参数
+    /// * `tid` - 传输ID
+    /// * `direction` - 传输方向 (0=下载, 1=上传)
+    /// * `priority` - 优先级（数值大者优先）
+    /// * `offset` - 起始偏移量
+    pub fn enqueue(&self, tid: i64, direction: i8, priority: i32, offset: u64) {
+        let mut state = self.lock();
+        let seq = state.next_seq;
+        state.next_seq += 1;
+
+        let entry = state.entries.entry(tid).or_insert_with(|| QueueEntry {
+            direction,
+            priority,
+            seq,
+            state: QueueItemState::Queued,
+            retries: 0,
+            acked: Arc::new(AtomicU64::new(offset)),
+            task: None,
+        });
+
+        entry.priority = priority;
+        if entry.state == QueueItemState::Failed {
+            entry.retries += 1;
+        }
+        if matches!(entry.state, QueueItemState::Paused | QueueItemState::Failed) {
+            entry.state = QueueItemState::Queued;
+            entry.acked.store(offset, Ordering::Relaxed);
+        }
+    }
+
+    /// 传输是否正在排队或运行
+    pub fn is_active(&self, tid: i64) -> bool {
+        matches!(
+            self.lock().entries.get(&tid),
+            Some(e) if matches!(e.state, QueueItemState::Queued | QueueItemState::Running)
+        )
+    }
+
+    /// 调整优先级
+    ///
+    /// # 返回
+    /// 传输不在队列中时返回 false
+    pub fn set_priority(&self, tid: i64, priority: i32) -> bool {
+        match self.lock().entries.get_mut(&tid) {
+            Some(entry) => {
+                entry.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 设置最大并发数（至少为 1）
+    pub fn set_max_concurrent(&self, max_concurrent: usize) {
+        self.lock().max_concurrent = max_concurrent.max(1);
+    }
+
+    /// 当前最大并发数
+    pub fn max_concurrent(&self) -> usize {
+        self.lock().max_concurrent
+    }
+
+    /// 暂停传输
+    ///
+    /// 运行中的任务会被中止
+    ///
+    /// # 返回
+    /// 传输不在队列中时返回 None
+    pub fn pause(&self, tid: i64) -> Option<PausedTransfer> {
+        let mut state = self.lock();
+        let entry = state.entries.get_mut(&tid)?;
+
+        let was_running = entry.state == QueueItemState::Running;
+        if let Some(task) = entry.task.take() {
+            task.abort();
+        }
+        entry.state = QueueItemState::Paused;
+
+        Some(PausedTransfer {
+            direction: entry.direction,
+            was_running,
+            acked: entry.acked.load(Ordering::Relaxed),
+        })
+    }
+
+    /// 将失败的传输重新排队，重试次数加一
+    ///
+    /// # 返回
+    /// 传输不在队列中或未失败时返回 false
+    pub fn retry(&self, tid: i64) -> bool {
+        match self.lock().entries.get_mut(&tid) {
+            Some(entry) if entry.state == QueueItemState::Failed => {
+                entry.state = QueueItemState::Queued;
+                entry.retries += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 传输失败后重新开始的次数（不在队列中时为 0）
+    pub fn retries(&self, tid: i64) -> u32 {
+        self.lock().entries.get(&tid).map(|e| e.retries).unwrap_or(0)
+    }
+
+    /// 从队列中移除（运行中的任务会被中止）
+    pub fn remove(&self, tid: i64) {
+        if let Some(entry) = self.lock().entries.remove(&tid) {
+            if let Some(task) = entry.task {
+                task.abort();
+            }
+        }
+    }
+
+    /// 取出可以启动的传输并标记为运行中
+    fn take_startable(&self) -> Vec<(i64, i8, Arc<AtomicU64>)> {
+        let mut state = self.lock();
+        let running = state.entries.values().filter(|e| e.state == QueueItemState::Running).count();
+        let slots = state.max_concurrent.saturating_sub(running);
+
+        let mut queued: Vec<(i64, i32, u64)> = state
+            .entries
+            .iter()
+            .filter(|(_, e)| e.state == QueueItemState::Queued)
+            .map(|(tid, e)| (*tid, e.priority, e.seq))
+            .collect();
+        queued.sort_by_key(|(_, priority, seq)| (-(*priority as i64), *seq));
+
+        queued
+            .into_iter()
+            .take(slots)
+            .filter_map(|(tid, _, _)| {
+                let entry = state.entries.get_mut(&tid)?;
+                entry.state = QueueItemState::Running;
+                Some((tid, entry.direction, entry.acked.clone()))
+            })
+            .collect()
+    }
+
+    /// 记录运行中任务的句柄（任务已被暂停或移除时直接中止）
+    fn attach_task(&self, tid: i64, task: AbortHandle) {
+        match self.lock().entries.get_mut(&tid) {
+            Some(entry) if entry.state == QueueItemState::Running => entry.task = Some(task),
+            _ => task.abort(),
+        }
+    }
+
+    /// 任务结束：成功则移出队列，失败则保留以便重试
+    fn finish(&self, tid: i64, success: bool) {
+        let mut state = self.lock();
+        let running = matches!(state.entries.get(&tid), Some(e) if e.state == QueueItemState::Running);
+        if !running {
+            return;
+        }
+        if success {
+            state.entries.remove(&tid);
+        } else if let Some(entry) = state.entries.get_mut(&tid) {
+            entry.state = QueueItemState::Failed;
+            entry.task = None;
+        }
+    }
+
+    /// 队列快照
+    ///
+    /// 顺序为：运行中、排队中（按实际启动顺序）、已暂停、失败
+    pub fn snapshot(&self) -> Vec<QueueSnapshotEntry> {
+        let state = self.lock();
+        let mut entries: Vec<(&i64, &QueueEntry)> = state.entries.iter().collect();
+        entries.sort_by_key(|(_, e)| {
+            let group = match e.state {
+                QueueItemState::Running => 0,
+                QueueItemState::Queued => 1,
+                QueueItemState::Paused => 2,
+                QueueItemState::Failed => 3,
+            };
+            (group, -(e.priority as i64), e.seq)
+        });
+
+        let mut position = 0;
+        entries
+            .into_iter()
+            .map(|(tid, e)| {
+                let position = if e.state == QueueItemState::Queued {
+                    position += 1;
+                    Some(position)
+                } else {
+                    None
+                };
+                QueueSnapshotEntry {
+                    tid: *tid,
+                    state: e.state,
+                    priority: e.priority,
+                    position,
+                    retries: e.retries,
+                }
+            })
+            .collect()
+    }
+}
+
+/// 启动排队中的传输，直到达到并发上限
+///
+/// 每个任务结束后会再次调用，以便补位
+pub fn pump(db: &DbConn) {
+    let scheduler = transfer_scheduler();
+
+    for (tid, direction, acked) in scheduler.take_startable() {
+        let task_db = db.clone();
+        let handle = tokio::spawn(async move {
+            let result = run_transfer(&task_db, tid, direction, acked).await;
+            if let Err(e) = &result {
+                error!("传输失败: tid={}, {}", tid, e);
+                if let Err(e) = TransferStateHandler::update_status(&task_db, tid, -1, Some(e.to_string())).await {
+                    error!("更新传输状态失败: {}", e);
+                }
+            }
+            transfer_scheduler().finish(tid, result.is_ok());
+            pump(&task_db);
+        });
+        scheduler.attach_task(tid, handle.abort_handle());
+        info!("传输已开始: tid={}", tid);
+    }
+}
+
+/// 暂停后把偏移量写回 transfer_state
+///
+/// 上传以连续确认位置为准；下载以本地部分文件的长度为准
+pub async fn persist_paused_offset(db: &DbConn, tid: i64, paused: &PausedTransfer) -> AppResult<u64> {
+    let transfer = TransferStateHandler::find_by_id(db, tid)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+
+    let offset = if !paused.was_running {
+        transfer.transferred as u64
+    } else if paused.direction == 1 {
+        paused.acked
+    } else {
+        let save_path = transfer.get_save_path();
+        let offset = tokio::fs::metadata(&save_path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or(0)
+            .min(transfer.file_size as u64);
+        // 下载的部分文件记录前缀校验点，恢复时据此确认内容未被改动
+        if offset > 0 {
+            record_download_checkpoint(db, tid, &save_path, offset).await?;
+        }
+        offset
+    };
+
+    TransferStateHandler::update_progress(db, tid, offset as i64, TransferStatus::Paused as i8).await?;
+    Ok(offset)
+}
+
+/// 运行单个传输
+async fn run_transfer(db: &DbConn, tid: i64, direction: i8, acked: Arc<AtomicU64>) -> AppResult<()> {
+    let transfer = TransferStateHandler::find_by_id(db, tid)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+    let offset = transfer.transferred as u64;
+    let addr = format!("{}:{}", transfer.target_ip, transfer.target_port);
+    let file_id = transfer.file_id as u64;
+
+    TransferStateHandler::update_status(db, tid, TransferStatus::Transferring as i8, None).await?;
+
+    if direction == 0 {
+        match TcpFileClient::connect(&addr).await {
+            Ok(client) => {
+                let stream = client.request_file_data(&transfer.packet_no, file_id, offset).await?;
+                FileTransferHandler::download_via_tcp(
+                    db,
+                    &transfer.target_ip,
+                    stream,
+                    &transfer.packet_no,
+                    file_id,
+                    offset,
+                )
+                .await?;
+            }
+            Err(e) => {
+                // UDP 回退由数据块事件驱动落盘，任务等到传输结束才让出并发名额
+                warn!("TCP 文件通道不可用，回退到 UDP: {}", e);
+                let packet = create_file_data_request(&transfer.packet_no, file_id, offset);
+                sender::send_packet_data(&addr, &packet.to_feiq_string())
+                    .await
+                    .map_err(|e| AppError::Network(format!("发送文件数据请求失败: {}", e)))?;
+                wait_udp_download(db, tid).await?;
+            }
+        }
+        return Ok(());
+    }
+
+    let file_storage = FileStorageHandler::find_by_id(db, transfer.file_id).await?;
+    let compress = should_compress(&transfer.target_ip, Path::new(&file_storage.file_path));
+    let file_sender = FileSender::new(file_storage.file_path.clone(), file_id, addr, transfer.packet_no.clone())
+        .with_offset(offset)
+        .with_compression(compress);
+
+    let started = Instant::now();
+    let progress_acked = acked.clone();
+    let send = file_sender.send_with_callback(move |progress| {
+        progress_acked.store(progress.acked_through, Ordering::Relaxed);
+        let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::TransferProgress {
+            file_id: file_id as i64,
+            progress: progress.offset,
+            total: progress.total,
+            rate: progress.rate,
+            compression_ratio: progress.compression_ratio(),
+            eta_secs: progress.eta_secs(),
+        }));
+    });
+    tokio::pin!(send);
+
+    let mut ticker = interval(PROGRESS_PERSIST_INTERVAL);
+    let progress = loop {
+        tokio::select! {
+            result = &mut send => break result?,
+            _ = ticker.tick() => {
+                let transferred = acked.load(Ordering::Relaxed) as i64;
+                TransferStateHandler::update_progress(db, tid, transferred, TransferStatus::Transferring as i8).await?;
+            }
+        }
+    };
+
+    TransferStateHandler::update_progress(db, tid, progress.offset as i64, TransferStatus::Completed as i8).await?;
+    release_transfer_limit(db, &transfer.packet_no, transfer.file_id as u64).await;
+    let bytes = progress.offset.saturating_sub(offset);
+    history::record_completed(db, &transfer, bytes, started.elapsed(), progress.retransmits).await;
+    if let Some(hash) = progress.checksum {
+        record_sent_hash(db, &file_storage, hash).await?;
+    }
+    Ok(())
+}
+
+/// 等待 UDP 回退下载结束
+///
+/// 数据块由事件处理器写入并更新 transfer_state，这里按间隔检查状态；
+/// 长时间没有进展时判定失败，保留在队列中以便重试
+async fn wait_udp_download(db: &DbConn, tid: i64) -> AppResult<()> {
+    let mut ticker = interval(UDP_POLL_INTERVAL);
+    let mut last_transferred = -1;
+    let mut last_progress_at = Instant::now();
+
+    loop {
+        ticker.tick().await;
+        let transfer = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+
+        match TransferStatus::from_code(transfer.status) {
+            TransferStatus::Completed | TransferStatus::Cancelled => return Ok(()),
+            TransferStatus::Failed => {
+                return Err(AppError::Network(
+                    transfer.error_message.unwrap_or_else(|| "UDP 文件接收失败".to_string()),
+                ))
+            }
+            _ => {}
+        }
+
+        if transfer.transferred != last_transferred {
+            last_transferred = transfer.transferred;
+            last_progress_at = Instant::now();
+        } else if last_progress_at.elapsed() > UDP_STALL_TIMEOUT {
+            return Err(AppError::Network(format!(
+                "UDP 文件接收超时: tid={}, {}/{}",
+                tid, transfer.transferred, transfer.file_size
+            )));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(scheduler: &TransferScheduler) -> Vec<i64> {
+        scheduler.take_startable().into_iter().map(|(tid, _, _)| tid).collect()
+    }
+
+    #[test]
+    fn test_priority_order_and_concurrency_limit() {
+        let scheduler = TransferScheduler::new(2);
+        scheduler.enqueue(1, 0, 0, 0);
+        scheduler.enqueue(2, 0, 0, 0);
+        scheduler.enqueue(3, 1, 5, 0);
+
+        assert_eq!(started(&scheduler), vec![3, 1]);
+        assert!(started(&scheduler).is_empty());
+
+        scheduler.finish(3, true);
+        assert_eq!(started(&scheduler), vec![2]);
+    }
+
+    #[test]
+    fn test_snapshot_positions() {
+        let scheduler = TransferScheduler::new(1);
+        scheduler.enqueue(1, 0, 0, 0);
+        scheduler.enqueue(2, 0, 0, 0);
+        scheduler.enqueue(3, 0, 0, 0);
+        started(&scheduler);
+        assert!(scheduler.set_priority(3, 10));
+
+        let snapshot = scheduler.snapshot();
+        let order: Vec<(i64, Option<usize>)> = snapshot.iter().map(|e| (e.tid, e.position)).collect();
+        assert_eq!(order, vec![(1, None), (3, Some(1)), (2, Some(2))]);
+        assert_eq!(snapshot[0].state, QueueItemState::Running);
+    }
+
+    #[test]
+    fn test_pause_resume_and_retry() {
+        let scheduler = TransferScheduler::new(1);
+        scheduler.enqueue(1, 1, 0, 0);
+        started(&scheduler);
+
+        let paused = scheduler.pause(1).unwrap();
+        assert!(paused.was_running);
+        assert!(started(&scheduler).is_empty());
+
+        // 恢复时从保存的偏移量继续
+        scheduler.enqueue(1, 1, 0, 4096);
+        let resumed = scheduler.take_startable();
+        assert_eq!(resumed[0].2.load(Ordering::Relaxed), 4096);
+
+        scheduler.finish(1, false);
+        assert_eq!(scheduler.snapshot()[0].state, QueueItemState::Failed);
+        assert!(scheduler.retry(1));
+        assert_eq!(scheduler.snapshot()[0].retries, 1);
+        assert_eq!(started(&scheduler), vec![1]);
+
+        // 失败后由上线恢复重新排队同样计为重试
+        scheduler.finish(1, false);
+        scheduler.enqueue(1, 1, 0, 8192);
+        assert_eq!(scheduler.retries(1), 2);
+    }
+}
@@ -5,18 +5,58 @@
 //! - 接受文件传输
 //! - 拒绝文件传输
 //! - 取消文件传输
-
-use crate::core::file::request::{create_file_attach_request, create_file_data_request, create_file_release};
-use crate::database::handler::{FileStorageHandler, TransferStateHandler, UserHandler};
+//! - 传输队列调度（优先级、暂停/恢复、重试）
+
+use crate::core::file::compress::UNCOMPRESSED_RATIO;
+use crate::core::file::handler::FileTransferHandler;
+use crate::core::file::rate_limit::{rate_limiter, release_transfer_limit};
+use crate::core::file::request::{
+    create_file_attach_request_with_packet_no, create_file_data_request, create_file_release,
+    create_file_release_with_reason,
+};
+use crate::core::file::scheduler::{self, transfer_scheduler};
+use crate::core::file::storage::{download_dir, download_path, store_local_file, DOWNLOAD_FILE_PREFIX};
+use crate::core::file::thumbnail::image_size;
+use crate::core::file::transfer::{estimate_eta, RateMeter};
+use crate::database::handler::group::GroupMemberHandler;
+use crate::database::handler::{
+    ChatMessageHandler, ChatSessionHandler, FileOfferHandler, FileStorageHandler, TransferStateHandler, UserHandler,
+};
 use crate::error::{AppError, AppResult};
+use crate::network::tcp::TcpFileClient;
 use crate::network::udp::sender;
-use crate::types::{PendingTransfer, TransferStatus};
+use crate::types::{
+    FileOfferProgress, PendingTransfer, QueueItemState, QueuedTransfer, RateLimitSettings, RecipientProgress,
+    TransferQueue, TransferStatus,
+};
 use sea_orm::DbConn;
-use tracing::info;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tracing::{error, info, warn};
 
 /// 文件服务
 pub struct FileService;
 
+/// 文件请求的接收方
+struct OfferRecipient {
+    uid: i64,
+    ip: String,
+    port: u16,
+    online: bool,
+}
+
+/// 生成文件请求的包编号
+///
+/// 以毫秒时间戳为基础并保证单调递增，同一时刻发出的多个文件请求不会共用编号
+fn next_transfer_id() -> i64 {
+    static LAST: AtomicI64 = AtomicI64::new(0);
+    let now = chrono::Utc::now().timestamp_millis();
+    let previous = LAST
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| Some(now.max(last + 1)))
+        .unwrap_or_else(|last| last);
+    now.max(previous + 1)
+}
+
 impl FileService {
     /// 发送文件请求
     ///
@@ -39,9 +79,147 @@ impl FileService {
             .await?
             .ok_or_else(|| AppError::NotFound(format!("未找到目标用户: {}", target_ip)))?;
 
+        let recipient = OfferRecipient {
+            uid: target_user.uid,
+            ip: target_ip,
+            port: target_user.feiq_port,
+            online: true,
+        };
+
+        let (transfer_id, delivered) = Self::offer_files(db, &file_paths, &[recipient], owner_uid, None).await?;
+        if delivered == 0 {
+            return Err(AppError::Network("发送文件请求失败".to_string()));
+        }
+
+        Ok(transfer_id)
+    }
+
+    /// 一次把文件发给多个用户
+    ///
+    /// 所有接收方共用一条文件存储记录，每个接收方各有一条传输记录
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `file_paths`: 文件路径列表
+    /// - `target_ips`: 接收方IP地址列表
+    /// - `owner_uid`: 发送者用户ID
+    ///
+    /// # 返回
+    /// 返回文件传输ID（所有接收方共用）
+    pub async fn send_file_to_users(
+        db: &DbConn,
+        file_paths: Vec<String>,
+        target_ips: Vec<String>,
+        owner_uid: i64,
+    ) -> AppResult<i64> {
+        let mut recipients: Vec<OfferRecipient> = Vec::new();
+        for ip in target_ips {
+            if recipients.iter().any(|r| r.ip == ip) {
+                continue;
+            }
+            let user = UserHandler::find_by_ip_port(db, &ip, 2425)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("未找到目标用户: {}", ip)))?;
+            recipients.push(OfferRecipient {
+                uid: user.uid,
+                ip,
+                port: user.feiq_port,
+                online: true,
+            });
+        }
+
+        if recipients.is_empty() {
+            return Err(AppError::Business("没有指定接收方".to_string()));
+        }
+
+        let (transfer_id, delivered) = Self::offer_files(db, &file_paths, &recipients, owner_uid, None).await?;
+        if delivered == 0 {
+            return Err(AppError::Network("发送文件请求失败".to_string()));
+        }
+
+        Ok(transfer_id)
+    }
+
+    /// 向群组发送文件
+    ///
+    /// 每个群成员各有一条传输记录；离线成员的记录保持等待状态，
+    /// 对方上线时由断点续传流程重新发出文件请求。文件在群会话中显示为一条文件消息
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `file_paths`: 文件路径列表
+    /// - `gid`: 群组ID
+    /// - `owner_uid`: 发送者用户ID
+    ///
+    /// # 返回
+    /// 返回文件传输ID
+    pub async fn send_group_file(db: &DbConn, file_paths: Vec<String>, gid: i64, owner_uid: i64) -> AppResult<i64> {
+        let members = GroupMemberHandler::list_by_group(db, gid).await?;
+
+        let mut recipients = Vec::new();
+        for member in members {
+            if member.member_uid == owner_uid {
+                continue;
+            }
+            match UserHandler::find_by_id(db, member.member_uid).await {
+                Ok(user) => recipients.push(OfferRecipient {
+                    uid: user.uid,
+                    ip: user.feiq_ip,
+                    port: user.feiq_port,
+                    online: user.status == 1,
+                }),
+                Err(e) => warn!("群成员信息缺失，跳过: gid={}, uid={}, error={}", gid, member.member_uid, e),
+            }
+        }
+
+        if recipients.is_empty() {
+            return Err(AppError::Business(format!("群组 {} 没有其他成员", gid)));
+        }
+
+        let (transfer_id, delivered) = Self::offer_files(db, &file_paths, &recipients, owner_uid, Some(gid)).await?;
+        info!(
+            "群文件请求已发送: gid={}, transfer_id={}, online={}/{}",
+            gid,
+            transfer_id,
+            delivered,
+            recipients.len()
+        );
+
+        // 在群会话中记录文件消息（msg_no 即文件传输ID，用于关联发送进度）
+        let content = file_paths
+            .iter()
+            .filter_map(|p| std::path::Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let message =
+            ChatMessageHandler::create_with_msg_no(db, 1, gid, owner_uid, content, 1, Some(transfer_id.to_string()))
+                .await?;
+        ChatMessageHandler::update_status(db, message.mid, 1).await?;
+
+        let session = ChatSessionHandler::get_or_create(db, owner_uid, 1, gid).await?;
+        ChatSessionHandler::update_last_message(db, session.sid, message.mid).await?;
+
+        Ok(transfer_id)
+    }
+
+    /// 创建文件存储和传输记录，并向在线接收方发出文件请求
+    ///
+    /// # 参数
+    /// - `group`: 群组ID（群文件时），单聊时传输记录的目标为接收方用户ID
+    ///
+    /// # 返回
+    /// 返回 (文件传输ID, 成功发出请求的接收方数量)
+    async fn offer_files(
+        db: &DbConn,
+        file_paths: &[String],
+        recipients: &[OfferRecipient],
+        owner_uid: i64,
+        group: Option<i64>,
+    ) -> AppResult<(i64, usize)> {
         // 构建文件附件列表
         let mut files = Vec::new();
-        for path in &file_paths {
+        for path in file_paths {
             use std::path::Path;
             let path_obj = Path::new(path);
             let metadata = path_obj.metadata().map_err(AppError::Io)?;
@@ -60,74 +238,97 @@ impl FileService {
                     .map_err(|e| AppError::Business(e.to_string()))?
                     .as_secs(),
                 attr: if path_obj.is_dir() { 2 } else { 1 },
+                // 登记文件记录后填入
+                image_size: None,
+                file_id: 0,
             });
         }
 
-        // 创建文件附件包
-        let receiver = format!("{}:{}", target_ip, target_user.feiq_port);
-        let packet = create_file_attach_request(&files, &target_ip, target_user.feiq_port as u16);
-        let packet_str = packet.to_feiq_string();
-
-        // 发送 UDP 包
-        sender::send_packet_data(&receiver, &packet_str)
-            .await
-            .map_err(|e| AppError::Network(format!("发送文件请求失败: {}", e)))?;
+        // 包编号与传输记录一致，便于对方断点续传时关联
+        let transfer_id = next_transfer_id();
 
-        // 保存到数据库 - 创建文件存储记录
-        let transfer_id = chrono::Utc::now().timestamp();
+        // 保存到数据库 - 创建文件存储记录（所有接收方共用，相同内容复用已有记录）
         let mut file_ids = Vec::new();
 
-        for file in &files {
-            let file_storage = FileStorageHandler::create(
-                db,
-                file.file_name.clone(),
-                file_paths.first().unwrap_or(&String::new()).clone(),
-                file.file_size,
-                "application/octet-stream".to_string(),
-                owner_uid,
-            )
-            .await?;
-
+        for (file, path) in files.iter_mut().zip(file_paths) {
+            let file_storage = store_local_file(db, path, owner_uid).await?;
+            file.image_size = image_size(&file_storage);
+            file.file_id = file_storage.fid as u64;
             file_ids.push(file_storage.fid);
         }
 
-        // 创建传输状态记录
-        for (index, file_storage) in file_ids.iter().enumerate() {
+        // 每个接收方各自的传输状态记录
+        let mut tids_by_recipient = Vec::with_capacity(recipients.len());
+        for recipient in recipients {
             use crate::database::model::transfer_state;
             use sea_orm::ActiveValue::*;
 
-            let transfer_model = transfer_state::ActiveModel {
-                tid: NotSet,
-                file_id: Set(*file_storage),
-                session_type: Set(0), // 单聊
-                target_id: Set(target_user.uid),
-                direction: Set(1), // 1=上传
-                transferred: Set(0),
-                file_size: Set(files[index].file_size),
-                status: Set(0), // 0=等待对方接受
-                packet_no: Set(transfer_id.to_string()),
-                target_ip: Set(target_ip.clone()),
-                target_port: Set(target_user.feiq_port as u16),
-                checksum: Set(String::new()),
-                error_message: NotSet,
-                update_time: Set(chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()),
-                create_time: Set(chrono::Utc::now().naive_utc()),
-            };
+            let mut tids = Vec::new();
+            for (index, file_id) in file_ids.iter().enumerate() {
+                let transfer_model = transfer_state::ActiveModel {
+                    tid: NotSet,
+                    file_id: Set(*file_id),
+                    session_type: Set(if group.is_some() { 1 } else { 0 }),
+                    target_id: Set(group.unwrap_or(recipient.uid)),
+                    direction: Set(1), // 1=上传
+                    transferred: Set(0),
+                    file_size: Set(files[index].file_size),
+                    status: Set(0), // 0=等待对方接受
+                    packet_no: Set(transfer_id.to_string()),
+                    target_ip: Set(recipient.ip.clone()),
+                    target_port: Set(recipient.port),
+                    checksum: Set(String::new()),
+                    error_message: NotSet,
+                    rate_limit: Set(0),
+                    update_time: Set(chrono::Utc::now().naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()),
+                    create_time: Set(chrono::Utc::now().naive_utc()),
+                };
+
+                match TransferStateHandler::create(db, transfer_model).await {
+                    Ok(t) => tids.push(t.tid),
+                    Err(e) => error!("创建传输记录失败: ip={}, error={}", recipient.ip, e),
+                }
+            }
+            tids_by_recipient.push(tids);
+        }
 
-            let _ = TransferStateHandler::create(db, transfer_model).await;
+        // 向在线接收方发送文件附件包
+        let packet_str = create_file_attach_request_with_packet_no(&files, &transfer_id.to_string()).to_feiq_string();
+        let mut delivered = 0;
+
+        for (recipient, tids) in recipients.iter().zip(&tids_by_recipient) {
+            if !recipient.online {
+                continue;
+            }
+
+            let receiver = format!("{}:{}", recipient.ip, recipient.port);
+            match sender::send_packet_data(&receiver, &packet_str).await {
+                Ok(_) => delivered += 1,
+                Err(e) => {
+                    warn!("发送文件请求失败: receiver={}, error={}", receiver, e);
+                    for tid in tids {
+                        let _ = TransferStateHandler::update_status(db, *tid, -1, Some(format!("发送文件请求失败: {}", e)))
+                            .await;
+                    }
+                }
+            }
         }
 
         info!(
-            "文件请求已发送: transfer_id={}, files_count={}",
+            "文件请求已发送: transfer_id={}, files_count={}, recipients={}",
             transfer_id,
-            file_paths.len()
+            file_paths.len(),
+            recipients.len()
         );
 
-        Ok(transfer_id)
+        Ok((transfer_id, delivered))
     }
 
     /// 接受文件传输
     ///
+    /// 已有下载记录的传输进入调度队列；否则直接拉取：
+    /// 优先通过 TCP 通道拉取原始字节流，对方不支持 TCP 时回退到 UDP GETFILEDATA
+    ///
     /// # 参数
     /// - `packet_no`: 包编号
     /// - `file_id`: 文件ID
@@ -137,17 +338,63 @@ impl FileService {
     /// # 返回
     /// 返回操作结果
     pub async fn accept_file(
-        _db: &DbConn,
+        db: &DbConn,
         packet_no: String,
         file_id: u64,
         offset: u64,
         target_ip: String,
     ) -> AppResult<()> {
+        let download = TransferStateHandler::find_by_packet_no(db, &packet_no)
+            .await?
+            .into_iter()
+            .find(|t| t.file_id as u64 == file_id && t.direction == 0);
+
+        FileOfferHandler::set_decision(db, &packet_no, 1, None).await?;
+
+        if let Some(t) = download {
+            TransferStateHandler::update_progress(db, t.tid, offset as i64, TransferStatus::Pending as i8).await?;
+            transfer_scheduler().enqueue(t.tid, t.direction, 0, offset);
+            scheduler::pump(db);
+
+            info!("文件传输已接受并排队: tid={}, packet_no={}", t.tid, packet_no);
+            return Ok(());
+        }
+
+        let addr = format!("{}:{}", target_ip, 2425);
+
+        // 优先使用 TCP 通道
+        match TcpFileClient::connect(&addr).await {
+            Ok(client) => {
+                let stream = client.request_file_data(&packet_no, file_id, offset).await?;
+                let db = db.clone();
+                let packet_no_clone = packet_no.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = FileTransferHandler::download_via_tcp(
+                        &db,
+                        &target_ip,
+                        stream,
+                        &packet_no_clone,
+                        file_id,
+                        offset,
+                    )
+                    .await
+                    {
+                        error!("TCP 文件接收失败: {}", e);
+                    }
+                });
+
+                info!("文件传输已接受 (TCP): packet_no={}, file_id={}", packet_no, file_id);
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("TCP 文件通道不可用，回退到 UDP: {}", e);
+            }
+        }
+
         // 创建文件数据请求包
         let packet = create_file_data_request(&packet_no, file_id, offset);
 
         // 发送 GETFILEDATA 包
-        let addr = format!("{}:{}", target_ip, 2425);
         let packet_str = packet.to_feiq_string();
 
         sender::send_packet_data(&addr, &packet_str)
@@ -159,17 +406,109 @@ impl FileService {
         Ok(())
     }
 
+    /// 接受目录传输
+    ///
+    /// 目录只能通过 TCP 通道 (GETDIRFILES) 传输，没有 UDP 回退
+    ///
+    /// # 参数
+    /// - `packet_no`: 包编号
+    /// - `file_id`: 文件ID
+    /// - `target_ip`: 目标IP
+    /// - `dest_dir`: 用户选择的保存目录（为空时保存到下载目录下以传输ID命名的目录）
+    ///
+    /// # 返回
+    /// 返回操作结果
+    pub async fn accept_directory(
+        db: &DbConn,
+        packet_no: String,
+        file_id: u64,
+        target_ip: String,
+        dest_dir: Option<String>,
+    ) -> AppResult<()> {
+        let addr = format!("{}:{}", target_ip, 2425);
+        let client = TcpFileClient::connect(&addr).await?;
+
+        let transfer_state = TransferStateHandler::find_by_packet_no(db, &packet_no)
+            .await?
+            .into_iter()
+            .find(|t| t.file_id as u64 == file_id);
+
+        let dest_dir = match (dest_dir, &transfer_state) {
+            (Some(dir), _) => PathBuf::from(dir),
+            (None, Some(t)) => PathBuf::from(download_path(t.tid)),
+            (None, None) => download_dir().join(format!("{}{}_{}", DOWNLOAD_FILE_PREFIX, packet_no, file_id)),
+        };
+
+        let db = db.clone();
+        tokio::spawn(async move {
+            use crate::event::bus::EVENT_SENDER;
+            use crate::event::model::{AppEvent, FileEvent};
+
+            if let Err(e) = tokio::fs::create_dir_all(&dest_dir).await {
+                error!("创建目录失败: {}", e);
+                return;
+            }
+
+            let total = transfer_state.as_ref().map(|t| t.file_size as u64).unwrap_or(0);
+            let mut meter = RateMeter::new();
+            let mut last_received = 0u64;
+            let result = client
+                .fetch_dir_files(&packet_no, file_id, &dest_dir, |received| {
+                    meter.record(received - last_received);
+                    last_received = received;
+                    let rate = meter.rate();
+                    let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::TransferProgress {
+                        file_id: file_id as i64,
+                        progress: received,
+                        total,
+                        rate,
+                        compression_ratio: UNCOMPRESSED_RATIO,
+                        eta_secs: estimate_eta(total.saturating_sub(received), rate),
+                    }));
+                })
+                .await;
+
+            match (result, transfer_state) {
+                (Ok(received), Some(t)) => {
+                    let _ = TransferStateHandler::update_progress(&db, t.tid, received as i64, 2).await;
+                    release_transfer_limit(&db, &t.packet_no, t.file_id as u64).await;
+                }
+                (Ok(_), None) => {}
+                (Err(e), Some(t)) => {
+                    error!("目录接收失败: {}", e);
+                    let _ = TransferStateHandler::update_status(&db, t.tid, -1, Some(e.to_string())).await;
+                }
+                (Err(e), None) => {
+                    error!("目录接收失败: {}", e);
+                }
+            }
+        });
+
+        info!("目录传输已接受 (TCP): file_id={}", file_id);
+
+        Ok(())
+    }
+
     /// 拒绝文件传输
     ///
     /// # 参数
     /// - `packet_no`: 包编号
     /// - `target_ip`: 目标IP
+    /// - `reason`: 拒绝原因（随释放包告知发送方）
     ///
     /// # 返回
     /// 返回操作结果
-    pub async fn reject_file(_db: &DbConn, packet_no: String, target_ip: String) -> AppResult<()> {
+    pub async fn reject_file(
+        db: &DbConn,
+        packet_no: String,
+        target_ip: String,
+        reason: Option<String>,
+    ) -> AppResult<()> {
         // 创建文件释放包
-        let packet = create_file_release(&packet_no);
+        let packet = match &reason {
+            Some(reason) => create_file_release_with_reason(&packet_no, reason),
+            None => create_file_release(&packet_no),
+        };
 
         // 发送 RELEASEFILES 包
         let addr = format!("{}:{}", target_ip, 2425);
@@ -179,6 +518,16 @@ impl FileService {
             .await
             .map_err(|e| AppError::Network(format!("发送文件拒绝包失败: {}", e)))?;
 
+        // 收到请求时已建立的下载记录一并取消
+        for t in TransferStateHandler::find_by_packet_no(db, &packet_no).await? {
+            if t.direction == 0 && t.target_ip == target_ip && t.status == TransferStatus::Pending as i8 {
+                let status = TransferStatus::Cancelled as i8;
+                TransferStateHandler::update_status(db, t.tid, status, reason.clone()).await?;
+                release_transfer_limit(db, &packet_no, t.file_id as u64).await;
+            }
+        }
+        FileOfferHandler::set_decision(db, &packet_no, 2, reason).await?;
+
         info!("文件传输已拒绝: packet_no={}", packet_no);
 
         Ok(())
@@ -193,8 +542,12 @@ impl FileService {
     /// # 返回
     /// 返回操作结果
     pub async fn cancel_transfer(db: &DbConn, transfer_id: i64) -> AppResult<()> {
+        transfer_scheduler().remove(transfer_id);
+        scheduler::pump(db);
+
         // 更新传输状态为已取消
-        TransferStateHandler::update_status(db, transfer_id, -2, Some("已取消".to_string())).await?;
+        let t = TransferStateHandler::update_status(db, transfer_id, -2, Some("已取消".to_string())).await?;
+        release_transfer_limit(db, &t.packet_no, t.file_id as u64).await;
 
         info!("文件传输已取消: transfer_id={}", transfer_id);
 
@@ -226,17 +579,7 @@ impl FileService {
                 file_path: file_storage.file_path,
                 transferred: t.transferred,
                 file_size: t.file_size,
-                status: if t.status == 0 {
-                    TransferStatus::Pending
-                } else if t.status == 1 {
-                    TransferStatus::Transferring
-                } else if t.status == 2 {
-                    TransferStatus::Completed
-                } else if t.status == -1 {
-                    TransferStatus::Failed
-                } else {
-                    TransferStatus::Cancelled
-                },
+                status: TransferStatus::from_code(t.status),
                 target_ip: t.target_ip,
                 direction: t.direction,
             });
@@ -245,6 +588,47 @@ impl FileService {
         Ok(result)
     }
 
+    /// 获取一次文件发送按接收方汇总的进度
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `packet_no`: 文件传输ID（发送时返回的包编号）
+    ///
+    /// # 返回
+    /// 返回每个接收方的进度和总体进度
+    pub async fn get_file_offer_progress(db: &DbConn, packet_no: String) -> AppResult<FileOfferProgress> {
+        let uploads: Vec<_> = TransferStateHandler::find_by_packet_no(db, &packet_no)
+            .await?
+            .into_iter()
+            .filter(|t| t.direction == 1)
+            .collect();
+
+        let first = uploads
+            .first()
+            .ok_or_else(|| AppError::NotFound(format!("文件传输 {}", packet_no)))?;
+        let (session_type, target_id) = (first.session_type, first.target_id);
+
+        let mut recipients = aggregate_recipients(&uploads);
+        for recipient in &mut recipients {
+            recipient.target_uid = UserHandler::find_by_ip_port(db, &recipient.target_ip, 2425)
+                .await?
+                .map(|u| u.uid);
+        }
+
+        Ok(FileOfferProgress {
+            packet_no,
+            session_type,
+            target_id,
+            completed_recipients: recipients
+                .iter()
+                .filter(|r| r.status == TransferStatus::Completed)
+                .count(),
+            transferred: recipients.iter().map(|r| r.transferred).sum(),
+            total: recipients.iter().map(|r| r.total).sum(),
+            recipients,
+        })
+    }
+
     /// 更新文件传输进度
     ///
     /// # 参数
@@ -259,4 +643,334 @@ impl FileService {
         TransferStateHandler::update_status(db, transfer_id, 1, None).await?;
         Ok(())
     }
+
+    /// 设置全局传输限速
+    ///
+    /// # 参数
+    /// - `bytes_per_sec`: 字节/秒，0 表示不限速
+    pub fn set_global_rate_limit(bytes_per_sec: u64) {
+        rate_limiter().set_global_limit(bytes_per_sec);
+        info!("全局传输限速已更新: {} B/s", bytes_per_sec);
+    }
+
+    /// 设置对端传输限速
+    ///
+    /// # 参数
+    /// - `peer_ip`: 对端IP
+    /// - `bytes_per_sec`: 字节/秒，0 表示取消限速
+    pub fn set_peer_rate_limit(peer_ip: &str, bytes_per_sec: u64) {
+        rate_limiter().set_peer_limit(peer_ip, bytes_per_sec);
+        info!("对端传输限速已更新: {} -> {} B/s", peer_ip, bytes_per_sec);
+    }
+
+    /// 设置单个传输的限速
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `tid`: 传输ID
+    /// - `bytes_per_sec`: 字节/秒，0 表示取消限速
+    pub async fn set_transfer_rate_limit(db: &DbConn, tid: i64, bytes_per_sec: u64) -> AppResult<()> {
+        let transfer = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+
+        // 同时写入传输记录，重试或重启后继续生效
+        TransferStateHandler::set_rate_limit(db, tid, bytes_per_sec as i64).await?;
+        rate_limiter().set_transfer_limit((transfer.packet_no, transfer.file_id as u64), bytes_per_sec);
+        info!("传输限速已更新: tid={}, {} B/s", tid, bytes_per_sec);
+
+        Ok(())
+    }
+
+    /// 获取当前限速设置
+    pub fn get_rate_limits() -> RateLimitSettings {
+        rate_limiter().settings()
+    }
+
+    /// 将传输加入调度队列
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `tid`: 传输ID
+    /// - `priority`: 优先级（数值大者优先）
+    pub async fn enqueue_transfer(db: &DbConn, tid: i64, priority: i32) -> AppResult<()> {
+        let transfer = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+
+        if transfer.status == TransferStatus::Completed as i8 || transfer.status == TransferStatus::Cancelled as i8 {
+            return Err(AppError::Business(format!("传输已结束，无法排队: tid={}", tid)));
+        }
+
+        TransferStateHandler::update_status(db, tid, TransferStatus::Pending as i8, None).await?;
+        transfer_scheduler().enqueue(tid, transfer.direction, priority, transfer.transferred as u64);
+        scheduler::pump(db);
+
+        info!("传输已加入队列: tid={}, priority={}", tid, priority);
+        Ok(())
+    }
+
+    /// 暂停传输
+    ///
+    /// 运行中的传输会被中止，已确认的偏移量写回 transfer_state
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `tid`: 传输ID
+    pub async fn pause_transfer(db: &DbConn, tid: i64) -> AppResult<()> {
+        let offset = match transfer_scheduler().pause(tid) {
+            Some(paused) => scheduler::persist_paused_offset(db, tid, &paused).await?,
+            None => {
+                // 不在队列中的传输（如对方正在拉取的上传）只标记状态，拒绝后续请求
+                let transfer = TransferStateHandler::find_by_id(db, tid)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+                TransferStateHandler::update_status(db, tid, TransferStatus::Paused as i8, None).await?;
+                transfer.transferred as u64
+            }
+        };
+        scheduler::pump(db);
+
+        info!("传输已暂停: tid={}, offset={}", tid, offset);
+        Ok(())
+    }
+
+    /// 恢复传输
+    ///
+    /// 从 transfer_state 中保存的偏移量重新排队
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `tid`: 传输ID
+    pub async fn resume_transfer(db: &DbConn, tid: i64) -> AppResult<()> {
+        let transfer = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("传输不存在: {}", tid)))?;
+
+        let resumable = [TransferStatus::Pending, TransferStatus::Transferring, TransferStatus::Paused];
+        if !resumable.iter().any(|s| *s as i8 == transfer.status) {
+            return Err(AppError::Business(format!("传输状态无法恢复: {}", transfer.status)));
+        }
+
+        TransferStateHandler::update_status(db, tid, TransferStatus::Pending as i8, None).await?;
+        transfer_scheduler().enqueue(tid, transfer.direction, 0, transfer.transferred as u64);
+        scheduler::pump(db);
+
+        info!("传输已恢复: tid={}, offset={}", tid, transfer.transferred);
+        Ok(())
+    }
+
+    /// 重试失败的传输
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `tid`: 传输ID
+    pub async fn retry_transfer(db: &DbConn, tid: i64) -> AppResult<()> {
+        let transfer = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("传输记录 {}", tid)))?;
+
+        if transfer.status != TransferStatus::Failed as i8 {
+            return Err(AppError::Business(format!("只能重试失败的传输: tid={}", tid)));
+        }
+
+        TransferStateHandler::update_status(db, tid, TransferStatus::Pending as i8, None).await?;
+        if !transfer_scheduler().retry(tid) {
+            transfer_scheduler().enqueue(tid, transfer.direction, 0, transfer.transferred as u64);
+        }
+        scheduler::pump(db);
+
+        info!("传输已重试: tid={}", tid);
+        Ok(())
+    }
+
+    /// 调整传输优先级
+    ///
+    /// # 参数
+    /// - `tid`: 传输ID
+    /// - `priority`: 优先级（数值大者优先）
+    pub fn set_transfer_priority(tid: i64, priority: i32) -> AppResult<()> {
+        if !transfer_scheduler().set_priority(tid, priority) {
+            return Err(AppError::NotFound(format!("传输不在队列中: {}", tid)));
+        }
+        Ok(())
+    }
+
+    /// 设置最大并发传输数
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `max_concurrent`: 最大并发数（至少为 1）
+    pub fn set_max_concurrent_transfers(db: &DbConn, max_concurrent: usize) {
+        transfer_scheduler().set_max_concurrent(max_concurrent);
+        scheduler::pump(db);
+        info!("最大并发传输数已更新: {}", transfer_scheduler().max_concurrent());
+    }
+
+    /// 获取传输队列状态
+    ///
+    /// 包括调度器中的传输和重启前暂停、尚未重新排队的传输
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    pub async fn get_transfer_queue(db: &DbConn) -> AppResult<TransferQueue> {
+        let snapshot = transfer_scheduler().snapshot();
+        let mut items = Vec::new();
+
+        for entry in &snapshot {
+            let Some(t) = TransferStateHandler::find_by_id(db, entry.tid).await? else {
+                continue;
+            };
+            items.push(Self::queued_transfer(db, t, entry.state, entry.priority, entry.position, entry.retries).await);
+        }
+
+        for t in TransferStateHandler::find_by_status(db, TransferStatus::Paused as i8).await? {
+            if snapshot.iter().all(|e| e.tid != t.tid) {
+                items.push(Self::queued_transfer(db, t, QueueItemState::Paused, 0, None, 0).await);
+            }
+        }
+
+        Ok(TransferQueue {
+            max_concurrent: transfer_scheduler().max_concurrent(),
+            running: snapshot.iter().filter(|e| e.state == QueueItemState::Running).count(),
+            items,
+        })
+    }
+
+    async fn queued_transfer(
+        db: &DbConn,
+        t: crate::database::model::transfer_state::Model,
+        state: QueueItemState,
+        priority: i32,
+        position: Option<usize>,
+        retries: u32,
+    ) -> QueuedTransfer {
+        // 下载记录的 file_id 是对方的文件ID，本地不一定有 file_storage 记录
+        let file_name = FileStorageHandler::find_by_id(db, t.file_id)
+            .await
+            .map(|f| f.file_name)
+            .unwrap_or_else(|_| format!("file_{}", t.file_id));
+
+        QueuedTransfer {
+            tid: t.tid,
+            file_id: t.file_id,
+            file_name,
+            direction: t.direction,
+            target_ip: t.target_ip,
+            state,
+            priority,
+            position,
+            retries,
+            transferred: t.transferred,
+            file_size: t.file_size,
+            error_message: t.error_message,
+        }
+    }
+}
+
+/// 按接收方汇总上传记录
+///
+/// 接收方的状态：全部完成为已完成；否则按 传输中 > 失败 > 暂停 > 等待 > 取消 取最靠前的一个
+fn aggregate_recipients(uploads: &[crate::database::model::transfer_state::Model]) -> Vec<RecipientProgress> {
+    let mut recipients: Vec<RecipientProgress> = Vec::new();
+
+    for t in uploads {
+        let index = match recipients.iter().position(|r| r.target_ip == t.target_ip) {
+            Some(index) => index,
+            None => {
+                recipients.push(RecipientProgress {
+                    target_ip: t.target_ip.clone(),
+                    target_uid: None,
+                    status: TransferStatus::Completed,
+                    file_count: 0,
+                    completed_files: 0,
+                    transferred: 0,
+                    total: 0,
+                });
+                recipients.len() - 1
+            }
+        };
+
+        let recipient = &mut recipients[index];
+        let status = TransferStatus::from_code(t.status);
+        recipient.file_count += 1;
+        recipient.transferred += t.transferred;
+        recipient.total += t.file_size;
+        if status == TransferStatus::Completed {
+            recipient.completed_files += 1;
+            // 已完成的文件按总大小计，避免进度记录滞后
+            recipient.transferred += t.file_size - t.transferred;
+        } else if status_rank(status) < status_rank(recipient.status) {
+            recipient.status = status;
+        }
+    }
+
+    recipients
+}
+
+fn status_rank(status: TransferStatus) -> u8 {
+    match status {
+        TransferStatus::Transferring => 0,
+        TransferStatus::Failed => 1,
+        TransferStatus::Paused => 2,
+        TransferStatus::Pending => 3,
+        TransferStatus::Cancelled => 4,
+        TransferStatus::Completed => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(target_ip: &str, status: i8, transferred: i64) -> crate::database::model::transfer_state::Model {
+        crate::database::model::transfer_state::Model {
+            tid: 0,
+            file_id: 1,
+            session_type: 1,
+            target_id: 3,
+            direction: 1,
+            transferred,
+            file_size: 100,
+            status,
+            packet_no: "1700000000".to_string(),
+            target_ip: target_ip.to_string(),
+            target_port: 2425,
+            checksum: String::new(),
+            error_message: None,
+            rate_limit: 0,
+            update_time: String::new(),
+            create_time: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    #[test]
+    fn test_aggregate_recipients() {
+        let uploads = vec![
+            upload("192.168.1.10", 2, 90),
+            upload("192.168.1.11", 1, 40),
+            upload("192.168.1.10", 2, 100),
+            upload("192.168.1.11", 0, 0),
+            upload("192.168.1.12", -1, 10),
+        ];
+
+        let recipients = aggregate_recipients(&uploads);
+        assert_eq!(recipients.len(), 3);
+
+        assert_eq!(recipients[0].status, TransferStatus::Completed);
+        assert_eq!(recipients[0].completed_files, 2);
+        assert_eq!((recipients[0].transferred, recipients[0].total), (200, 200));
+
+        assert_eq!(recipients[1].status, TransferStatus::Transferring);
+        assert_eq!(recipients[1].file_count, 2);
+        assert_eq!(recipients[1].transferred, 40);
+
+        assert_eq!(recipients[2].status, TransferStatus::Failed);
+    }
+
+    #[test]
+    fn test_transfer_ids_unique() {
+        let ids: Vec<i64> = (0..100).map(|_| next_transfer_id()).collect();
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
 }
@@ -0,0 +1,378 @@
+// src-tauri/src/core/file/share.rs
+//
+//! 共享文件夹
+//!
+//! 本端把只读目录发布为共享，对端无需聊天握手即可通过 TCP 文件通道浏览并按需拉取。
+//! 浏览使用 LISTSHARES / LISTDIR 请求；拉取文件沿用 GETFILEDATA：packet_no 为
+//! `share-` 加 base64url 编码的共享路径，file_id 由对端地址和共享路径哈希得出，
+//! 因此下载记录可以直接交给传输调度器排队、暂停和续传。
+
+use crate::core::file::resume::create_transfer_state;
+use crate::core::file::service::FileService;
+use crate::database::handler::{ContactHandler, SharedFolderHandler, UserHandler};
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::constants::{FEIQ_DEFAULT_PORT, IPMSG_FILE_DIR, IPMSG_FILE_REGULAR};
+use crate::network::tcp::protocol::{is_safe_entry_name, DirEntryHeader};
+use crate::network::tcp::TcpFileClient;
+use crate::types::{RemoteShareEntry, SharedFolderInfo};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sea_orm::DbConn;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 共享文件请求的 packet_no 前缀
+pub const SHARE_PACKET_PREFIX: &str = "share-";
+
+/// 共享文件请求的 packet_no
+pub fn share_packet_no(share_path: &str) -> String {
+    format!("{}{}", SHARE_PACKET_PREFIX, URL_SAFE_NO_PAD.encode(share_path))
+}
+
+/// 从 packet_no 中解析共享路径，不是共享文件请求时返回 None
+pub fn parse_share_packet_no(packet_no: &str) -> Option<String> {
+    let encoded = packet_no.strip_prefix(SHARE_PACKET_PREFIX)?;
+    let bytes = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// 共享文件的下载 ID
+///
+/// 同一对端的同一路径总是得到相同的 ID（保存路径因此稳定，便于续传），
+/// 取 53 位以便前端以 number 精确表示
+pub fn share_file_id(owner_ip: &str, share_path: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(owner_ip.as_bytes())
+        .chain_update([0u8])
+        .chain_update(share_path.as_bytes())
+        .finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes) & ((1 << 53) - 1)
+}
+
+/// 拆分共享路径 "共享名/相对路径"
+///
+/// 每一级都必须是合法的条目名，防止路径穿越
+fn split_share_path(share_path: &str) -> AppResult<(&str, Vec<&str>)> {
+    let mut parts = share_path.split('/').filter(|p| !p.is_empty());
+    let name = parts.next().ok_or_else(|| AppError::Business("共享路径为空".to_string()))?;
+    let components: Vec<&str> = parts.collect();
+
+    if !is_safe_entry_name(name) || !components.iter().all(|c| is_safe_entry_name(c)) {
+        return Err(AppError::Business(format!("非法共享路径: {}", share_path)));
+    }
+
+    Ok((name, components))
+}
+
+/// 共享文件夹服务
+pub struct ShareService;
+
+impl ShareService {
+    /// 发布共享文件夹
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `name`: 共享名（对端浏览时看到的名称）
+    /// - `path`: 本地目录
+    /// - `contact_uids`: 允许访问的联系人，为空表示所有联系人
+    pub async fn add_share(
+        db: &DbConn,
+        name: String,
+        path: String,
+        contact_uids: Vec<i64>,
+    ) -> AppResult<SharedFolderInfo> {
+        if !is_safe_entry_name(&name) {
+            return Err(AppError::Business(format!("非法共享名: {}", name)));
+        }
+        if !Path::new(&path).is_dir() {
+            return Err(AppError::Business(format!("共享路径不是目录: {}", path)));
+        }
+
+        let access = Self::validate_contacts(db, contact_uids).await?;
+        let share = SharedFolderHandler::create(db, name, path).await?;
+        SharedFolderHandler::set_access(db, share.share_id, &access).await?;
+
+        info!("共享文件夹已发布: share_id={}, name={}", share.share_id, share.name);
+        Ok(SharedFolderInfo {
+            share_id: share.share_id,
+            name: share.name,
+            path: share.path,
+            contact_uids: access,
+        })
+    }
+
+    /// 取消共享
+    pub async fn remove_share(db: &DbConn, share_id: i64) -> AppResult<()> {
+        SharedFolderHandler::delete(db, share_id).await?;
+        info!("共享文件夹已取消: share_id={}", share_id);
+        Ok(())
+    }
+
+    /// 更新共享的访问列表
+    ///
+    /// # 参数
+    /// - `contact_uids`: 允许访问的联系人，为空表示所有联系人
+    pub async fn set_share_access(db: &DbConn, share_id: i64, contact_uids: Vec<i64>) -> AppResult<()> {
+        SharedFolderHandler::find_by_id(db, share_id).await?;
+        let access = Self::validate_contacts(db, contact_uids).await?;
+        SharedFolderHandler::set_access(db, share_id, &access).await
+    }
+
+    /// 获取本端发布的共享
+    pub async fn list_local_shares(db: &DbConn) -> AppResult<Vec<SharedFolderInfo>> {
+        let mut result = Vec::new();
+        for share in SharedFolderHandler::list_all(db).await? {
+            result.push(SharedFolderInfo {
+                contact_uids: SharedFolderHandler::list_access(db, share.share_id).await?,
+                share_id: share.share_id,
+                name: share.name,
+                path: share.path,
+            });
+        }
+        Ok(result)
+    }
+
+    /// 访问列表只能包含当前用户的联系人
+    async fn validate_contacts(db: &DbConn, mut contact_uids: Vec<i64>) -> AppResult<Vec<i64>> {
+        contact_uids.sort_unstable();
+        contact_uids.dedup();
+
+        let owner_uid = UserHandler::get_current_user_id(db).await?;
+        for uid in &contact_uids {
+            if ContactHandler::find_by_owner_and_contact(db, owner_uid, *uid).await?.is_none() {
+                return Err(AppError::Business(format!("用户 {} 不是联系人", uid)));
+            }
+        }
+
+        Ok(contact_uids)
+    }
+
+    // ============================================================
+    // 对端请求（TCP 文件通道）
+    // ============================================================
+
+    /// 请求方能否访问该共享：必须是联系人，且在访问列表中（访问列表为空时对所有联系人开放）
+    ///
+    /// TCP 连接不携带对端的 FeiQ 端口，按 IP 匹配用户，同一 IP 上任一用户有权访问即可
+    async fn can_access(db: &DbConn, peer_ip: &str, share_id: i64) -> AppResult<bool> {
+        let peers = UserHandler::list_by_ip(db, peer_ip).await?;
+        if peers.is_empty() {
+            return Ok(false);
+        }
+        let owner_uid = UserHandler::get_current_user_id(db).await?;
+        let access = SharedFolderHandler::list_access(db, share_id).await?;
+
+        for peer in peers {
+            if ContactHandler::find_by_owner_and_contact(db, owner_uid, peer.uid)
+                .await?
+                .is_some()
+                && (access.is_empty() || access.contains(&peer.uid))
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// 解析请求方可访问的共享路径
+    ///
+    /// 不存在和无权访问返回同样的错误，避免泄露共享名
+    async fn resolve_for_peer(db: &DbConn, peer_ip: &str, share_path: &str) -> AppResult<PathBuf> {
+        let (name, components) = split_share_path(share_path)?;
+        let not_found = || AppError::NotFound(format!("共享路径 {}", share_path));
+
+        let share = SharedFolderHandler::find_by_name(db, name).await?.ok_or_else(not_found)?;
+        if !Self::can_access(db, peer_ip, share.share_id).await? {
+            return Err(not_found());
+        }
+
+        let mut path = PathBuf::from(&share.path);
+        path.extend(components);
+
+        // 拒绝指向共享目录之外的符号链接
+        let root = tokio::fs::canonicalize(&share.path).await.map_err(AppError::Io)?;
+        let resolved = tokio::fs::canonicalize(&path).await.map_err(|_| not_found())?;
+        if !resolved.starts_with(&root) {
+            return Err(not_found());
+        }
+
+        Ok(resolved)
+    }
+
+    /// 列出向请求方开放的共享
+    pub async fn list_shares_for_peer(db: &DbConn, peer_ip: &str) -> AppResult<Vec<DirEntryHeader>> {
+        let mut entries = Vec::new();
+        for share in SharedFolderHandler::list_all(db).await? {
+            if Self::can_access(db, peer_ip, share.share_id).await? {
+                entries.push(DirEntryHeader {
+                    name: share.name,
+                    size: 0,
+                    attr: IPMSG_FILE_DIR,
+                });
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 列出共享中的目录
+    pub async fn list_dir_for_peer(db: &DbConn, peer_ip: &str, share_path: &str) -> AppResult<Vec<DirEntryHeader>> {
+        let dir = Self::resolve_for_peer(db, peer_ip, share_path).await?;
+
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(&dir).await.map_err(AppError::Io)?;
+        while let Some(entry) = read_dir.next_entry().await.map_err(AppError::Io)? {
+            let metadata = entry.metadata().await.map_err(AppError::Io)?;
+            entries.push(DirEntryHeader {
+                name: entry.file_name().to_string_lossy().to_string(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                attr: if metadata.is_dir() {
+                    IPMSG_FILE_DIR
+                } else {
+                    IPMSG_FILE_REGULAR
+                },
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(entries)
+    }
+
+    /// 解析请求方要拉取的共享文件
+    pub async fn resolve_file_for_peer(db: &DbConn, peer_ip: &str, share_path: &str) -> AppResult<PathBuf> {
+        let path = Self::resolve_for_peer(db, peer_ip, share_path).await?;
+        if !path.is_file() {
+            return Err(AppError::Business(format!("共享路径不是文件: {}", share_path)));
+        }
+        Ok(path)
+    }
+
+    // ============================================================
+    // 浏览对端共享
+    // ============================================================
+
+    /// 对端的用户 ID 和 FeiQ 端口（TCP 文件通道与 UDP 使用同一端口）
+    ///
+    /// 未登记的对端用户 ID 为 0，端口为 FeiQ 默认端口
+    async fn remote_peer(db: &DbConn, target_ip: &str) -> AppResult<(i64, u16)> {
+        Ok(UserHandler::list_by_ip(db, target_ip)
+            .await?
+            .first()
+            .map_or((0, FEIQ_DEFAULT_PORT), |user| (user.uid, user.feiq_port)))
+    }
+
+    /// 浏览对端向本端开放的共享
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `target_ip`: 对端IP
+    pub async fn browse_remote_shares(db: &DbConn, target_ip: &str) -> AppResult<Vec<RemoteShareEntry>> {
+        let (_, port) = Self::remote_peer(db, target_ip).await?;
+        let client = TcpFileClient::connect(&format!("{}:{}", target_ip, port)).await?;
+        let entries = client.list_shares().await?;
+        Ok(entries.into_iter().map(|e| remote_entry("", e)).collect())
+    }
+
+    /// 浏览对端共享中的目录
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `target_ip`: 对端IP
+    /// - `share_path`: "共享名/相对路径"
+    pub async fn browse_remote_dir(db: &DbConn, target_ip: &str, share_path: &str) -> AppResult<Vec<RemoteShareEntry>> {
+        split_share_path(share_path)?;
+        let (_, port) = Self::remote_peer(db, target_ip).await?;
+        let client = TcpFileClient::connect(&format!("{}:{}", target_ip, port)).await?;
+        let entries = client.list_dir(share_path).await?;
+        Ok(entries.into_iter().map(|e| remote_entry(share_path, e)).collect())
+    }
+
+    /// 把对端共享中的文件加入下载队列
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `target_ip`: 对端IP
+    /// - `share_path`: 文件的共享路径
+    /// - `file_size`: 文件大小（浏览结果中的大小）
+    /// - `priority`: 调度优先级
+    ///
+    /// # 返回
+    /// 返回传输ID
+    pub async fn queue_download(
+        db: &DbConn,
+        target_ip: &str,
+        share_path: &str,
+        file_size: i64,
+        priority: i32,
+    ) -> AppResult<i64> {
+        split_share_path(share_path)?;
+        let (owner_uid, port) = Self::remote_peer(db, target_ip).await?;
+
+        let tid = create_transfer_state(
+            db,
+            share_file_id(target_ip, share_path) as i64,
+            0,
+            owner_uid,
+            0, // 下载
+            file_size,
+            &share_packet_no(share_path),
+            target_ip,
+            port,
+            "",
+        )
+        .await?;
+
+        FileService::enqueue_transfer(db, tid, priority).await?;
+        info!("共享文件已加入下载队列: tid={}, path={}", tid, share_path);
+        Ok(tid)
+    }
+}
+
+/// 把对端返回的目录头部转换为前端条目
+fn remote_entry(parent: &str, header: DirEntryHeader) -> RemoteShareEntry {
+    let path = if parent.is_empty() {
+        header.name.clone()
+    } else {
+        format!("{}/{}", parent.trim_end_matches('/'), header.name)
+    };
+
+    RemoteShareEntry {
+        is_dir: header.attr & 0xff == IPMSG_FILE_DIR,
+        name: header.name,
+        path,
+        size: header.size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_packet_no_roundtrip() {
+        let path = "builds/2026:10/app 1.0.zip";
+        let packet_no = share_packet_no(path);
+        assert!(!packet_no.contains(':'));
+        assert_eq!(parse_share_packet_no(&packet_no).as_deref(), Some(path));
+        assert_eq!(parse_share_packet_no("1700000000"), None);
+    }
+
+    #[test]
+    fn test_share_file_id_is_stable() {
+        let id = share_file_id("192.168.1.10", "builds/app.zip");
+        assert_eq!(id, share_file_id("192.168.1.10", "builds/app.zip"));
+        assert_ne!(id, share_file_id("192.168.1.11", "builds/app.zip"));
+        assert!(id < 1 << 53);
+    }
+
+    #[test]
+    fn test_split_share_path() {
+        let (name, components) = split_share_path("builds/nightly/app.zip").unwrap();
+        assert_eq!(name, "builds");
+        assert_eq!(components, vec!["nightly", "app.zip"]);
+
+        assert!(split_share_path("builds/../secret").is_err());
+        assert!(split_share_path("").is_err());
+    }
+}
@@ -0,0 +1,407 @@
+// src-tauri/src/core/file/storage.rs
+//
+//! 内容寻址的文件存储
+//!
+//! 文件记录以内容 SHA256 为键：再次发送相同内容的文件时复用已有记录，
+//! 接收到与已有文件内容相同的文件时只保留一份。
+//! 接收到的文件通过传输记录的校验和被引用（文件消息的 msg_no 即传输的 packet_no），
+//! 被文件消息引用的传输记录不会被清理，不再被任何传输记录引用的接收文件由垃圾回收删除。
+
+use crate::core::file::handler::TransferStateExt;
+use crate::core::file::policy::FilePolicyService;
+use crate::core::file::thumbnail::prepare_image;
+use crate::database::handler::{FileOfferHandler, FileStorageHandler, TransferStateHandler, UserHandler};
+use crate::database::model::{file_storage, transfer_state};
+use crate::error::{AppError, AppResult};
+use crate::types::{FileGcReport, TransferStatus};
+use sea_orm::DbConn;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+/// 下载文件名前缀（后接本地传输ID）
+pub const DOWNLOAD_FILE_PREFIX: &str = "feiqiu_download_";
+
+static DOWNLOAD_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 设置下载文件的保存目录（启动时设为应用数据目录下的 downloads）
+pub fn set_download_dir(dir: PathBuf) -> AppResult<()> {
+    std::fs::create_dir_all(&dir).map_err(AppError::Io)?;
+    let _ = DOWNLOAD_DIR.set(dir);
+    Ok(())
+}
+
+/// 下载文件的保存目录（未设置时使用系统临时目录下的独立目录）
+pub fn download_dir() -> PathBuf {
+    DOWNLOAD_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("feiqiu_downloads"))
+}
+
+/// 下载文件的保存路径
+///
+/// 以本地传输ID命名：对端的文件ID只在对端唯一，不同对端的文件不会互相覆盖
+pub fn download_path(tid: i64) -> String {
+    download_dir()
+        .join(format!("{}{}", DOWNLOAD_FILE_PREFIX, tid))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// 超过该大小的文件在提供时不计算哈希，由首次发送边读边算，避免发送前整文件读一遍
+pub const INLINE_HASH_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// 哈希缓存：路径、大小和修改时间不变时不再重新计算
+type HashCache = Mutex<HashMap<(PathBuf, u64, SystemTime), String>>;
+
+fn hash_cache() -> &'static HashCache {
+    static CACHE: OnceLock<HashCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn hash_cache_key(path: &Path, metadata: &std::fs::Metadata) -> (PathBuf, u64, SystemTime) {
+    (
+        path.to_path_buf(),
+        metadata.len(),
+        metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+    )
+}
+
+/// 已缓存的文件哈希
+async fn cached_hash(path: &Path) -> AppResult<Option<String>> {
+    let metadata = tokio::fs::metadata(path).await.map_err(AppError::Io)?;
+    let key = hash_cache_key(path, &metadata);
+    Ok(hash_cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key).cloned())
+}
+
+/// 记录传输过程中增量计算出的哈希，之后登记文件时不再重新读取
+pub async fn remember_hash(path: &Path, hash: String) -> AppResult<()> {
+    let metadata = tokio::fs::metadata(path).await.map_err(AppError::Io)?;
+    let key = hash_cache_key(path, &metadata);
+    hash_cache().lock().unwrap_or_else(|e| e.into_inner()).insert(key, hash);
+    Ok(())
+}
+
+/// 计算文件内容的 SHA256
+pub async fn hash_file(path: &Path) -> AppResult<String> {
+    let metadata = tokio::fs::metadata(path).await.map_err(AppError::Io)?;
+    let key = hash_cache_key(path, &metadata);
+
+    if let Some(hash) = hash_cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(hash.clone());
+    }
+
+    let owned = path.to_path_buf();
+    let hash = tokio::task::spawn_blocking(move || -> AppResult<String> {
+        let mut file = std::fs::File::open(&owned).map_err(AppError::Io)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let n = file.read(&mut buffer).map_err(AppError::Io)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await
+    .map_err(|e| AppError::Business(format!("计算文件哈希失败: {}", e)))??;
+
+    hash_cache().lock().unwrap_or_else(|e| e.into_inner()).insert(key, hash.clone());
+    Ok(hash)
+}
+
+/// 计算文件前 `len` 字节的 SHA256（文件短于 `len` 时只计算实际内容）
+pub async fn hash_prefix(path: &Path, len: u64) -> AppResult<String> {
+    let owned = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> AppResult<String> {
+        let mut file = std::fs::File::open(&owned).map_err(AppError::Io)?.take(len);
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let n = file.read(&mut buffer).map_err(AppError::Io)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buffer[..n]);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    })
+    .await
+    .map_err(|e| AppError::Business(format!("计算文件哈希失败: {}", e)))?
+}
+
+/// 根据扩展名推断 MIME 类型
+pub fn guess_mime(path: &Path) -> String {
+    if path.is_dir() {
+        return "inode/directory".to_string();
+    }
+    mime_guess::from_path(path).first_or_octet_stream().to_string()
+}
+
+/// 记录要发送的本地文件
+///
+/// 相同内容的文件已有记录时直接复用；原记录的文件已不存在时改指向新路径
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `path`: 本地文件或目录路径
+/// - `owner_uid`: 发送者用户ID
+pub async fn store_local_file(db: &DbConn, path: &str, owner_uid: i64) -> AppResult<file_storage::Model> {
+    let path_obj = Path::new(path);
+    let metadata = tokio::fs::metadata(path_obj).await.map_err(AppError::Io)?;
+    let file_name = path_obj
+        .file_name()
+        .ok_or_else(|| AppError::Business("未知文件".to_string()))?
+        .to_string_lossy()
+        .to_string();
+
+    // 目录内容随时可能变化，不做去重
+    if metadata.is_dir() {
+        return FileStorageHandler::create(db, file_name, path.to_string(), 0, guess_mime(path_obj), owner_uid).await;
+    }
+
+    let hash = match cached_hash(path_obj).await? {
+        Some(hash) => hash,
+        None if metadata.len() > INLINE_HASH_LIMIT => {
+            let record = FileStorageHandler::create(
+                db,
+                file_name,
+                path.to_string(),
+                metadata.len() as i64,
+                guess_mime(path_obj),
+                owner_uid,
+            )
+            .await?;
+            return prepare_image(db, record).await;
+        }
+        None => hash_file(path_obj).await?,
+    };
+    let existing = FileStorageHandler::find_by_hash(db, &hash)
+        .await?
+        .into_iter()
+        .find(|f| f.received == 0 && f.file_size == metadata.len() as i64);
+
+    if let Some(mut record) = existing {
+        if record.file_path != path && !Path::new(&record.file_path).is_file() {
+            FileStorageHandler::update_path(db, record.fid, path.to_string()).await?;
+            record.file_path = path.to_string();
+        }
+        info!("复用已有文件记录: fid={}, hash={}", record.fid, hash);
+        return prepare_image(db, record).await;
+    }
+
+    let record = FileStorageHandler::create_with_hash(
+        db,
+        file_name,
+        path.to_string(),
+        metadata.len() as i64,
+        guess_mime(path_obj),
+        owner_uid,
+        Some(hash),
+        false,
+    )
+    .await?;
+
+    // 图片文件识别尺寸并生成缩略图
+    prepare_image(db, record).await
+}
+
+/// 记录发送过程中计算出的内容哈希
+///
+/// 大文件提供时没有计算哈希，首次完整发送后补齐，之后再次发送相同内容时可以复用记录
+pub async fn record_sent_hash(db: &DbConn, record: &file_storage::Model, hash: String) -> AppResult<()> {
+    remember_hash(Path::new(&record.file_path), hash.clone()).await?;
+    if record.content_hash.is_none() {
+        FileStorageHandler::set_content_hash(db, record.fid, hash).await?;
+    }
+    Ok(())
+}
+
+/// 登记下载完成的文件
+///
+/// 计算内容哈希并写入传输记录的校验和；已接收过相同内容的文件时删除新副本
+///
+/// # 返回
+/// 返回文件最终所在路径
+pub async fn register_received_file(db: &DbConn, transfer: &transfer_state::Model) -> AppResult<String> {
+    let save_path = transfer.get_save_path();
+    let hash = hash_file(Path::new(&save_path)).await?;
+    TransferStateHandler::set_checksum(db, transfer.tid, hash.clone()).await?;
+
+    // 被隔离的文件不进入存储，也不参与去重
+    if let Some(quarantined) = FilePolicyService::quarantine_if_needed(db, transfer, &save_path).await? {
+        return Ok(quarantined);
+    }
+
+    let existing = FileStorageHandler::find_by_hash(db, &hash)
+        .await?
+        .into_iter()
+        .find(|f| f.received == 1 && Path::new(&f.file_path).is_file());
+
+    if let Some(record) = existing {
+        if record.file_path != save_path {
+            tokio::fs::remove_file(&save_path).await.map_err(AppError::Io)?;
+            info!(
+                "接收到重复内容，复用已有文件: fid={}, path={}",
+                record.fid, record.file_path
+            );
+        }
+        return Ok(record.file_path);
+    }
+
+    let uploader_uid = UserHandler::find_by_ip_port(db, &transfer.target_ip, transfer.target_port)
+        .await?
+        .map(|u| u.uid)
+        .unwrap_or(0);
+
+    let file_name = FileOfferHandler::find(db, &transfer.packet_no, transfer.file_id)
+        .await?
+        .map(|offer| offer.file_name)
+        .unwrap_or_else(|| format!("file_{}", transfer.file_id));
+
+    let record = FileStorageHandler::create_with_hash(
+        db,
+        file_name,
+        save_path.clone(),
+        transfer.file_size,
+        guess_mime(Path::new(&save_path)),
+        uploader_uid,
+        Some(hash),
+        true,
+    )
+    .await?;
+    prepare_image(db, record).await?;
+
+    Ok(save_path)
+}
+
+/// 回收不再被引用的接收文件
+///
+/// - 接收文件的内容哈希不再被任何传输记录引用时，删除文件和记录
+/// - 下载目录中既未登记、下载记录也已删除或已取消的残留文件直接删除
+pub async fn collect_garbage(db: &DbConn) -> AppResult<FileGcReport> {
+    let mut report = FileGcReport {
+        removed_files: 0,
+        freed_bytes: 0,
+    };
+
+    let received = FileStorageHandler::list_received(db).await?;
+    let mut registered: Vec<String> = Vec::new();
+
+    for record in received {
+        let referenced = match &record.content_hash {
+            Some(hash) => TransferStateHandler::count_by_checksum(db, hash).await? > 0,
+            None => false,
+        };
+        if referenced {
+            registered.push(record.file_path);
+            continue;
+        }
+
+        if is_download_path(&record.file_path) {
+            report.freed_bytes += remove_if_exists(&record.file_path).await;
+        }
+        // 缩略图按内容哈希缓存，本地发送的同内容文件仍在使用时保留
+        if let (Some(thumbnail), Some(hash)) = (&record.thumbnail_path, &record.content_hash) {
+            if FileStorageHandler::find_by_hash(db, hash).await?.len() <= 1 {
+                report.freed_bytes += remove_if_exists(thumbnail).await;
+            }
+        }
+        FileStorageHandler::delete(db, record.fid).await?;
+        report.removed_files += 1;
+    }
+
+    let dir = download_dir();
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(report),
+        Err(e) => return Err(AppError::Io(e)),
+    };
+    while let Some(entry) = entries.next_entry().await.map_err(AppError::Io)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let tid = match name.strip_prefix(DOWNLOAD_FILE_PREFIX).and_then(|id| id.parse::<i64>().ok()) {
+            Some(tid) => tid,
+            None => continue,
+        };
+
+        let path = download_path(tid);
+        if registered.contains(&path) {
+            continue;
+        }
+
+        // 下载记录还在（包括已完成但未登记的文件）时保留，记录已删除或下载已取消时删除
+        let kept = TransferStateHandler::find_by_id(db, tid)
+            .await?
+            .is_some_and(|t| t.direction == 0 && t.status != TransferStatus::Cancelled as i8);
+        if !kept {
+            report.freed_bytes += remove_if_exists(&path).await;
+            report.removed_files += 1;
+        }
+    }
+
+    if report.removed_files > 0 {
+        info!(
+            "文件垃圾回收完成: 删除 {} 个文件, 释放 {} 字节",
+            report.removed_files, report.freed_bytes
+        );
+    }
+    Ok(report)
+}
+
+/// 只回收下载目录中的文件，不碰用户自己的文件
+fn is_download_path(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with(DOWNLOAD_FILE_PREFIX))
+        && Path::new(path).parent() == Some(download_dir().as_path())
+}
+
+/// 删除文件，返回释放的字节数
+async fn remove_if_exists(path: &str) -> u64 {
+    let size = tokio::fs::metadata(path).await.map(|m| m.len()).unwrap_or(0);
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => size,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => {
+            warn!("删除文件失败: {}: {}", path, e);
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hash_file() {
+        let path = std::env::temp_dir().join("feiqiu_storage_hash_test.txt");
+        tokio::fs::write(&path, b"hello").await.unwrap();
+
+        let hash = hash_file(&path).await.unwrap();
+        assert_eq!(hash, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+        // 命中缓存
+        assert_eq!(hash_file(&path).await.unwrap(), hash);
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn test_guess_mime_and_download_path() {
+        assert_eq!(guess_mime(Path::new("shot.png")), "image/png");
+        assert_eq!(guess_mime(Path::new("no_extension")), "application/octet-stream");
+
+        assert!(is_download_path(&download_path(42)));
+        assert!(!is_download_path("/home/user/feiqiu_download_42"));
+        assert!(!is_download_path("/tmp/feiqiu_download_42"));
+        assert!(!is_download_path("/tmp/report.txt"));
+        assert_ne!(download_path(1), download_path(2));
+    }
+}
@@ -0,0 +1,453 @@
+// src-tauri/src/core/file/thumbnail.rs
+//
+//! 图片附件的尺寸识别与缩略图
+//!
+//! - 发送方登记图片文件时识别尺寸并生成缩略图，尺寸随文件请求一起发出
+//! - 接收方通过 TCP 通道先取回缩略图（GETTHUMB）并缓存，点击后再下载原图
+//! - PNG、JPEG、WebP 解码后按比例缩小为 PNG；其他格式不解码，体积足够小时原图直接作为缩略图
+
+use crate::core::file::storage::download_dir;
+use crate::database::handler::FileStorageHandler;
+use crate::database::model::file_storage;
+use crate::error::{AppError, AppResult};
+use crate::network::tcp::TcpFileClient;
+use sea_orm::DbConn;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+
+/// 缩略图最长边（像素）
+pub const THUMBNAIL_MAX_EDGE: u32 = 160;
+
+/// 无法解码的图片（GIF、BMP 等）不超过此大小时原图直接作为缩略图
+pub const RAW_THUMBNAIL_MAX_SIZE: u64 = 64 * 1024;
+
+/// 允许解码的最大像素数，防止超大图片占满内存
+const MAX_DECODE_PIXELS: u64 = 40_000_000;
+
+/// 识别尺寸时读取的文件头字节数（JPEG 的 SOF 段可能位于较大的 EXIF 之后）
+const SNIFF_LEN: u64 = 256 * 1024;
+
+/// PNG 文件签名
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// JPEG 文件签名 (SOI)
+const JPEG_SIGNATURE: &[u8] = b"\xFF\xD8";
+
+/// 缩略图缓存文件名前缀
+pub const THUMBNAIL_FILE_PREFIX: &str = "feiqiu_thumb_";
+
+/// 文件记录中的图片尺寸
+pub fn image_size(record: &file_storage::Model) -> Option<(u32, u32)> {
+    match (record.image_width, record.image_height) {
+        (Some(width), Some(height)) if width > 0 && height > 0 => Some((width as u32, height as u32)),
+        _ => None,
+    }
+}
+
+/// 根据文件头识别图片尺寸
+///
+/// 支持 PNG、GIF、BMP、JPEG 和 WebP，无法识别时返回 None
+pub fn sniff_image_size(header: &[u8]) -> Option<(u32, u32)> {
+    let le16 = |i: usize| header.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as u32);
+    let le24 = |i: usize| header.get(i..i + 3).map(|b| u32::from_le_bytes([b[0], b[1], b[2], 0]));
+
+    let size = if header.starts_with(PNG_SIGNATURE) {
+        let width = header.get(16..20)?;
+        let height = header.get(20..24)?;
+        Some((
+            u32::from_be_bytes([width[0], width[1], width[2], width[3]]),
+            u32::from_be_bytes([height[0], height[1], height[2], height[3]]),
+        ))
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        Some((le16(6)?, le16(8)?))
+    } else if header.starts_with(b"BM") {
+        let width = header.get(18..22)?;
+        let height = header.get(22..26)?;
+        Some((
+            i32::from_le_bytes([width[0], width[1], width[2], width[3]]).unsigned_abs(),
+            i32::from_le_bytes([height[0], height[1], height[2], height[3]]).unsigned_abs(),
+        ))
+    } else if header.starts_with(&[0xFF, 0xD8]) {
+        sniff_jpeg_size(header)
+    } else if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+        match header.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3FFF, le16(28)? & 0x3FFF)),
+            b"VP8L" => {
+                let b = header.get(21..25)?;
+                let width = 1 + (b[0] as u32 | ((b[1] as u32 & 0x3F) << 8));
+                let height = 1 + ((b[1] as u32 >> 6) | ((b[2] as u32) << 2) | ((b[3] as u32 & 0x0F) << 10));
+                Some((width, height))
+            }
+            b"VP8X" => Some((1 + le24(24)?, 1 + le24(27)?)),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    size.filter(|(width, height)| *width > 0 && *height > 0)
+}
+
+/// 遍历 JPEG 分段，读取 SOF 段中的尺寸
+fn sniff_jpeg_size(data: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| data.get(i..i + 2).map(|b| u16::from_be_bytes([b[0], b[1]]) as u32);
+
+    let mut i = 2;
+    while i + 1 < data.len() {
+        if data[i] != 0xFF {
+            return None;
+        }
+        let marker = data[i + 1];
+        // 填充字节
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        // 无长度字段的独立标记
+        if marker == 0x01 || (0xD0..=0xD9).contains(&marker) {
+            i += 2;
+            continue;
+        }
+
+        let len = be16(i + 2)? as usize;
+        let is_sof = (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+        if is_sof {
+            return Some((be16(i + 7)?, be16(i + 5)?));
+        }
+        i += 2 + len;
+    }
+    None
+}
+
+/// 读取文件头
+async fn read_header(path: &Path) -> Option<Vec<u8>> {
+    let file = tokio::fs::File::open(path).await.ok()?;
+    let mut header = Vec::new();
+    file.take(SNIFF_LEN).read_to_end(&mut header).await.ok()?;
+    Some(header)
+}
+
+/// 缩略图缓存路径
+fn thumbnail_cache_path(key: &str) -> String {
+    download_dir()
+        .join(format!("{}{}", THUMBNAIL_FILE_PREFIX, key))
+        .to_string_lossy()
+        .to_string()
+}
+
+/// 识别图片文件的尺寸并生成缩略图
+///
+/// 非图片文件或已处理过的记录原样返回
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `record`: 文件存储记录
+///
+/// # 返回
+/// 返回更新后的文件记录
+pub async fn prepare_image(db: &DbConn, record: file_storage::Model) -> AppResult<file_storage::Model> {
+    let thumbnail_ready = record.thumbnail_path.as_deref().is_none_or(|p| Path::new(p).is_file());
+    if record.image_width.is_some() && thumbnail_ready {
+        return Ok(record);
+    }
+
+    let path = Path::new(&record.file_path);
+    let Some(header) = read_header(path).await else {
+        return Ok(record);
+    };
+    let Some((width, height)) = sniff_image_size(&header) else {
+        return Ok(record);
+    };
+
+    // 可解码格式的像素数在限制内时解码缩小，其他图片足够小时直接使用原图
+    let decodable = is_decodable(&header) && width as u64 * height as u64 <= MAX_DECODE_PIXELS;
+    let key = record.content_hash.clone().unwrap_or_else(|| format!("fid{}", record.fid));
+    let thumbnail = match generate_thumbnail(path, &key, record.file_size as u64, decodable).await {
+        Ok(thumbnail) => thumbnail,
+        Err(e) => {
+            warn!("生成缩略图失败: {}: {}", record.file_path, e);
+            None
+        }
+    };
+
+    info!(
+        "识别图片: fid={}, {}x{}, thumbnail={:?}",
+        record.fid, width, height, thumbnail
+    );
+    FileStorageHandler::set_image_info(db, record.fid, width as i32, height as i32, thumbnail).await
+}
+
+/// 生成缩略图文件
+///
+/// # 返回
+/// 返回缩略图路径，无法生成时返回 None
+async fn generate_thumbnail(path: &Path, key: &str, file_size: u64, decodable: bool) -> AppResult<Option<String>> {
+    if !decodable && file_size > RAW_THUMBNAIL_MAX_SIZE {
+        return Ok(None);
+    }
+
+    let output = thumbnail_cache_path(key);
+    let source = path.to_path_buf();
+
+    tokio::task::spawn_blocking(move || -> AppResult<Option<String>> {
+        let data = std::fs::read(&source).map_err(AppError::Io)?;
+        let thumbnail = if decodable {
+            match downscale_image(&data, THUMBNAIL_MAX_EDGE) {
+                Ok(thumbnail) => thumbnail,
+                // 解码失败时退回原图
+                Err(_) if file_size <= RAW_THUMBNAIL_MAX_SIZE => data,
+                Err(e) => return Err(e),
+            }
+        } else {
+            data
+        };
+
+        std::fs::write(&output, thumbnail).map_err(AppError::Io)?;
+        Ok(Some(output))
+    })
+    .await
+    .map_err(|e| AppError::Business(format!("生成缩略图失败: {}", e)))?
+}
+
+/// 是否为可解码缩小的图片格式（PNG、JPEG、WebP）
+fn is_decodable(header: &[u8]) -> bool {
+    header.starts_with(PNG_SIGNATURE) || header.starts_with(JPEG_SIGNATURE) || is_webp(header)
+}
+
+fn is_webp(header: &[u8]) -> bool {
+    header.len() >= 12 && &header[0..4] == b"RIFF" && &header[8..12] == b"WEBP"
+}
+
+/// 将图片等比缩小到最长边不超过 `max_edge`，输出 RGBA PNG
+///
+/// 按文件签名选择 PNG、JPEG 或 WebP 解码
+pub fn downscale_image(data: &[u8], max_edge: u32) -> AppResult<Vec<u8>> {
+    if data.starts_with(PNG_SIGNATURE) {
+        downscale_png(data, max_edge)
+    } else if data.starts_with(JPEG_SIGNATURE) {
+        downscale_jpeg(data, max_edge)
+    } else if is_webp(data) {
+        downscale_webp(data, max_edge)
+    } else {
+        Err(AppError::Business("不支持解码的图片格式".to_string()))
+    }
+}
+
+/// 将 JPEG 图片等比缩小，解码时先按 DCT 缩放减少像素数
+pub fn downscale_jpeg(data: &[u8], max_edge: u32) -> AppResult<Vec<u8>> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    decoder
+        .read_info()
+        .map_err(|e| AppError::Business(format!("JPEG 解码失败: {}", e)))?;
+    let edge = max_edge.min(u16::MAX as u32) as u16;
+    decoder
+        .scale(edge, edge)
+        .map_err(|e| AppError::Business(format!("JPEG 解码失败: {}", e)))?;
+    let pixels = decoder
+        .decode()
+        .map_err(|e| AppError::Business(format!("JPEG 解码失败: {}", e)))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| AppError::Business("JPEG 解码失败: 缺少图片信息".to_string()))?;
+
+    // 统一转换为 RGBA（CMYK 按 Adobe 反相存储近似换算）
+    let rgba: Vec<u8> = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        jpeg_decoder::PixelFormat::L8 => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        jpeg_decoder::PixelFormat::L16 => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], 255]).collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => pixels
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let k = p[3] as u32;
+                [(p[0] as u32 * k / 255) as u8, (p[1] as u32 * k / 255) as u8, (p[2] as u32 * k / 255) as u8, 255]
+            })
+            .collect(),
+    };
+
+    downscale_rgba(&rgba, info.width as u32, info.height as u32, max_edge)
+}
+
+/// 将 WebP 图片（动图取第一帧）等比缩小
+pub fn downscale_webp(data: &[u8], max_edge: u32) -> AppResult<Vec<u8>> {
+    let mut decoder = image_webp::WebPDecoder::new(std::io::Cursor::new(data))
+        .map_err(|e| AppError::Business(format!("WebP 解码失败: {}", e)))?;
+    let (width, height) = decoder.dimensions();
+    let size = decoder
+        .output_buffer_size()
+        .ok_or_else(|| AppError::Business("WebP 图片过大".to_string()))?;
+
+    let mut pixels = vec![0u8; size];
+    decoder
+        .read_image(&mut pixels)
+        .map_err(|e| AppError::Business(format!("WebP 解码失败: {}", e)))?;
+
+    let rgba: Vec<u8> = if decoder.has_alpha() {
+        pixels
+    } else {
+        pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect()
+    };
+
+    downscale_rgba(&rgba, width, height, max_edge)
+}
+
+/// 将 PNG 图片等比缩小到最长边不超过 `max_edge`，输出 RGBA PNG
+pub fn downscale_png(data: &[u8], max_edge: u32) -> AppResult<Vec<u8>> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| AppError::Business(format!("PNG 解码失败: {}", e)))?;
+
+    let mut buffer = vec![0u8; reader.output_buffer_size()];
+    let frame = reader
+        .next_frame(&mut buffer)
+        .map_err(|e| AppError::Business(format!("PNG 解码失败: {}", e)))?;
+    let (width, height) = (frame.width, frame.height);
+    let pixels = &buffer[..frame.buffer_size()];
+
+    // 统一转换为 RGBA
+    let rgba: Vec<u8> = match frame.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(AppError::Business("PNG 调色板未展开".to_string()));
+        }
+    };
+
+    downscale_rgba(&rgba, width, height, max_edge)
+}
+
+/// 将 RGBA 像素区域平均缩小到最长边不超过 `max_edge`，编码为 PNG
+fn downscale_rgba(rgba: &[u8], width: u32, height: u32, max_edge: u32) -> AppResult<Vec<u8>> {
+    let scale = (max_edge as f64 / width.max(height) as f64).min(1.0);
+    let target_width = ((width as f64 * scale).round() as u32).max(1);
+    let target_height = ((height as f64 * scale).round() as u32).max(1);
+
+    // 区域平均缩小
+    let mut output = Vec::with_capacity((target_width * target_height * 4) as usize);
+    for ty in 0..target_height {
+        let y0 = ty * height / target_height;
+        let y1 = ((ty + 1) * height / target_height).max(y0 + 1);
+        for tx in 0..target_width {
+            let x0 = tx * width / target_width;
+            let x1 = ((tx + 1) * width / target_width).max(x0 + 1);
+
+            let mut sum = [0u64; 4];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let offset = ((y * width + x) * 4) as usize;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += rgba[offset + channel] as u64;
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            output.extend(sum.iter().map(|total| (total / count) as u8));
+        }
+    }
+
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut encoded, target_width, target_height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| AppError::Business(format!("PNG 编码失败: {}", e)))?;
+        writer
+            .write_image_data(&output)
+            .map_err(|e| AppError::Business(format!("PNG 编码失败: {}", e)))?;
+    }
+    Ok(encoded)
+}
+
+/// 获取对方提供的图片附件缩略图
+///
+/// 已缓存时直接返回本地路径，否则通过 TCP 通道取回后缓存
+///
+/// # 参数
+/// - `target_ip`: 发送方 IP
+/// - `packet_no`: 文件请求包编号
+/// - `file_id`: 文件ID
+///
+/// # 返回
+/// 返回缩略图本地路径
+pub async fn fetch_remote_thumbnail(target_ip: &str, packet_no: &str, file_id: u64) -> AppResult<String> {
+    // 包编号只在发送方内唯一，缓存按发送方区分
+    let path = thumbnail_cache_path(&format!("{}_{}_{}", target_ip, packet_no, file_id));
+    if Path::new(&path).is_file() {
+        return Ok(path);
+    }
+
+    let client = TcpFileClient::connect(&format!("{}:2425", target_ip)).await?;
+    let data = client.fetch_thumbnail(packet_no, file_id).await?;
+    if sniff_image_size(&data).is_none() {
+        return Err(AppError::Protocol("缩略图不是有效的图片".to_string()));
+    }
+
+    tokio::fs::write(&path, &data).await.map_err(AppError::Io)?;
+    info!(
+        "缩略图已缓存: packet_no={}, file_id={}, path={}",
+        packet_no, file_id, path
+    );
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_png(width: u32, height: u32) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut encoded, width, height);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&vec![200u8; (width * height * 3) as usize]).unwrap();
+        }
+        encoded
+    }
+
+    #[test]
+    fn test_sniff_image_size() {
+        assert_eq!(sniff_image_size(&encode_png(640, 480)), Some((640, 480)));
+
+        let gif = b"GIF89a\x20\x03\x58\x02\x00\x00\x00";
+        assert_eq!(sniff_image_size(gif), Some((800, 600)));
+
+        // SOI + APP0(长度 4) + SOF0
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x04, 0x38, 0x07, 0x80,
+        ];
+        assert_eq!(sniff_image_size(&jpeg), Some((1920, 1080)));
+
+        assert_eq!(sniff_image_size(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_downscale_png() {
+        let thumbnail = downscale_png(&encode_png(640, 320), THUMBNAIL_MAX_EDGE).unwrap();
+        assert_eq!(sniff_image_size(&thumbnail), Some((160, 80)));
+
+        // 小图不放大
+        let thumbnail = downscale_png(&encode_png(32, 16), THUMBNAIL_MAX_EDGE).unwrap();
+        assert_eq!(sniff_image_size(&thumbnail), Some((32, 16)));
+    }
+
+    #[test]
+    fn test_downscale_webp() {
+        let mut webp = Vec::new();
+        image_webp::WebPEncoder::new(&mut webp)
+            .encode(&vec![120u8; 400 * 200 * 3], 400, 200, image_webp::ColorType::Rgb8)
+            .unwrap();
+        assert!(is_decodable(&webp));
+
+        let thumbnail = downscale_image(&webp, THUMBNAIL_MAX_EDGE).unwrap();
+        assert!(thumbnail.starts_with(PNG_SIGNATURE));
+        assert_eq!(sniff_image_size(&thumbnail), Some((160, 80)));
+    }
+}
@@ -1,20 +1,59 @@
 // src-tauri/src/core/file/transfer.rs
 //
 //! 文件分块传输逻辑
-
+//!
+//! 文件读写都走 tokio 异步 I/O，不占用运行时工作线程；
+//! 内容哈希随传输按顺序增量计算，传输完成时即可得到，不需要再整文件读一遍
+
+use crate::core::file::compress::{compress_chunk, decompress_chunk};
+use crate::core::file::rate_limit::rate_limiter;
+use crate::core::file::storage::hash_file;
+use crate::core::file::window::{ChunkSizer, CongestionWindow, RttEstimator, DEFAULT_PATH_MTU};
 use crate::error::{AppError, AppResult};
 use crate::network::udp::sender;
 use sha2::{Digest, Sha256};
-use std::fs::File;
-use std::io::{self, Read};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::Path;
-use tokio::time::{timeout, Duration};
+use std::sync::OnceLock;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration, Instant};
 
 /// 文件传输配置
-const CHUNK_SIZE: usize = 4 * 1024; // 4KB
 const TRANSFER_TIMEOUT: Duration = Duration::from_secs(30);
 const MAX_RETRIES: u32 = 3;
 
+/// 速率统计的滚动窗口
+const RATE_WINDOW: Duration = Duration::from_secs(2);
+
+/// 数据块确认通道
+///
+/// key: (peer_ip, packet_no, file_id)，同一文件发给多个接收方时按对端区分
+/// value: 正在发送该文件的 FileSender 的确认接收端
+type AckChannels = std::sync::Mutex<HashMap<(String, String, u64), mpsc::UnboundedSender<u64>>>;
+
+fn ack_channels() -> &'static AckChannels {
+    static CHANNELS: OnceLock<AckChannels> = OnceLock::new();
+    CHANNELS.get_or_init(|| std::sync::Mutex::new(HashMap::new()))
+}
+
+/// 将收到的数据块确认分发给对应的发送器
+///
+/// # 返回
+/// 找到正在进行的发送器时返回 true
+pub fn dispatch_chunk_ack(peer_ip: &str, packet_no: &str, file_id: u64, offset: u64) -> bool {
+    let channels = match ack_channels().lock() {
+        Ok(channels) => channels,
+        Err(_) => return false,
+    };
+
+    channels
+        .get(&(peer_ip.to_string(), packet_no.to_string(), file_id))
+        .map(|tx| tx.send(offset).is_ok())
+        .unwrap_or(false)
+}
+
 /// 文件传输进度
 #[derive(Debug, Clone)]
 pub struct FileTransferProgress {
@@ -22,6 +61,17 @@ pub struct FileTransferProgress {
     pub offset: u64,
     pub total: u64,
     pub progress: u8, // 0-100
+    pub rate: u64,    // 当前速率 (字节/秒)
+    /// 已被连续确认的位置，之前的数据都已送达，可作为断点续传的偏移量
+    pub acked_through: u64,
+    /// 已确认数据块的原始字节数
+    pub raw_bytes: u64,
+    /// 已确认数据块实际发送的字节数（压缩后）
+    pub wire_bytes: u64,
+    /// 文件内容 SHA256（从头完整发送后才有）
+    pub checksum: Option<String>,
+    /// 重传的数据块数
+    pub retransmits: u32,
 }
 
 impl FileTransferProgress {
@@ -31,9 +81,23 @@ impl FileTransferProgress {
             offset: 0,
             total,
             progress: 0,
+            rate: 0,
+            acked_through: 0,
+            raw_bytes: 0,
+            wire_bytes: 0,
+            checksum: None,
+            retransmits: 0,
         }
     }
 
+    /// 从指定偏移量开始（断点续传）
+    pub fn starting_at(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self.acked_through = offset;
+        self.update(0);
+        self
+    }
+
     pub fn update(&mut self, chunk_size: usize) {
         self.offset += chunk_size as u64;
         self.progress = ((self.offset as f64 / self.total as f64) * 100.0) as u8;
@@ -43,14 +107,164 @@ impl FileTransferProgress {
     pub fn is_complete(&self) -> bool {
         self.offset >= self.total
     }
+
+    /// 按当前速率估算的剩余时间（秒）
+    pub fn eta_secs(&self) -> Option<u64> {
+        estimate_eta(self.total.saturating_sub(self.offset), self.rate)
+    }
+
+    /// 压缩率（实际发送/原始大小，1.0 表示未压缩）
+    pub fn compression_ratio(&self) -> f64 {
+        if self.raw_bytes == 0 {
+            1.0
+        } else {
+            self.wire_bytes as f64 / self.raw_bytes as f64
+        }
+    }
+}
+
+/// 滚动速率计
+///
+/// 统计最近 `RATE_WINDOW` 内的字节数，得出当前速率
+#[derive(Debug, Clone, Default)]
+pub struct RateMeter {
+    samples: VecDeque<(Instant, u64)>,
+    window_bytes: u64,
+}
+
+impl RateMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一次传输的字节数
+    pub fn record(&mut self, bytes: u64) {
+        let now = Instant::now();
+        self.samples.push_back((now, bytes));
+        self.window_bytes += bytes;
+
+        while let Some((at, n)) = self.samples.front().copied() {
+            if now.duration_since(at) <= RATE_WINDOW {
+                break;
+            }
+            self.samples.pop_front();
+            self.window_bytes -= n;
+        }
+    }
+
+    /// 当前速率（字节/秒）
+    pub fn rate(&self) -> u64 {
+        let first = match self.samples.front() {
+            Some((at, _)) => *at,
+            None => return 0,
+        };
+        let elapsed = first.elapsed().max(Duration::from_millis(100));
+        (self.window_bytes as f64 / elapsed.as_secs_f64()) as u64
+    }
+}
+
+/// 增量 SHA256
+///
+/// 数据按偏移顺序到达时边传输边计算；重复的数据被忽略，
+/// 出现空洞或从中途开始（断点续传）时失效，由调用方回退为整文件计算
+#[derive(Clone)]
+pub struct IncrementalHash {
+    hasher: Sha256,
+    position: u64,
+    valid: bool,
+}
+
+impl IncrementalHash {
+    /// 从指定偏移量开始（非 0 时哈希直接失效）
+    pub fn starting_at(offset: u64) -> Self {
+        Self {
+            hasher: Sha256::new(),
+            position: 0,
+            valid: offset == 0,
+        }
+    }
+
+    /// 计入一段数据
+    pub fn update(&mut self, offset: u64, data: &[u8]) {
+        if !self.valid {
+            return;
+        }
+        let end = offset + data.len() as u64;
+        if offset == self.position {
+            self.hasher.update(data);
+            self.position = end;
+        } else if end > self.position {
+            self.valid = false;
+        }
+    }
+
+    /// 已连续计入的前缀长度及其摘要（数据不连续时为 None）
+    pub fn prefix(&self) -> Option<(u64, String)> {
+        self.valid
+            .then(|| (self.position, format!("{:x}", self.hasher.clone().finalize())))
+    }
+
+    /// 已连续覆盖 [0, len) 时返回十六进制摘要
+    pub fn finish(&self, len: u64) -> Option<String> {
+        if self.valid && self.position == len {
+            Some(format!("{:x}", self.hasher.clone().finalize()))
+        } else {
+            None
+        }
+    }
+}
+
+/// 估算剩余时间（秒）
+///
+/// 速率为 0（刚开始或已停滞）时无法估算，返回 None
+pub fn estimate_eta(remaining: u64, rate: u64) -> Option<u64> {
+    if remaining == 0 {
+        Some(0)
+    } else if rate == 0 {
+        None
+    } else {
+        Some(remaining.div_ceil(rate))
+    }
+}
+
+/// 发送结束（包括任务被中止）时注销确认通道
+///
+/// 限速配置不在此移除：暂停或失败后重试的发送仍受原限速约束
+struct SendGuard {
+    peer_ip: String,
+    key: (String, u64),
+}
+
+impl Drop for SendGuard {
+    fn drop(&mut self) {
+        if let Ok(mut channels) = ack_channels().lock() {
+            channels.remove(&(self.peer_ip.clone(), self.key.0.clone(), self.key.1));
+        }
+    }
+}
+
+/// 在途数据块
+struct InFlightChunk {
+    /// 实际发送的数据（可能已压缩）
+    payload: Vec<u8>,
+    raw_len: usize,
+    compressed: bool,
+    sent_at: Instant,
+    retries: u32,
 }
 
 /// 文件发送器
+///
+/// 基于滑动窗口的流水线发送：窗口内的数据块连续发出，
+/// 按接收方的逐块确认推进窗口，超时未确认的数据块单独重传
 pub struct FileSender {
     file_path: String,
     file_id: u64,
     target_addr: String,
     packet_no: String,
+    path_mtu: usize,
+    start_offset: u64,
+    compress: bool,
 }
 
 impl FileSender {
@@ -60,58 +274,200 @@ impl FileSender {
             file_id,
             target_addr,
             packet_no,
+            path_mtu: DEFAULT_PATH_MTU,
+            start_offset: 0,
+            compress: false,
         }
     }
 
-    /// 发送文件（分块传输），带进度回调
+    /// 启用数据块压缩（仅当对方声明支持压缩时）
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// 设置起始偏移量（从已确认的位置继续发送）
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.start_offset = offset;
+        self
+    }
+
+    /// 设置路径 MTU（决定初始数据块大小）
+    pub fn with_path_mtu(mut self, path_mtu: usize) -> Self {
+        self.path_mtu = path_mtu;
+        self
+    }
+
+    /// 发送文件（滑动窗口分块传输），带进度回调
     pub async fn send_with_callback<F>(&self, mut on_progress: F) -> AppResult<FileTransferProgress>
     where
         F: FnMut(FileTransferProgress),
     {
         let path = Path::new(&self.file_path);
-        let file_size = path.metadata().map_err(AppError::Io)?.len();
+        let file_size = tokio::fs::metadata(path).await.map_err(AppError::Io)?.len();
+
+        let mut progress = FileTransferProgress::new(self.file_id, file_size).starting_at(self.start_offset);
+        let mut file = File::open(path).await.map_err(AppError::Io)?;
+        file.seek(SeekFrom::Start(self.start_offset)).await.map_err(AppError::Io)?;
+
+        let (ack_tx, mut ack_rx) = mpsc::unbounded_channel();
+        let peer_ip = self.peer_ip();
+        ack_channels()
+            .lock()
+            .map_err(|e| AppError::Business(format!("获取确认通道失败: {}", e)))?
+            .insert((peer_ip.clone(), self.packet_no.clone(), self.file_id), ack_tx);
+        let _guard = SendGuard {
+            peer_ip,
+            key: (self.packet_no.clone(), self.file_id),
+        };
+
+        let mut hash = IncrementalHash::starting_at(self.start_offset);
+        self.run_window(&mut file, file_size, &mut progress, &mut hash, &mut ack_rx, &mut on_progress)
+            .await?;
+        progress.checksum = hash.finish(file_size);
+        Ok(progress)
+    }
 
-        let mut progress = FileTransferProgress::new(self.file_id, file_size);
-        let mut file = File::open(path).map_err(AppError::Io)?;
+    /// 滑动窗口主循环
+    async fn run_window<F>(
+        &self,
+        file: &mut File,
+        file_size: u64,
+        progress: &mut FileTransferProgress,
+        hash: &mut IncrementalHash,
+        ack_rx: &mut mpsc::UnboundedReceiver<u64>,
+        on_progress: &mut F,
+    ) -> AppResult<()>
+    where
+        F: FnMut(FileTransferProgress),
+    {
+        let mut window = CongestionWindow::new();
+        let mut sizer = ChunkSizer::new(self.path_mtu);
+        let mut rtt = RttEstimator::new();
+        let mut in_flight: BTreeMap<u64, InFlightChunk> = BTreeMap::new();
+        let mut meter = RateMeter::new();
+        let mut next_offset = progress.offset;
+        let mut last_progress_at = Instant::now();
+        let peer_ip = self.peer_ip();
+        let key = (self.packet_no.clone(), self.file_id);
 
         loop {
-            if progress.is_complete() {
-                break;
+            // 填满窗口
+            while in_flight.len() < window.size() && next_offset < file_size {
+                let mut chunk = vec![0u8; sizer.size()];
+                let n = file.read(&mut chunk).await.map_err(AppError::Io)?;
+                if n == 0 {
+                    break;
+                }
+                chunk.truncate(n);
+                hash.update(next_offset, &chunk);
+
+                let compressed = if self.compress { compress_chunk(&chunk) } else { None };
+                let (payload, compressed) = match compressed {
+                    Some(payload) => (payload, true),
+                    None => (chunk, false),
+                };
+
+                rate_limiter().acquire(&peer_ip, &key, payload.len()).await;
+                self.send_chunk(&payload, next_offset, compressed).await?;
+                in_flight.insert(
+                    next_offset,
+                    InFlightChunk {
+                        payload,
+                        raw_len: n,
+                        compressed,
+                        sent_at: Instant::now(),
+                        retries: 0,
+                    },
+                );
+                next_offset += n as u64;
             }
 
-            let mut chunk = vec![0u8; CHUNK_SIZE];
-            let n = file.read(&mut chunk).map_err(AppError::Io)?;
-
-            if n == 0 {
+            if in_flight.is_empty() {
                 break;
             }
 
-            chunk.truncate(n);
-
-            let mut retries = 0;
-            loop {
-                match self.send_chunk(&chunk, progress.offset).await {
-                    Ok(_) => {
-                        progress.update(n);
+            // 等待确认，直到最早的在途数据块超时
+            let rto = rtt.rto();
+            let oldest_deadline = in_flight
+                .values()
+                .map(|c| c.sent_at + rto)
+                .min()
+                .unwrap_or_else(|| Instant::now() + rto);
+
+            match tokio::time::timeout_at(oldest_deadline, ack_rx.recv()).await {
+                Ok(Some(offset)) => {
+                    if let Some(chunk) = in_flight.remove(&offset) {
+                        // 重传过的数据块 RTT 不可靠 (Karn 算法)
+                        if chunk.retries == 0 {
+                            rtt.sample(chunk.sent_at.elapsed());
+                        }
+                        window.on_ack();
+                        sizer.on_ack();
+                        meter.record(chunk.raw_len as u64);
+                        progress.update(chunk.raw_len);
+                        progress.raw_bytes += chunk.raw_len as u64;
+                        progress.wire_bytes += chunk.payload.len() as u64;
+                        progress.rate = meter.rate();
+                        progress.acked_through = in_flight.keys().next().copied().unwrap_or(next_offset);
                         on_progress(progress.clone());
-                        break;
+                        last_progress_at = Instant::now();
                     }
-                    Err(e) if retries < MAX_RETRIES => {
-                        retries += 1;
-                        tracing::warn!("Chunk send failed (attempt {}/{}): {}", retries, MAX_RETRIES, e);
-                        tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                Ok(None) => {
+                    return Err(AppError::Network("确认通道已关闭".to_string()));
+                }
+                Err(_) => {
+                    if last_progress_at.elapsed() > TRANSFER_TIMEOUT {
+                        return Err(AppError::Network("Transfer timeout".to_string()));
                     }
-                    Err(e) => {
-                        return Err(AppError::Network(format!(
-                            "Failed to send chunk after {} retries: {}",
-                            MAX_RETRIES, e
-                        )));
+
+                    // 重传所有已超时的数据块
+                    let now = Instant::now();
+                    let expired: Vec<u64> = in_flight
+                        .iter()
+                        .filter(|(_, c)| now.duration_since(c.sent_at) >= rto)
+                        .map(|(offset, _)| *offset)
+                        .collect();
+
+                    if !expired.is_empty() {
+                        window.on_loss(rto);
+                        sizer.on_loss();
+                    }
+
+                    for offset in expired {
+                        let chunk = in_flight.get_mut(&offset).expect("expired chunk must be in flight");
+                        if chunk.retries >= MAX_RETRIES {
+                            return Err(AppError::Network(format!(
+                                "Failed to send chunk at offset {} after {} retries",
+                                offset, MAX_RETRIES
+                            )));
+                        }
+                        chunk.retries += 1;
+                        progress.retransmits += 1;
+                        tracing::warn!(
+                            "Chunk at offset {} not acknowledged, retransmitting ({}/{})",
+                            offset,
+                            chunk.retries,
+                            MAX_RETRIES
+                        );
+                        rate_limiter().acquire(&peer_ip, &key, chunk.payload.len()).await;
+                        self.send_chunk(&chunk.payload, offset, chunk.compressed).await?;
+                        chunk.sent_at = Instant::now();
                     }
                 }
             }
         }
 
-        Ok(progress)
+        Ok(())
+    }
+
+    /// 目标地址中的 IP 部分（用于按对端限速）
+    fn peer_ip(&self) -> String {
+        self.target_addr
+            .rsplit_once(':')
+            .map(|(ip, _)| ip.to_string())
+            .unwrap_or_else(|| self.target_addr.clone())
     }
 
     /// 发送文件（分块传输）
@@ -120,56 +476,69 @@ impl FileSender {
     }
 
     /// 发送单个数据块
-    async fn send_chunk(&self, chunk: &[u8], offset: u64) -> AppResult<()> {
+    async fn send_chunk(&self, chunk: &[u8], offset: u64, compressed: bool) -> AppResult<()> {
+        use crate::network::feiq::constants::FEIQ_FLAG_COMPRESSED;
         use crate::network::feiq::model::FeiQPacket;
 
         // 构建 FeiQ 文件数据包
-        let packet = FeiQPacket::make_feiq_file_data_packet(
-            &self.packet_no,
-            self.file_id,
-            offset,
-            chunk,
-            None,
-        );
+        let mut packet = FeiQPacket::make_feiq_file_data_packet(&self.packet_no, self.file_id, offset, chunk, None);
+        if compressed {
+            packet.extra_flag = FEIQ_FLAG_COMPRESSED;
+        }
 
         // 使用 base64 编码数据（已在 make_feiq_file_data_packet 中完成）
         let packet_str = packet.to_feiq_string();
 
-        timeout(
-            TRANSFER_TIMEOUT,
-            sender::send_packet_data(&self.target_addr, &packet_str),
-        )
-        .await
-        .map_err(|_| AppError::Network("Transfer timeout".to_string()))?
-        .map_err(|e| AppError::Network(e.to_string()))?;
+        timeout(TRANSFER_TIMEOUT, sender::send_packet_data(&self.target_addr, &packet_str))
+            .await
+            .map_err(|_| AppError::Network("Transfer timeout".to_string()))?
+            .map_err(|e| AppError::Network(e.to_string()))?;
 
         Ok(())
     }
 
-    /// 计算文件 SHA256 校验和
-    pub fn checksum(&self) -> AppResult<String> {
-        let path = Path::new(&self.file_path);
-        let mut file = File::open(path).map_err(AppError::Io)?;
-        let mut hasher = Sha256::new();
-        let mut buffer = vec![0u8; 8192];
-
-        loop {
-            let n = file.read(&mut buffer).map_err(AppError::Io)?;
-            if n == 0 {
-                break;
-            }
-            hasher.update(&buffer[..n]);
-        }
-
-        Ok(format!("{:x}", hasher.finalize()))
+    /// 计算文件 SHA256 校验和（在阻塞线程池中读取）
+    pub async fn checksum(&self) -> AppResult<String> {
+        hash_file(Path::new(&self.file_path)).await
     }
 }
 
+/// 接收统计
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiveStats {
+    /// 本次接收的字节数（不含重复数据块）
+    pub received: u64,
+    /// 自收到第一个数据块起的耗时
+    pub elapsed: Duration,
+    /// 重复收到的数据块数（对方重传）
+    pub duplicates: u32,
+    /// 已收到的字节总数（含断点之前的部分）
+    pub progress: u64,
+    /// 当前接收速率（字节/秒）
+    pub rate: u64,
+    /// 压缩率（实际收到/解压后，1.0 表示未压缩）
+    pub compression_ratio: f64,
+}
+
 /// 文件接收器
+///
+/// 保持文件句柄打开直到接收结束，按到达顺序增量计算内容哈希
 pub struct FileReceiver {
     save_path: String,
     _file_id: u64,
-    _expected_size: u64,
+    expected_size: u64,
+    file: Option<File>,
+    hash: IncrementalHash,
+    started: Instant,
+    /// 已收到的字节区间 (起点 -> 末尾)，相邻或重叠的区间合并为一段
+    ranges: BTreeMap<u64, u64>,
+    received: u64,
+    duplicates: u32,
+    meter: RateMeter,
+    /// 实际收到的数据块字节数（压缩时为压缩后大小）
+    wire_bytes: u64,
+    /// 解压后的数据块字节数
+    raw_bytes: u64,
 }
 
 impl FileReceiver {
@@ -177,54 +546,179 @@ impl FileReceiver {
         Self {
             save_path,
             _file_id: file_id,
-            _expected_size: expected_size,
+            expected_size,
+            file: None,
+            hash: IncrementalHash::starting_at(0),
+            started: Instant::now(),
+            ranges: BTreeMap::new(),
+            received: 0,
+            duplicates: 0,
+            meter: RateMeter::new(),
+            wire_bytes: 0,
+            raw_bytes: 0,
         }
     }
 
-    /// 接收文件数据块
-    pub fn receive_chunk(&mut self, offset: u64, data: &[u8]) -> AppResult<usize> {
-        // 以读写模式打开文件，追加数据
-        use std::io::{Seek, Write};
+    /// 从指定偏移量继续接收（断点续传）
+    pub fn starting_at(mut self, offset: u64) -> Self {
+        self.hash = IncrementalHash::starting_at(offset);
+        if offset > 0 {
+            self.ranges.insert(0, offset);
+        }
+        self
+    }
 
-        let mut file = std::fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(false)
-            .open(&self.save_path)
-            .map_err(AppError::Io)?;
+    /// 记录数据块到达，识别重传的重复数据块
+    fn track(&mut self, offset: u64, len: usize) {
+        let mut start = offset;
+        let mut end = offset + len as u64;
+        let new_bytes = (end - start) - self.covered(start, end);
+        if new_bytes == 0 {
+            self.duplicates += 1;
+            return;
+        }
+        self.received += new_bytes;
+        self.meter.record(new_bytes);
+
+        // 与相邻或重叠的区间合并
+        let touching: Vec<(u64, u64)> = self
+            .ranges
+            .range(..=end)
+            .filter(|(_, e)| **e >= start)
+            .map(|(s, e)| (*s, *e))
+            .collect();
+        for (s, e) in touching {
+            self.ranges.remove(&s);
+            start = start.min(s);
+            end = end.max(e);
+        }
+        self.ranges.insert(start, end);
+    }
 
-        file.seek(io::SeekFrom::Start(offset)).map_err(AppError::Io)?;
+    /// [start, end) 中已收到的字节数
+    fn covered(&self, start: u64, end: u64) -> u64 {
+        self.ranges
+            .range(..end)
+            .filter(|(_, e)| **e > start)
+            .map(|(s, e)| (*e).min(end) - (*s).max(start))
+            .sum()
+    }
 
-        file.write_all(data).map_err(AppError::Io)?;
+    /// 从文件开头起连续收到的数据末尾（可安全持久化为断点）
+    pub fn received_through(&self) -> u64 {
+        match self.ranges.first_key_value() {
+            Some((0, end)) => *end,
+            _ => 0,
+        }
+    }
+
+    /// 整个文件是否都已收到（中间没有空洞）
+    pub fn is_complete(&self) -> bool {
+        self.received_through() >= self.expected_size
+    }
+
+    /// 接收统计
+    pub fn stats(&self) -> ReceiveStats {
+        let compression_ratio = if self.raw_bytes == 0 {
+            1.0
+        } else {
+            self.wire_bytes as f64 / self.raw_bytes as f64
+        };
+        ReceiveStats {
+            received: self.received,
+            elapsed: self.started.elapsed(),
+            duplicates: self.duplicates,
+            progress: self.covered(0, u64::MAX),
+            rate: self.meter.rate(),
+            compression_ratio,
+        }
+    }
+
+    /// 打开（不存在时创建）目标文件
+    async fn file(&mut self) -> AppResult<&mut File> {
+        if self.file.is_none() {
+            let file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(false)
+                .open(&self.save_path)
+                .await
+                .map_err(AppError::Io)?;
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().expect("file opened above"))
+    }
+
+    /// 接收文件数据块
+    ///
+    /// 超出广告的文件大小的数据块被拒绝，不会写入
+    pub async fn receive_chunk(&mut self, offset: u64, data: &[u8]) -> AppResult<usize> {
+        if offset.checked_add(data.len() as u64).is_none_or(|end| end > self.expected_size) {
+            return Err(AppError::Protocol(format!(
+                "数据块超出文件大小: offset={}, len={}, size={}",
+                offset,
+                data.len(),
+                self.expected_size
+            )));
+        }
+
+        let file = self.file().await?;
+        file.seek(SeekFrom::Start(offset)).await.map_err(AppError::Io)?;
+        file.write_all(data).await.map_err(AppError::Io)?;
+        self.hash.update(offset, data);
+        self.track(offset, data.len());
 
         Ok(data.len())
     }
 
-    /// 验证文件完整性
-    pub fn verify(&self, expected_checksum: &str) -> AppResult<bool> {
-        let mut file = File::open(&self.save_path).map_err(AppError::Io)?;
-        let mut hasher = Sha256::new();
-        let mut buffer = vec![0u8; 8192];
+    /// 接收可能经过压缩的文件数据块
+    ///
+    /// # 返回
+    /// 返回写入的原始字节数
+    pub async fn receive_encoded_chunk(&mut self, offset: u64, data: &[u8], compressed: bool) -> AppResult<usize> {
+        let written = if compressed {
+            let decoded = decompress_chunk(data)?;
+            self.receive_chunk(offset, &decoded).await?
+        } else {
+            self.receive_chunk(offset, data).await?
+        };
+        self.wire_bytes += data.len() as u64;
+        self.raw_bytes += written as u64;
+        Ok(written)
+    }
 
-        loop {
-            let n = file.read(&mut buffer).map_err(AppError::Io)?;
-            if n == 0 {
-                break;
-            }
-            hasher.update(&buffer[..n]);
+    /// 写完剩余数据并返回内容哈希
+    ///
+    /// 数据块按顺序到达时直接使用增量结果，否则在阻塞线程池中整文件计算
+    pub async fn checksum(&mut self) -> AppResult<String> {
+        if let Some(file) = self.file.as_mut() {
+            file.flush().await.map_err(AppError::Io)?;
+        }
+        match self.hash.finish(self.expected_size) {
+            Some(hash) => Ok(hash),
+            None => hash_file(Path::new(&self.save_path)).await,
         }
+    }
+
+    /// 增量计算出的内容哈希（数据不连续时为 None）
+    pub fn incremental_checksum(&self) -> Option<String> {
+        self.hash.finish(self.expected_size)
+    }
 
-        let checksum = format!("{:x}", hasher.finalize());
-        Ok(checksum == expected_checksum)
+    /// 验证文件完整性
+    pub async fn verify(&mut self, expected_checksum: &str) -> AppResult<bool> {
+        Ok(self.checksum().await? == expected_checksum)
     }
 
     /// 获取当前文件大小
-    pub fn current_size(&self) -> AppResult<u64> {
-        let path = Path::new(&self.save_path);
-        if path.exists() {
-            Ok(path.metadata().map_err(AppError::Io)?.len())
-        } else {
-            Ok(0)
+    pub async fn current_size(&mut self) -> AppResult<u64> {
+        if let Some(file) = self.file.as_mut() {
+            file.flush().await.map_err(AppError::Io)?;
+        }
+        match tokio::fs::metadata(&self.save_path).await {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(AppError::Io(e)),
         }
     }
 }
@@ -246,4 +740,115 @@ mod tests {
         assert_eq!(progress.progress, 100);
         assert!(progress.is_complete());
     }
+
+    #[test]
+    fn test_transfer_progress_starting_at_offset() {
+        let progress = FileTransferProgress::new(1, 1000).starting_at(250);
+        assert_eq!(progress.offset, 250);
+        assert_eq!(progress.acked_through, 250);
+        assert_eq!(progress.progress, 25);
+    }
+
+    #[test]
+    fn test_incremental_hash() {
+        let data = b"hello incremental hash";
+        let expected = format!("{:x}", Sha256::digest(data));
+
+        let mut hash = IncrementalHash::starting_at(0);
+        hash.update(0, &data[..5]);
+        hash.update(0, &data[..5]);
+        hash.update(5, &data[5..]);
+        assert_eq!(hash.finish(data.len() as u64), Some(expected));
+
+        let mut gap = IncrementalHash::starting_at(0);
+        gap.update(5, &data[5..]);
+        gap.update(0, &data[..5]);
+        assert_eq!(gap.finish(data.len() as u64), None);
+
+        let resumed = IncrementalHash::starting_at(5);
+        assert_eq!(resumed.finish(0), None);
+    }
+
+    #[tokio::test]
+    async fn test_receiver_out_of_order_checksum() {
+        let path = std::env::temp_dir().join(format!("feiqiu_receiver_test_{}", std::process::id()));
+        let data = b"0123456789abcdef";
+
+        let mut receiver = FileReceiver::new(path.to_string_lossy().to_string(), 1, data.len() as u64);
+        receiver.receive_chunk(8, &data[8..]).await.unwrap();
+        receiver.receive_chunk(0, &data[..8]).await.unwrap();
+
+        assert_eq!(receiver.current_size().await.unwrap(), data.len() as u64);
+        assert!(receiver.incremental_checksum().is_none());
+        let expected = format!("{:x}", Sha256::digest(data));
+        assert!(receiver.verify(&expected).await.unwrap());
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_receiver_rejects_chunk_past_size() {
+        let path = std::env::temp_dir().join(format!("feiqiu_receiver_overflow_{}", std::process::id()));
+        let mut receiver = FileReceiver::new(path.to_string_lossy().to_string(), 1, 16);
+
+        assert!(receiver.receive_chunk(8, &[0u8; 9]).await.is_err());
+        assert!(receiver.receive_chunk(u64::MAX, &[0u8; 1]).await.is_err());
+        assert!(!path.exists(), "Rejected chunks are not written");
+        assert_eq!(receiver.receive_chunk(8, &[0u8; 8]).await.unwrap(), 8);
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+
+    #[test]
+    fn test_receiver_counts_duplicates() {
+        let mut receiver = FileReceiver::new(String::new(), 1, 30).starting_at(10);
+        receiver.track(10, 10);
+        receiver.track(20, 10);
+        receiver.track(10, 10);
+
+        let stats = receiver.stats();
+        assert_eq!(stats.received, 20);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.progress, 30);
+    }
+
+    #[test]
+    fn test_receiver_holes_not_complete() {
+        let mut receiver = FileReceiver::new(String::new(), 1, 30);
+        receiver.track(0, 10);
+        receiver.track(20, 10);
+        assert!(!receiver.is_complete());
+        assert_eq!(receiver.received_through(), 10);
+
+        // 部分重叠的重传只计入新数据
+        receiver.track(5, 10);
+        assert_eq!(receiver.received_through(), 15);
+        assert_eq!(receiver.stats().received, 25);
+
+        receiver.track(15, 5);
+        assert!(receiver.is_complete());
+        assert_eq!(receiver.stats().received, 30);
+    }
+
+    #[test]
+    fn test_eta() {
+        assert_eq!(estimate_eta(0, 0), Some(0));
+        assert_eq!(estimate_eta(1000, 0), None);
+        assert_eq!(estimate_eta(1000, 300), Some(4));
+
+        let mut progress = FileTransferProgress::new(1, 1000);
+        progress.update(400);
+        progress.rate = 200;
+        assert_eq!(progress.eta_secs(), Some(3));
+    }
+
+    #[test]
+    fn test_compression_ratio() {
+        let mut progress = FileTransferProgress::new(1, 1000);
+        assert_eq!(progress.compression_ratio(), 1.0);
+
+        progress.raw_bytes = 1000;
+        progress.wire_bytes = 250;
+        assert_eq!(progress.compression_ratio(), 0.25);
+    }
 }
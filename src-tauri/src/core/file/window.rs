@@ -0,0 +1,260 @@
+// src-tauri/src/core/file/window.rs
+//
+//! 滑动窗口传输的拥塞控制与分块大小自适应
+//!
+//! - `CongestionWindow`: 以数据块为单位的拥塞窗口（慢启动 + 加性增/乘性减）
+//! - `ChunkSizer`: 根据路径 MTU 和丢包情况调整数据块大小
+//! - `RttEstimator`: 按 RFC 6298 估算往返时间和重传超时
+
+use std::time::{Duration, Instant};
+
+/// 以太网默认 MTU
+pub const DEFAULT_PATH_MTU: usize = 1500;
+
+/// IP + UDP 头部开销
+const IP_UDP_OVERHEAD: usize = 28;
+
+/// 飞秋包头预留（版本、MAC、主机名、昵称、packet_no:file_id:offset 等）
+const PACKET_HEADER_RESERVE: usize = 256;
+
+/// 最小数据块大小
+pub const MIN_CHUNK_SIZE: usize = 512;
+
+/// 最大数据块大小（base64 编码后仍需小于 UDP 最大报文 65507 字节）
+pub const MAX_CHUNK_SIZE: usize = 45 * 1024;
+
+/// 初始拥塞窗口（数据块个数）
+const INITIAL_WINDOW: f64 = 4.0;
+
+/// 初始慢启动阈值
+const INITIAL_SSTHRESH: f64 = 64.0;
+
+/// 最小拥塞窗口
+const MIN_WINDOW: f64 = 2.0;
+
+/// 最大拥塞窗口
+const MAX_WINDOW: f64 = 256.0;
+
+/// 连续确认多少个数据块后尝试增大块大小
+const GROW_AFTER_ACKS: u32 = 32;
+
+/// 重传超时下限
+const MIN_RTO: Duration = Duration::from_millis(50);
+
+/// 重传超时上限
+const MAX_RTO: Duration = Duration::from_secs(5);
+
+/// 首个 RTT 样本前的初始重传超时
+const INITIAL_RTO: Duration = Duration::from_millis(500);
+
+/// 拥塞窗口
+#[derive(Debug, Clone)]
+pub struct CongestionWindow {
+    cwnd: f64,
+    ssthresh: f64,
+    last_reduction: Option<Instant>,
+}
+
+impl CongestionWindow {
+    pub fn new() -> Self {
+        Self {
+            cwnd: INITIAL_WINDOW,
+            ssthresh: INITIAL_SSTHRESH,
+            last_reduction: None,
+        }
+    }
+
+    /// 当前允许在途的数据块个数
+    pub fn size(&self) -> usize {
+        self.cwnd as usize
+    }
+
+    /// 收到确认：慢启动阶段每个 ACK 加 1，拥塞避免阶段每个 RTT 加 1
+    pub fn on_ack(&mut self) {
+        if self.cwnd < self.ssthresh {
+            self.cwnd += 1.0;
+        } else {
+            self.cwnd += 1.0 / self.cwnd;
+        }
+        self.cwnd = self.cwnd.min(MAX_WINDOW);
+    }
+
+    /// 检测到丢包：窗口减半
+    ///
+    /// 同一个 RTO 内的多次丢包只减一次，避免窗口被突发丢包打到底
+    pub fn on_loss(&mut self, rto: Duration) {
+        let now = Instant::now();
+        if let Some(last) = self.last_reduction {
+            if now.duration_since(last) < rto {
+                return;
+            }
+        }
+        self.last_reduction = Some(now);
+        self.ssthresh = (self.cwnd / 2.0).max(MIN_WINDOW);
+        self.cwnd = self.ssthresh;
+    }
+}
+
+impl Default for CongestionWindow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 数据块大小自适应
+///
+/// 从恰好放入一个 MTU 的大小开始；链路干净时逐步增大（依赖 IP 分片，
+/// 局域网内代价很小），出现丢包时先回落到 MTU 大小，仍丢包再继续减半
+#[derive(Debug, Clone)]
+pub struct ChunkSizer {
+    size: usize,
+    mtu_size: usize,
+    acks_since_loss: u32,
+}
+
+impl ChunkSizer {
+    pub fn new(path_mtu: usize) -> Self {
+        let mtu_size = Self::payload_for_mtu(path_mtu);
+        Self {
+            size: mtu_size,
+            mtu_size,
+            acks_since_loss: 0,
+        }
+    }
+
+    /// 计算一个 MTU 能容纳的原始数据字节数（考虑 base64 的 4/3 膨胀）
+    pub fn payload_for_mtu(path_mtu: usize) -> usize {
+        let available = path_mtu.saturating_sub(IP_UDP_OVERHEAD + PACKET_HEADER_RESERVE);
+        (available / 4 * 3).clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE)
+    }
+
+    /// 当前数据块大小
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 收到确认
+    pub fn on_ack(&mut self) {
+        self.acks_since_loss += 1;
+        if self.acks_since_loss >= GROW_AFTER_ACKS {
+            self.acks_since_loss = 0;
+            self.size = (self.size + self.mtu_size).min(MAX_CHUNK_SIZE);
+        }
+    }
+
+    /// 检测到丢包
+    pub fn on_loss(&mut self) {
+        self.acks_since_loss = 0;
+        self.size = if self.size > self.mtu_size {
+            self.mtu_size
+        } else {
+            (self.size / 2).max(MIN_CHUNK_SIZE)
+        };
+    }
+}
+
+/// 往返时间估算器
+#[derive(Debug, Clone)]
+pub struct RttEstimator {
+    srtt: Option<Duration>,
+    rttvar: Duration,
+}
+
+impl RttEstimator {
+    pub fn new() -> Self {
+        Self {
+            srtt: None,
+            rttvar: Duration::ZERO,
+        }
+    }
+
+    /// 记录一个 RTT 样本（重传过的数据块不应作为样本）
+    pub fn sample(&mut self, rtt: Duration) {
+        match self.srtt {
+            None => {
+                self.srtt = Some(rtt);
+                self.rttvar = rtt / 2;
+            }
+            Some(srtt) => {
+                let delta = srtt.abs_diff(rtt);
+                self.rttvar = (self.rttvar * 3 + delta) / 4;
+                self.srtt = Some((srtt * 7 + rtt) / 8);
+            }
+        }
+    }
+
+    /// 当前重传超时
+    pub fn rto(&self) -> Duration {
+        match self.srtt {
+            None => INITIAL_RTO,
+            Some(srtt) => (srtt + self.rttvar * 4).clamp(MIN_RTO, MAX_RTO),
+        }
+    }
+}
+
+impl Default for RttEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_congestion_window_slow_start_and_loss() {
+        let mut window = CongestionWindow::new();
+        assert_eq!(window.size(), 4);
+
+        for _ in 0..10 {
+            window.on_ack();
+        }
+        assert_eq!(window.size(), 14);
+
+        window.on_loss(Duration::from_millis(100));
+        assert_eq!(window.size(), 7);
+
+        // 同一 RTO 内的第二次丢包不再减半
+        window.on_loss(Duration::from_secs(10));
+        assert_eq!(window.size(), 7);
+    }
+
+    #[test]
+    fn test_chunk_sizer_adapts_to_loss() {
+        let mut sizer = ChunkSizer::new(DEFAULT_PATH_MTU);
+        let mtu_size = sizer.size();
+        assert!((MIN_CHUNK_SIZE..DEFAULT_PATH_MTU).contains(&mtu_size));
+
+        for _ in 0..GROW_AFTER_ACKS * 2 {
+            sizer.on_ack();
+        }
+        assert_eq!(sizer.size(), mtu_size * 3);
+
+        sizer.on_loss();
+        assert_eq!(sizer.size(), mtu_size);
+
+        sizer.on_loss();
+        assert_eq!(sizer.size(), (mtu_size / 2).max(MIN_CHUNK_SIZE));
+    }
+
+    #[test]
+    fn test_chunk_sizer_respects_bounds() {
+        assert_eq!(ChunkSizer::payload_for_mtu(100), MIN_CHUNK_SIZE);
+        assert_eq!(ChunkSizer::payload_for_mtu(1_000_000), MAX_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_rtt_estimator() {
+        let mut rtt = RttEstimator::new();
+        assert_eq!(rtt.rto(), INITIAL_RTO);
+
+        rtt.sample(Duration::from_millis(20));
+        assert_eq!(rtt.rto(), Duration::from_millis(60));
+
+        for _ in 0..50 {
+            rtt.sample(Duration::from_millis(1));
+        }
+        assert_eq!(rtt.rto(), MIN_RTO);
+    }
+}
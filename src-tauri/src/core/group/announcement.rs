@@ -0,0 +1,386 @@
+// src-tauri/src/core/group/announcement.rs
+//
+//! 群公告
+//!
+//! 群公告的发布、编辑、置顶和成员确认都记为群操作，随群状态同步给所有成员，
+//! 新成员入群时收到完整的操作记录，也就能看到已有的公告。
+//! 本地的公告表和确认表按合并后的群状态更新；公告被编辑后之前的确认作废，成员需要重新确认。
+//!
+//! 群操作每行一个，公告内容中的换行在操作值中编码为 "\n"。
+
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::sync::{AnnouncementState, GroupOpKind, GroupSyncService};
+use crate::core::group::GroupService;
+use crate::database::handler::group::{GroupAnnouncementAckHandler, GroupAnnouncementHandler, GroupMemberHandler};
+use crate::database::handler::UserHandler;
+use crate::database::model::group_announcement;
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::types::{GroupAnnouncementAckInfo, GroupAnnouncementInfo};
+use chrono::NaiveDateTime;
+use sea_orm::DbConn;
+use std::collections::{BTreeMap, HashSet};
+use tracing::info;
+
+/// 公告内容的最大长度（字符）
+pub const MAX_ANNOUNCEMENT_LEN: usize = 2000;
+
+/// 生成公告编号
+pub fn make_announcement_no(machine_id: &str, now: NaiveDateTime) -> String {
+    let machine_id: String = machine_id.split_whitespace().collect();
+    format!("{}-{}", machine_id, now.and_utc().timestamp_millis())
+}
+
+/// 把公告内容编码为操作值（换行编码为 "\n"）
+pub fn encode_content(content: &str) -> String {
+    content.replace('\r', "").replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// 把操作值解码为公告内容
+pub fn decode_content(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(c) => result.push(c),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 群公告服务
+pub struct GroupAnnouncementService;
+
+impl GroupAnnouncementService {
+    /// 发布群公告
+    ///
+    /// # 参数
+    /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `content`: 公告内容
+    /// - `pinned`: 是否置顶
+    ///
+    /// # 返回
+    /// 新发布的公告
+    pub async fn create(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        content: &str,
+        pinned: bool,
+    ) -> AppResult<GroupAnnouncementInfo> {
+        let content = Self::validate_content(content)?;
+        GroupService::authorize(db, gid, operator_uid, GroupOpKind::Announce, None, None).await?;
+
+        let operator = UserHandler::find_by_id(db, operator_uid).await?;
+        let announcement_no = make_announcement_no(&operator.feiq_machine_id, chrono::Utc::now().naive_utc());
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Announce,
+            Some(announcement_no.clone()),
+            Some(encode_content(content)),
+        )
+        .await?;
+        if pinned {
+            Self::record_pin(db, gid, operator_uid, &announcement_no, true).await?;
+        }
+
+        let announcement = GroupAnnouncementHandler::find_by_no(db, gid, &announcement_no)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("群公告 {}", announcement_no)))?;
+        info!(
+            "群公告已发布: gid={}, id={}, operator={}",
+            gid, announcement.id, operator_uid
+        );
+        Self::to_info(db, announcement, operator_uid).await
+    }
+
+    /// 编辑群公告（成员需要重新确认）
+    ///
+    /// # 参数
+    /// - `announcement_id`: 公告ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `content`: 新的公告内容
+    pub async fn edit(db: &DbConn, announcement_id: i64, operator_uid: i64, content: &str) -> AppResult<()> {
+        let content = Self::validate_content(content)?;
+        let announcement = GroupAnnouncementHandler::find_by_id(db, announcement_id).await?;
+        GroupService::authorize(db, announcement.gid, operator_uid, GroupOpKind::Announce, None, None).await?;
+
+        if announcement.content == content {
+            return Ok(());
+        }
+        GroupSyncService::record(
+            db,
+            announcement.gid,
+            operator_uid,
+            GroupOpKind::Announce,
+            Some(announcement.announcement_no),
+            Some(encode_content(content)),
+        )
+        .await?;
+
+        info!("群公告已编辑: id={}, operator={}", announcement_id, operator_uid);
+        Ok(())
+    }
+
+    /// 置顶或取消置顶群公告
+    ///
+    /// # 参数
+    /// - `announcement_id`: 公告ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `pinned`: 是否置顶
+    pub async fn set_pinned(db: &DbConn, announcement_id: i64, operator_uid: i64, pinned: bool) -> AppResult<()> {
+        let announcement = GroupAnnouncementHandler::find_by_id(db, announcement_id).await?;
+        GroupService::authorize(db, announcement.gid, operator_uid, GroupOpKind::Pin, None, None).await?;
+
+        if (announcement.pinned != 0) == pinned {
+            return Ok(());
+        }
+        Self::record_pin(
+            db,
+            announcement.gid,
+            operator_uid,
+            &announcement.announcement_no,
+            pinned,
+        )
+        .await
+    }
+
+    /// 确认已读群公告
+    ///
+    /// # 参数
+    /// - `announcement_id`: 公告ID
+    /// - `operator_uid`: 确认的成员用户ID
+    pub async fn acknowledge(db: &DbConn, announcement_id: i64, operator_uid: i64) -> AppResult<()> {
+        let announcement = GroupAnnouncementHandler::find_by_id(db, announcement_id).await?;
+        GroupService::authorize(db, announcement.gid, operator_uid, GroupOpKind::Ack, None, None).await?;
+
+        let acks = GroupAnnouncementAckHandler::list_by_announcement(db, announcement_id).await?;
+        if acks.iter().any(|ack| ack.member_uid == operator_uid) {
+            return Ok(());
+        }
+        GroupSyncService::record(
+            db,
+            announcement.gid,
+            operator_uid,
+            GroupOpKind::Ack,
+            Some(announcement.announcement_no),
+            None,
+        )
+        .await
+    }
+
+    /// 获取群组的公告（置顶在前，按更新时间倒序）
+    ///
+    /// # 参数
+    /// - `gid`: 群组ID
+    /// - `current_uid`: 当前用户ID（用于标记是否已确认）
+    pub async fn list(db: &DbConn, gid: i64, current_uid: i64) -> AppResult<Vec<GroupAnnouncementInfo>> {
+        let mut result = Vec::new();
+        for announcement in GroupAnnouncementHandler::list_by_group(db, gid).await? {
+            result.push(Self::to_info(db, announcement, current_uid).await?);
+        }
+        Ok(result)
+    }
+
+    /// 获取当前成员对公告的确认情况（已确认的在前）
+    pub async fn list_acks(db: &DbConn, announcement_id: i64) -> AppResult<Vec<GroupAnnouncementAckInfo>> {
+        let announcement = GroupAnnouncementHandler::find_by_id(db, announcement_id).await?;
+        let acks = GroupAnnouncementAckHandler::list_by_announcement(db, announcement_id).await?;
+
+        let mut acked = Vec::new();
+        let mut pending = Vec::new();
+        for member in GroupMemberHandler::list_by_group(db, announcement.gid).await? {
+            let nickname = match UserHandler::find_by_id(db, member.member_uid).await {
+                Ok(user) => user.nickname,
+                _ => format!("User{}", member.member_uid),
+            };
+            match acks.iter().find(|ack| ack.member_uid == member.member_uid) {
+                Some(ack) => acked.push(GroupAnnouncementAckInfo {
+                    member_uid: member.member_uid,
+                    nickname,
+                    acknowledged: true,
+                    ack_time: Some(ack.ack_time.to_string()),
+                }),
+                None => pending.push(GroupAnnouncementAckInfo {
+                    member_uid: member.member_uid,
+                    nickname,
+                    acknowledged: false,
+                    ack_time: None,
+                }),
+            }
+        }
+
+        acked.extend(pending);
+        Ok(acked)
+    }
+
+    /// 按合并后的群状态更新本地公告和确认记录
+    ///
+    /// 有变化的公告发送 `UiEvent::GroupAnnouncementUpdated`
+    pub async fn reconcile(
+        db: &DbConn,
+        gid: i64,
+        announcements: &BTreeMap<String, AnnouncementState>,
+    ) -> AppResult<()> {
+        for (announcement_no, state) in announcements {
+            let content = decode_content(&state.content);
+            let editor_uid = member_uid_for(db, &state.editor).await?;
+
+            let (announcement, mut changed) = match GroupAnnouncementHandler::find_by_no(db, gid, announcement_no)
+                .await?
+            {
+                Some(existing)
+                    if existing.content == content
+                        && existing.editor_uid == editor_uid
+                        && (existing.pinned != 0) == state.pinned =>
+                {
+                    (existing, false)
+                }
+                Some(existing) => {
+                    let updated =
+                        GroupAnnouncementHandler::update(db, existing.id, &content, editor_uid, state.pinned).await?;
+                    (updated, true)
+                }
+                None => {
+                    let author_uid = member_uid_for(db, &state.author).await?;
+                    let created = GroupAnnouncementHandler::create(
+                        db,
+                        gid,
+                        announcement_no,
+                        &content,
+                        author_uid,
+                        editor_uid,
+                        state.pinned,
+                    )
+                    .await?;
+                    (created, true)
+                }
+            };
+
+            let mut ack_uids = HashSet::new();
+            for machine_id in &state.acks {
+                ack_uids.insert(member_uid_for(db, machine_id).await?);
+            }
+            let existing_acks = GroupAnnouncementAckHandler::list_by_announcement(db, announcement.id).await?;
+            for ack in &existing_acks {
+                if !ack_uids.contains(&ack.member_uid) {
+                    GroupAnnouncementAckHandler::delete(db, ack.id).await?;
+                    changed = true;
+                }
+            }
+            for uid in ack_uids {
+                if !existing_acks.iter().any(|ack| ack.member_uid == uid) {
+                    GroupAnnouncementAckHandler::create(db, announcement.id, uid).await?;
+                    changed = true;
+                }
+            }
+
+            if changed {
+                let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::GroupAnnouncementUpdated {
+                    gid,
+                    announcement_id: announcement.id,
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_content(content: &str) -> AppResult<&str> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(AppError::Business("公告内容不能为空".to_string()));
+        }
+        if content.chars().count() > MAX_ANNOUNCEMENT_LEN {
+            return Err(AppError::Business(format!(
+                "公告内容不能超过 {} 个字符",
+                MAX_ANNOUNCEMENT_LEN
+            )));
+        }
+        Ok(content)
+    }
+
+    async fn record_pin(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        announcement_no: &str,
+        pinned: bool,
+    ) -> AppResult<()> {
+        let value = if pinned { "1" } else { "0" };
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Pin,
+            Some(announcement_no.to_string()),
+            Some(value.to_string()),
+        )
+        .await
+    }
+
+    async fn to_info(
+        db: &DbConn,
+        announcement: group_announcement::Model,
+        current_uid: i64,
+    ) -> AppResult<GroupAnnouncementInfo> {
+        let members = GroupMemberHandler::list_by_group(db, announcement.gid).await?;
+        let acks = GroupAnnouncementAckHandler::list_by_announcement(db, announcement.id).await?;
+        let ack_count = members
+            .iter()
+            .filter(|member| acks.iter().any(|ack| ack.member_uid == member.member_uid))
+            .count();
+        let author_nickname = match UserHandler::find_by_id(db, announcement.author_uid).await {
+            Ok(user) => user.nickname,
+            _ => format!("User{}", announcement.author_uid),
+        };
+
+        Ok(GroupAnnouncementInfo {
+            id: announcement.id,
+            gid: announcement.gid,
+            content: announcement.content,
+            author_uid: announcement.author_uid,
+            author_nickname,
+            editor_uid: announcement.editor_uid,
+            pinned: announcement.pinned != 0,
+            ack_count,
+            member_count: members.len(),
+            acknowledged: acks.iter().any(|ack| ack.member_uid == current_uid),
+            create_time: announcement.create_time.to_string(),
+            update_time: announcement.update_time.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_encoding() {
+        let content = "值班：张三\r\n构建服务器：\\\\build01";
+        let encoded = encode_content(content);
+        assert!(!encoded.contains('\n'));
+        assert_eq!(decode_content(&encoded), "值班：张三\n构建服务器：\\\\build01");
+        assert_eq!(decode_content("没有换行"), "没有换行");
+    }
+
+    #[test]
+    fn test_announcement_no() {
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().naive_utc();
+        assert_eq!(
+            make_announcement_no("10.0.0.1:2425", now),
+            "10.0.0.1:2425-1700000000000"
+        );
+    }
+}
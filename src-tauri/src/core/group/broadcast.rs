@@ -4,6 +4,7 @@
 use crate::database::handler::group::GroupMemberHandler;
 use crate::database::handler::user::UserHandler;
 use crate::error::AppResult;
+use crate::network::feiq::capability::peer_supports;
 use crate::network::feiq::model::FeiQPacket;
 use crate::network::udp::sender;
 use sea_orm::DbConn;
@@ -49,6 +50,54 @@ impl GroupBroadcaster {
         Ok(sent_count)
     }
 
+    /// 按成员能力向群组广播扩展包
+    ///
+    /// 支持 `capability` 的在线成员收到 `packet`，其余在线成员收到 `fallback`
+    /// （为 None 时跳过不支持的成员）
+    ///
+    /// # 返回
+    /// 成功发送的成员数量
+    pub async fn broadcast_by_capability(
+        db: &DbConn,
+        gid: i64,
+        capability: u32,
+        packet: &FeiQPacket,
+        fallback: Option<&FeiQPacket>,
+        sender_uid: i64,
+    ) -> AppResult<usize> {
+        let members = GroupMemberHandler::list_by_group(db, gid).await?;
+
+        let mut sent_count = 0;
+
+        for member in members {
+            if member.member_uid == sender_uid {
+                continue;
+            }
+
+            let user = match UserHandler::find_by_id(db, member.member_uid).await {
+                Ok(user) if user.status == 1 => user,
+                _ => continue,
+            };
+
+            let packet = if peer_supports(&user.feiq_ip, capability) {
+                packet
+            } else if let Some(fallback) = fallback {
+                fallback
+            } else {
+                continue;
+            };
+
+            let addr = format!("{}:{}", user.feiq_ip, user.feiq_port);
+            if sender::send_packet(&addr, packet).await.is_err() {
+                tracing::warn!("Failed to send group packet to {}", addr);
+            } else {
+                sent_count += 1;
+            }
+        }
+
+        Ok(sent_count)
+    }
+
     /// 获取群组成员数量
     pub async fn get_member_count(db: &DbConn, gid: i64) -> AppResult<usize> {
         let members = GroupMemberHandler::list_by_group(db, gid).await?;
@@ -0,0 +1,235 @@
+// src-tauri/src/core/group/envelope.rs
+//
+//! 群消息信封
+//!
+//! 本地群组 ID 只在本机有效，群消息在网络上用群编号标识群组。群编号由创建者生成，
+//! 随群状态同步发给各成员。每条群消息带上群编号、发送者在该群内的消息序号和发送者机器标识，
+//! 接收方据此存入对应的群会话，并用消息序号识别重发的同一条消息。
+//! 消息中的 @ 提及也以成员机器标识放在信封中。
+//! 不支持 `FEIQ_CAP_GROUP` 的成员收到带群名前缀的普通消息。
+
+use crate::core::chat::MentionService;
+use crate::core::group::GroupBroadcaster;
+use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
+use crate::database::handler::{ChatMessageHandler, GroupMessageReceiptHandler, UserHandler};
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, ChatEvent};
+use crate::network::feiq::constants::FEIQ_CAP_GROUP;
+use crate::network::feiq::model::{FeiQPacket, GroupEnvelope};
+use chrono::NaiveDateTime;
+use sea_orm::DbConn;
+use tracing::{info, warn};
+
+/// 群状态中没有群名称时使用的群名称
+pub const UNKNOWN_GROUP_NAME: &str = "群聊";
+
+/// 生成群编号
+///
+/// # 参数
+/// - `creator_machine_id`: 创建者机器标识（"ip:port"）
+/// - `gid`: 创建者本地的群组 ID
+/// - `created_at`: 群组创建时间
+pub fn make_group_no(creator_machine_id: &str, gid: i64, created_at: NaiveDateTime) -> String {
+    let machine_id: String = creator_machine_id.split_whitespace().collect();
+    format!("{}-{}-{}", machine_id, gid, created_at.and_utc().timestamp())
+}
+
+/// 不支持群消息信封的成员看到的消息内容
+pub fn fallback_content(group_name: &str, content: &str) -> String {
+    format!("[{}] {}", group_name, content)
+}
+
+/// 根据机器标识得到用户 ID，还没见过的成员先记为离线用户
+pub(crate) async fn member_uid_for(db: &DbConn, machine_id: &str) -> AppResult<i64> {
+    if let Some(user) = UserHandler::find_by_machine_id(db, machine_id).await? {
+        return Ok(user.uid);
+    }
+
+    let (ip, port) = machine_id.rsplit_once(':').unwrap_or((machine_id, ""));
+    let port = port.parse::<u16>().unwrap_or(2425);
+    let user = UserHandler::upsert_by_machine_id(db, machine_id, ip, port, ip, 0).await?;
+    Ok(user.uid)
+}
+
+/// 群消息信封服务
+pub struct GroupEnvelopeService;
+
+impl GroupEnvelopeService {
+    /// 获取群编号，旧群组没有群编号时按本机身份生成
+    pub async fn ensure_group_no(db: &DbConn, gid: i64) -> AppResult<String> {
+        let group = GroupHandler::find_by_id(db, gid).await?;
+        if let Some(group_no) = group.group_no {
+            return Ok(group_no);
+        }
+
+        let current_user = UserHandler::get_current_user(db).await?;
+        let group_no = make_group_no(&current_user.feiq_machine_id, gid, group.create_time);
+        GroupHandler::set_group_no(db, gid, &group_no).await?;
+        info!("群编号已生成: gid={}, group_no={}", gid, group_no);
+
+        Ok(group_no)
+    }
+
+    /// 为群消息加上信封并广播给群成员
+    ///
+    /// 消息第一次发送时分配群内消息序号，重发时沿用原序号
+    ///
+    /// # 参数
+    /// - `mid`: 本地消息 ID
+    /// - `inner`: 普通消息包（可以是引用回复）
+    ///
+    /// # 返回
+    /// 成功发送的成员数量
+    pub async fn broadcast(db: &DbConn, mid: i64, inner: &FeiQPacket) -> AppResult<usize> {
+        let message = ChatMessageHandler::find_by_id(db, mid).await?;
+        let gid = message.target_id;
+
+        let group_msg_no = match message.group_msg_no {
+            Some(group_msg_no) => group_msg_no,
+            None => {
+                let group_msg_no = ChatMessageHandler::next_group_msg_no(db, gid, message.sender_uid).await?;
+                ChatMessageHandler::set_group_msg_no(db, mid, group_msg_no).await?;
+                group_msg_no
+            }
+        };
+
+        // 为每个接收成员记录回执，由 RECVMSG/READMSG 更新
+        let recipients: Vec<i64> = GroupMemberHandler::list_by_group(db, gid)
+            .await?
+            .into_iter()
+            .map(|member| member.member_uid)
+            .filter(|uid| *uid != message.sender_uid)
+            .collect();
+        GroupMessageReceiptHandler::create_pending(db, mid, gid, &recipients).await?;
+
+        let sender = UserHandler::find_by_id(db, message.sender_uid).await?;
+        let envelope = GroupEnvelope {
+            group_no: Self::ensure_group_no(db, gid).await?,
+            group_msg_no,
+            sender: sender.feiq_machine_id,
+            mentions: MentionService::envelope_mentions(db, mid).await?,
+        };
+        let packet = FeiQPacket::make_feiq_group_packet(inner, &envelope);
+
+        let group = GroupHandler::find_by_id(db, gid).await?;
+        let mut fallback = inner.clone();
+        fallback.ext_info.remark = fallback_content(&group.group_name, &inner.ext_info.remark);
+
+        GroupBroadcaster::broadcast_by_capability(db, gid, FEIQ_CAP_GROUP, &packet, Some(&fallback), message.sender_uid)
+            .await
+    }
+
+    /// 解析收到的群消息信封
+    ///
+    /// 发送者以数据包来源地址为准，信封中声明的发送者不可信。
+    /// 群组只由群状态同步或邀请创建，未知群组和非群成员发来的消息都被拒绝
+    ///
+    /// # 参数
+    /// - `sender_ip` / `sender_port`: 数据包来源地址
+    ///
+    /// # 返回
+    /// (本地群组 ID, 发送者用户 ID)
+    pub async fn resolve_incoming(
+        db: &DbConn,
+        envelope: &GroupEnvelope,
+        sender_ip: &str,
+        sender_port: u16,
+    ) -> AppResult<(i64, i64)> {
+        let machine_id = format!("{}:{}", sender_ip, sender_port);
+        if envelope.sender != machine_id {
+            warn!("群消息信封中的发送者与来源地址不一致: {} != {}", envelope.sender, machine_id);
+        }
+
+        let group = GroupHandler::find_by_group_no(db, &envelope.group_no)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("未知群组: group_no={}", envelope.group_no)))?;
+
+        // 群成员在加入群组时都已记为用户，找不到用户即不是成员
+        let sender = UserHandler::find_by_machine_id(db, &machine_id)
+            .await?
+            .ok_or_else(|| AppError::Permission(format!("{} 不是群成员", machine_id)))?;
+        if GroupMemberHandler::find_by_group_and_member(db, group.gid, sender.uid)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::Permission(format!("{} 不是群成员", machine_id)));
+        }
+
+        Ok((group.gid, sender.uid))
+    }
+
+    /// 确保其他成员创建的群组在本地存在，并补齐缺少的成员
+    ///
+    /// # 参数
+    /// - `group_no`: 群编号
+    /// - `group_name`: 群名称（群组已存在时不修改）
+    /// - `inviter`: 邀请者机器标识
+    /// - `members`: 成员机器标识列表
+    ///
+    /// # 返回
+    /// 本地群组 ID
+    pub async fn ensure_remote_group(
+        db: &DbConn,
+        group_no: &str,
+        group_name: &str,
+        inviter: &str,
+        members: &[String],
+    ) -> AppResult<i64> {
+        let gid = match GroupHandler::find_by_group_no(db, group_no).await? {
+            Some(group) => group.gid,
+            None => {
+                let inviter_uid = member_uid_for(db, inviter).await?;
+                match GroupHandler::create_remote(db, group_no, group_name.to_string(), inviter_uid).await {
+                    Ok(group) => {
+                        info!("已在本地创建群组: gid={}, group_no={}", group.gid, group_no);
+                        let _ = EVENT_SENDER.send(AppEvent::Chat(ChatEvent::GroupCreated { group_id: group.gid }));
+                        group.gid
+                    }
+                    // 同一群组的群状态和消息可能同时到达，另一方已经创建
+                    Err(e) => match GroupHandler::find_by_group_no(db, group_no).await? {
+                        Some(group) => group.gid,
+                        None => return Err(e),
+                    },
+                }
+            }
+        };
+
+        for machine_id in members {
+            let member_uid = member_uid_for(db, machine_id).await?;
+            if GroupMemberHandler::find_by_group_and_member(db, gid, member_uid)
+                .await?
+                .is_none()
+            {
+                GroupMemberHandler::add_member(db, gid, member_uid, 0).await?;
+                let _ = EVENT_SENDER.send(AppEvent::Chat(ChatEvent::GroupMemberAdded {
+                    group_id: gid,
+                    user_id: member_uid,
+                }));
+            }
+        }
+
+        Ok(gid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_group_no() {
+        let created_at = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().naive_utc();
+        let group_no = make_group_no("192.168.1.2:2425", 3, created_at);
+        assert_eq!(group_no, "192.168.1.2:2425-3-1700000000");
+        assert!(
+            !group_no.contains(char::is_whitespace),
+            "Envelope fields are space separated"
+        );
+    }
+
+    #[test]
+    fn test_fallback_content() {
+        assert_eq!(fallback_content("项目组", "明天开会"), "[项目组] 明天开会");
+    }
+}
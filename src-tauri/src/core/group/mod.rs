@@ -1,8 +1,15 @@
 // src-tauri/src/core/group/mod.rs
 //
 /// 群组相关业务逻辑
+pub mod announcement;
 pub mod broadcast;
+pub mod envelope;
+pub mod permission;
 pub mod service;
+pub mod sync;
 
+pub use announcement::GroupAnnouncementService;
 pub use broadcast::GroupBroadcaster;
+pub use envelope::GroupEnvelopeService;
 pub use service::GroupService;
+pub use sync::GroupSyncService;
@@ -0,0 +1,157 @@
+// src-tauri/src/core/group/permission.rs
+//
+//! 群组权限
+//!
+//! 群主和管理员可以邀请、移除成员和修改群信息，管理员只能移除角色低于自己的成员，
+//! 也不能修改群主的角色；只有群主可以转让群主和解散群组。
+//! 群公告由群主和管理员发布、编辑和置顶，所有成员都可以确认已读。
+//! 本机发起的操作由 `GroupService` 按本地成员角色检查，
+//! 其他成员同步来的群操作在合并群状态时按同样的规则检查，未授权的操作不生效。
+
+use crate::core::group::sync::{GroupOpKind, ROLE_ADMIN, ROLE_MEMBER, ROLE_OWNER};
+
+/// 检查群操作是否允许
+///
+/// 创建群组不在此检查，由调用方保证群组尚未创建
+///
+/// # 参数
+/// - `kind`: 操作类型
+/// - `actor_role`: 操作者角色（不是群成员时为 None）
+/// - `is_self`: 操作对象是否为操作者自己
+/// - `target_role`: 操作对象当前角色（不是群成员时为 None）
+/// - `new_role`: 修改角色时的新角色（缺少或无法解析时为 None）
+///
+/// # 返回
+/// 不允许时返回原因
+pub fn check_op(
+    kind: GroupOpKind,
+    actor_role: Option<i8>,
+    is_self: bool,
+    target_role: Option<i8>,
+    new_role: Option<i8>,
+) -> Result<(), &'static str> {
+    match kind {
+        GroupOpKind::Create => Ok(()),
+        GroupOpKind::Join => {
+            if is_self {
+                Ok(())
+            } else {
+                Err("只能自己加入群组")
+            }
+        }
+        GroupOpKind::Leave => {
+            if !is_self {
+                return Err("只能自己退出群组");
+            }
+            if member_role(actor_role)? == ROLE_OWNER {
+                return Err("群主需要先转让群主才能退出群组");
+            }
+            Ok(())
+        }
+        GroupOpKind::Invite
+        | GroupOpKind::Rename
+        | GroupOpKind::Describe
+        | GroupOpKind::Announce
+        | GroupOpKind::Pin => require_admin(actor_role).map(|_| ()),
+        GroupOpKind::Ack => member_role(actor_role).map(|_| ()),
+        GroupOpKind::Kick => {
+            let actor_role = require_admin(actor_role)?;
+            if is_self {
+                return Err("不能移除自己");
+            }
+            match target_role {
+                Some(target_role) if target_role < actor_role => Ok(()),
+                Some(_) => Err("只能移除角色低于自己的成员"),
+                None => Err("对方不是群成员"),
+            }
+        }
+        GroupOpKind::Role => {
+            let actor_role = require_admin(actor_role)?;
+            let target_role = target_role.ok_or("对方不是群成员")?;
+            let new_role = match new_role {
+                Some(role) if (ROLE_MEMBER..=ROLE_OWNER).contains(&role) => role,
+                _ => return Err("无效的角色"),
+            };
+            if new_role == ROLE_OWNER && actor_role != ROLE_OWNER {
+                return Err("只有群主可以转让群主");
+            }
+            // 群主只能在转让群主时修改自己的角色
+            if target_role == ROLE_OWNER && !is_self {
+                return Err("不能修改群主的角色");
+            }
+            Ok(())
+        }
+    }
+}
+
+/// 检查是否可以解散群组
+pub fn check_delete(actor_role: Option<i8>) -> Result<(), &'static str> {
+    match actor_role {
+        Some(ROLE_OWNER) => Ok(()),
+        _ => Err("只有群主可以解散群组"),
+    }
+}
+
+fn member_role(actor_role: Option<i8>) -> Result<i8, &'static str> {
+    actor_role.ok_or("不是群成员")
+}
+
+fn require_admin(actor_role: Option<i8>) -> Result<i8, &'static str> {
+    let actor_role = member_role(actor_role)?;
+    if actor_role >= ROLE_ADMIN {
+        Ok(actor_role)
+    } else {
+        Err("需要群主或管理员权限")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMBER: Option<i8> = Some(ROLE_MEMBER);
+    const ADMIN: Option<i8> = Some(ROLE_ADMIN);
+    const OWNER: Option<i8> = Some(ROLE_OWNER);
+
+    #[test]
+    fn test_member_management() {
+        assert!(check_op(GroupOpKind::Invite, ADMIN, false, None, None).is_ok());
+        assert!(check_op(GroupOpKind::Invite, MEMBER, false, None, None).is_err());
+        assert!(check_op(GroupOpKind::Invite, None, false, None, None).is_err());
+
+        assert!(check_op(GroupOpKind::Kick, OWNER, false, ADMIN, None).is_ok());
+        assert!(check_op(GroupOpKind::Kick, ADMIN, false, MEMBER, None).is_ok());
+        assert!(check_op(GroupOpKind::Kick, ADMIN, false, ADMIN, None).is_err());
+        assert!(check_op(GroupOpKind::Kick, ADMIN, false, OWNER, None).is_err());
+        assert!(check_op(GroupOpKind::Kick, MEMBER, false, MEMBER, None).is_err());
+
+        assert!(check_op(GroupOpKind::Leave, MEMBER, true, MEMBER, None).is_ok());
+        assert!(check_op(GroupOpKind::Leave, OWNER, true, OWNER, None).is_err());
+        assert!(check_op(GroupOpKind::Join, None, true, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_roles_and_ownership() {
+        assert!(check_op(GroupOpKind::Role, ADMIN, false, MEMBER, ADMIN).is_ok());
+        assert!(check_op(GroupOpKind::Role, ADMIN, false, OWNER, MEMBER).is_err());
+        assert!(check_op(GroupOpKind::Role, ADMIN, false, MEMBER, OWNER).is_err());
+        assert!(check_op(GroupOpKind::Role, MEMBER, true, MEMBER, ADMIN).is_err());
+
+        // 转让群主：先设置新群主，再把自己降为管理员
+        assert!(check_op(GroupOpKind::Role, OWNER, false, ADMIN, OWNER).is_ok());
+        assert!(check_op(GroupOpKind::Role, OWNER, true, OWNER, ADMIN).is_ok());
+
+        // 超出角色范围或无法解析的新角色
+        assert!(check_op(GroupOpKind::Role, OWNER, false, MEMBER, Some(ROLE_OWNER + 1)).is_err());
+        assert!(check_op(GroupOpKind::Role, OWNER, false, ADMIN, Some(-1)).is_err());
+        assert!(check_op(GroupOpKind::Role, OWNER, false, MEMBER, None).is_err());
+
+        assert!(check_op(GroupOpKind::Announce, ADMIN, false, None, None).is_ok());
+        assert!(check_op(GroupOpKind::Pin, MEMBER, false, None, None).is_err());
+        assert!(check_op(GroupOpKind::Ack, MEMBER, false, None, None).is_ok());
+        assert!(check_op(GroupOpKind::Ack, None, false, None, None).is_err());
+
+        assert!(check_delete(OWNER).is_ok());
+        assert!(check_delete(ADMIN).is_err());
+    }
+}
@@ -6,11 +6,76 @@
 //! - 添加群成员
 //! - 移除群成员
 //! - 管理群组信息
+//!
+//! 创建、改名和成员变更都记为群操作（包括操作者）并同步给其他成员。
+//! 除创建外的操作都先按操作者在群内的角色检查权限，见 `permission` 模块。
+//!
+//! 邀请成员时先发送群邀请，对方接受后才加入群组；允许申请加入的群组可以被其他用户发现，
+//! 入群申请由群主或管理员审核。邀请和申请在收发双方各保存一条，过期后不能再处理；
+//! 后台定期把到期的请求标记为过期并通知界面。
 
-use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
-use crate::error::AppResult;
+use crate::core::group::envelope::member_uid_for;
+use crate::core::group::permission;
+use crate::core::group::sync::{GroupOpKind, GroupSyncService, ROLE_ADMIN, ROLE_MEMBER, ROLE_OWNER};
+use crate::core::group::GroupEnvelopeService;
+use crate::database::handler::group::{
+    GroupAnnouncementHandler, GroupHandler, GroupMemberHandler, GroupOpHandler, GroupRequestHandler,
+};
+use crate::database::handler::UserHandler;
+use crate::database::model::{group_request, user};
+use crate::error::{AppError, AppResult};
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, UiEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_GROUP;
+use crate::network::feiq::model::{FeiQPacket, GroupRequest};
+use crate::network::udp::sender;
+use crate::types::GroupRequestInfo;
+use chrono::NaiveDateTime;
 use sea_orm::DbConn;
-use tracing::{error, info};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// 群邀请和入群申请的有效期（秒）
+pub const GROUP_REQUEST_TTL_SECS: i64 = 3 * 24 * 60 * 60;
+
+/// 过期请求的检查间隔
+const REQUEST_EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 请求类型：群邀请
+pub const REQUEST_KIND_INVITE: i8 = 0;
+
+/// 请求类型：入群申请
+pub const REQUEST_KIND_JOIN: i8 = 1;
+
+/// 请求方向：收到
+pub const REQUEST_INCOMING: i8 = 0;
+
+/// 请求方向：发出
+pub const REQUEST_OUTGOING: i8 = 1;
+
+/// 请求状态：待处理
+pub const REQUEST_PENDING: i8 = 0;
+
+/// 请求状态：已接受
+pub const REQUEST_ACCEPTED: i8 = 1;
+
+/// 请求状态：已拒绝
+pub const REQUEST_DECLINED: i8 = 2;
+
+/// 请求状态：已过期
+pub const REQUEST_EXPIRED: i8 = 3;
+
+/// 生成请求编号
+pub fn make_request_no(machine_id: &str, now: NaiveDateTime) -> String {
+    let machine_id: String = machine_id.split_whitespace().collect();
+    format!("{}-{}", machine_id, now.and_utc().timestamp_millis())
+}
+
+/// 请求是否已过期
+pub fn is_expired(expire_time: NaiveDateTime, now: NaiveDateTime) -> bool {
+    expire_time <= now
+}
 
 /// 群组服务
 pub struct GroupService;
@@ -55,6 +120,21 @@ impl GroupService {
                 })?;
         }
 
+        // 3. 生成群编号，其他成员通过群编号识别该群组
+        GroupEnvelopeService::ensure_group_no(db, gid).await?;
+
+        // 4. 记录创建操作
+        let creator = UserHandler::find_by_id(db, creator_uid).await?;
+        GroupSyncService::record(
+            db,
+            gid,
+            creator_uid,
+            GroupOpKind::Create,
+            Some(creator.feiq_machine_id),
+            Some(group_name),
+        )
+        .await?;
+
         Ok(gid)
     }
 
@@ -96,14 +176,24 @@ impl GroupService {
     /// # 参数
     /// - `db`: 数据库连接
     /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
     /// - `member_uid`: 成员用户ID
     /// - `nickname`: 成员昵称
     ///
     /// # 返回
     /// 返回操作结果
-    pub async fn add_member(db: &DbConn, gid: i64, member_uid: i64, nickname: String) -> AppResult<()> {
-        // 1. 添加群成员（role=0 表示普通成员）
-        GroupMemberHandler::add_member(db, gid, member_uid, 0)
+    pub async fn add_member(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        member_uid: i64,
+        nickname: String,
+    ) -> AppResult<()> {
+        // 1. 检查权限
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Invite, Some(member_uid), None).await?;
+
+        // 2. 添加群成员（role=0 表示普通成员）
+        GroupMemberHandler::add_member(db, gid, member_uid, ROLE_MEMBER)
             .await
             .map_err(|e| {
                 error!("添加群成员失败: gid={}, member_uid={}, error={}", gid, member_uid, e);
@@ -111,28 +201,173 @@ impl GroupService {
             })?;
 
         info!("群成员已添加: gid={}, member_uid={}, nickname={}", gid, member_uid, nickname);
+
+        // 3. 记录邀请操作并同步给群成员（新成员据此在本地创建群组）
+        let member = UserHandler::find_by_id(db, member_uid).await?;
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Invite,
+            Some(member.feiq_machine_id),
+            None,
+        )
+        .await?;
+
         Ok(())
     }
 
     /// 移除群成员
     ///
+    /// 移除操作者自己时记为离开群组，否则记为移除成员
+    ///
     /// # 参数
     /// - `db`: 数据库连接
     /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（移除他人需要群主或管理员权限）
     /// - `member_uid`: 成员用户ID
     ///
     /// # 返回
     /// 返回操作结果
-    pub async fn remove_member(db: &DbConn, gid: i64, member_uid: i64) -> AppResult<()> {
-        // 1. 移除群成员
-        GroupMemberHandler::remove_member(db, gid, member_uid)
+    pub async fn remove_member(db: &DbConn, gid: i64, operator_uid: i64, member_uid: i64) -> AppResult<()> {
+        // 1. 检查成员是否在群组中
+        if Self::role_of(db, gid, member_uid).await?.is_none() {
+            return Err(AppError::NotFound(format!("群成员 {} 不存在", member_uid)));
+        }
+
+        // 2. 检查权限
+        let kind = if member_uid == operator_uid {
+            GroupOpKind::Leave
+        } else {
+            GroupOpKind::Kick
+        };
+        Self::authorize(db, gid, operator_uid, kind, Some(member_uid), None).await?;
+
+        // 3. 记录离开/移除操作，同步后从本地群组移除
+        let member = UserHandler::find_by_id(db, member_uid).await?;
+        GroupSyncService::record(db, gid, operator_uid, kind, Some(member.feiq_machine_id), None)
             .await
             .map_err(|e| {
                 error!("移除群成员失败: gid={}, member_uid={}, error={}", gid, member_uid, e);
                 e
             })?;
 
-        info!("群成员已移除: gid={}, member_uid={}", gid, member_uid);
+        info!("群成员已移除: gid={}, member_uid={}, operator={}", gid, member_uid, operator_uid);
+        Ok(())
+    }
+
+    /// 更新群成员角色
+    ///
+    /// 设置为群主时转让群主
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `member_uid`: 成员用户ID
+    /// - `role`: 新角色
+    ///
+    /// # 返回
+    /// 返回操作结果
+    pub async fn update_member_role(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        member_uid: i64,
+        role: i8,
+    ) -> AppResult<()> {
+        if !(ROLE_MEMBER..=ROLE_OWNER).contains(&role) {
+            return Err(AppError::Business(format!("无效的成员角色: {}", role)));
+        }
+        if role == ROLE_OWNER {
+            return Self::transfer_owner(db, gid, operator_uid, member_uid).await;
+        }
+
+        // 1. 检查成员是否在群组中
+        let current_role = match Self::role_of(db, gid, member_uid).await? {
+            Some(current_role) => current_role,
+            None => return Err(AppError::NotFound(format!("群成员 {} 不存在", member_uid))),
+        };
+        if current_role == ROLE_OWNER && member_uid == operator_uid {
+            return Err(AppError::Permission("群主需要通过转让群主修改自己的角色".to_string()));
+        }
+
+        // 2. 检查权限
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Role, Some(member_uid), Some(role)).await?;
+
+        // 3. 记录角色变更操作并同步
+        let member = UserHandler::find_by_id(db, member_uid).await?;
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Role,
+            Some(member.feiq_machine_id),
+            Some(role.to_string()),
+        )
+        .await?;
+
+        info!(
+            "群成员角色已更新: gid={}, member_uid={}, role={}, operator={}",
+            gid, member_uid, role, operator_uid
+        );
+        Ok(())
+    }
+
+    /// 转让群主
+    ///
+    /// 新群主必须是群成员，原群主转为管理员
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（必须是群主）
+    /// - `new_owner_uid`: 新群主用户ID
+    ///
+    /// # 返回
+    /// 返回操作结果
+    pub async fn transfer_owner(db: &DbConn, gid: i64, operator_uid: i64, new_owner_uid: i64) -> AppResult<()> {
+        if new_owner_uid == operator_uid {
+            return Err(AppError::Business("不能把群主转让给自己".to_string()));
+        }
+        if Self::role_of(db, gid, new_owner_uid).await?.is_none() {
+            return Err(AppError::NotFound(format!("群成员 {} 不存在", new_owner_uid)));
+        }
+
+        // 1. 检查权限（只有群主可以设置新群主）
+        Self::authorize(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Role,
+            Some(new_owner_uid),
+            Some(ROLE_OWNER),
+        )
+        .await?;
+
+        // 2. 先设置新群主，再把自己降为管理员，群组始终有群主
+        let new_owner = UserHandler::find_by_id(db, new_owner_uid).await?;
+        let operator = UserHandler::find_by_id(db, operator_uid).await?;
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Role,
+            Some(new_owner.feiq_machine_id),
+            Some(ROLE_OWNER.to_string()),
+        )
+        .await?;
+        GroupSyncService::record(
+            db,
+            gid,
+            operator_uid,
+            GroupOpKind::Role,
+            Some(operator.feiq_machine_id),
+            Some(ROLE_ADMIN.to_string()),
+        )
+        .await?;
+
+        info!("群主已转让: gid={}, from={}, to={}", gid, operator_uid, new_owner_uid);
         Ok(())
     }
 
@@ -162,6 +397,7 @@ impl GroupService {
     /// # 参数
     /// - `db`: 数据库连接
     /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
     /// - `group_name`: 群组名称（可选）
     /// - `desc`: 群组描述（可选）
     /// - `avatar`: 群组头像（可选）
@@ -171,19 +407,32 @@ impl GroupService {
     pub async fn update_group(
         db: &DbConn,
         gid: i64,
+        operator_uid: i64,
         group_name: Option<String>,
         desc: Option<String>,
         avatar: Option<String>,
     ) -> AppResult<()> {
-        // 1. 更新群组信息
-        GroupHandler::update(db, gid, group_name.clone(), avatar, desc)
+        // 1. 检查权限
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Rename, None, None).await?;
+        let group = GroupHandler::find_by_id(db, gid).await?;
+
+        // 2. 更新群组信息
+        GroupHandler::update(db, gid, group_name.clone(), avatar, desc.clone())
             .await
             .map_err(|e| {
                 error!("更新群组信息失败: gid={}, error={}", gid, e);
                 e
             })?;
 
-        info!("群组信息已更新: gid={}", gid);
+        // 3. 记录改名和修改描述操作并同步
+        if let Some(name) = group_name.filter(|name| *name != group.group_name) {
+            GroupSyncService::record(db, gid, operator_uid, GroupOpKind::Rename, None, Some(name)).await?;
+        }
+        if let Some(desc) = desc.filter(|desc| Some(desc) != group.description.as_ref()) {
+            GroupSyncService::record(db, gid, operator_uid, GroupOpKind::Describe, None, Some(desc)).await?;
+        }
+
+        info!("群组信息已更新: gid={}, operator={}", gid, operator_uid);
         Ok(())
     }
 
@@ -192,11 +441,16 @@ impl GroupService {
     /// # 参数
     /// - `db`: 数据库连接
     /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（必须是群主）
     ///
     /// # 返回
     /// 返回操作结果
-    pub async fn delete_group(db: &DbConn, gid: i64) -> AppResult<()> {
-        // 1. 删除群组
+    pub async fn delete_group(db: &DbConn, gid: i64, operator_uid: i64) -> AppResult<()> {
+        // 1. 检查权限
+        let operator_role = Self::role_of(db, gid, operator_uid).await?;
+        permission::check_delete(operator_role).map_err(|reason| AppError::Permission(reason.to_string()))?;
+
+        // 2. 删除群组
         GroupHandler::delete(db, gid)
             .await
             .map_err(|e| {
@@ -204,7 +458,603 @@ impl GroupService {
                 e
             })?;
 
-        info!("群组已删除: gid={}", gid);
+        // 3. 删除群操作记录和群公告
+        GroupOpHandler::delete_by_group(db, gid).await?;
+        GroupAnnouncementHandler::delete_by_group(db, gid).await?;
+
+        info!("群组已删除: gid={}, operator={}", gid, operator_uid);
+        Ok(())
+    }
+
+    /// 邀请成员加入群组
+    ///
+    /// 支持群扩展的用户收到群邀请，接受后才加入群组；
+    /// 在线但不支持群扩展的客户端无法应答邀请，直接加入群组。
+    /// 离线用户的邀请在对方上线时发送
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `member_uid`: 被邀请的用户ID
+    ///
+    /// # 返回
+    /// 发出的群邀请（直接加入时为 None）
+    pub async fn invite_member(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        member_uid: i64,
+    ) -> AppResult<Option<group_request::Model>> {
+        // 1. 检查权限
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Invite, Some(member_uid), None).await?;
+        if Self::role_of(db, gid, member_uid).await?.is_some() {
+            return Err(AppError::AlreadyExists(format!("用户 {} 已在群组 {} 中", member_uid, gid)));
+        }
+
+        // 2. 不支持群扩展的客户端直接加入
+        let member = UserHandler::find_by_id(db, member_uid).await?;
+        if member.status == 1 && !peer_supports(&member.feiq_ip, FEIQ_CAP_GROUP) {
+            Self::add_member(db, gid, operator_uid, member_uid, member.nickname).await?;
+            return Ok(None);
+        }
+
+        // 3. 已有未过期的邀请时重新发送
+        let now = chrono::Utc::now().naive_utc();
+        if let Some(pending) = GroupRequestHandler::find_pending_invite(db, gid, member_uid).await? {
+            if !is_expired(pending.expire_time, now) {
+                Self::send_request(&member, &pending, "invite").await;
+                return Ok(Some(pending));
+            }
+        }
+
+        // 4. 记录并发送群邀请
+        let group_no = GroupEnvelopeService::ensure_group_no(db, gid).await?;
+        let group = GroupHandler::find_by_id(db, gid).await?;
+        let current_user = UserHandler::get_current_user(db).await?;
+        let request = GroupRequestHandler::create(
+            db,
+            &make_request_no(&current_user.feiq_machine_id, now),
+            REQUEST_KIND_INVITE,
+            REQUEST_OUTGOING,
+            Some(gid),
+            &group_no,
+            &group.group_name,
+            operator_uid,
+            member_uid,
+            now + chrono::Duration::seconds(GROUP_REQUEST_TTL_SECS),
+        )
+        .await?
+        .ok_or_else(|| AppError::AlreadyExists("群邀请".to_string()))?;
+
+        if member.status == 1 {
+            Self::send_request(&member, &request, "invite").await;
+        }
+
+        info!("群邀请已发出: gid={}, member_uid={}, operator={}", gid, member_uid, operator_uid);
+        Ok(Some(request))
+    }
+
+    /// 接受或拒绝收到的群邀请
+    ///
+    /// 接受后由邀请者把本机用户加入群组，群组随之后的群状态同步在本地创建
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `request_id`: 群邀请记录 ID
+    /// - `operator_uid`: 操作者用户ID（必须是被邀请者）
+    /// - `accept`: 是否接受
+    ///
+    /// # 返回
+    /// 处理后的群邀请
+    pub async fn respond_invitation(
+        db: &DbConn,
+        request_id: i64,
+        operator_uid: i64,
+        accept: bool,
+    ) -> AppResult<group_request::Model> {
+        let request = GroupRequestHandler::find_by_id(db, request_id).await?;
+        if request.kind != REQUEST_KIND_INVITE || request.direction != REQUEST_INCOMING {
+            return Err(AppError::Business("不是收到的群邀请".to_string()));
+        }
+        if request.to_uid != operator_uid {
+            return Err(AppError::Permission("只能处理发给自己的群邀请".to_string()));
+        }
+        let request = Self::ensure_pending(db, request).await?;
+
+        let inviter = UserHandler::find_by_id(db, request.from_uid).await?;
+        if accept {
+            if inviter.status != 1 {
+                return Err(AppError::Business("邀请者不在线，暂时无法接受邀请".to_string()));
+            }
+            Self::send_request(&inviter, &request, "accept").await;
+        } else {
+            Self::send_request(&inviter, &request, "decline").await;
+        }
+
+        let status = if accept { REQUEST_ACCEPTED } else { REQUEST_DECLINED };
+        Self::settle_request(db, &request, status, Some(operator_uid)).await
+    }
+
+    /// 申请加入其他用户公开的群组
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `operator_uid`: 申请者用户ID
+    /// - `group_no`: 群编号
+    /// - `group_name`: 群名称
+    /// - `admin_uid`: 公开该群组的管理员用户ID（申请发给该管理员审核）
+    ///
+    /// # 返回
+    /// 发出的入群申请
+    pub async fn request_join(
+        db: &DbConn,
+        operator_uid: i64,
+        group_no: &str,
+        group_name: &str,
+        admin_uid: i64,
+    ) -> AppResult<group_request::Model> {
+        if let Some(group) = GroupHandler::find_by_group_no(db, group_no).await? {
+            if Self::role_of(db, group.gid, operator_uid).await?.is_some() {
+                return Err(AppError::AlreadyExists(format!("已在群组 {} 中", group.group_name)));
+            }
+        }
+
+        let admin = UserHandler::find_by_id(db, admin_uid).await?;
+        if admin.status != 1 || !peer_supports(&admin.feiq_ip, FEIQ_CAP_GROUP) {
+            return Err(AppError::Business("管理员不在线或不支持入群申请".to_string()));
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let current_user = UserHandler::get_current_user(db).await?;
+        let request = GroupRequestHandler::create(
+            db,
+            &make_request_no(&current_user.feiq_machine_id, now),
+            REQUEST_KIND_JOIN,
+            REQUEST_OUTGOING,
+            None,
+            group_no,
+            group_name,
+            operator_uid,
+            admin_uid,
+            now + chrono::Duration::seconds(GROUP_REQUEST_TTL_SECS),
+        )
+        .await?
+        .ok_or_else(|| AppError::AlreadyExists("入群申请".to_string()))?;
+
+        Self::send_request(&admin, &request, "join").await;
+
+        info!("入群申请已发出: group_no={}, admin_uid={}", group_no, admin_uid);
+        Ok(request)
+    }
+
+    /// 审核收到的入群申请
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `request_id`: 入群申请记录 ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `approve`: 是否同意
+    ///
+    /// # 返回
+    /// 处理后的入群申请
+    pub async fn review_join_request(
+        db: &DbConn,
+        request_id: i64,
+        operator_uid: i64,
+        approve: bool,
+    ) -> AppResult<group_request::Model> {
+        let request = GroupRequestHandler::find_by_id(db, request_id).await?;
+        let gid = match request.gid {
+            Some(gid) if request.kind == REQUEST_KIND_JOIN && request.direction == REQUEST_INCOMING => gid,
+            _ => return Err(AppError::Business("不是收到的入群申请".to_string())),
+        };
+        let request = Self::ensure_pending(db, request).await?;
+
+        // 1. 检查权限（与邀请成员相同）
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Invite, Some(request.from_uid), None).await?;
+
+        // 2. 同意时加入群组（其他管理员已同意时不重复添加）
+        if approve && Self::role_of(db, gid, request.from_uid).await?.is_none() {
+            let applicant = UserHandler::find_by_id(db, request.from_uid).await?;
+            Self::add_member(db, gid, operator_uid, request.from_uid, applicant.nickname).await?;
+        }
+
+        // 3. 通知申请者
+        let applicant = UserHandler::find_by_id(db, request.from_uid).await?;
+        Self::send_request(&applicant, &request, if approve { "accept" } else { "decline" }).await;
+
+        let status = if approve { REQUEST_ACCEPTED } else { REQUEST_DECLINED };
+        Self::settle_request(db, &request, status, Some(operator_uid)).await
+    }
+
+    /// 设置群组是否允许申请加入
+    ///
+    /// # 参数
+    /// - `db`: 数据库连接
+    /// - `gid`: 群组ID
+    /// - `operator_uid`: 操作者用户ID（需要群主或管理员权限）
+    /// - `discoverable`: 是否允许申请加入
+    pub async fn set_discoverable(db: &DbConn, gid: i64, operator_uid: i64, discoverable: bool) -> AppResult<()> {
+        // 与修改群信息相同，需要群主或管理员权限
+        Self::authorize(db, gid, operator_uid, GroupOpKind::Describe, None, None).await?;
+        GroupHandler::set_discoverable(db, gid, discoverable).await?;
+
+        info!("群组公开状态已更新: gid={}, discoverable={}", gid, discoverable);
+        Ok(())
+    }
+
+    /// 向所有在线用户查询允许申请加入的群组
+    ///
+    /// 结果通过 `UiEvent::GroupDiscovered` 陆续返回
+    ///
+    /// # 返回
+    /// 发出查询的用户数量
+    pub async fn discover_groups(db: &DbConn) -> AppResult<usize> {
+        let current_user = UserHandler::get_current_user(db).await?;
+        let query = GroupRequest {
+            request_no: make_request_no(&current_user.feiq_machine_id, chrono::Utc::now().naive_utc()),
+            action: "discover".to_string(),
+            group_no: "-".to_string(),
+            expire_at: 0,
+            group_name: String::new(),
+        };
+        let packet = FeiQPacket::make_feiq_group_request_packet(&query, None);
+
+        let mut sent = 0;
+        for user in UserHandler::find_by_status(db, 1).await? {
+            if user.uid == current_user.uid || !peer_supports(&user.feiq_ip, FEIQ_CAP_GROUP) {
+                continue;
+            }
+            let addr = format!("{}:{}", user.feiq_ip, user.feiq_port);
+            match sender::send_packet(&addr, &packet).await {
+                Ok(()) => sent += 1,
+                Err(e) => warn!("发送群组发现请求失败: {}, {}", addr, e),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// 获取待处理的群邀请和入群申请（先把已过期的标记为过期）
+    pub async fn list_requests(db: &DbConn) -> AppResult<Vec<GroupRequestInfo>> {
+        Self::expire_requests(db).await?;
+
+        let mut result = Vec::new();
+        for request in GroupRequestHandler::list_pending(db).await? {
+            result.push(Self::to_request_info(db, request).await);
+        }
+        Ok(result)
+    }
+
+    /// 把已过期的待处理请求标记为过期
+    ///
+    /// # 返回
+    /// 标记为过期的数量
+    pub async fn expire_requests(db: &DbConn) -> AppResult<usize> {
+        let expired = GroupRequestHandler::list_expired(db, chrono::Utc::now().naive_utc()).await?;
+        for request in &expired {
+            Self::settle_request(db, request, REQUEST_EXPIRED, None).await?;
+        }
+        Ok(expired.len())
+    }
+
+    /// 定期把到期的请求标记为过期，界面无需主动刷新即可收到 `GroupRequestUpdated`
+    pub async fn run_request_expiry(db: DbConn) {
+        let mut ticker = tokio::time::interval(REQUEST_EXPIRY_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match Self::expire_requests(&db).await {
+                Ok(0) => {}
+                Ok(count) => info!("群请求已过期: {} 条", count),
+                Err(e) => error!("检查过期群请求失败: {}", e),
+            }
+        }
+    }
+
+    /// 用户上线时发送其离线期间待发的群邀请和入群申请
+    pub async fn resend_pending_requests(db: &DbConn, machine_id: &str) -> AppResult<()> {
+        let user = match UserHandler::find_by_machine_id(db, machine_id).await? {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+        let now = chrono::Utc::now().naive_utc();
+
+        for request in GroupRequestHandler::list_pending_sent_to(db, user.uid).await? {
+            if is_expired(request.expire_time, now) {
+                continue;
+            }
+            let action = if request.kind == REQUEST_KIND_INVITE { "invite" } else { "join" };
+
+            // 对方不支持群扩展，无法应答邀请，直接加入
+            if request.kind == REQUEST_KIND_INVITE && !peer_supports(&user.feiq_ip, FEIQ_CAP_GROUP) {
+                if let Some(gid) = request.gid {
+                    Self::add_member(db, gid, request.from_uid, user.uid, user.nickname.clone()).await?;
+                    Self::settle_request(db, &request, REQUEST_ACCEPTED, Some(request.from_uid)).await?;
+                }
+                continue;
+            }
+            Self::send_request(&user, &request, action).await;
+        }
+
         Ok(())
     }
+
+    /// 处理收到的群邀请、入群申请及其应答
+    ///
+    /// # 参数
+    /// - `sender_ip` / `sender_port`: 发送者地址
+    /// - `packet`: 群邀请/入群申请包内容
+    pub async fn handle_request(db: &DbConn, sender_ip: &str, sender_port: u16, packet: GroupRequest) -> AppResult<()> {
+        let sender_uid = member_uid_for(db, &format!("{}:{}", sender_ip, sender_port)).await?;
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        let now = chrono::Utc::now().naive_utc();
+        let expire_time = chrono::DateTime::from_timestamp(packet.expire_at, 0)
+            .map(|t| t.naive_utc())
+            .unwrap_or(now);
+
+        match packet.action.as_str() {
+            "invite" => {
+                if is_expired(expire_time, now) {
+                    return Ok(());
+                }
+                let gid = match GroupHandler::find_by_group_no(db, &packet.group_no).await? {
+                    Some(group) if Self::role_of(db, group.gid, current_uid).await?.is_some() => {
+                        info!("已在群组中，忽略群邀请: group_no={}", packet.group_no);
+                        return Ok(());
+                    }
+                    Some(group) => Some(group.gid),
+                    None => None,
+                };
+                let created = GroupRequestHandler::create(
+                    db,
+                    &packet.request_no,
+                    REQUEST_KIND_INVITE,
+                    REQUEST_INCOMING,
+                    gid,
+                    &packet.group_no,
+                    &packet.group_name,
+                    sender_uid,
+                    current_uid,
+                    expire_time,
+                )
+                .await?;
+                if let Some(request) = created {
+                    Self::notify_received(db, request).await;
+                }
+            }
+            "join" => {
+                let group = GroupHandler::find_by_group_no(db, &packet.group_no).await?;
+                let reviewable = match &group {
+                    Some(group) if group.discoverable != 0 => {
+                        Self::role_of(db, group.gid, current_uid).await?.unwrap_or(ROLE_MEMBER) >= ROLE_ADMIN
+                    }
+                    _ => false,
+                };
+                let group = match group {
+                    Some(group) if reviewable && !is_expired(expire_time, now) => group,
+                    _ => {
+                        Self::reply(sender_ip, sender_port, &packet, "decline").await;
+                        return Ok(());
+                    }
+                };
+                if Self::role_of(db, group.gid, sender_uid).await?.is_some() {
+                    Self::reply(sender_ip, sender_port, &packet, "accept").await;
+                    return Ok(());
+                }
+
+                let created = GroupRequestHandler::create(
+                    db,
+                    &packet.request_no,
+                    REQUEST_KIND_JOIN,
+                    REQUEST_INCOMING,
+                    Some(group.gid),
+                    &packet.group_no,
+                    &group.group_name,
+                    sender_uid,
+                    current_uid,
+                    expire_time,
+                )
+                .await?;
+                if let Some(request) = created {
+                    Self::notify_received(db, request).await;
+                }
+            }
+            "accept" | "decline" => {
+                let request = match GroupRequestHandler::find_by_request_no(db, &packet.request_no).await? {
+                    Some(request) if request.direction == REQUEST_OUTGOING && request.status == REQUEST_PENDING => {
+                        request
+                    }
+                    _ => return Ok(()),
+                };
+                if request.to_uid != sender_uid {
+                    warn!("忽略非接收者的应答: request_no={}, from={}", packet.request_no, sender_ip);
+                    return Ok(());
+                }
+                if is_expired(request.expire_time, now) {
+                    Self::settle_request(db, &request, REQUEST_EXPIRED, None).await?;
+                    return Ok(());
+                }
+
+                let accepted = packet.action == "accept";
+                // 对方接受邀请后以邀请者身份把对方加入群组
+                if accepted && request.kind == REQUEST_KIND_INVITE {
+                    if let Some(gid) = request.gid {
+                        let invitee = UserHandler::find_by_id(db, request.to_uid).await?;
+                        let added = Self::add_member(db, gid, request.from_uid, invitee.uid, invitee.nickname).await;
+                        if let Err(e) = added {
+                            warn!("接受邀请后加入群组失败: gid={}, {}", gid, e);
+                            return Ok(());
+                        }
+                    }
+                }
+
+                let status = if accepted { REQUEST_ACCEPTED } else { REQUEST_DECLINED };
+                Self::settle_request(db, &request, status, Some(sender_uid)).await?;
+            }
+            "discover" => {
+                for group in GroupHandler::list_discoverable(db).await? {
+                    let role = Self::role_of(db, group.gid, current_uid).await?;
+                    let group_no = match group.group_no {
+                        Some(group_no) if role.unwrap_or(ROLE_MEMBER) >= ROLE_ADMIN => group_no,
+                        _ => continue,
+                    };
+                    let listing = GroupRequest {
+                        request_no: packet.request_no.clone(),
+                        action: "listing".to_string(),
+                        group_no,
+                        expire_at: 0,
+                        group_name: group.group_name,
+                    };
+                    Self::reply(sender_ip, sender_port, &listing, "listing").await;
+                }
+            }
+            "listing" => {
+                let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::GroupDiscovered {
+                    group_no: packet.group_no,
+                    group_name: packet.group_name,
+                    admin_uid: sender_uid,
+                }));
+            }
+            action => warn!("未知的群请求动作: {}", action),
+        }
+
+        Ok(())
+    }
+
+    /// 检查请求仍待处理且未过期（已过期的标记为过期）
+    async fn ensure_pending(db: &DbConn, request: group_request::Model) -> AppResult<group_request::Model> {
+        if request.status != REQUEST_PENDING {
+            return Err(AppError::Business("请求已处理".to_string()));
+        }
+        if is_expired(request.expire_time, chrono::Utc::now().naive_utc()) {
+            Self::settle_request(db, &request, REQUEST_EXPIRED, None).await?;
+            return Err(AppError::Business("请求已过期".to_string()));
+        }
+        Ok(request)
+    }
+
+    /// 更新请求状态并通知界面
+    async fn settle_request(
+        db: &DbConn,
+        request: &group_request::Model,
+        status: i8,
+        operator_uid: Option<i64>,
+    ) -> AppResult<group_request::Model> {
+        let settled = GroupRequestHandler::update_status(db, request.id, status, operator_uid).await?;
+        info!("群请求状态已更新: request_no={}, status={}", settled.request_no, status);
+
+        let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::GroupRequestUpdated {
+            request_id: settled.id,
+            status,
+        }));
+        Ok(settled)
+    }
+
+    /// 通知界面收到新的请求
+    async fn notify_received(db: &DbConn, request: group_request::Model) {
+        let info = Self::to_request_info(db, request).await;
+        info!("收到群请求: group={}, from={}", info.group_name, info.from_nickname);
+
+        match serde_json::to_string(&info) {
+            Ok(request) => {
+                let _ = EVENT_SENDER.send(AppEvent::Ui(UiEvent::GroupRequestReceived { request }));
+            }
+            Err(e) => error!("序列化群请求失败: {}", e),
+        }
+    }
+
+    /// 向请求的另一方发送请求或应答
+    async fn send_request(user: &user::Model, request: &group_request::Model, action: &str) {
+        let packet = GroupRequest {
+            request_no: request.request_no.clone(),
+            action: action.to_string(),
+            group_no: request.group_no.clone(),
+            expire_at: request.expire_time.and_utc().timestamp(),
+            group_name: request.group_name.clone(),
+        };
+        Self::reply(&user.feiq_ip, user.feiq_port, &packet, action).await;
+    }
+
+    /// 按原请求编号发送应答
+    async fn reply(ip: &str, port: u16, request: &GroupRequest, action: &str) {
+        let packet = GroupRequest {
+            action: action.to_string(),
+            ..request.clone()
+        };
+        let packet = FeiQPacket::make_feiq_group_request_packet(&packet, None);
+        let addr = format!("{}:{}", ip, port);
+        if let Err(e) = sender::send_packet(&addr, &packet).await {
+            warn!("发送群请求失败: {}, action={}, {}", addr, action, e);
+        }
+    }
+
+    /// 转换为前端请求信息
+    async fn to_request_info(db: &DbConn, request: group_request::Model) -> GroupRequestInfo {
+        let from_nickname = match UserHandler::find_by_id(db, request.from_uid).await {
+            Ok(user) => user.nickname,
+            _ => format!("User{}", request.from_uid),
+        };
+
+        GroupRequestInfo {
+            id: request.id,
+            kind: request.kind,
+            direction: request.direction,
+            gid: request.gid,
+            group_no: request.group_no,
+            group_name: request.group_name,
+            from_uid: request.from_uid,
+            from_nickname,
+            to_uid: request.to_uid,
+            status: request.status,
+            expire_time: request.expire_time.to_string(),
+            create_time: request.create_time.to_string(),
+        }
+    }
+
+    /// 获取用户在群组中的角色（不是群成员时为 None）
+    async fn role_of(db: &DbConn, gid: i64, uid: i64) -> AppResult<Option<i8>> {
+        Ok(GroupMemberHandler::find_by_group_and_member(db, gid, uid)
+            .await?
+            .map(|member| member.role))
+    }
+
+    /// 按操作者和操作对象在群组中的角色检查权限
+    pub(crate) async fn authorize(
+        db: &DbConn,
+        gid: i64,
+        operator_uid: i64,
+        kind: GroupOpKind,
+        target_uid: Option<i64>,
+        new_role: Option<i8>,
+    ) -> AppResult<()> {
+        let operator_role = Self::role_of(db, gid, operator_uid).await?;
+        let target_role = match target_uid {
+            Some(target_uid) => Self::role_of(db, gid, target_uid).await?,
+            None => None,
+        };
+        let is_self = target_uid == Some(operator_uid);
+
+        permission::check_op(kind, operator_role, is_self, target_role, new_role).map_err(|reason| {
+            info!("群操作被拒绝: gid={}, kind={}, operator={}, {}", gid, kind.as_str(), operator_uid, reason);
+            AppError::Permission(reason.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_no_and_expiry() {
+        let now = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap().naive_utc();
+        let request_no = make_request_no("192.168.1.2:2425", now);
+        assert_eq!(request_no, "192.168.1.2:2425-1700000000000");
+        assert!(!request_no.contains(' '), "Request fields are space separated");
+
+        let expire_time = now + chrono::Duration::seconds(GROUP_REQUEST_TTL_SECS);
+        assert!(!is_expired(expire_time, now));
+        assert!(is_expired(expire_time, expire_time));
+    }
 }
@@ -0,0 +1,1001 @@
+// src-tauri/src/core/group/sync.rs
+//
+//! 群状态同步
+//!
+//! 群组的创建、邀请、加入、离开、移除、改名、修改描述、角色变更和群公告都记为带 Lamport 时钟的群操作，
+//! 各成员保存完整的操作记录。群状态按 (时钟, 来源) 顺序重放操作得到，
+//! 每个操作按重放到该处时的成员角色检查权限，未授权的操作不生效。
+//! 重放顺序只取决于操作集合，各成员以任意顺序收到操作后都得到相同的结果；
+//! 两个管理员同时修改同一项时，来源较大者的修改在后，以它为准。
+//!
+//! 每次变更只向群成员广播新的操作，同步包同时带上发送者完整记录的摘要（操作数和最大时钟）；
+//! 合并后本地摘要与对方不一致时把本地完整记录发给对方，成员重新上线时也补发一次，
+//! 丢包或离线期间错过的操作都能追上。完整记录先压缩（去掉被之后的改名、改描述覆盖的操作），
+//! 再按大小分成多个同步包，不会超出 UDP 数据报的长度限制；本地记录过长时同样压缩保存。
+
+use crate::core::group::envelope::{member_uid_for, UNKNOWN_GROUP_NAME};
+use crate::core::group::permission;
+use crate::core::group::{GroupAnnouncementService, GroupBroadcaster, GroupEnvelopeService};
+use crate::database::handler::group::{GroupHandler, GroupMemberHandler, GroupOpHandler};
+use crate::database::handler::UserHandler;
+use crate::database::model::group_op;
+use crate::error::AppResult;
+use crate::event::bus::EVENT_SENDER;
+use crate::event::model::{AppEvent, ChatEvent};
+use crate::network::feiq::capability::peer_supports;
+use crate::network::feiq::constants::FEIQ_CAP_GROUP;
+use crate::network::feiq::model::{FeiQPacket, GroupSync, GroupSyncOp};
+use crate::network::udp::sender;
+use sea_orm::DbConn;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 成员角色：普通成员
+pub const ROLE_MEMBER: i8 = 0;
+
+/// 成员角色：管理员
+pub const ROLE_ADMIN: i8 = 1;
+
+/// 成员角色：群主
+pub const ROLE_OWNER: i8 = 2;
+
+/// 单个群状态同步包中群操作的最大字节数（远小于 UDP 数据报的长度限制）
+pub const GROUP_SYNC_MAX_BYTES: usize = 16 * 1024;
+
+/// 本地操作记录超过该数量时压缩保存
+pub const GROUP_LOG_COMPACT_THRESHOLD: usize = 256;
+
+/// 向同一成员回发完整记录的最小间隔（完整记录分成多个包时每个包都可能触发回发）
+const SYNC_REPLY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 未知群组的同步包等待收齐的时间
+const PENDING_SYNC_EXPIRE: Duration = Duration::from_secs(60);
+
+/// 群操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupOpKind {
+    /// 创建群组（成员为创建者，值为群名称）
+    Create,
+    /// 邀请成员
+    Invite,
+    /// 成员加入
+    Join,
+    /// 成员离开
+    Leave,
+    /// 移除成员
+    Kick,
+    /// 修改群名称（值为新名称）
+    Rename,
+    /// 修改群描述（值为新描述）
+    Describe,
+    /// 修改成员角色（值为新角色）
+    Role,
+    /// 发布或编辑群公告（成员为公告编号，值为公告内容）
+    Announce,
+    /// 置顶或取消置顶群公告（成员为公告编号，值为 "1" 或 "0"）
+    Pin,
+    /// 确认已读群公告（成员为公告编号）
+    Ack,
+}
+
+impl GroupOpKind {
+    /// 协议中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupOpKind::Create => "create",
+            GroupOpKind::Invite => "invite",
+            GroupOpKind::Join => "join",
+            GroupOpKind::Leave => "leave",
+            GroupOpKind::Kick => "kick",
+            GroupOpKind::Rename => "rename",
+            GroupOpKind::Describe => "describe",
+            GroupOpKind::Role => "role",
+            GroupOpKind::Announce => "announce",
+            GroupOpKind::Pin => "pin",
+            GroupOpKind::Ack => "ack",
+        }
+    }
+
+    /// 根据协议中的名称解析
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "create" => Some(GroupOpKind::Create),
+            "invite" => Some(GroupOpKind::Invite),
+            "join" => Some(GroupOpKind::Join),
+            "leave" => Some(GroupOpKind::Leave),
+            "kick" => Some(GroupOpKind::Kick),
+            "rename" => Some(GroupOpKind::Rename),
+            "describe" => Some(GroupOpKind::Describe),
+            "role" => Some(GroupOpKind::Role),
+            "announce" => Some(GroupOpKind::Announce),
+            "pin" => Some(GroupOpKind::Pin),
+            "ack" => Some(GroupOpKind::Ack),
+            _ => None,
+        }
+    }
+}
+
+/// 合并群操作后得到的群状态
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GroupState {
+    /// 群名称
+    pub name: Option<String>,
+
+    /// 群描述
+    pub description: Option<String>,
+
+    /// 当前成员（机器标识 → 角色）
+    pub members: BTreeMap<String, i8>,
+
+    /// 已离开或被移除的成员
+    pub removed: BTreeSet<String>,
+
+    /// 群公告（公告编号 → 公告）
+    pub announcements: BTreeMap<String, AnnouncementState>,
+}
+
+/// 合并群操作后得到的群公告
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnnouncementState {
+    /// 公告内容（编码后的操作值，见 `announcement::decode_content`）
+    pub content: String,
+
+    /// 发布者机器标识
+    pub author: String,
+
+    /// 最后编辑者机器标识
+    pub editor: String,
+
+    /// 是否置顶
+    pub pinned: bool,
+
+    /// 已确认的成员（公告被编辑后重新确认）
+    pub acks: BTreeSet<String>,
+}
+
+/// 操作涉及的成员（加入/离开未指明成员时为发起者自己，群公告操作为公告编号）
+fn op_member(op: &GroupSyncOp) -> String {
+    op.member.clone().unwrap_or_else(|| op.origin.clone())
+}
+
+/// 按重放到该处时的群状态检查操作是否生效
+fn authorized(state: &GroupState, kind: GroupOpKind, op: &GroupSyncOp) -> bool {
+    if kind == GroupOpKind::Create {
+        // 只有第一个创建操作生效
+        return state.members.is_empty() && state.removed.is_empty();
+    }
+    if matches!(kind, GroupOpKind::Announce | GroupOpKind::Pin | GroupOpKind::Ack) && op.member.is_none() {
+        return false;
+    }
+
+    let member = op_member(op);
+    let new_role = op.value.as_deref().and_then(|v| v.parse().ok());
+    permission::check_op(
+        kind,
+        state.members.get(&op.origin).copied(),
+        member == op.origin,
+        state.members.get(&member).copied(),
+        new_role,
+    )
+    .is_ok()
+}
+
+/// 合并群操作，得到群状态
+///
+/// 结果只取决于操作集合，与操作的顺序和重复无关
+pub fn materialize(ops: &[GroupSyncOp]) -> GroupState {
+    replay(ops).0
+}
+
+/// 压缩群操作
+///
+/// 只去掉被覆盖的操作：早于最后一次生效的改名（改描述）的改名（改描述）操作。
+/// 当前未生效的操作仍然保留，之后收到时钟更早的操作（如迟到的提升角色）时它可能变为生效；
+/// 压缩后的操作重放得到相同的群状态，同一操作集合压缩的结果也相同
+pub fn compact(ops: &[GroupSyncOp]) -> Vec<GroupSyncOp> {
+    let (_, applied) = replay(ops);
+    let last_of = |kind: GroupOpKind| {
+        applied
+            .iter()
+            .rev()
+            .find(|op| op.kind == kind.as_str())
+            .map(|op| (op.clock, op.origin.clone()))
+    };
+    let last_rename = last_of(GroupOpKind::Rename);
+    let last_describe = last_of(GroupOpKind::Describe);
+    let superseded = |op: &GroupSyncOp, last: &Option<(i64, String)>| match last {
+        Some(last) => (op.clock, &op.origin) < (last.0, &last.1),
+        None => false,
+    };
+
+    ordered(ops)
+        .into_iter()
+        .filter(|op| match GroupOpKind::parse(&op.kind) {
+            Some(GroupOpKind::Rename) => !superseded(op, &last_rename),
+            Some(GroupOpKind::Describe) => !superseded(op, &last_describe),
+            Some(_) => true,
+            None => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// 操作记录的摘要：(压缩后的操作数, 最大时钟)
+///
+/// 双方摘要一致时认为记录已经同步
+pub fn digest(ops: &[GroupSyncOp]) -> (usize, i64) {
+    let compacted = compact(ops);
+    let head = compacted.iter().map(|op| op.clock).max().unwrap_or(0);
+    (compacted.len(), head)
+}
+
+/// 把群操作按大小分段，每段放入一个同步包
+pub fn chunk_ops(ops: Vec<GroupSyncOp>, max_bytes: usize) -> Vec<Vec<GroupSyncOp>> {
+    let mut chunks: Vec<Vec<GroupSyncOp>> = Vec::new();
+    let mut chunk = Vec::new();
+    let mut size = 0;
+    for op in ops {
+        // 与同步包中的操作行等长："\n时钟 来源 类型 成员 值"
+        let op_size = 24
+            + op.origin.len()
+            + op.kind.len()
+            + op.member.as_deref().map_or(1, str::len)
+            + op.value.as_deref().map_or(0, str::len);
+        if !chunk.is_empty() && size + op_size > max_bytes {
+            chunks.push(std::mem::take(&mut chunk));
+            size = 0;
+        }
+        size += op_size;
+        chunk.push(op);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// 按 (时钟, 来源) 排序并去重
+fn ordered(ops: &[GroupSyncOp]) -> Vec<&GroupSyncOp> {
+    let mut sorted: Vec<&GroupSyncOp> = ops.iter().collect();
+    // 不同成员生成的种子可能同来源同时钟，按内容排序保证保留哪一个与收到的顺序无关
+    sorted.sort_by(|a, b| {
+        (a.clock, &a.origin, &a.kind, &a.member, &a.value).cmp(&(b.clock, &b.origin, &b.kind, &b.member, &b.value))
+    });
+    sorted.dedup_by(|a, b| (a.clock, &a.origin) == (b.clock, &b.origin));
+    sorted
+}
+
+/// 按 (时钟, 来源) 顺序重放群操作，返回群状态和生效的操作
+fn replay(ops: &[GroupSyncOp]) -> (GroupState, Vec<&GroupSyncOp>) {
+    let mut state = GroupState::default();
+    let mut applied = Vec::new();
+    for op in ordered(ops) {
+        let kind = match GroupOpKind::parse(&op.kind) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        if !authorized(&state, kind, op) {
+            continue;
+        }
+        applied.push(op);
+
+        let member = op_member(op);
+        match kind {
+            GroupOpKind::Create => {
+                state.name = op.value.clone();
+                state.members.insert(member, ROLE_OWNER);
+            }
+            GroupOpKind::Invite | GroupOpKind::Join => {
+                state.removed.remove(&member);
+                state.members.entry(member).or_insert(ROLE_MEMBER);
+            }
+            GroupOpKind::Leave | GroupOpKind::Kick => {
+                state.members.remove(&member);
+                state.removed.insert(member);
+            }
+            GroupOpKind::Rename => state.name = op.value.clone(),
+            GroupOpKind::Describe => state.description = op.value.clone(),
+            GroupOpKind::Role => {
+                if let Some(role) = op.value.as_deref().and_then(|v| v.parse().ok()) {
+                    state.members.insert(member, role);
+                }
+            }
+            GroupOpKind::Announce => {
+                let content = op.value.clone().unwrap_or_default();
+                let announcement = state.announcements.entry(member).or_insert_with(|| AnnouncementState {
+                    author: op.origin.clone(),
+                    ..Default::default()
+                });
+                if announcement.content != content {
+                    announcement.content = content;
+                    announcement.acks.clear();
+                }
+                announcement.editor = op.origin.clone();
+            }
+            GroupOpKind::Pin => {
+                if let Some(announcement) = state.announcements.get_mut(&member) {
+                    announcement.pinned = op.value.as_deref() == Some("1");
+                }
+            }
+            GroupOpKind::Ack => {
+                if let Some(announcement) = state.announcements.get_mut(&member) {
+                    announcement.acks.insert(op.origin.clone());
+                }
+            }
+        }
+    }
+
+    (state, applied)
+}
+
+/// 暂存未知群组的同步包中的操作，收齐对方的完整记录后返回
+///
+/// 完整记录分成多个包发送时，只有收齐之后才能判断本机是否为成员
+fn buffer_unknown_group(
+    group_no: &str,
+    sender: &str,
+    ops: Vec<GroupSyncOp>,
+    expected: (usize, i64),
+) -> Option<Vec<GroupSyncOp>> {
+    type PendingSyncs = HashMap<(String, String), (Instant, Vec<GroupSyncOp>)>;
+    static PENDING: OnceLock<Mutex<PendingSyncs>> = OnceLock::new();
+    let mut pending = PENDING
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    pending.retain(|_, (at, _)| now.duration_since(*at) < PENDING_SYNC_EXPIRE);
+
+    let key = (group_no.to_string(), sender.to_string());
+    let (_, buffered) = pending.entry(key.clone()).or_insert_with(|| (now, Vec::new()));
+    buffered.extend(ops);
+    if digest(buffered) != expected {
+        return None;
+    }
+    pending.remove(&key).map(|(_, ops)| ops)
+}
+
+/// 是否可以向该成员回发完整记录（限制回发频率）
+fn should_reply(gid: i64, machine_id: &str) -> bool {
+    static REPLIED: OnceLock<Mutex<HashMap<(i64, String), Instant>>> = OnceLock::new();
+    let mut replied = REPLIED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let now = Instant::now();
+    let key = (gid, machine_id.to_string());
+    if replied.get(&key).is_some_and(|at| now.duration_since(*at) < SYNC_REPLY_INTERVAL) {
+        return false;
+    }
+    replied.retain(|_, at| now.duration_since(*at) < SYNC_REPLY_INTERVAL);
+    replied.insert(key, now);
+    true
+}
+
+/// 同步之前已有的群名称和成员对应的操作
+///
+/// 操作都以创建者为来源：重放时创建者已是群主，在任何成员上生成的种子都能生效
+fn seed_ops(creator: &str, name: &str, description: Option<&str>, members: &[(String, i8)]) -> Vec<GroupSyncOp> {
+    let mut seeds = vec![(GroupOpKind::Create, Some(creator.to_string()), Some(name.to_string()))];
+    if let Some(description) = description {
+        seeds.push((GroupOpKind::Describe, None, Some(description.to_string())));
+    }
+    for (machine_id, role) in members {
+        seeds.push((GroupOpKind::Invite, Some(machine_id.clone()), None));
+        if *role != ROLE_MEMBER {
+            seeds.push((GroupOpKind::Role, Some(machine_id.clone()), Some(role.to_string())));
+        }
+    }
+
+    (1..)
+        .zip(seeds)
+        .map(|(clock, (kind, member, value))| GroupSyncOp {
+            clock,
+            origin: creator.to_string(),
+            kind: kind.as_str().to_string(),
+            member,
+            value,
+        })
+        .collect()
+}
+
+fn to_sync_op(op: group_op::Model) -> GroupSyncOp {
+    GroupSyncOp {
+        clock: op.clock,
+        origin: op.origin,
+        kind: op.kind,
+        member: op.member,
+        value: op.value,
+    }
+}
+
+/// 群状态同步服务
+pub struct GroupSyncService;
+
+impl GroupSyncService {
+    /// 记录本机发起的群操作，广播群状态后更新本地群组
+    ///
+    /// 先广播再更新本地群组，被移除的成员也能收到移除操作
+    ///
+    /// # 参数
+    /// - `gid`: 群组 ID
+    /// - `actor_uid`: 操作者用户 ID
+    /// - `kind`: 操作类型
+    /// - `member`: 操作涉及的成员机器标识
+    /// - `value`: 操作值（群名称、群描述或角色）
+    pub async fn record(
+        db: &DbConn,
+        gid: i64,
+        actor_uid: i64,
+        kind: GroupOpKind,
+        member: Option<String>,
+        value: Option<String>,
+    ) -> AppResult<()> {
+        let current_user = UserHandler::get_current_user(db).await?;
+        let origin = current_user.feiq_machine_id.clone();
+
+        // 同步之前创建的群组先把已有的名称和成员记为操作
+        if kind != GroupOpKind::Create && GroupOpHandler::max_clock(db, gid).await? == 0 {
+            Self::seed(db, gid, current_user.uid).await?;
+        }
+
+        let clock = GroupOpHandler::max_clock(db, gid).await? + 1;
+        let op = GroupSyncOp {
+            clock,
+            origin: origin.clone(),
+            kind: kind.as_str().to_string(),
+            member: member.clone(),
+            value: value.clone(),
+        };
+        let member_machine_id = member.clone();
+        GroupOpHandler::create(db, gid, clock, &origin, kind.as_str(), member, value, actor_uid).await?;
+        info!(
+            "群操作已记录: gid={}, kind={}, clock={}, actor={}",
+            gid,
+            kind.as_str(),
+            clock,
+            actor_uid
+        );
+
+        let sent_count = Self::broadcast_ops(db, gid, vec![op]).await?;
+        info!("群状态已同步到 {} 个成员", sent_count);
+
+        // 被邀请的成员本地还没有群组，需要完整记录
+        if kind == GroupOpKind::Invite {
+            if let Some(invitee) = member_machine_id {
+                if let Err(e) = Self::send_state_to(db, gid, &invitee).await {
+                    warn!("向被邀请成员发送群状态失败: gid={}, to={}, {}", gid, invitee, e);
+                }
+            }
+        }
+
+        Self::reconcile(db, gid).await?;
+        Self::compact_log(db, gid).await
+    }
+
+    /// 合并其他成员发来的群状态
+    ///
+    /// 本地没有该群组时，只有发送者和本机用户都是成员才会创建群组；
+    /// 本地已有的群组只接受群成员发来的群状态，也不再接受新的创建操作。
+    /// 合并后本地记录的摘要与对方不一致时把本地完整记录发给对方
+    ///
+    /// # 参数
+    /// - `sender_ip` / `sender_port`: 发送者地址
+    /// - `sync`: 群状态同步（群操作可能只是对方记录的一部分）
+    ///
+    /// # 返回
+    /// 本地群组 ID（与本机无关的群组为 None）
+    pub async fn apply_remote(
+        db: &DbConn,
+        sender_ip: &str,
+        sender_port: u16,
+        sync: GroupSync,
+    ) -> AppResult<Option<i64>> {
+        let sender_machine_id = format!("{}:{}", sender_ip, sender_port);
+        let GroupSync {
+            group_no,
+            ops,
+            count,
+            head,
+        } = sync;
+        let group_no = group_no.as_str();
+
+        let (gid, accepts_create, ops) = match GroupHandler::find_by_group_no(db, group_no).await? {
+            Some(group) => {
+                let is_member = match UserHandler::find_by_machine_id(db, &sender_machine_id).await? {
+                    Some(user) => GroupMemberHandler::find_by_group_and_member(db, group.gid, user.uid)
+                        .await?
+                        .is_some(),
+                    None => false,
+                };
+                if !is_member {
+                    warn!("忽略非群成员发来的群状态: gid={}, from={}", group.gid, sender_machine_id);
+                    return Ok(None);
+                }
+                // 同步之前创建的群组还没有操作记录，由对方补记的创建操作仍然接受
+                (group.gid, GroupOpHandler::max_clock(db, group.gid).await? == 0, ops)
+            }
+            None => {
+                let ops = match buffer_unknown_group(group_no, &sender_machine_id, ops, (count, head)) {
+                    Some(ops) => ops,
+                    None => return Ok(None),
+                };
+                let current_user = UserHandler::get_current_user(db).await?;
+                let state = materialize(&ops);
+                if !state.members.contains_key(&current_user.feiq_machine_id) {
+                    info!("忽略与本机无关的群状态: group_no={}", group_no);
+                    return Ok(None);
+                }
+                if !state.members.contains_key(&sender_machine_id) {
+                    warn!("忽略非群成员发来的群状态: group_no={}, from={}", group_no, sender_machine_id);
+                    return Ok(None);
+                }
+
+                let name = state.name.as_deref().unwrap_or(UNKNOWN_GROUP_NAME);
+                let gid =
+                    GroupEnvelopeService::ensure_remote_group(db, group_no, name, &sender_machine_id, &[]).await?;
+                (gid, true, ops)
+            }
+        };
+
+        let mut inserted = 0;
+        for op in &ops {
+            match GroupOpKind::parse(&op.kind) {
+                Some(GroupOpKind::Create) if !accepts_create => {
+                    if GroupOpHandler::find(db, gid, &op.origin, op.clock).await?.is_none() {
+                        warn!("忽略已有群组的创建操作: gid={}, origin={}", gid, op.origin);
+                    }
+                    continue;
+                }
+                Some(_) => {}
+                None => {
+                    warn!("忽略无法识别的群操作: kind={}", op.kind);
+                    continue;
+                }
+            }
+            let actor_uid = member_uid_for(db, &op.origin).await?;
+            let created = GroupOpHandler::create(
+                db,
+                gid,
+                op.clock,
+                &op.origin,
+                &op.kind,
+                op.member.clone(),
+                op.value.clone(),
+                actor_uid,
+            )
+            .await?;
+            if created.is_some() {
+                inserted += 1;
+            }
+        }
+
+        if inserted > 0 {
+            info!("已合并 {} 个群操作: gid={}, from={}", inserted, gid, sender_machine_id);
+            Self::reconcile(db, gid).await?;
+            Self::compact_log(db, gid).await?;
+        }
+
+        // 双方记录不一致时把本地完整记录发给对方；对方有本地没有的操作时，
+        // 对方合并后的摘要也与本地不一致，会把它的完整记录发回来
+        let local = Self::load_ops(db, gid).await?;
+        if digest(&local) != (count, head) && should_reply(gid, &sender_machine_id) {
+            Self::send_state(db, gid, sender_ip, sender_port).await?;
+        }
+
+        Ok(Some(gid))
+    }
+
+    /// 向重新上线的成员补发群状态
+    ///
+    /// 包括对方已离开或被移除的群组，对方离线期间错过的移除操作也能送达
+    ///
+    /// # 返回
+    /// 补发的群组数量
+    pub async fn catch_up_peer(db: &DbConn, machine_id: &str) -> AppResult<usize> {
+        let user = match UserHandler::find_by_machine_id(db, machine_id).await? {
+            Some(user) => user,
+            None => return Ok(0),
+        };
+        if !peer_supports(&user.feiq_ip, FEIQ_CAP_GROUP) {
+            return Ok(0);
+        }
+
+        let mut gids = GroupOpHandler::list_gids_by_member(db, machine_id).await?;
+        for membership in GroupMemberHandler::list_by_member(db, user.uid).await? {
+            if !gids.contains(&membership.gid) {
+                gids.push(membership.gid);
+            }
+        }
+
+        let mut sent = 0;
+        for gid in gids {
+            if GroupOpHandler::max_clock(db, gid).await? == 0 {
+                continue;
+            }
+            match Self::send_state(db, gid, &user.feiq_ip, user.feiq_port).await {
+                Ok(()) => sent += 1,
+                Err(e) => warn!("补发群状态失败: gid={}, to={}, {}", gid, machine_id, e),
+            }
+        }
+
+        if sent > 0 {
+            info!("已向 {} 补发 {} 个群组的状态", machine_id, sent);
+        }
+        Ok(sent)
+    }
+
+    /// 获取群组当前的群状态
+    pub async fn state(db: &DbConn, gid: i64) -> AppResult<GroupState> {
+        Ok(materialize(&Self::load_ops(db, gid).await?))
+    }
+
+    async fn load_ops(db: &DbConn, gid: i64) -> AppResult<Vec<GroupSyncOp>> {
+        let ops = GroupOpHandler::list_by_group(db, gid).await?;
+        Ok(ops.into_iter().map(to_sync_op).collect())
+    }
+
+    /// 把同步之前已有的群名称和成员记为操作
+    async fn seed(db: &DbConn, gid: i64, actor_uid: i64) -> AppResult<()> {
+        let group = GroupHandler::find_by_id(db, gid).await?;
+        let creator = UserHandler::find_by_id(db, group.creator_uid).await?;
+
+        let mut members = Vec::new();
+        for member in GroupMemberHandler::list_by_group(db, gid).await? {
+            if member.member_uid == group.creator_uid {
+                continue;
+            }
+            if let Ok(user) = UserHandler::find_by_id(db, member.member_uid).await {
+                members.push((user.feiq_machine_id, member.role));
+            }
+        }
+
+        let ops = seed_ops(&creator.feiq_machine_id, &group.group_name, group.description.as_deref(), &members);
+        for op in ops {
+            GroupOpHandler::create(db, gid, op.clock, &op.origin, &op.kind, op.member, op.value, actor_uid).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 按合并后的群状态更新本地群组、成员和群公告
+    async fn reconcile(db: &DbConn, gid: i64) -> AppResult<()> {
+        let state = Self::state(db, gid).await?;
+
+        let group = GroupHandler::find_by_id(db, gid).await?;
+        if let Some(name) = state.name.filter(|name| *name != group.group_name) {
+            GroupHandler::update(db, gid, Some(name), None, None).await?;
+        }
+        if state.description.is_some() && state.description != group.description {
+            GroupHandler::update(db, gid, None, None, state.description).await?;
+        }
+
+        for (machine_id, role) in &state.members {
+            let member_uid = member_uid_for(db, machine_id).await?;
+            match GroupMemberHandler::find_by_group_and_member(db, gid, member_uid).await? {
+                Some(member) if member.role == *role => {}
+                Some(_) => GroupMemberHandler::update_role(db, gid, member_uid, *role).await?,
+                None => {
+                    GroupMemberHandler::add_member(db, gid, member_uid, *role).await?;
+                    let _ = EVENT_SENDER.send(AppEvent::Chat(ChatEvent::GroupMemberAdded {
+                        group_id: gid,
+                        user_id: member_uid,
+                    }));
+                }
+            }
+        }
+
+        for machine_id in &state.removed {
+            let user = match UserHandler::find_by_machine_id(db, machine_id).await? {
+                Some(user) => user,
+                None => continue,
+            };
+            if GroupMemberHandler::find_by_group_and_member(db, gid, user.uid).await?.is_some() {
+                GroupMemberHandler::remove_member(db, gid, user.uid).await?;
+                let _ = EVENT_SENDER.send(AppEvent::Chat(ChatEvent::GroupMemberRemoved {
+                    group_id: gid,
+                    user_id: user.uid,
+                }));
+            }
+        }
+
+        GroupAnnouncementService::reconcile(db, gid, &state.announcements).await
+    }
+
+    /// 本地操作记录过长时压缩保存
+    ///
+    /// 只删除被覆盖的操作（见 `compact`）；时钟最大的操作总是保留，本机之后的操作不会复用已用过的时钟
+    async fn compact_log(db: &DbConn, gid: i64) -> AppResult<()> {
+        let stored = GroupOpHandler::list_by_group(db, gid).await?;
+        if stored.len() <= GROUP_LOG_COMPACT_THRESHOLD {
+            return Ok(());
+        }
+
+        let kept: BTreeSet<(i64, String)> = compact(&Self::load_ops(db, gid).await?)
+            .into_iter()
+            .map(|op| (op.clock, op.origin))
+            .collect();
+        let max_clock = stored.iter().map(|op| op.clock).max().unwrap_or(0);
+        let dropped: Vec<i64> = stored
+            .iter()
+            .filter(|op| op.clock != max_clock && !kept.contains(&(op.clock, op.origin.clone())))
+            .map(|op| op.id)
+            .collect();
+        if dropped.is_empty() {
+            return Ok(());
+        }
+
+        GroupOpHandler::delete_by_ids(db, dropped.clone()).await?;
+        info!("群操作记录已压缩: gid={}, {} -> {}", gid, stored.len(), stored.len() - dropped.len());
+        Ok(())
+    }
+
+    /// 构造群状态同步包
+    ///
+    /// 群操作按大小分成多个包，每个包都带上本地完整记录的摘要
+    async fn make_state_packets(db: &DbConn, gid: i64, ops: Vec<GroupSyncOp>) -> AppResult<Vec<FeiQPacket>> {
+        let group_no = GroupEnvelopeService::ensure_group_no(db, gid).await?;
+        let (count, head) = digest(&Self::load_ops(db, gid).await?);
+        Ok(chunk_ops(ops, GROUP_SYNC_MAX_BYTES)
+            .into_iter()
+            .map(|ops| {
+                let sync = GroupSync {
+                    group_no: group_no.clone(),
+                    ops,
+                    count,
+                    head,
+                };
+                FeiQPacket::make_feiq_group_sync_packet(&sync, None)
+            })
+            .collect())
+    }
+
+    /// 向群组所有支持同步的在线成员广播群操作
+    async fn broadcast_ops(db: &DbConn, gid: i64, ops: Vec<GroupSyncOp>) -> AppResult<usize> {
+        let current_uid = UserHandler::get_current_user_id(db).await?;
+        let mut sent_count = 0;
+        for packet in Self::make_state_packets(db, gid, ops).await? {
+            let sent =
+                GroupBroadcaster::broadcast_by_capability(db, gid, FEIQ_CAP_GROUP, &packet, None, current_uid).await?;
+            sent_count = sent_count.max(sent);
+        }
+        Ok(sent_count)
+    }
+
+    /// 向在线且支持同步的成员发送压缩后的完整记录
+    async fn send_state_to(db: &DbConn, gid: i64, machine_id: &str) -> AppResult<()> {
+        let user = match UserHandler::find_by_machine_id(db, machine_id).await? {
+            Some(user) if user.status == 1 && peer_supports(&user.feiq_ip, FEIQ_CAP_GROUP) => user,
+            _ => return Ok(()),
+        };
+        Self::send_state(db, gid, &user.feiq_ip, user.feiq_port).await
+    }
+
+    /// 向指定成员发送压缩后的完整记录
+    async fn send_state(db: &DbConn, gid: i64, ip: &str, port: u16) -> AppResult<()> {
+        let ops = compact(&Self::load_ops(db, gid).await?);
+        let addr = format!("{}:{}", ip, port);
+        for packet in Self::make_state_packets(db, gid, ops).await? {
+            sender::send_packet(&addr, &packet)
+                .await
+                .map_err(|e| crate::error::AppError::Network(format!("发送群状态失败: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn op(clock: i64, origin: &str, kind: GroupOpKind, member: Option<&str>, value: Option<&str>) -> GroupSyncOp {
+        GroupSyncOp {
+            clock,
+            origin: origin.to_string(),
+            kind: kind.as_str().to_string(),
+            member: member.map(|s| s.to_string()),
+            value: value.map(|s| s.to_string()),
+        }
+    }
+
+    const A: &str = "10.0.0.1:2425";
+    const B: &str = "10.0.0.2:2425";
+    const C: &str = "10.0.0.3:2425";
+
+    #[test]
+    fn test_materialize_converges() {
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            op(3, A, GroupOpKind::Role, Some(B), Some("1")),
+            op(4, A, GroupOpKind::Invite, Some(C), None),
+            // A 和 B 同时改名：时钟相同，来源较大者在后
+            op(5, A, GroupOpKind::Rename, None, Some("A 的名字")),
+            op(5, B, GroupOpKind::Rename, None, Some("B 的名字")),
+            // A 把 C 设为管理员的同时 B 移除 C：A 的操作在前，B 无权再移除同级的管理员
+            op(6, B, GroupOpKind::Kick, Some(C), None),
+            op(6, A, GroupOpKind::Role, Some(C), Some("1")),
+        ];
+
+        let state = materialize(&ops);
+        assert_eq!(state.name.as_deref(), Some("B 的名字"));
+        assert_eq!(state.members.get(A), Some(&ROLE_OWNER));
+        assert_eq!(state.members.get(B), Some(&ROLE_ADMIN));
+        assert_eq!(state.members.get(C), Some(&ROLE_ADMIN));
+        assert!(state.removed.is_empty());
+
+        // 任意顺序、重复收到都得到相同的状态
+        let mut reordered: Vec<GroupSyncOp> = ops.iter().rev().cloned().collect();
+        reordered.extend(ops.iter().take(3).cloned());
+        assert_eq!(materialize(&reordered), state);
+    }
+
+    #[test]
+    fn test_unauthorized_ops_ignored() {
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            // 普通成员无权邀请、改名和提升自己
+            op(3, B, GroupOpKind::Invite, Some(C), None),
+            op(4, B, GroupOpKind::Rename, None, Some("改名")),
+            op(5, B, GroupOpKind::Role, Some(B), Some("2")),
+            // 后来的创建操作不生效
+            op(6, C, GroupOpKind::Create, Some(C), Some("另一个群")),
+            // 转让群主后原群主降为管理员
+            op(7, A, GroupOpKind::Role, Some(B), Some("2")),
+            op(8, A, GroupOpKind::Role, Some(A), Some("1")),
+            op(9, A, GroupOpKind::Kick, Some(B), None),
+            // 超出范围或无法解析的角色不生效
+            op(10, B, GroupOpKind::Role, Some(A), Some("9")),
+            op(11, B, GroupOpKind::Role, Some(A), Some("admin")),
+        ];
+
+        let state = materialize(&ops);
+        assert_eq!(state.name.as_deref(), Some("项目组"));
+        assert!(!state.members.contains_key(C));
+        assert_eq!(state.members.get(A), Some(&ROLE_ADMIN));
+        assert_eq!(state.members.get(B), Some(&ROLE_OWNER));
+    }
+
+    #[test]
+    fn test_leave_and_rejoin() {
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            op(3, B, GroupOpKind::Leave, None, None),
+        ];
+        let state = materialize(&ops);
+        assert!(state.removed.contains(B));
+
+        let mut rejoined = ops.clone();
+        rejoined.push(op(4, B, GroupOpKind::Join, None, None));
+        let state = materialize(&rejoined);
+        assert_eq!(state.members.get(B), Some(&ROLE_MEMBER));
+        assert!(state.removed.is_empty());
+
+        assert_eq!(GroupOpKind::parse("kick"), Some(GroupOpKind::Kick));
+        assert_eq!(GroupOpKind::parse("disband"), None);
+    }
+
+    #[test]
+    fn test_announcements() {
+        let no = "10.0.0.1:2425-1700000000000";
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            op(3, A, GroupOpKind::Invite, Some(C), None),
+            op(4, A, GroupOpKind::Announce, Some(no), Some("值班：张三")),
+            op(5, B, GroupOpKind::Ack, Some(no), None),
+            // 普通成员无权编辑和置顶
+            op(6, C, GroupOpKind::Announce, Some(no), Some("改了")),
+            op(6, B, GroupOpKind::Pin, Some(no), Some("1")),
+            op(7, A, GroupOpKind::Pin, Some(no), Some("1")),
+            op(8, C, GroupOpKind::Ack, Some(no), None),
+        ];
+        let state = materialize(&ops);
+        let announcement = &state.announcements[no];
+        assert_eq!(announcement.content, "值班：张三");
+        assert_eq!(announcement.author, A);
+        assert!(announcement.pinned);
+        assert_eq!(announcement.acks, BTreeSet::from([B.to_string(), C.to_string()]));
+
+        // 编辑后需要重新确认
+        let mut edited = ops.clone();
+        edited.push(op(9, A, GroupOpKind::Announce, Some(no), Some("值班：李四")));
+        let state = materialize(&edited);
+        assert_eq!(state.announcements[no].content, "值班：李四");
+        assert!(state.announcements[no].acks.is_empty());
+        assert!(state.announcements[no].pinned);
+    }
+
+    #[test]
+    fn test_compact_and_digest() {
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            op(3, B, GroupOpKind::Rename, None, Some("无权改名")),
+            op(4, A, GroupOpKind::Rename, None, Some("第一次改名")),
+            op(5, A, GroupOpKind::Rename, None, Some("第二次改名")),
+            op(6, A, GroupOpKind::Describe, None, Some("描述")),
+        ];
+
+        let compacted = compact(&ops);
+        let clocks: Vec<i64> = compacted.iter().map(|op| op.clock).collect();
+        assert_eq!(clocks, vec![1, 2, 5, 6], "Superseded renames are dropped");
+        assert_eq!(materialize(&compacted), materialize(&ops));
+        assert_eq!(compact(&compacted), compacted);
+
+        assert_eq!(digest(&ops), (4, 6));
+        assert_eq!(digest(&compacted), digest(&ops));
+        assert_ne!(digest(&ops[..5]), digest(&ops));
+    }
+
+    #[test]
+    fn test_compact_keeps_rejected_ops() {
+        let ops = vec![
+            op(1, A, GroupOpKind::Create, Some(A), Some("项目组")),
+            op(2, A, GroupOpKind::Invite, Some(B), None),
+            // B 还是普通成员时邀请 C，暂不生效
+            op(5, B, GroupOpKind::Invite, Some(C), None),
+        ];
+        let compacted = compact(&ops);
+        assert_eq!(compacted.len(), 3, "Rejected ops may become valid later");
+        assert!(!materialize(&compacted).members.contains_key(C));
+
+        // 迟到的提升角色使邀请生效，压缩过的成员与未压缩的成员结果一致
+        let late = op(3, A, GroupOpKind::Role, Some(B), Some("1"));
+        let mut merged = compacted.clone();
+        merged.push(late.clone());
+        let mut full = ops.clone();
+        full.push(late);
+        assert_eq!(materialize(&merged).members.get(C), Some(&ROLE_MEMBER));
+        assert_eq!(digest(&merged), digest(&full));
+    }
+
+    #[test]
+    fn test_seed_from_non_creator() {
+        // B 不是创建者，在 B 上生成的种子仍以创建者 A 为来源
+        let mut ops = seed_ops(A, "项目组", Some("描述"), &[(B.to_string(), ROLE_ADMIN), (C.to_string(), ROLE_MEMBER)]);
+        assert!(ops.iter().all(|op| op.origin == A));
+
+        let state = materialize(&ops);
+        assert_eq!(state.name.as_deref(), Some("项目组"));
+        assert_eq!(state.description.as_deref(), Some("描述"));
+        assert_eq!(state.members.get(A), Some(&ROLE_OWNER));
+        assert_eq!(state.members.get(B), Some(&ROLE_ADMIN));
+        assert_eq!(state.members.get(C), Some(&ROLE_MEMBER));
+
+        // B 之后记录的操作按管理员权限生效
+        let clock = ops.len() as i64 + 1;
+        ops.push(op(clock, B, GroupOpKind::Rename, None, Some("新名字")));
+        assert_eq!(materialize(&ops).name.as_deref(), Some("新名字"));
+    }
+
+    #[test]
+    fn test_chunk_ops() {
+        let ops: Vec<GroupSyncOp> = (1..=100)
+            .map(|clock| op(clock, A, GroupOpKind::Describe, None, Some(&"描".repeat(100))))
+            .collect();
+
+        let chunks = chunk_ops(ops.clone(), 4 * 1024);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            let packet = FeiQPacket::make_feiq_group_sync_packet(
+                &GroupSync {
+                    group_no: "g".to_string(),
+                    ops: chunk.clone(),
+                    count: 0,
+                    head: 0,
+                },
+                None,
+            );
+            assert!(packet.ext_info.remark.len() <= 4 * 1024 + 16);
+        }
+        assert_eq!(chunks.concat(), ops);
+
+        assert!(chunk_ops(Vec::new(), 4 * 1024).is_empty());
+        assert_eq!(chunk_ops(ops[..1].to_vec(), 1).len(), 1, "An oversized op still gets its own chunk");
+    }
+}
@@ -2,7 +2,11 @@
 //
 //! 聊天消息和会话 CRUD 操作
 
-use crate::database::model::{chat_message, chat_session, ChatMessage, ChatSession};
+use crate::database::model::{
+    chat_message, chat_message_mention, chat_message_revision, chat_session, group_message_receipt, group_poll,
+    group_poll_vote, message_reaction, pending_revision, ChatMessage, ChatMessageMention, ChatMessageRevision,
+    ChatSession, GroupMessageReceipt, GroupPoll, GroupPollVote, MessageReaction, PendingRevision,
+};
 use crate::error::{AppError, AppResult};
 use sea_orm::{prelude::*, *};
 
@@ -43,6 +47,10 @@ impl ChatMessageHandler {
             send_time: ActiveValue::Set(now),
             status: ActiveValue::Set(0), // 0 = 发送中
             msg_no: ActiveValue::Set(msg_no),
+            edit_state: ActiveValue::Set(0),
+            reply_to_mid: ActiveValue::Set(None),
+            reply_quote: ActiveValue::Set(None),
+            group_msg_no: ActiveValue::Set(None),
             create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
             update_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
         };
@@ -77,6 +85,171 @@ impl ChatMessageHandler {
         Ok(message)
     }
 
+    /// 根据发送者和消息编号查找消息
+    ///
+    /// 消息编号只在同一发送者内有意义，编号相同时取最新的一条
+    pub async fn find_by_sender_and_msg_no(
+        db: &DbConn,
+        sender_uid: i64,
+        msg_no: &str,
+    ) -> AppResult<Option<chat_message::Model>> {
+        ChatMessage::find()
+            .filter(chat_message::Column::SenderUid.eq(sender_uid))
+            .filter(chat_message::Column::MsgNo.eq(msg_no))
+            .order_by_desc(chat_message::Column::Mid)
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 记录发出消息的消息编号（撤回/编辑时用于对端定位消息）
+    pub async fn set_msg_no(db: &DbConn, mid: i64, msg_no: &str) -> AppResult<()> {
+        let message = Self::find_by_id(db, mid).await?;
+
+        let mut message_update: chat_message::ActiveModel = message.into();
+        message_update.msg_no = ActiveValue::Set(Some(msg_no.to_string()));
+        message_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        message_update.update(db).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 记录群消息的群内消息序号
+    pub async fn set_group_msg_no(db: &DbConn, mid: i64, group_msg_no: i64) -> AppResult<chat_message::Model> {
+        let message = Self::find_by_id(db, mid).await?;
+
+        let mut message_update: chat_message::ActiveModel = message.into();
+        message_update.group_msg_no = ActiveValue::Set(Some(group_msg_no));
+
+        message_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 获取发送者在群内的下一个消息序号
+    pub async fn next_group_msg_no(db: &DbConn, gid: i64, sender_uid: i64) -> AppResult<i64> {
+        let last = ChatMessage::find()
+            .filter(chat_message::Column::SessionType.eq(1))
+            .filter(chat_message::Column::TargetId.eq(gid))
+            .filter(chat_message::Column::SenderUid.eq(sender_uid))
+            .filter(chat_message::Column::GroupMsgNo.is_not_null())
+            .order_by_desc(chat_message::Column::GroupMsgNo)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(last.and_then(|m| m.group_msg_no).unwrap_or(0) + 1)
+    }
+
+    /// 根据群内消息序号查找群消息
+    pub async fn find_by_group_msg_no(
+        db: &DbConn,
+        gid: i64,
+        sender_uid: i64,
+        group_msg_no: i64,
+    ) -> AppResult<Option<chat_message::Model>> {
+        ChatMessage::find()
+            .filter(chat_message::Column::SessionType.eq(1))
+            .filter(chat_message::Column::TargetId.eq(gid))
+            .filter(chat_message::Column::SenderUid.eq(sender_uid))
+            .filter(chat_message::Column::GroupMsgNo.eq(group_msg_no))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 修订消息内容
+    ///
+    /// # 参数
+    /// - `content`: 新内容（撤回时为空）
+    /// - `edit_state`: 修订状态 (1-已编辑, 2-已撤回)
+    pub async fn revise(db: &DbConn, mid: i64, content: String, edit_state: i8) -> AppResult<chat_message::Model> {
+        let message = Self::find_by_id(db, mid).await?;
+
+        let mut message_update: chat_message::ActiveModel = message.into();
+        message_update.content = ActiveValue::Set(content);
+        message_update.edit_state = ActiveValue::Set(edit_state);
+        message_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        message_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 记录引用回复信息
+    ///
+    /// # 参数
+    /// - `reply_to_mid`: 原消息 ID（本地找不到原消息时为 None）
+    /// - `quote`: 引用摘要
+    pub async fn set_reply(
+        db: &DbConn,
+        mid: i64,
+        reply_to_mid: Option<i64>,
+        quote: String,
+    ) -> AppResult<chat_message::Model> {
+        let message = Self::find_by_id(db, mid).await?;
+
+        let mut message_update: chat_message::ActiveModel = message.into();
+        message_update.reply_to_mid = ActiveValue::Set(reply_to_mid);
+        message_update.reply_quote = ActiveValue::Set(Some(quote));
+
+        message_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 查找引用回复的原消息
+    ///
+    /// 只在同一会话中按消息编号查找：消息编号由各发送者各自生成，跨会话查找可能关联到
+    /// 其他会话中编号相同的消息。找不到时调用方只保留引用摘要
+    pub async fn find_reply_target(
+        db: &DbConn,
+        session_type: i8,
+        target_id: i64,
+        msg_no: &str,
+    ) -> AppResult<Option<chat_message::Model>> {
+        if msg_no.is_empty() {
+            return Ok(None);
+        }
+
+        Self::find_in_session(db, session_type, target_id, msg_no).await
+    }
+
+    /// 在会话中根据消息编号查找消息
+    pub async fn find_in_session(
+        db: &DbConn,
+        session_type: i8,
+        target_id: i64,
+        msg_no: &str,
+    ) -> AppResult<Option<chat_message::Model>> {
+        ChatMessage::find()
+            .filter(chat_message::Column::SessionType.eq(session_type))
+            .filter(chat_message::Column::TargetId.eq(target_id))
+            .filter(chat_message::Column::MsgNo.eq(msg_no))
+            .order_by_desc(chat_message::Column::Mid)
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 根据 ID 批量查找消息
+    pub async fn find_by_ids(db: &DbConn, mids: Vec<i64>) -> AppResult<Vec<chat_message::Model>> {
+        if mids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        ChatMessage::find()
+            .filter(chat_message::Column::Mid.is_in(mids))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取回复了指定消息的所有消息（按时间正序）
+    pub async fn find_replies(db: &DbConn, mid: i64) -> AppResult<Vec<chat_message::Model>> {
+        ChatMessage::find()
+            .filter(chat_message::Column::ReplyToMid.eq(mid))
+            .order_by_asc(chat_message::Column::SendTime)
+            .order_by_asc(chat_message::Column::Mid)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
     /// 获取会话的聊天消息
     pub async fn find_by_session(
         db: &DbConn,
@@ -140,8 +313,13 @@ impl ChatMessageHandler {
         Ok(())
     }
 
-    /// 删除消息
+    /// 删除消息（连同修订历史、提及和群消息回执）
     pub async fn delete(db: &DbConn, mid: i64) -> AppResult<()> {
+        ChatMessageRevisionHandler::delete_by_message(db, mid).await?;
+        ChatMessageMentionHandler::delete_by_message(db, mid).await?;
+        GroupMessageReceiptHandler::delete_by_message(db, mid).await?;
+        GroupPollHandler::delete_by_message(db, mid).await?;
+        MessageReactionHandler::delete_by_message(db, mid).await?;
         ChatMessage::delete_by_id(mid)
             .exec(db)
             .await
@@ -200,6 +378,553 @@ impl ChatMessageHandler {
     }
 }
 
+/// 消息修订历史处理器
+pub struct ChatMessageRevisionHandler;
+
+impl ChatMessageRevisionHandler {
+    /// 记录一次编辑或撤回
+    ///
+    /// # 参数
+    /// - `action`: 操作 (1-编辑, 2-撤回)
+    /// - `old_content`: 修订前的内容
+    /// - `new_content`: 修订后的内容（撤回时为 None）
+    /// - `actor_uid`: 操作者用户 ID
+    pub async fn create(
+        db: &DbConn,
+        mid: i64,
+        action: i8,
+        old_content: String,
+        new_content: Option<String>,
+        actor_uid: i64,
+    ) -> AppResult<chat_message_revision::Model> {
+        chat_message_revision::ActiveModel {
+            id: ActiveValue::NotSet,
+            mid: ActiveValue::Set(mid),
+            action: ActiveValue::Set(action),
+            old_content: ActiveValue::Set(old_content),
+            new_content: ActiveValue::Set(new_content),
+            actor_uid: ActiveValue::Set(actor_uid),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        }
+        .insert(db)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// 获取消息的修订历史（按时间正序）
+    pub async fn list_by_message(db: &DbConn, mid: i64) -> AppResult<Vec<chat_message_revision::Model>> {
+        ChatMessageRevision::find()
+            .filter(chat_message_revision::Column::Mid.eq(mid))
+            .order_by_asc(chat_message_revision::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除消息的修订历史
+    pub async fn delete_by_message(db: &DbConn, mid: i64) -> AppResult<()> {
+        ChatMessageRevision::delete_many()
+            .filter(chat_message_revision::Column::Mid.eq(mid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 待补发修订通知处理器
+pub struct PendingRevisionHandler;
+
+impl PendingRevisionHandler {
+    /// 排队一条修订通知（同一对端同一消息的旧通知被覆盖）
+    ///
+    /// # 参数
+    /// - `action`: 操作 (1-编辑, 2-撤回)
+    /// - `content`: 修订后的内容（撤回时为空）
+    /// - `window_secs`: 撤回时限（秒，0 表示不限制）
+    /// - `revise_time`: 修订时间
+    pub async fn upsert(
+        db: &DbConn,
+        target_uid: i64,
+        msg_no: &str,
+        action: i8,
+        content: &str,
+        window_secs: i64,
+        revise_time: DateTime,
+    ) -> AppResult<()> {
+        PendingRevision::delete_many()
+            .filter(pending_revision::Column::TargetUid.eq(target_uid))
+            .filter(pending_revision::Column::MsgNo.eq(msg_no))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        pending_revision::ActiveModel {
+            id: ActiveValue::NotSet,
+            target_uid: ActiveValue::Set(target_uid),
+            msg_no: ActiveValue::Set(msg_no.to_string()),
+            action: ActiveValue::Set(action),
+            content: ActiveValue::Set(content.to_string()),
+            window_secs: ActiveValue::Set(window_secs),
+            revise_time: ActiveValue::Set(revise_time),
+        }
+        .insert(db)
+        .await
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 获取对端的待补发通知（按排队顺序）
+    pub async fn list_by_target(db: &DbConn, target_uid: i64) -> AppResult<Vec<pending_revision::Model>> {
+        PendingRevision::find()
+            .filter(pending_revision::Column::TargetUid.eq(target_uid))
+            .order_by_asc(pending_revision::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除已补发的通知
+    pub async fn delete(db: &DbConn, id: i64) -> AppResult<()> {
+        PendingRevision::delete_by_id(id)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 消息提及处理器
+pub struct ChatMessageMentionHandler;
+
+impl ChatMessageMentionHandler {
+    /// 记录一次提及
+    ///
+    /// # 参数
+    /// - `mentioned_uid`: 被提及的用户 ID（@所有人 时为 None）
+    pub async fn create(
+        db: &DbConn,
+        mid: i64,
+        gid: i64,
+        mentioned_uid: Option<i64>,
+    ) -> AppResult<chat_message_mention::Model> {
+        chat_message_mention::ActiveModel {
+            id: ActiveValue::NotSet,
+            mid: ActiveValue::Set(mid),
+            gid: ActiveValue::Set(gid),
+            mentioned_uid: ActiveValue::Set(mentioned_uid),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        }
+        .insert(db)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// 获取消息中的提及
+    pub async fn list_by_message(db: &DbConn, mid: i64) -> AppResult<Vec<chat_message_mention::Model>> {
+        ChatMessageMention::find()
+            .filter(chat_message_mention::Column::Mid.eq(mid))
+            .order_by_asc(chat_message_mention::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取提及指定用户的记录（包括 @所有人，按时间倒序）
+    pub async fn list_for_user(db: &DbConn, uid: i64) -> AppResult<Vec<chat_message_mention::Model>> {
+        ChatMessageMention::find()
+            .filter(
+                Condition::any()
+                    .add(chat_message_mention::Column::MentionedUid.eq(uid))
+                    .add(chat_message_mention::Column::MentionedUid.is_null()),
+            )
+            .order_by_desc(chat_message_mention::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除消息中的提及
+    pub async fn delete_by_message(db: &DbConn, mid: i64) -> AppResult<()> {
+        ChatMessageMention::delete_many()
+            .filter(chat_message_mention::Column::Mid.eq(mid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 群消息回执处理器
+pub struct GroupMessageReceiptHandler;
+
+impl GroupMessageReceiptHandler {
+    /// 为发出的群消息创建接收成员的回执（已有的不重复创建）
+    pub async fn create_pending(db: &DbConn, mid: i64, gid: i64, member_uids: &[i64]) -> AppResult<()> {
+        let existing: Vec<i64> = Self::list_by_message(db, mid)
+            .await?
+            .into_iter()
+            .map(|receipt| receipt.member_uid)
+            .collect();
+
+        for member_uid in member_uids.iter().filter(|uid| !existing.contains(uid)) {
+            group_message_receipt::ActiveModel {
+                id: ActiveValue::NotSet,
+                mid: ActiveValue::Set(mid),
+                gid: ActiveValue::Set(gid),
+                member_uid: ActiveValue::Set(*member_uid),
+                status: ActiveValue::Set(0),
+                deliver_time: ActiveValue::Set(None),
+                read_time: ActiveValue::Set(None),
+                create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+            }
+            .insert(db)
+            .await
+            .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+
+    /// 获取消息的所有回执
+    pub async fn list_by_message(db: &DbConn, mid: i64) -> AppResult<Vec<group_message_receipt::Model>> {
+        GroupMessageReceipt::find()
+            .filter(group_message_receipt::Column::Mid.eq(mid))
+            .order_by_asc(group_message_receipt::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 更新成员的回执状态（只前进不后退，已读同时视为已送达）
+    ///
+    /// # 参数
+    /// - `status`: 新状态 (1-已送达, 2-已读)
+    ///
+    /// # 返回
+    /// 状态有变化时返回更新后的回执
+    pub async fn advance(
+        db: &DbConn,
+        mid: i64,
+        member_uid: i64,
+        status: i8,
+    ) -> AppResult<Option<group_message_receipt::Model>> {
+        let receipt = GroupMessageReceipt::find()
+            .filter(group_message_receipt::Column::Mid.eq(mid))
+            .filter(group_message_receipt::Column::MemberUid.eq(member_uid))
+            .one(db)
+            .await
+            .map_err(AppError::Database)?;
+        let receipt = match receipt {
+            Some(receipt) if receipt.status < status => receipt,
+            _ => return Ok(None),
+        };
+
+        let now = chrono::Utc::now().naive_utc();
+        let deliver_time = receipt.deliver_time.unwrap_or(now);
+        let mut receipt_update: group_message_receipt::ActiveModel = receipt.into();
+        receipt_update.status = ActiveValue::Set(status);
+        receipt_update.deliver_time = ActiveValue::Set(Some(deliver_time));
+        if status == 2 {
+            receipt_update.read_time = ActiveValue::Set(Some(now));
+        }
+
+        receipt_update.update(db).await.map(Some).map_err(AppError::Database)
+    }
+
+    /// 删除消息的回执
+    pub async fn delete_by_message(db: &DbConn, mid: i64) -> AppResult<()> {
+        GroupMessageReceipt::delete_many()
+            .filter(group_message_receipt::Column::Mid.eq(mid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 群投票处理器
+pub struct GroupPollHandler;
+
+impl GroupPollHandler {
+    /// 记录投票消息中的投票
+    ///
+    /// # 参数
+    /// - `options`: 选项（JSON 字符串数组）
+    /// - `counts`: 每个选项的票数（JSON 数组）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        db: &DbConn,
+        mid: i64,
+        gid: i64,
+        poll_no: &str,
+        creator_uid: i64,
+        question: &str,
+        options: String,
+        multiple: bool,
+        anonymous: bool,
+        deadline: Option<chrono::NaiveDateTime>,
+        counts: String,
+    ) -> AppResult<group_poll::Model> {
+        let now = chrono::Utc::now().naive_utc();
+        group_poll::ActiveModel {
+            id: ActiveValue::NotSet,
+            mid: ActiveValue::Set(mid),
+            gid: ActiveValue::Set(gid),
+            poll_no: ActiveValue::Set(poll_no.to_string()),
+            creator_uid: ActiveValue::Set(creator_uid),
+            question: ActiveValue::Set(question.to_string()),
+            options: ActiveValue::Set(options),
+            multiple: ActiveValue::Set(multiple as i8),
+            anonymous: ActiveValue::Set(anonymous as i8),
+            deadline: ActiveValue::Set(deadline),
+            closed: ActiveValue::Set(0),
+            counts: ActiveValue::Set(counts),
+            voter_count: ActiveValue::Set(0),
+            create_time: ActiveValue::Set(now),
+            update_time: ActiveValue::Set(now),
+        }
+        .insert(db)
+        .await
+        .map_err(AppError::Database)
+    }
+
+    /// 根据 ID 查找投票
+    pub async fn find_by_id(db: &DbConn, id: i64) -> AppResult<group_poll::Model> {
+        GroupPoll::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("投票 {}", id)))
+    }
+
+    /// 根据投票编号查找投票
+    pub async fn find_by_poll_no(db: &DbConn, poll_no: &str) -> AppResult<Option<group_poll::Model>> {
+        GroupPoll::find()
+            .filter(group_poll::Column::PollNo.eq(poll_no))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取多条消息中的投票
+    pub async fn find_by_messages(db: &DbConn, mids: Vec<i64>) -> AppResult<Vec<group_poll::Model>> {
+        if mids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        GroupPoll::find()
+            .filter(group_poll::Column::Mid.is_in(mids))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 更新投票结果
+    ///
+    /// # 参数
+    /// - `counts`: 每个选项的票数（JSON 数组）
+    pub async fn update_result(
+        db: &DbConn,
+        id: i64,
+        counts: String,
+        voter_count: i64,
+        closed: bool,
+    ) -> AppResult<group_poll::Model> {
+        let existing = Self::find_by_id(db, id).await?;
+
+        let mut poll_update: group_poll::ActiveModel = existing.into();
+        poll_update.counts = ActiveValue::Set(counts);
+        poll_update.voter_count = ActiveValue::Set(voter_count);
+        poll_update.closed = ActiveValue::Set(closed as i8);
+        poll_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        poll_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 查找指定创建者的已到截止时间但尚未结束的投票
+    pub async fn list_due(
+        db: &DbConn,
+        creator_uid: i64,
+        now: chrono::NaiveDateTime,
+    ) -> AppResult<Vec<group_poll::Model>> {
+        GroupPoll::find()
+            .filter(group_poll::Column::CreatorUid.eq(creator_uid))
+            .filter(group_poll::Column::Closed.eq(0))
+            .filter(group_poll::Column::Deadline.lte(now))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除消息中的投票和投票记录
+    pub async fn delete_by_message(db: &DbConn, mid: i64) -> AppResult<()> {
+        let polls = GroupPoll::find()
+            .filter(group_poll::Column::Mid.eq(mid))
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+        for poll in polls {
+            GroupPollVoteHandler::delete_by_poll(db, poll.id).await?;
+            GroupPoll::delete_by_id(poll.id)
+                .exec(db)
+                .await
+                .map_err(AppError::Database)?;
+        }
+        Ok(())
+    }
+}
+
+/// 群投票记录处理器
+pub struct GroupPollVoteHandler;
+
+impl GroupPollVoteHandler {
+    /// 记录投票人的选择（重新投票时覆盖之前的选择）
+    ///
+    /// # 参数
+    /// - `choices`: 所选选项序号（逗号分隔）
+    pub async fn upsert(db: &DbConn, poll_id: i64, voter_uid: i64, choices: &str) -> AppResult<()> {
+        let now = chrono::Utc::now().naive_utc();
+        match Self::find(db, poll_id, voter_uid).await? {
+            Some(vote) if vote.choices == choices => {}
+            Some(vote) => {
+                let mut vote_update: group_poll_vote::ActiveModel = vote.into();
+                vote_update.choices = ActiveValue::Set(choices.to_string());
+                vote_update.vote_time = ActiveValue::Set(now);
+                vote_update.update(db).await.map_err(AppError::Database)?;
+            }
+            None => {
+                group_poll_vote::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    poll_id: ActiveValue::Set(poll_id),
+                    voter_uid: ActiveValue::Set(voter_uid),
+                    choices: ActiveValue::Set(choices.to_string()),
+                    vote_time: ActiveValue::Set(now),
+                }
+                .insert(db)
+                .await
+                .map_err(AppError::Database)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 查找投票人的投票记录
+    pub async fn find(db: &DbConn, poll_id: i64, voter_uid: i64) -> AppResult<Option<group_poll_vote::Model>> {
+        GroupPollVote::find()
+            .filter(group_poll_vote::Column::PollId.eq(poll_id))
+            .filter(group_poll_vote::Column::VoterUid.eq(voter_uid))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取投票的所有投票记录
+    pub async fn list_by_poll(db: &DbConn, poll_id: i64) -> AppResult<Vec<group_poll_vote::Model>> {
+        GroupPollVote::find()
+            .filter(group_poll_vote::Column::PollId.eq(poll_id))
+            .order_by_asc(group_poll_vote::Column::VoteTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除投票记录
+    pub async fn delete(db: &DbConn, id: i64) -> AppResult<()> {
+        GroupPollVote::delete_by_id(id)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 删除投票的所有投票记录
+    pub async fn delete_by_poll(db: &DbConn, poll_id: i64) -> AppResult<()> {
+        GroupPollVote::delete_many()
+            .filter(group_poll_vote::Column::PollId.eq(poll_id))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 消息表情回应处理器
+pub struct MessageReactionHandler;
+
+impl MessageReactionHandler {
+    /// 记录回应者的表情（重新回应时覆盖之前的表情）
+    pub async fn upsert(db: &DbConn, mid: i64, reactor_uid: i64, emoji: &str) -> AppResult<()> {
+        let now = chrono::Utc::now().naive_utc();
+        match Self::find(db, mid, reactor_uid).await? {
+            Some(reaction) if reaction.emoji == emoji => {}
+            Some(reaction) => {
+                let mut reaction_update: message_reaction::ActiveModel = reaction.into();
+                reaction_update.emoji = ActiveValue::Set(emoji.to_string());
+                reaction_update.react_time = ActiveValue::Set(now);
+                reaction_update.update(db).await.map_err(AppError::Database)?;
+            }
+            None => {
+                message_reaction::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    mid: ActiveValue::Set(mid),
+                    reactor_uid: ActiveValue::Set(reactor_uid),
+                    emoji: ActiveValue::Set(emoji.to_string()),
+                    react_time: ActiveValue::Set(now),
+                }
+                .insert(db)
+                .await
+                .map_err(AppError::Database)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 查找回应者对消息的回应
+    pub async fn find(db: &DbConn, mid: i64, reactor_uid: i64) -> AppResult<Option<message_reaction::Model>> {
+        MessageReaction::find()
+            .filter(message_reaction::Column::Mid.eq(mid))
+            .filter(message_reaction::Column::ReactorUid.eq(reactor_uid))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 批量获取消息的回应（按回应时间正序）
+    pub async fn list_by_messages(db: &DbConn, mids: Vec<i64>) -> AppResult<Vec<message_reaction::Model>> {
+        if mids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        MessageReaction::find()
+            .filter(message_reaction::Column::Mid.is_in(mids))
+            .order_by_asc(message_reaction::Column::ReactTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除回应者对消息的回应
+    pub async fn delete(db: &DbConn, mid: i64, reactor_uid: i64) -> AppResult<()> {
+        MessageReaction::delete_many()
+            .filter(message_reaction::Column::Mid.eq(mid))
+            .filter(message_reaction::Column::ReactorUid.eq(reactor_uid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 删除消息的所有回应
+    pub async fn delete_by_message(db: &DbConn, mid: i64) -> AppResult<()> {
+        MessageReaction::delete_many()
+            .filter(message_reaction::Column::Mid.eq(mid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
 /// 聊天会话处理器
 pub struct ChatSessionHandler;
 
@@ -224,6 +949,7 @@ impl ChatSessionHandler {
             target_id: ActiveValue::Set(target_id),
             last_msg_id: ActiveValue::Set(None),
             unread_count: ActiveValue::Set(0),
+            mention_count: ActiveValue::Set(0),
             update_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
             create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
         };
@@ -302,12 +1028,25 @@ impl ChatSessionHandler {
         Ok(())
     }
 
-    /// 清空未读消息数
+    /// 增加提及本机用户的未读消息数
+    pub async fn increment_mention(db: &DbConn, sid: i64) -> AppResult<()> {
+        let session = Self::find_by_id(db, sid).await?;
+        let mention_count = session.mention_count;
+
+        let mut session_update: chat_session::ActiveModel = session.into();
+        session_update.mention_count = ActiveValue::Set(mention_count + 1);
+
+        session_update.update(db).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 清空未读消息数（包括提及数）
     pub async fn clear_unread(db: &DbConn, sid: i64) -> AppResult<()> {
         let session = Self::find_by_id(db, sid).await?;
 
         let mut session_update: chat_session::ActiveModel = session.into();
         session_update.unread_count = ActiveValue::Set(0);
+        session_update.mention_count = ActiveValue::Set(0);
         session_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
 
         session_update.update(db).await.map_err(AppError::Database)?;
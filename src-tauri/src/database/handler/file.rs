@@ -19,6 +19,21 @@ impl FileStorageHandler {
         file_size: i64,
         file_type: String,
         uploader_uid: i64,
+    ) -> AppResult<file_storage::Model> {
+        Self::create_with_hash(db, file_name, file_path, file_size, file_type, uploader_uid, None, false).await
+    }
+
+    /// 记录文件（带内容哈希）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_with_hash(
+        db: &DbConn,
+        file_name: String,
+        file_path: String,
+        file_size: i64,
+        file_type: String,
+        uploader_uid: i64,
+        content_hash: Option<String>,
+        received: bool,
     ) -> AppResult<file_storage::Model> {
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         let new_file = file_storage::ActiveModel {
@@ -30,6 +45,11 @@ impl FileStorageHandler {
             uploader_uid: ActiveValue::Set(uploader_uid),
             upload_time: ActiveValue::Set(now),
             create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+            content_hash: ActiveValue::Set(content_hash),
+            received: ActiveValue::Set(received as i8),
+            image_width: ActiveValue::Set(None),
+            image_height: ActiveValue::Set(None),
+            thumbnail_path: ActiveValue::Set(None),
         };
 
         let result = FileStorage::insert(new_file)
@@ -51,6 +71,66 @@ impl FileStorageHandler {
         Ok(file)
     }
 
+    /// 根据内容哈希查找文件
+    pub async fn find_by_hash(db: &DbConn, content_hash: &str) -> AppResult<Vec<file_storage::Model>> {
+        let files = FileStorage::find()
+            .filter(file_storage::Column::ContentHash.eq(content_hash))
+            .order_by_asc(file_storage::Column::Fid)
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(files)
+    }
+
+    /// 查找所有接收到的文件
+    pub async fn list_received(db: &DbConn) -> AppResult<Vec<file_storage::Model>> {
+        let files = FileStorage::find()
+            .filter(file_storage::Column::Received.eq(1))
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(files)
+    }
+
+    /// 更新文件路径
+    pub async fn update_path(db: &DbConn, fid: i64, file_path: String) -> AppResult<()> {
+        let file = Self::find_by_id(db, fid).await?;
+
+        let mut active: file_storage::ActiveModel = file.into();
+        active.file_path = ActiveValue::Set(file_path);
+        active.update(db).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 写入内容哈希（大文件在首次发送时计算）
+    pub async fn set_content_hash(db: &DbConn, fid: i64, content_hash: String) -> AppResult<()> {
+        let file = Self::find_by_id(db, fid).await?;
+
+        let mut active: file_storage::ActiveModel = file.into();
+        active.content_hash = ActiveValue::Set(Some(content_hash));
+        active.update(db).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 更新图片尺寸和缩略图路径
+    pub async fn set_image_info(
+        db: &DbConn,
+        fid: i64,
+        width: i32,
+        height: i32,
+        thumbnail_path: Option<String>,
+    ) -> AppResult<file_storage::Model> {
+        let file = Self::find_by_id(db, fid).await?;
+
+        let mut active: file_storage::ActiveModel = file.into();
+        active.image_width = ActiveValue::Set(Some(width));
+        active.image_height = ActiveValue::Set(Some(height));
+        active.thumbnail_path = ActiveValue::Set(thumbnail_path);
+        active.update(db).await.map_err(AppError::Database)
+    }
+
     /// 根据上传者 ID 查找文件
     pub async fn list_by_uploader(db: &DbConn, uploader_uid: i64) -> AppResult<Vec<file_storage::Model>> {
         let files = FileStorage::find()
@@ -0,0 +1,174 @@
+// src-tauri/src/database/handler/file_policy.rs
+//
+//! 文件接收策略和收到的文件请求 CRUD 操作
+
+use crate::database::model::{file_accept_policy, file_offer, FileAcceptPolicy, FileOffer};
+use crate::error::{AppError, AppResult};
+use sea_orm::{prelude::*, *};
+
+/// 文件接收策略处理器
+pub struct FilePolicyHandler;
+
+impl FilePolicyHandler {
+    /// 添加策略
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        db: &DbConn,
+        name: String,
+        priority: i32,
+        action: i8,
+        contact_tag: Option<String>,
+        extensions: Option<String>,
+        min_size: Option<i64>,
+        max_size: Option<i64>,
+    ) -> AppResult<file_accept_policy::Model> {
+        let new_policy = file_accept_policy::ActiveModel {
+            id: ActiveValue::NotSet,
+            name: ActiveValue::Set(name),
+            priority: ActiveValue::Set(priority),
+            action: ActiveValue::Set(action),
+            contact_tag: ActiveValue::Set(contact_tag),
+            extensions: ActiveValue::Set(extensions),
+            min_size: ActiveValue::Set(min_size),
+            max_size: ActiveValue::Set(max_size),
+            enabled: ActiveValue::Set(1),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+
+        let result = FileAcceptPolicy::insert(new_policy)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Self::find_by_id(db, result.last_insert_id).await
+    }
+
+    /// 根据 ID 查找策略
+    pub async fn find_by_id(db: &DbConn, id: i64) -> AppResult<file_accept_policy::Model> {
+        FileAcceptPolicy::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("接收策略 {} 不存在", id)))
+    }
+
+    /// 获取所有策略（按优先级排序）
+    pub async fn list_all(db: &DbConn) -> AppResult<Vec<file_accept_policy::Model>> {
+        FileAcceptPolicy::find()
+            .order_by_asc(file_accept_policy::Column::Priority)
+            .order_by_asc(file_accept_policy::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取启用的策略（按优先级排序）
+    pub async fn list_enabled(db: &DbConn) -> AppResult<Vec<file_accept_policy::Model>> {
+        FileAcceptPolicy::find()
+            .filter(file_accept_policy::Column::Enabled.eq(1))
+            .order_by_asc(file_accept_policy::Column::Priority)
+            .order_by_asc(file_accept_policy::Column::Id)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 启用或停用策略
+    pub async fn set_enabled(db: &DbConn, id: i64, enabled: bool) -> AppResult<()> {
+        let policy = Self::find_by_id(db, id).await?;
+
+        let mut active: file_accept_policy::ActiveModel = policy.into();
+        active.enabled = ActiveValue::Set(enabled as i8);
+        active.update(db).await.map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 删除策略
+    pub async fn delete(db: &DbConn, id: i64) -> AppResult<()> {
+        FileAcceptPolicy::delete_by_id(id)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 收到的文件请求处理器
+pub struct FileOfferHandler;
+
+impl FileOfferHandler {
+    /// 记录收到的文件
+    pub async fn create(
+        db: &DbConn,
+        packet_no: &str,
+        file_id: i64,
+        from_ip: &str,
+        file_name: String,
+        file_size: i64,
+        quarantine_reason: Option<String>,
+    ) -> AppResult<file_offer::Model> {
+        let new_offer = file_offer::ActiveModel {
+            id: ActiveValue::NotSet,
+            packet_no: ActiveValue::Set(packet_no.to_string()),
+            file_id: ActiveValue::Set(file_id),
+            from_ip: ActiveValue::Set(from_ip.to_string()),
+            file_name: ActiveValue::Set(file_name),
+            file_size: ActiveValue::Set(file_size),
+            decision: ActiveValue::Set(0),
+            reason: ActiveValue::Set(None),
+            quarantine_reason: ActiveValue::Set(quarantine_reason),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+
+        let result = FileOffer::insert(new_offer).exec(db).await.map_err(AppError::Database)?;
+
+        FileOffer::find_by_id(result.last_insert_id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("文件请求记录 {} 不存在", result.last_insert_id)))
+    }
+
+    /// 查找文件请求中的某个文件
+    pub async fn find(db: &DbConn, packet_no: &str, file_id: i64) -> AppResult<Option<file_offer::Model>> {
+        FileOffer::find()
+            .filter(file_offer::Column::PacketNo.eq(packet_no))
+            .filter(file_offer::Column::FileId.eq(file_id))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 更新整个文件请求的处理结果
+    pub async fn set_decision(db: &DbConn, packet_no: &str, decision: i8, reason: Option<String>) -> AppResult<()> {
+        FileOffer::update_many()
+            .col_expr(file_offer::Column::Decision, Expr::value(decision))
+            .col_expr(file_offer::Column::Reason, Expr::value(reason))
+            .filter(file_offer::Column::PacketNo.eq(packet_no))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 标记文件被隔离
+    pub async fn set_quarantine(db: &DbConn, id: i64, reason: String) -> AppResult<()> {
+        FileOffer::update_many()
+            .col_expr(file_offer::Column::QuarantineReason, Expr::value(Some(reason)))
+            .filter(file_offer::Column::Id.eq(id))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 获取被隔离的文件
+    pub async fn list_quarantined(db: &DbConn) -> AppResult<Vec<file_offer::Model>> {
+        FileOffer::find()
+            .filter(file_offer::Column::QuarantineReason.is_not_null())
+            .order_by_desc(file_offer::Column::CreateTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+}
@@ -2,7 +2,10 @@
 //
 //! 群组表 CRUD 操作
 
-use crate::database::model::{group, group_member, Group, GroupMember};
+use crate::database::model::{
+    group, group_announcement, group_announcement_ack, group_member, group_op, group_request, Group, GroupAnnouncement,
+    GroupAnnouncementAck, GroupMember, GroupOp, GroupRequest,
+};
 use crate::error::{AppError, AppResult};
 use sea_orm::*;
 
@@ -23,6 +26,8 @@ impl GroupHandler {
             avatar: ActiveValue::Set(None),
             creator_uid: ActiveValue::Set(creator_uid),
             description: ActiveValue::Set(description),
+            group_no: ActiveValue::Set(None),
+            discoverable: ActiveValue::Set(0),
             create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
             update_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
         };
@@ -30,11 +35,76 @@ impl GroupHandler {
         let result = Group::insert(new_group).exec(db).await.map_err(AppError::Database)?;
 
         // 自动将创建者添加为群主
-        GroupMemberHandler::add_member(db, result.last_insert_id, creator_uid, 1).await?;
+        GroupMemberHandler::add_member(db, result.last_insert_id, creator_uid, 2).await?;
 
         Self::find_by_id(db, result.last_insert_id).await
     }
 
+    /// 创建从其他成员处得知的群组
+    ///
+    /// 与 `create` 不同，不会自动添加成员，成员由调用方按邀请内容添加
+    pub async fn create_remote(
+        db: &DbConn,
+        group_no: &str,
+        group_name: String,
+        creator_uid: i64,
+    ) -> AppResult<group::Model> {
+        let new_group = group::ActiveModel {
+            gid: ActiveValue::NotSet,
+            group_name: ActiveValue::Set(group_name),
+            avatar: ActiveValue::Set(None),
+            creator_uid: ActiveValue::Set(creator_uid),
+            description: ActiveValue::Set(None),
+            group_no: ActiveValue::Set(Some(group_no.to_string())),
+            discoverable: ActiveValue::Set(0),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+            update_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+
+        let result = Group::insert(new_group).exec(db).await.map_err(AppError::Database)?;
+
+        Self::find_by_id(db, result.last_insert_id).await
+    }
+
+    /// 根据群编号查找群组
+    pub async fn find_by_group_no(db: &DbConn, group_no: &str) -> AppResult<Option<group::Model>> {
+        Group::find()
+            .filter(group::Column::GroupNo.eq(group_no))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 设置群编号
+    pub async fn set_group_no(db: &DbConn, gid: i64, group_no: &str) -> AppResult<group::Model> {
+        let existing_group = Self::find_by_id(db, gid).await?;
+
+        let mut group_update: group::ActiveModel = existing_group.into();
+        group_update.group_no = ActiveValue::Set(Some(group_no.to_string()));
+
+        group_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 设置群组是否允许申请加入
+    pub async fn set_discoverable(db: &DbConn, gid: i64, discoverable: bool) -> AppResult<group::Model> {
+        let existing_group = Self::find_by_id(db, gid).await?;
+
+        let mut group_update: group::ActiveModel = existing_group.into();
+        group_update.discoverable = ActiveValue::Set(discoverable as i8);
+        group_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        group_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 获取允许申请加入的群组
+    pub async fn list_discoverable(db: &DbConn) -> AppResult<Vec<group::Model>> {
+        Group::find()
+            .filter(group::Column::Discoverable.eq(1))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
     /// 根据 ID 查找群组
     pub async fn find_by_id(db: &DbConn, gid: i64) -> AppResult<group::Model> {
         let group = Group::find_by_id(gid)
@@ -195,3 +265,414 @@ impl GroupMemberHandler {
         Ok(())
     }
 }
+
+/// 群操作日志处理器
+pub struct GroupOpHandler;
+
+impl GroupOpHandler {
+    /// 记录群操作
+    ///
+    /// 同一来源、同一时钟的操作已存在时不重复记录
+    ///
+    /// # 返回
+    /// 新记录的操作（已存在时为 None）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        db: &DbConn,
+        gid: i64,
+        clock: i64,
+        origin: &str,
+        kind: &str,
+        member: Option<String>,
+        value: Option<String>,
+        actor_uid: i64,
+    ) -> AppResult<Option<group_op::Model>> {
+        if Self::find(db, gid, origin, clock).await?.is_some() {
+            return Ok(None);
+        }
+
+        let new_op = group_op::ActiveModel {
+            id: ActiveValue::NotSet,
+            gid: ActiveValue::Set(gid),
+            clock: ActiveValue::Set(clock),
+            origin: ActiveValue::Set(origin.to_string()),
+            kind: ActiveValue::Set(kind.to_string()),
+            member: ActiveValue::Set(member),
+            value: ActiveValue::Set(value),
+            actor_uid: ActiveValue::Set(actor_uid),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+
+        let result = GroupOp::insert(new_op).exec(db).await.map_err(AppError::Database)?;
+
+        GroupOp::find_by_id(result.last_insert_id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 根据来源和时钟查找群操作
+    pub async fn find(db: &DbConn, gid: i64, origin: &str, clock: i64) -> AppResult<Option<group_op::Model>> {
+        GroupOp::find()
+            .filter(group_op::Column::Gid.eq(gid))
+            .filter(group_op::Column::Origin.eq(origin))
+            .filter(group_op::Column::Clock.eq(clock))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取群组的所有操作（按时钟正序）
+    pub async fn list_by_group(db: &DbConn, gid: i64) -> AppResult<Vec<group_op::Model>> {
+        GroupOp::find()
+            .filter(group_op::Column::Gid.eq(gid))
+            .order_by_asc(group_op::Column::Clock)
+            .order_by_asc(group_op::Column::Origin)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取群组当前的 Lamport 时钟（没有操作时为 0）
+    pub async fn max_clock(db: &DbConn, gid: i64) -> AppResult<i64> {
+        let last = GroupOp::find()
+            .filter(group_op::Column::Gid.eq(gid))
+            .order_by_desc(group_op::Column::Clock)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(last.map(|op| op.clock).unwrap_or(0))
+    }
+
+    /// 获取操作中涉及某成员的所有群组 ID（包括已离开或被移除的群组）
+    pub async fn list_gids_by_member(db: &DbConn, member: &str) -> AppResult<Vec<i64>> {
+        let ops = GroupOp::find()
+            .filter(group_op::Column::Member.eq(member))
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        let mut gids: Vec<i64> = ops.into_iter().map(|op| op.gid).collect();
+        gids.sort_unstable();
+        gids.dedup();
+        Ok(gids)
+    }
+
+    /// 删除群组的所有操作
+    pub async fn delete_by_group(db: &DbConn, gid: i64) -> AppResult<()> {
+        GroupOp::delete_many()
+            .filter(group_op::Column::Gid.eq(gid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 删除指定的群操作（压缩操作记录时使用）
+    pub async fn delete_by_ids(db: &DbConn, ids: Vec<i64>) -> AppResult<()> {
+        GroupOp::delete_many()
+            .filter(group_op::Column::Id.is_in(ids))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 群邀请/入群申请处理器
+pub struct GroupRequestHandler;
+
+impl GroupRequestHandler {
+    /// 记录群邀请或入群申请
+    ///
+    /// 同一请求编号已存在时不重复记录
+    ///
+    /// # 返回
+    /// 新记录（已存在时为 None）
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        db: &DbConn,
+        request_no: &str,
+        kind: i8,
+        direction: i8,
+        gid: Option<i64>,
+        group_no: &str,
+        group_name: &str,
+        from_uid: i64,
+        to_uid: i64,
+        expire_time: chrono::NaiveDateTime,
+    ) -> AppResult<Option<group_request::Model>> {
+        if Self::find_by_request_no(db, request_no).await?.is_some() {
+            return Ok(None);
+        }
+
+        let now = chrono::Utc::now().naive_utc();
+        let new_request = group_request::ActiveModel {
+            id: ActiveValue::NotSet,
+            request_no: ActiveValue::Set(request_no.to_string()),
+            kind: ActiveValue::Set(kind),
+            direction: ActiveValue::Set(direction),
+            gid: ActiveValue::Set(gid),
+            group_no: ActiveValue::Set(group_no.to_string()),
+            group_name: ActiveValue::Set(group_name.to_string()),
+            from_uid: ActiveValue::Set(from_uid),
+            to_uid: ActiveValue::Set(to_uid),
+            status: ActiveValue::Set(0),
+            operator_uid: ActiveValue::Set(None),
+            expire_time: ActiveValue::Set(expire_time),
+            create_time: ActiveValue::Set(now),
+            update_time: ActiveValue::Set(now),
+        };
+
+        let result = GroupRequest::insert(new_request)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Self::find_by_id(db, result.last_insert_id).await.map(Some)
+    }
+
+    /// 根据 ID 查找请求
+    pub async fn find_by_id(db: &DbConn, id: i64) -> AppResult<group_request::Model> {
+        GroupRequest::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("群请求 {}", id)))
+    }
+
+    /// 根据请求编号查找请求
+    pub async fn find_by_request_no(db: &DbConn, request_no: &str) -> AppResult<Option<group_request::Model>> {
+        GroupRequest::find()
+            .filter(group_request::Column::RequestNo.eq(request_no))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 查找发给某用户、仍待处理的群邀请
+    pub async fn find_pending_invite(db: &DbConn, gid: i64, to_uid: i64) -> AppResult<Option<group_request::Model>> {
+        GroupRequest::find()
+            .filter(group_request::Column::Kind.eq(0))
+            .filter(group_request::Column::Direction.eq(1))
+            .filter(group_request::Column::Gid.eq(gid))
+            .filter(group_request::Column::ToUid.eq(to_uid))
+            .filter(group_request::Column::Status.eq(0))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取待处理的请求（按创建时间倒序）
+    pub async fn list_pending(db: &DbConn) -> AppResult<Vec<group_request::Model>> {
+        GroupRequest::find()
+            .filter(group_request::Column::Status.eq(0))
+            .order_by_desc(group_request::Column::CreateTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取已过期但仍待处理的请求
+    pub async fn list_expired(db: &DbConn, now: chrono::NaiveDateTime) -> AppResult<Vec<group_request::Model>> {
+        GroupRequest::find()
+            .filter(group_request::Column::Status.eq(0))
+            .filter(group_request::Column::ExpireTime.lte(now))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取发给某用户、仍待处理的请求
+    pub async fn list_pending_sent_to(db: &DbConn, to_uid: i64) -> AppResult<Vec<group_request::Model>> {
+        GroupRequest::find()
+            .filter(group_request::Column::Direction.eq(1))
+            .filter(group_request::Column::ToUid.eq(to_uid))
+            .filter(group_request::Column::Status.eq(0))
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 更新请求状态
+    pub async fn update_status(
+        db: &DbConn,
+        id: i64,
+        status: i8,
+        operator_uid: Option<i64>,
+    ) -> AppResult<group_request::Model> {
+        let existing = Self::find_by_id(db, id).await?;
+
+        let mut request_update: group_request::ActiveModel = existing.into();
+        request_update.status = ActiveValue::Set(status);
+        request_update.operator_uid = ActiveValue::Set(operator_uid);
+        request_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        request_update.update(db).await.map_err(AppError::Database)
+    }
+}
+
+/// 群公告处理器
+pub struct GroupAnnouncementHandler;
+
+impl GroupAnnouncementHandler {
+    /// 创建群公告
+    pub async fn create(
+        db: &DbConn,
+        gid: i64,
+        announcement_no: &str,
+        content: &str,
+        author_uid: i64,
+        editor_uid: i64,
+        pinned: bool,
+    ) -> AppResult<group_announcement::Model> {
+        let now = chrono::Utc::now().naive_utc();
+        let new_announcement = group_announcement::ActiveModel {
+            id: ActiveValue::NotSet,
+            gid: ActiveValue::Set(gid),
+            announcement_no: ActiveValue::Set(announcement_no.to_string()),
+            content: ActiveValue::Set(content.to_string()),
+            author_uid: ActiveValue::Set(author_uid),
+            editor_uid: ActiveValue::Set(editor_uid),
+            pinned: ActiveValue::Set(pinned as i8),
+            create_time: ActiveValue::Set(now),
+            update_time: ActiveValue::Set(now),
+        };
+
+        let result = GroupAnnouncement::insert(new_announcement)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Self::find_by_id(db, result.last_insert_id).await
+    }
+
+    /// 根据 ID 查找群公告
+    pub async fn find_by_id(db: &DbConn, id: i64) -> AppResult<group_announcement::Model> {
+        GroupAnnouncement::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("群公告 {}", id)))
+    }
+
+    /// 根据公告编号查找群公告
+    pub async fn find_by_no(
+        db: &DbConn,
+        gid: i64,
+        announcement_no: &str,
+    ) -> AppResult<Option<group_announcement::Model>> {
+        GroupAnnouncement::find()
+            .filter(group_announcement::Column::Gid.eq(gid))
+            .filter(group_announcement::Column::AnnouncementNo.eq(announcement_no))
+            .one(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取群组的公告（置顶在前，按更新时间倒序）
+    pub async fn list_by_group(db: &DbConn, gid: i64) -> AppResult<Vec<group_announcement::Model>> {
+        GroupAnnouncement::find()
+            .filter(group_announcement::Column::Gid.eq(gid))
+            .order_by_desc(group_announcement::Column::Pinned)
+            .order_by_desc(group_announcement::Column::UpdateTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 更新公告内容、编辑者和置顶状态
+    pub async fn update(
+        db: &DbConn,
+        id: i64,
+        content: &str,
+        editor_uid: i64,
+        pinned: bool,
+    ) -> AppResult<group_announcement::Model> {
+        let existing = Self::find_by_id(db, id).await?;
+
+        let mut announcement_update: group_announcement::ActiveModel = existing.into();
+        announcement_update.content = ActiveValue::Set(content.to_string());
+        announcement_update.editor_uid = ActiveValue::Set(editor_uid);
+        announcement_update.pinned = ActiveValue::Set(pinned as i8);
+        announcement_update.update_time = ActiveValue::Set(chrono::Utc::now().naive_utc());
+
+        announcement_update.update(db).await.map_err(AppError::Database)
+    }
+
+    /// 删除群组的所有公告和确认记录
+    pub async fn delete_by_group(db: &DbConn, gid: i64) -> AppResult<()> {
+        for announcement in Self::list_by_group(db, gid).await? {
+            GroupAnnouncementAckHandler::delete_by_announcement(db, announcement.id).await?;
+        }
+        GroupAnnouncement::delete_many()
+            .filter(group_announcement::Column::Gid.eq(gid))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
+
+/// 群公告确认处理器
+pub struct GroupAnnouncementAckHandler;
+
+impl GroupAnnouncementAckHandler {
+    /// 记录成员确认（已确认时不重复记录）
+    pub async fn create(db: &DbConn, announcement_id: i64, member_uid: i64) -> AppResult<()> {
+        let existing = GroupAnnouncementAck::find()
+            .filter(group_announcement_ack::Column::AnnouncementId.eq(announcement_id))
+            .filter(group_announcement_ack::Column::MemberUid.eq(member_uid))
+            .one(db)
+            .await
+            .map_err(AppError::Database)?;
+        if existing.is_some() {
+            return Ok(());
+        }
+
+        let new_ack = group_announcement_ack::ActiveModel {
+            id: ActiveValue::NotSet,
+            announcement_id: ActiveValue::Set(announcement_id),
+            member_uid: ActiveValue::Set(member_uid),
+            ack_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+        GroupAnnouncementAck::insert(new_ack)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 获取公告的所有确认记录
+    pub async fn list_by_announcement(
+        db: &DbConn,
+        announcement_id: i64,
+    ) -> AppResult<Vec<group_announcement_ack::Model>> {
+        GroupAnnouncementAck::find()
+            .filter(group_announcement_ack::Column::AnnouncementId.eq(announcement_id))
+            .order_by_asc(group_announcement_ack::Column::AckTime)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 删除确认记录
+    pub async fn delete(db: &DbConn, id: i64) -> AppResult<()> {
+        GroupAnnouncementAck::delete_by_id(id)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 删除公告的所有确认记录
+    pub async fn delete_by_announcement(db: &DbConn, announcement_id: i64) -> AppResult<()> {
+        GroupAnnouncementAck::delete_many()
+            .filter(group_announcement_ack::Column::AnnouncementId.eq(announcement_id))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+}
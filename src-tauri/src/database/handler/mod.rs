@@ -5,12 +5,21 @@
 pub mod chat;
 pub mod contact;
 pub mod file;
+pub mod file_policy;
 pub mod group;
+pub mod shared_folder;
+pub mod transfer_history;
 pub mod transfer_state;
 pub mod user;
 
-pub use chat::{ChatMessageHandler, ChatSessionHandler};
+pub use chat::{
+    ChatMessageHandler, ChatMessageMentionHandler, ChatMessageRevisionHandler, ChatSessionHandler,
+    GroupMessageReceiptHandler, GroupPollHandler, GroupPollVoteHandler, MessageReactionHandler, PendingRevisionHandler,
+};
 pub use contact::ContactHandler;
 pub use file::FileStorageHandler;
+pub use file_policy::{FileOfferHandler, FilePolicyHandler};
+pub use shared_folder::SharedFolderHandler;
+pub use transfer_history::TransferHistoryHandler;
 pub use transfer_state::TransferStateHandler;
 pub use user::UserHandler;
@@ -0,0 +1,108 @@
+// src-tauri/src/database/handler/shared_folder.rs
+//
+//! 共享文件夹 CRUD 操作
+
+use crate::database::model::{shared_folder, shared_folder_access, SharedFolder, SharedFolderAccess};
+use crate::error::{AppError, AppResult};
+use sea_orm::*;
+
+/// 共享文件夹处理器
+pub struct SharedFolderHandler;
+
+impl SharedFolderHandler {
+    /// 添加共享文件夹
+    pub async fn create(db: &DbConn, name: String, path: String) -> AppResult<shared_folder::Model> {
+        if Self::find_by_name(db, &name).await?.is_some() {
+            return Err(AppError::AlreadyExists(format!("共享名 {}", name)));
+        }
+
+        let new_share = shared_folder::ActiveModel {
+            share_id: ActiveValue::NotSet,
+            name: ActiveValue::Set(name),
+            path: ActiveValue::Set(path),
+            create_time: ActiveValue::Set(chrono::Utc::now().naive_utc()),
+        };
+
+        let result = SharedFolder::insert(new_share).exec(db).await.map_err(AppError::Database)?;
+
+        Self::find_by_id(db, result.last_insert_id).await
+    }
+
+    /// 根据 ID 查找共享
+    pub async fn find_by_id(db: &DbConn, share_id: i64) -> AppResult<shared_folder::Model> {
+        let share = SharedFolder::find_by_id(share_id)
+            .one(db)
+            .await
+            .map_err(AppError::Database)?
+            .ok_or_else(|| AppError::NotFound(format!("共享 {} 不存在", share_id)))?;
+
+        Ok(share)
+    }
+
+    /// 根据共享名查找共享
+    pub async fn find_by_name(db: &DbConn, name: &str) -> AppResult<Option<shared_folder::Model>> {
+        let share = SharedFolder::find()
+            .filter(shared_folder::Column::Name.eq(name))
+            .one(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(share)
+    }
+
+    /// 获取所有共享
+    pub async fn list_all(db: &DbConn) -> AppResult<Vec<shared_folder::Model>> {
+        let shares = SharedFolder::find()
+            .order_by_asc(shared_folder::Column::Name)
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(shares)
+    }
+
+    /// 删除共享及其访问列表
+    pub async fn delete(db: &DbConn, share_id: i64) -> AppResult<()> {
+        SharedFolderAccess::delete_many()
+            .filter(shared_folder_access::Column::ShareId.eq(share_id))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        SharedFolder::delete_by_id(share_id)
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// 获取共享的访问列表（联系人用户 ID）
+    pub async fn list_access(db: &DbConn, share_id: i64) -> AppResult<Vec<i64>> {
+        let access = SharedFolderAccess::find()
+            .filter(shared_folder_access::Column::ShareId.eq(share_id))
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(access.into_iter().map(|a| a.contact_uid).collect())
+    }
+
+    /// 替换共享的访问列表
+    pub async fn set_access(db: &DbConn, share_id: i64, contact_uids: &[i64]) -> AppResult<()> {
+        SharedFolderAccess::delete_many()
+            .filter(shared_folder_access::Column::ShareId.eq(share_id))
+            .exec(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        for contact_uid in contact_uids {
+            let access = shared_folder_access::ActiveModel {
+                id: ActiveValue::NotSet,
+                share_id: ActiveValue::Set(share_id),
+                contact_uid: ActiveValue::Set(*contact_uid),
+            };
+            SharedFolderAccess::insert(access).exec(db).await.map_err(AppError::Database)?;
+        }
+
+        Ok(())
+    }
+}
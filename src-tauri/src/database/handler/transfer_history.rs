@@ -0,0 +1,46 @@
+// src-tauri/src/database/handler/transfer_history.rs
+//
+//! 传输历史 CRUD 操作
+
+use crate::database::model::{transfer_history, TransferHistory};
+use crate::error::{AppError, AppResult};
+use sea_orm::*;
+
+/// 传输历史处理器
+pub struct TransferHistoryHandler;
+
+impl TransferHistoryHandler {
+    /// 记录一次完成的传输
+    pub async fn create(db: &DbConn, model: transfer_history::ActiveModel) -> AppResult<transfer_history::Model> {
+        model.insert(db).await.map_err(AppError::Database)
+    }
+
+    /// 按完成时间倒序获取传输历史
+    ///
+    /// # 参数
+    /// - `peer_uid`: 只返回与该用户之间的传输（None 表示全部）
+    /// - `limit`: 最多返回的条数
+    pub async fn list(db: &DbConn, peer_uid: Option<i64>, limit: u64) -> AppResult<Vec<transfer_history::Model>> {
+        let mut query = TransferHistory::find();
+        if let Some(peer_uid) = peer_uid {
+            query = query.filter(transfer_history::Column::PeerUid.eq(peer_uid));
+        }
+
+        query
+            .order_by_desc(transfer_history::Column::FinishedAt)
+            .order_by_desc(transfer_history::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+
+    /// 获取全部传输历史
+    pub async fn list_all(db: &DbConn) -> AppResult<Vec<transfer_history::Model>> {
+        TransferHistory::find()
+            .order_by_asc(transfer_history::Column::FinishedAt)
+            .all(db)
+            .await
+            .map_err(AppError::Database)
+    }
+}
@@ -59,6 +59,12 @@ impl TransferStateHandler {
         Ok(results)
     }
 
+    /// 根据状态查找传输记录
+    pub async fn find_by_status(db: &DbConn, status: i8) -> Result<Vec<Model>> {
+        let result = Entity::find().filter(Column::Status.eq(status)).all(db).await?;
+        Ok(result)
+    }
+
     /// 更新传输进度
     pub async fn update_progress(db: &DbConn, tid: i64, transferred: i64, status: i8) -> Result<Model> {
         let transfer = Self::find_by_id(db, tid).await?.ok_or(TransferStateError::NotFound(tid))?;
@@ -88,6 +94,44 @@ impl TransferStateHandler {
         Ok(result)
     }
 
+    /// 记录文件校验和
+    pub async fn set_checksum(db: &DbConn, tid: i64, checksum: String) -> Result<Model> {
+        let transfer = Self::find_by_id(db, tid).await?.ok_or(TransferStateError::NotFound(tid))?;
+
+        let mut active: ActiveModel = transfer.into();
+        active.checksum = Set(checksum);
+
+        let result = active.update(db).await?;
+        Ok(result)
+    }
+
+    /// 记录单个传输的限速（字节/秒，0 表示不限）
+    pub async fn set_rate_limit(db: &DbConn, tid: i64, bytes_per_sec: i64) -> Result<Model> {
+        let transfer = Self::find_by_id(db, tid).await?.ok_or(TransferStateError::NotFound(tid))?;
+
+        let mut active: ActiveModel = transfer.into();
+        active.rate_limit = Set(bytes_per_sec);
+
+        let result = active.update(db).await?;
+        Ok(result)
+    }
+
+    /// 查找设置了限速且尚未完成或取消的传输
+    pub async fn find_rate_limited(db: &DbConn) -> Result<Vec<Model>> {
+        let result = Entity::find()
+            .filter(Column::RateLimit.gt(0))
+            .filter(Column::Status.is_not_in([2, -2]))
+            .all(db)
+            .await?;
+        Ok(result)
+    }
+
+    /// 统计引用指定校验和的传输记录
+    pub async fn count_by_checksum(db: &DbConn, checksum: &str) -> Result<u64> {
+        let count = Entity::find().filter(Column::Checksum.eq(checksum)).count(db).await?;
+        Ok(count)
+    }
+
     /// 删除传输记录
     pub async fn delete(db: &DbConn, tid: i64) -> Result<()> {
         Entity::delete_by_id(tid).exec(db).await?;
@@ -95,11 +139,22 @@ impl TransferStateHandler {
     }
 
     /// 清理已完成的传输记录（超过指定天数）
+    ///
+    /// 文件消息（msg_no 即 packet_no）仍引用的传输记录保留，接收文件通过它被引用
     pub async fn cleanup_completed(db: &DbConn, days: i64) -> Result<u64> {
+        use crate::database::model::chat_message;
+
         let cutoff_date = chrono::Utc::now() - chrono::Duration::days(days);
+        let file_messages = sea_query::Query::select()
+            .column(chat_message::Column::MsgNo)
+            .from(chat_message::Entity)
+            .and_where(chat_message::Column::MsgType.eq(1))
+            .and_where(chat_message::Column::MsgNo.is_not_null())
+            .to_owned();
 
         let result = Entity::delete_many()
             .filter(Column::Status.eq(2)) // 已完成
+            .filter(Column::PacketNo.not_in_subquery(file_messages))
             .filter(Column::UpdateTime.lt(cutoff_date.format("%Y-%m-%d %H:%M:%S").to_string()))
             .exec(db)
             .await?;
@@ -52,6 +52,21 @@ impl UserHandler {
         Ok(user)
     }
 
+    /// 根据 IP 查找用户（在线用户在前）
+    ///
+    /// TCP 连接的源端口是临时端口，只能按 IP 对应到用户
+    pub async fn list_by_ip(db: &DbConn, ip: &str) -> AppResult<Vec<user::Model>> {
+        let users = User::find()
+            .filter(user::Column::FeiqIp.eq(ip))
+            .order_by_desc(user::Column::Status)
+            .order_by_asc(user::Column::Uid)
+            .all(db)
+            .await
+            .map_err(AppError::Database)?;
+
+        Ok(users)
+    }
+
     /// 获取当前用户（本地用户）
     ///
     /// 返回数据库中的第一个用户作为当前用户
@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create shared_folder table
+        manager
+            .create_table(
+                Table::create()
+                    .table(SharedFolder::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SharedFolder::ShareId)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SharedFolder::Name).string().not_null().unique_key())
+                    .col(ColumnDef::new(SharedFolder::Path).string().not_null())
+                    .col(ColumnDef::new(SharedFolder::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create shared_folder_access table
+        manager
+            .create_table(
+                Table::create()
+                    .table(SharedFolderAccess::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SharedFolderAccess::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SharedFolderAccess::ShareId).integer().not_null())
+                    .col(ColumnDef::new(SharedFolderAccess::ContactUid).integer().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create unique index on (share_id, contact_uid)
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_shared_folder_access_share_uid")
+                    .table(SharedFolderAccess::Table)
+                    .col(SharedFolderAccess::ShareId)
+                    .col(SharedFolderAccess::ContactUid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_shared_folder_access_share_uid").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(SharedFolderAccess::Table).to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(SharedFolder::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SharedFolder {
+    Table,
+    ShareId,
+    Name,
+    Path,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+enum SharedFolderAccess {
+    Table,
+    Id,
+    ShareId,
+    ContactUid,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 每条 ALTER TABLE 只能添加一列
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .add_column(ColumnDef::new(FileStorage::ContentHash).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .add_column(ColumnDef::new(FileStorage::Received).tiny_integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_file_storage_content_hash")
+                    .table(FileStorage::Table)
+                    .col(FileStorage::ContentHash)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_file_storage_content_hash").to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .drop_column(FileStorage::Received)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .drop_column(FileStorage::ContentHash)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FileStorage {
+    Table,
+    ContentHash,
+    Received,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite 每条 ALTER TABLE 只能添加一列
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .add_column(ColumnDef::new(FileStorage::ImageWidth).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .add_column(ColumnDef::new(FileStorage::ImageHeight).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(FileStorage::Table)
+                    .add_column(ColumnDef::new(FileStorage::ThumbnailPath).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [FileStorage::ThumbnailPath, FileStorage::ImageHeight, FileStorage::ImageWidth] {
+            manager
+                .alter_table(Table::alter().table(FileStorage::Table).drop_column(column).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum FileStorage {
+    Table,
+    ImageWidth,
+    ImageHeight,
+    ThumbnailPath,
+}
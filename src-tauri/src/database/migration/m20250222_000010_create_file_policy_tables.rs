@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create file_accept_policy table
+        manager
+            .create_table(
+                Table::create()
+                    .table(FileAcceptPolicy::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FileAcceptPolicy::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FileAcceptPolicy::Name).string().not_null())
+                    .col(ColumnDef::new(FileAcceptPolicy::Priority).integer().not_null().default(0))
+                    .col(ColumnDef::new(FileAcceptPolicy::Action).tiny_integer().not_null())
+                    .col(ColumnDef::new(FileAcceptPolicy::ContactTag).string())
+                    .col(ColumnDef::new(FileAcceptPolicy::Extensions).string())
+                    .col(ColumnDef::new(FileAcceptPolicy::MinSize).big_integer())
+                    .col(ColumnDef::new(FileAcceptPolicy::MaxSize).big_integer())
+                    .col(ColumnDef::new(FileAcceptPolicy::Enabled).tiny_integer().not_null().default(1))
+                    .col(ColumnDef::new(FileAcceptPolicy::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 默认规则：拒绝可执行文件和脚本
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(FileAcceptPolicy::Table)
+                    .columns([
+                        FileAcceptPolicy::Name,
+                        FileAcceptPolicy::Priority,
+                        FileAcceptPolicy::Action,
+                        FileAcceptPolicy::Extensions,
+                        FileAcceptPolicy::Enabled,
+                        FileAcceptPolicy::CreateTime,
+                    ])
+                    .values_panic([
+                        "拒绝可执行文件和脚本".into(),
+                        0.into(),
+                        2.into(),
+                        "exe,com,scr,msi,bat,cmd,ps1,vbs,vbe,wsf,jar,lnk".into(),
+                        1.into(),
+                        Expr::current_timestamp().into(),
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create file_offer table（收到的文件请求）
+        manager
+            .create_table(
+                Table::create()
+                    .table(FileOffer::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FileOffer::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FileOffer::PacketNo).string().not_null())
+                    .col(ColumnDef::new(FileOffer::FileId).big_integer().not_null())
+                    .col(ColumnDef::new(FileOffer::FromIp).string().not_null())
+                    .col(ColumnDef::new(FileOffer::FileName).string().not_null())
+                    .col(ColumnDef::new(FileOffer::FileSize).big_integer().not_null())
+                    .col(ColumnDef::new(FileOffer::Decision).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(FileOffer::Reason).string())
+                    .col(ColumnDef::new(FileOffer::QuarantineReason).string())
+                    .col(ColumnDef::new(FileOffer::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_file_offer_packet_file")
+                    .table(FileOffer::Table)
+                    .col(FileOffer::PacketNo)
+                    .col(FileOffer::FileId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_file_offer_packet_file").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(FileOffer::Table).to_owned()).await?;
+        manager
+            .drop_table(Table::drop().table(FileAcceptPolicy::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FileAcceptPolicy {
+    Table,
+    Id,
+    Name,
+    Priority,
+    Action,
+    ContactTag,
+    Extensions,
+    MinSize,
+    MaxSize,
+    Enabled,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+enum FileOffer {
+    Table,
+    Id,
+    PacketNo,
+    FileId,
+    FromIp,
+    FileName,
+    FileSize,
+    Decision,
+    Reason,
+    QuarantineReason,
+    CreateTime,
+}
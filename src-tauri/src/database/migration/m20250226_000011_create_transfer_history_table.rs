@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create transfer_history table（已完成传输的统计记录）
+        manager
+            .create_table(
+                Table::create()
+                    .table(TransferHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TransferHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TransferHistory::Tid).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::FileId).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::FileName).string().not_null())
+                    .col(ColumnDef::new(TransferHistory::FileSize).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::Direction).tiny_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::PeerUid).big_integer().not_null().default(0))
+                    .col(ColumnDef::new(TransferHistory::PeerIp).string().not_null())
+                    .col(ColumnDef::new(TransferHistory::Bytes).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::DurationMs).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::AvgSpeed).big_integer().not_null())
+                    .col(ColumnDef::new(TransferHistory::Retries).integer().not_null().default(0))
+                    .col(ColumnDef::new(TransferHistory::StartedAt).timestamp().not_null())
+                    .col(ColumnDef::new(TransferHistory::FinishedAt).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transfer_history_peer")
+                    .table(TransferHistory::Table)
+                    .col(TransferHistory::PeerUid)
+                    .col(TransferHistory::FinishedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_transfer_history_peer").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(TransferHistory::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransferHistory {
+    Table,
+    Id,
+    Tid,
+    FileId,
+    FileName,
+    FileSize,
+    Direction,
+    PeerUid,
+    PeerIp,
+    Bytes,
+    DurationMs,
+    AvgSpeed,
+    Retries,
+    StartedAt,
+    FinishedAt,
+}
@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 消息修订状态 (0-原始, 1-已编辑, 2-已撤回)
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .add_column(ColumnDef::new(ChatMessage::EditState).tiny_integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create chat_message_revision table（消息的编辑/撤回历史）
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatMessageRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChatMessageRevision::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ChatMessageRevision::Mid).big_integer().not_null())
+                    .col(ColumnDef::new(ChatMessageRevision::Action).tiny_integer().not_null())
+                    .col(ColumnDef::new(ChatMessageRevision::OldContent).string().not_null())
+                    .col(ColumnDef::new(ChatMessageRevision::NewContent).string())
+                    .col(ColumnDef::new(ChatMessageRevision::ActorUid).big_integer().not_null())
+                    .col(ColumnDef::new(ChatMessageRevision::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_message_revision_mid")
+                    .table(ChatMessageRevision::Table)
+                    .col(ChatMessageRevision::Mid)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_chat_message_revision_mid").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ChatMessageRevision::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .drop_column(ChatMessage::EditState)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChatMessage {
+    Table,
+    EditState,
+}
+
+#[derive(DeriveIden)]
+enum ChatMessageRevision {
+    Table,
+    Id,
+    Mid,
+    Action,
+    OldContent,
+    NewContent,
+    ActorUid,
+    CreateTime,
+}
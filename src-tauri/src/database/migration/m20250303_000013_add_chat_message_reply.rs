@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 引用回复的原消息 ID（本地找不到原消息时为空）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .add_column(ColumnDef::new(ChatMessage::ReplyToMid).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 引用摘要（原消息不在本地时用于显示上下文）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .add_column(ColumnDef::new(ChatMessage::ReplyQuote).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_message_reply_to")
+                    .table(ChatMessage::Table)
+                    .col(ChatMessage::ReplyToMid)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_chat_message_reply_to").to_owned())
+            .await?;
+        for column in [ChatMessage::ReplyQuote, ChatMessage::ReplyToMid] {
+            manager
+                .alter_table(Table::alter().table(ChatMessage::Table).drop_column(column).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChatMessage {
+    Table,
+    ReplyToMid,
+    ReplyQuote,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 群编号（创建者生成，各端一致，用于在群消息信封中标识群组）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Group::Table)
+                    .add_column(ColumnDef::new(Group::GroupNo).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_group_no")
+                    .table(Group::Table)
+                    .col(Group::GroupNo)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // 群内消息序号（按发送者递增，用于识别重复收到的群消息）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .add_column(ColumnDef::new(ChatMessage::GroupMsgNo).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_message_group_msg_no")
+                    .table(ChatMessage::Table)
+                    .col(ChatMessage::TargetId)
+                    .col(ChatMessage::SenderUid)
+                    .col(ChatMessage::GroupMsgNo)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_chat_message_group_msg_no").to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatMessage::Table)
+                    .drop_column(ChatMessage::GroupMsgNo)
+                    .to_owned(),
+            )
+            .await?;
+        manager.drop_index(Index::drop().name("idx_group_group_no").to_owned()).await?;
+        manager
+            .alter_table(Table::alter().table(Group::Table).drop_column(Group::GroupNo).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Group {
+    Table,
+    GroupNo,
+}
+
+#[derive(DeriveIden)]
+enum ChatMessage {
+    Table,
+    TargetId,
+    SenderUid,
+    GroupMsgNo,
+}
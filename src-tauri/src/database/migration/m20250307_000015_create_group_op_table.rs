@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create group_op table（群操作日志，各端合并后得到一致的群状态）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupOp::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(GroupOp::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(GroupOp::Gid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupOp::Clock).big_integer().not_null())
+                    .col(ColumnDef::new(GroupOp::Origin).string().not_null())
+                    .col(ColumnDef::new(GroupOp::Kind).string().not_null())
+                    .col(ColumnDef::new(GroupOp::Member).string())
+                    .col(ColumnDef::new(GroupOp::Value).string())
+                    .col(ColumnDef::new(GroupOp::ActorUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupOp::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // 同一来源的时钟不会重复，重复收到的操作只保存一次
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_op_gid_origin_clock")
+                    .table(GroupOp::Table)
+                    .col(GroupOp::Gid)
+                    .col(GroupOp::Origin)
+                    .col(GroupOp::Clock)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_group_op_gid_origin_clock").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(GroupOp::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupOp {
+    Table,
+    Id,
+    Gid,
+    Clock,
+    Origin,
+    Kind,
+    Member,
+    Value,
+    ActorUid,
+    CreateTime,
+}
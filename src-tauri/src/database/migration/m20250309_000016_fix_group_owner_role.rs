@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 之前创建群组时创建者被记为管理员（1），改为群主（2）
+        let update = Query::update()
+            .table(GroupMember::Table)
+            .value(GroupMember::Role, 2)
+            .and_where(Expr::col(GroupMember::Role).eq(1))
+            .and_where(
+                Expr::col(GroupMember::MemberUid).in_subquery(
+                    Query::select()
+                        .column(Group::CreatorUid)
+                        .from(Group::Table)
+                        .and_where(Expr::col((Group::Table, Group::Gid)).equals((GroupMember::Table, GroupMember::Gid)))
+                        .to_owned(),
+                ),
+            )
+            .to_owned();
+
+        manager.exec_stmt(update).await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // 无法区分原本的管理员，保留群主角色
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Group {
+    Table,
+    Gid,
+    CreatorUid,
+}
+
+#[derive(DeriveIden)]
+enum GroupMember {
+    Table,
+    Gid,
+    MemberUid,
+    Role,
+}
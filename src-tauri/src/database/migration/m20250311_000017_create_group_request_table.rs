@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 群组是否允许申请加入（本机管理员响应群组发现请求时公开）
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Group::Table)
+                    .add_column(ColumnDef::new(Group::Discoverable).tiny_integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create group_request table（群邀请和入群申请，收发双方各保存一条）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupRequest::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(GroupRequest::Id).integer().not_null().auto_increment().primary_key())
+                    .col(ColumnDef::new(GroupRequest::RequestNo).string().not_null())
+                    .col(ColumnDef::new(GroupRequest::Kind).tiny_integer().not_null())
+                    .col(ColumnDef::new(GroupRequest::Direction).tiny_integer().not_null())
+                    .col(ColumnDef::new(GroupRequest::Gid).big_integer())
+                    .col(ColumnDef::new(GroupRequest::GroupNo).string().not_null())
+                    .col(ColumnDef::new(GroupRequest::GroupName).string().not_null())
+                    .col(ColumnDef::new(GroupRequest::FromUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupRequest::ToUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupRequest::Status).tiny_integer().not_null())
+                    .col(ColumnDef::new(GroupRequest::OperatorUid).big_integer())
+                    .col(ColumnDef::new(GroupRequest::ExpireTime).timestamp().not_null())
+                    .col(ColumnDef::new(GroupRequest::CreateTime).timestamp().not_null())
+                    .col(ColumnDef::new(GroupRequest::UpdateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_request_request_no")
+                    .table(GroupRequest::Table)
+                    .col(GroupRequest::RequestNo)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_request_status")
+                    .table(GroupRequest::Table)
+                    .col(GroupRequest::Status)
+                    .col(GroupRequest::ExpireTime)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_group_request_status").to_owned())
+            .await?;
+        manager
+            .drop_index(Index::drop().name("idx_group_request_request_no").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupRequest::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(Table::alter().table(Group::Table).drop_column(Group::Discoverable).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Group {
+    Table,
+    Discoverable,
+}
+
+#[derive(DeriveIden)]
+enum GroupRequest {
+    Table,
+    Id,
+    RequestNo,
+    Kind,
+    Direction,
+    Gid,
+    GroupNo,
+    GroupName,
+    FromUid,
+    ToUid,
+    Status,
+    OperatorUid,
+    ExpireTime,
+    CreateTime,
+    UpdateTime,
+}
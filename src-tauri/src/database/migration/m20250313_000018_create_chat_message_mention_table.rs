@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create chat_message_mention table（群消息中的 @ 提及，@所有人 时 mentioned_uid 为空）
+        manager
+            .create_table(
+                Table::create()
+                    .table(ChatMessageMention::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ChatMessageMention::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ChatMessageMention::Mid).big_integer().not_null())
+                    .col(ColumnDef::new(ChatMessageMention::Gid).big_integer().not_null())
+                    .col(ColumnDef::new(ChatMessageMention::MentionedUid).big_integer())
+                    .col(ColumnDef::new(ChatMessageMention::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_message_mention_mid")
+                    .table(ChatMessageMention::Table)
+                    .col(ChatMessageMention::Mid)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_chat_message_mention_uid")
+                    .table(ChatMessageMention::Table)
+                    .col(ChatMessageMention::MentionedUid)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 会话中提及本机用户的未读消息数
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatSession::Table)
+                    .add_column(ColumnDef::new(ChatSession::MentionCount).integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ChatSession::Table)
+                    .drop_column(ChatSession::MentionCount)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(Index::drop().name("idx_chat_message_mention_uid").to_owned())
+            .await?;
+        manager
+            .drop_index(Index::drop().name("idx_chat_message_mention_mid").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(ChatMessageMention::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ChatMessageMention {
+    Table,
+    Id,
+    Mid,
+    Gid,
+    MentionedUid,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+enum ChatSession {
+    Table,
+    MentionCount,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create group_message_receipt table（群消息每个接收成员一条，记录送达和已读）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupMessageReceipt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupMessageReceipt::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupMessageReceipt::Mid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupMessageReceipt::Gid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupMessageReceipt::MemberUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupMessageReceipt::Status).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupMessageReceipt::DeliverTime).timestamp())
+                    .col(ColumnDef::new(GroupMessageReceipt::ReadTime).timestamp())
+                    .col(ColumnDef::new(GroupMessageReceipt::CreateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_message_receipt_mid_member")
+                    .table(GroupMessageReceipt::Table)
+                    .col(GroupMessageReceipt::Mid)
+                    .col(GroupMessageReceipt::MemberUid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_group_message_receipt_mid_member").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupMessageReceipt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupMessageReceipt {
+    Table,
+    Id,
+    Mid,
+    Gid,
+    MemberUid,
+    Status,
+    DeliverTime,
+    ReadTime,
+    CreateTime,
+}
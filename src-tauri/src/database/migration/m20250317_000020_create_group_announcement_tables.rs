@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create group_announcement table（按群操作合并出的群公告）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupAnnouncement::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupAnnouncement::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupAnnouncement::Gid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::AnnouncementNo).text().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::Content).text().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::AuthorUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::EditorUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::Pinned).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupAnnouncement::CreateTime).timestamp().not_null())
+                    .col(ColumnDef::new(GroupAnnouncement::UpdateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_announcement_gid_no")
+                    .table(GroupAnnouncement::Table)
+                    .col(GroupAnnouncement::Gid)
+                    .col(GroupAnnouncement::AnnouncementNo)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create group_announcement_ack table（每个确认公告的成员一条）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupAnnouncementAck::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupAnnouncementAck::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupAnnouncementAck::AnnouncementId).big_integer().not_null())
+                    .col(ColumnDef::new(GroupAnnouncementAck::MemberUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupAnnouncementAck::AckTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_announcement_ack_member")
+                    .table(GroupAnnouncementAck::Table)
+                    .col(GroupAnnouncementAck::AnnouncementId)
+                    .col(GroupAnnouncementAck::MemberUid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_group_announcement_ack_member").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupAnnouncementAck::Table).to_owned())
+            .await?;
+        manager
+            .drop_index(Index::drop().name("idx_group_announcement_gid_no").to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(GroupAnnouncement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupAnnouncement {
+    Table,
+    Id,
+    Gid,
+    AnnouncementNo,
+    Content,
+    AuthorUid,
+    EditorUid,
+    Pinned,
+    CreateTime,
+    UpdateTime,
+}
+
+#[derive(DeriveIden)]
+enum GroupAnnouncementAck {
+    Table,
+    Id,
+    AnnouncementId,
+    MemberUid,
+    AckTime,
+}
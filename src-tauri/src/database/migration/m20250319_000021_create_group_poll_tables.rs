@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create group_poll table（群会话中的投票，每条投票消息一条）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupPoll::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupPoll::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupPoll::Mid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupPoll::Gid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupPoll::PollNo).text().not_null().unique_key())
+                    .col(ColumnDef::new(GroupPoll::CreatorUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupPoll::Question).text().not_null())
+                    .col(ColumnDef::new(GroupPoll::Options).text().not_null())
+                    .col(ColumnDef::new(GroupPoll::Multiple).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupPoll::Anonymous).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupPoll::Deadline).timestamp())
+                    .col(ColumnDef::new(GroupPoll::Closed).tiny_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupPoll::Counts).text().not_null())
+                    .col(ColumnDef::new(GroupPoll::VoterCount).big_integer().not_null().default(0))
+                    .col(ColumnDef::new(GroupPoll::CreateTime).timestamp().not_null())
+                    .col(ColumnDef::new(GroupPoll::UpdateTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create group_poll_vote table（每个投票人一条，创建者保存全部投票）
+        manager
+            .create_table(
+                Table::create()
+                    .table(GroupPollVote::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupPollVote::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GroupPollVote::PollId).big_integer().not_null())
+                    .col(ColumnDef::new(GroupPollVote::VoterUid).big_integer().not_null())
+                    .col(ColumnDef::new(GroupPollVote::Choices).text().not_null())
+                    .col(ColumnDef::new(GroupPollVote::VoteTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_group_poll_vote_voter")
+                    .table(GroupPollVote::Table)
+                    .col(GroupPollVote::PollId)
+                    .col(GroupPollVote::VoterUid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_group_poll_vote_voter").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(GroupPollVote::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(GroupPoll::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupPoll {
+    Table,
+    Id,
+    Mid,
+    Gid,
+    PollNo,
+    CreatorUid,
+    Question,
+    Options,
+    Multiple,
+    Anonymous,
+    Deadline,
+    Closed,
+    Counts,
+    VoterCount,
+    CreateTime,
+    UpdateTime,
+}
+
+#[derive(DeriveIden)]
+enum GroupPollVote {
+    Table,
+    Id,
+    PollId,
+    VoterUid,
+    Choices,
+    VoteTime,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create message_reaction table（消息的表情回应，每条消息每个回应者一条）
+        manager
+            .create_table(
+                Table::create()
+                    .table(MessageReaction::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MessageReaction::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MessageReaction::Mid).big_integer().not_null())
+                    .col(ColumnDef::new(MessageReaction::ReactorUid).big_integer().not_null())
+                    .col(ColumnDef::new(MessageReaction::Emoji).text().not_null())
+                    .col(ColumnDef::new(MessageReaction::ReactTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_message_reaction_reactor")
+                    .table(MessageReaction::Table)
+                    .col(MessageReaction::Mid)
+                    .col(MessageReaction::ReactorUid)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_message_reaction_reactor").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(MessageReaction::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MessageReaction {
+    Table,
+    Id,
+    Mid,
+    ReactorUid,
+    Emoji,
+    ReactTime,
+}
@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 单个传输的限速（字节/秒，0 表示不限），随传输记录持久化以便重试/重启后继续生效
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransferState::Table)
+                    .add_column(ColumnDef::new(TransferState::RateLimit).big_integer().not_null().default(0))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TransferState::Table)
+                    .drop_column(TransferState::RateLimit)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TransferState {
+    Table,
+    RateLimit,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create pending_revision table（等待对端上线后补发的撤回/编辑通知，每个对端每条消息一条）
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingRevision::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PendingRevision::TargetUid).big_integer().not_null())
+                    .col(ColumnDef::new(PendingRevision::MsgNo).string().not_null())
+                    .col(ColumnDef::new(PendingRevision::Action).tiny_integer().not_null())
+                    .col(ColumnDef::new(PendingRevision::Content).text().not_null())
+                    .col(ColumnDef::new(PendingRevision::WindowSecs).big_integer().not_null())
+                    .col(ColumnDef::new(PendingRevision::ReviseTime).timestamp().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_pending_revision_target")
+                    .table(PendingRevision::Table)
+                    .col(PendingRevision::TargetUid)
+                    .col(PendingRevision::MsgNo)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_pending_revision_target").to_owned())
+            .await?;
+        manager.drop_table(Table::drop().table(PendingRevision::Table).to_owned()).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingRevision {
+    Table,
+    Id,
+    TargetUid,
+    MsgNo,
+    Action,
+    Content,
+    WindowSecs,
+    ReviseTime,
+}
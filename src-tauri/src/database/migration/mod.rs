@@ -10,6 +10,24 @@ pub mod m20250127_000003_create_group_tables;
 pub mod m20250127_000004_create_chat_tables;
 pub mod m20250127_000005_create_file_storage_table;
 pub mod m20250129_000006_create_transfer_state_table;
+pub mod m20250210_000007_create_shared_folder_tables;
+pub mod m20250215_000008_add_file_storage_content_hash;
+pub mod m20250218_000009_add_file_storage_image_info;
+pub mod m20250222_000010_create_file_policy_tables;
+pub mod m20250226_000011_create_transfer_history_table;
+pub mod m20250301_000012_create_chat_message_revision_table;
+pub mod m20250303_000013_add_chat_message_reply;
+pub mod m20250305_000014_add_group_envelope;
+pub mod m20250307_000015_create_group_op_table;
+pub mod m20250309_000016_fix_group_owner_role;
+pub mod m20250311_000017_create_group_request_table;
+pub mod m20250313_000018_create_chat_message_mention_table;
+pub mod m20250315_000019_create_group_message_receipt_table;
+pub mod m20250317_000020_create_group_announcement_tables;
+pub mod m20250319_000021_create_group_poll_tables;
+pub mod m20250321_000022_create_message_reaction_table;
+pub mod m20250323_000023_add_transfer_state_rate_limit;
+pub mod m20250325_000024_create_pending_revision_table;
 
 use sea_orm_migration::prelude::*;
 
@@ -25,6 +43,24 @@ impl MigratorTrait for Migrator {
             Box::new(m20250127_000004_create_chat_tables::Migration),
             Box::new(m20250127_000005_create_file_storage_table::Migration),
             Box::new(m20250129_000006_create_transfer_state_table::Migration),
+            Box::new(m20250210_000007_create_shared_folder_tables::Migration),
+            Box::new(m20250215_000008_add_file_storage_content_hash::Migration),
+            Box::new(m20250218_000009_add_file_storage_image_info::Migration),
+            Box::new(m20250222_000010_create_file_policy_tables::Migration),
+            Box::new(m20250226_000011_create_transfer_history_table::Migration),
+            Box::new(m20250301_000012_create_chat_message_revision_table::Migration),
+            Box::new(m20250303_000013_add_chat_message_reply::Migration),
+            Box::new(m20250305_000014_add_group_envelope::Migration),
+            Box::new(m20250307_000015_create_group_op_table::Migration),
+            Box::new(m20250309_000016_fix_group_owner_role::Migration),
+            Box::new(m20250311_000017_create_group_request_table::Migration),
+            Box::new(m20250313_000018_create_chat_message_mention_table::Migration),
+            Box::new(m20250315_000019_create_group_message_receipt_table::Migration),
+            Box::new(m20250317_000020_create_group_announcement_tables::Migration),
+            Box::new(m20250319_000021_create_group_poll_tables::Migration),
+            Box::new(m20250321_000022_create_message_reaction_table::Migration),
+            Box::new(m20250323_000023_add_transfer_state_rate_limit::Migration),
+            Box::new(m20250325_000024_create_pending_revision_table::Migration),
         ]
     }
 }
@@ -40,6 +40,19 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub msg_no: Option<String>,
 
+    /// 修订状态 (0-原始, 1-已编辑, 2-已撤回)
+    pub edit_state: i8,
+
+    /// 引用回复的原消息 ID（本地找不到原消息时为空）
+    pub reply_to_mid: Option<i64>,
+
+    /// 引用摘要
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reply_quote: Option<String>,
+
+    /// 群内消息序号（仅群消息，按发送者递增）
+    pub group_msg_no: Option<i64>,
+
     /// 创建时间
     pub create_time: DateTime,
 
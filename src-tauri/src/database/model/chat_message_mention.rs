@@ -0,0 +1,33 @@
+// src-tauri/src/database/model/chat_message_mention.rs
+//
+//! SeaORM 实体模型 - 消息提及表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 消息提及表实体（群消息中每个 @ 一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "chat_message_mention")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 消息 ID (关联 chat_message 表)
+    pub mid: i64,
+
+    /// 群组 ID
+    pub gid: i64,
+
+    /// 被提及的用户 ID（@所有人 时为空）
+    #[sea_orm(nullable)]
+    pub mentioned_uid: Option<i64>,
+
+    /// 创建时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
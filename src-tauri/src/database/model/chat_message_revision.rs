@@ -0,0 +1,40 @@
+// src-tauri/src/database/model/chat_message_revision.rs
+//
+//! SeaORM 实体模型 - 消息修订历史表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 消息修订历史表实体（每次编辑或撤回一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "chat_message_revision")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 消息 ID (关联 chat_message 表)
+    pub mid: i64,
+
+    /// 操作 (1-编辑, 2-撤回)
+    pub action: i8,
+
+    /// 修订前的内容
+    #[sea_orm(column_type = "Text")]
+    pub old_content: String,
+
+    /// 修订后的内容（撤回时为空）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub new_content: Option<String>,
+
+    /// 操作者用户 ID
+    pub actor_uid: i64,
+
+    /// 操作时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -29,6 +29,9 @@ pub struct Model {
     /// 未读消息数量
     pub unread_count: i32,
 
+    /// 提及本机用户的未读消息数量
+    pub mention_count: i32,
+
     /// 更新时间
     pub update_time: DateTime,
 
@@ -0,0 +1,52 @@
+// src-tauri/src/database/model/file_accept_policy.rs
+//
+//! SeaORM 实体模型 - 文件接收策略表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 文件接收策略表实体
+///
+/// 已设置的条件全部满足时规则命中，按优先级从小到大取第一条命中的规则
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "file_accept_policy")]
+pub struct Model {
+    /// 规则 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 规则名称
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+
+    /// 优先级（越小越先匹配）
+    pub priority: i32,
+
+    /// 动作 (0=询问, 1=自动接收, 2=拒绝)
+    pub action: i8,
+
+    /// 发送方联系人分组标签
+    #[sea_orm(column_type = "Text", nullable)]
+    pub contact_tag: Option<String>,
+
+    /// 扩展名列表（逗号分隔，不含点）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub extensions: Option<String>,
+
+    /// 最小文件大小 (字节)
+    pub min_size: Option<i64>,
+
+    /// 最大文件大小 (字节)
+    pub max_size: Option<i64>,
+
+    /// 是否启用
+    pub enabled: i8,
+
+    /// 创建时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,52 @@
+// src-tauri/src/database/model/file_offer.rs
+//
+//! SeaORM 实体模型 - 收到的文件请求表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 收到的文件请求表实体（每个文件一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "file_offer")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 文件请求包编号
+    #[sea_orm(column_type = "Text")]
+    pub packet_no: String,
+
+    /// 发送方的文件ID
+    pub file_id: i64,
+
+    /// 发送方 IP
+    #[sea_orm(column_type = "Text")]
+    pub from_ip: String,
+
+    /// 文件名
+    #[sea_orm(column_type = "Text")]
+    pub file_name: String,
+
+    /// 文件大小
+    pub file_size: i64,
+
+    /// 处理结果 (0=等待确认, 1=已接收, 2=已拒绝)
+    pub decision: i8,
+
+    /// 拒绝原因
+    #[sea_orm(column_type = "Text", nullable)]
+    pub reason: Option<String>,
+
+    /// 隔离原因（文件名或内容可疑）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub quarantine_reason: Option<String>,
+
+    /// 创建时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
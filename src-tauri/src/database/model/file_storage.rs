@@ -37,6 +37,23 @@ pub struct Model {
 
     /// 创建时间
     pub create_time: DateTime,
+
+    /// 文件内容 SHA256（目录为空）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub content_hash: Option<String>,
+
+    /// 是否为接收到的文件 (0=本地发送, 1=接收)
+    pub received: i8,
+
+    /// 图片宽度（非图片为空）
+    pub image_width: Option<i32>,
+
+    /// 图片高度（非图片为空）
+    pub image_height: Option<i32>,
+
+    /// 本地缓存的缩略图路径
+    #[sea_orm(column_type = "Text", nullable)]
+    pub thumbnail_path: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +28,13 @@ pub struct Model {
     #[sea_orm(column_type = "Text", nullable)]
     pub description: Option<String>,
 
+    /// 群编号（创建者生成，各端一致）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub group_no: Option<String>,
+
+    /// 是否允许申请加入 (0-否, 1-是)
+    pub discoverable: i8,
+
     /// 创建时间
     pub create_time: DateTime,
 
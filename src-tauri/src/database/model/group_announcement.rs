@@ -0,0 +1,46 @@
+// src-tauri/src/database/model/group_announcement.rs
+//
+//! SeaORM 实体模型 - 群公告表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群公告表实体（按群操作合并得到，每条公告一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_announcement")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 群组 ID (关联 group 表)
+    pub gid: i64,
+
+    /// 公告编号（发布者生成，各成员一致）
+    #[sea_orm(column_type = "Text")]
+    pub announcement_no: String,
+
+    /// 公告内容
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+
+    /// 发布者用户 ID
+    pub author_uid: i64,
+
+    /// 最后编辑者用户 ID
+    pub editor_uid: i64,
+
+    /// 是否置顶 (0-否, 1-是)
+    pub pinned: i8,
+
+    /// 创建时间
+    pub create_time: DateTime,
+
+    /// 更新时间
+    pub update_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,29 @@
+// src-tauri/src/database/model/group_announcement_ack.rs
+//
+//! SeaORM 实体模型 - 群公告确认表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群公告确认表实体（每个确认公告的成员一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_announcement_ack")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 公告 ID (关联 group_announcement 表)
+    pub announcement_id: i64,
+
+    /// 确认成员用户 ID
+    pub member_uid: i64,
+
+    /// 确认时间
+    pub ack_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
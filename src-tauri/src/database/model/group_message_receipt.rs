@@ -0,0 +1,43 @@
+// src-tauri/src/database/model/group_message_receipt.rs
+//
+//! SeaORM 实体模型 - 群消息回执表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群消息回执表实体（发出的群消息每个接收成员一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_message_receipt")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 消息 ID (关联 chat_message 表)
+    pub mid: i64,
+
+    /// 群组 ID
+    pub gid: i64,
+
+    /// 接收成员用户 ID
+    pub member_uid: i64,
+
+    /// 状态 (0-未送达, 1-已送达, 2-已读)
+    pub status: i8,
+
+    /// 送达时间
+    #[sea_orm(nullable)]
+    pub deliver_time: Option<DateTime>,
+
+    /// 已读时间
+    #[sea_orm(nullable)]
+    pub read_time: Option<DateTime>,
+
+    /// 创建时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,48 @@
+// src-tauri/src/database/model/group_op.rs
+//
+//! SeaORM 实体模型 - 群操作日志表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群操作日志表实体（本地和其他成员发起的每个群操作一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_op")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 群组 ID (关联 group 表)
+    pub gid: i64,
+
+    /// Lamport 时钟
+    pub clock: i64,
+
+    /// 发起操作的成员机器标识
+    #[sea_orm(column_type = "Text")]
+    pub origin: String,
+
+    /// 操作类型 (create/invite/join/leave/kick/rename/describe/role)
+    #[sea_orm(column_type = "Text")]
+    pub kind: String,
+
+    /// 操作涉及的成员机器标识
+    #[sea_orm(column_type = "Text", nullable)]
+    pub member: Option<String>,
+
+    /// 操作值（群名称、群描述或角色）
+    #[sea_orm(column_type = "Text", nullable)]
+    pub value: Option<String>,
+
+    /// 发起操作的用户 ID
+    pub actor_uid: i64,
+
+    /// 记录时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,67 @@
+// src-tauri/src/database/model/group_poll.rs
+//
+//! SeaORM 实体模型 - 群投票表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群投票表实体（每条投票消息一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_poll")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 投票消息 ID (关联 chat_message 表)
+    pub mid: i64,
+
+    /// 群组 ID
+    pub gid: i64,
+
+    /// 投票编号（创建者生成，各端一致）
+    #[sea_orm(column_type = "Text")]
+    pub poll_no: String,
+
+    /// 创建者用户 ID
+    pub creator_uid: i64,
+
+    /// 问题
+    #[sea_orm(column_type = "Text")]
+    pub question: String,
+
+    /// 选项（JSON 字符串数组）
+    #[sea_orm(column_type = "Text")]
+    pub options: String,
+
+    /// 是否多选 (0-单选, 1-多选)
+    pub multiple: i8,
+
+    /// 是否匿名 (0-否, 1-是)
+    pub anonymous: i8,
+
+    /// 截止时间
+    #[sea_orm(nullable)]
+    pub deadline: Option<DateTime>,
+
+    /// 是否已被创建者结束 (0-否, 1-是)
+    pub closed: i8,
+
+    /// 每个选项的票数（JSON 数组，以创建者广播的结果为准）
+    #[sea_orm(column_type = "Text")]
+    pub counts: String,
+
+    /// 投票人数
+    pub voter_count: i64,
+
+    /// 创建时间
+    pub create_time: DateTime,
+
+    /// 更新时间
+    pub update_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
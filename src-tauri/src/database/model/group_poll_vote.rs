@@ -0,0 +1,35 @@
+// src-tauri/src/database/model/group_poll_vote.rs
+//
+//! SeaORM 实体模型 - 群投票记录表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群投票记录表实体（每个投票人一条）
+///
+/// 创建者保存收到的全部投票；其他成员保存自己的投票，非匿名投票还保存结果中的投票人
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_poll_vote")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 投票 ID (关联 group_poll 表)
+    pub poll_id: i64,
+
+    /// 投票人用户 ID
+    pub voter_uid: i64,
+
+    /// 所选选项序号（逗号分隔）
+    #[sea_orm(column_type = "Text")]
+    pub choices: String,
+
+    /// 投票时间
+    pub vote_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,62 @@
+// src-tauri/src/database/model/group_request.rs
+//
+//! SeaORM 实体模型 - 群邀请/入群申请表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 群邀请/入群申请表实体
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "group_request")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 请求编号（发起方生成，收发双方一致）
+    #[sea_orm(column_type = "Text")]
+    pub request_no: String,
+
+    /// 类型 (0-群邀请, 1-入群申请)
+    pub kind: i8,
+
+    /// 方向 (0-收到, 1-发出)
+    pub direction: i8,
+
+    /// 本地群组 ID（本机还不是成员时为空）
+    pub gid: Option<i64>,
+
+    /// 群编号
+    #[sea_orm(column_type = "Text")]
+    pub group_no: String,
+
+    /// 群名称
+    #[sea_orm(column_type = "Text")]
+    pub group_name: String,
+
+    /// 发起者用户 ID（邀请者或申请者）
+    pub from_uid: i64,
+
+    /// 接收者用户 ID（被邀请者或审核的管理员）
+    pub to_uid: i64,
+
+    /// 状态 (0-待处理, 1-已接受, 2-已拒绝, 3-已过期)
+    pub status: i8,
+
+    /// 处理者用户 ID
+    pub operator_uid: Option<i64>,
+
+    /// 过期时间
+    pub expire_time: DateTime,
+
+    /// 创建时间
+    pub create_time: DateTime,
+
+    /// 更新时间
+    pub update_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,33 @@
+// src-tauri/src/database/model/message_reaction.rs
+//
+//! SeaORM 实体模型 - 消息表情回应表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 消息表情回应表实体（每条消息每个回应者一条，重新回应时覆盖）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "message_reaction")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 消息 ID (关联 chat_message 表)
+    pub mid: i64,
+
+    /// 回应者用户 ID
+    pub reactor_uid: i64,
+
+    /// 表情
+    #[sea_orm(column_type = "Text")]
+    pub emoji: String,
+
+    /// 回应时间
+    pub react_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
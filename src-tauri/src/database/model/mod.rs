@@ -5,19 +5,51 @@
 //! 定义所有数据库实体模型
 
 pub mod chat_message;
+pub mod chat_message_mention;
+pub mod chat_message_revision;
 pub mod chat_session;
 pub mod contact;
+pub mod file_accept_policy;
+pub mod file_offer;
 pub mod file_storage;
 pub mod group;
+pub mod group_announcement;
+pub mod group_announcement_ack;
 pub mod group_member;
+pub mod group_message_receipt;
+pub mod group_op;
+pub mod group_poll;
+pub mod group_poll_vote;
+pub mod group_request;
+pub mod message_reaction;
+pub mod pending_revision;
+pub mod shared_folder;
+pub mod shared_folder_access;
+pub mod transfer_history;
 pub mod transfer_state;
 pub mod user;
 
 // 导出所有实体
 pub use chat_message::Entity as ChatMessage;
+pub use chat_message_mention::Entity as ChatMessageMention;
+pub use chat_message_revision::Entity as ChatMessageRevision;
 pub use chat_session::Entity as ChatSession;
 pub use contact::Entity as Contact;
+pub use file_accept_policy::Entity as FileAcceptPolicy;
+pub use file_offer::Entity as FileOffer;
 pub use file_storage::Entity as FileStorage;
 pub use group::Entity as Group;
+pub use group_announcement::Entity as GroupAnnouncement;
+pub use group_announcement_ack::Entity as GroupAnnouncementAck;
 pub use group_member::Entity as GroupMember;
+pub use group_message_receipt::Entity as GroupMessageReceipt;
+pub use group_op::Entity as GroupOp;
+pub use group_poll::Entity as GroupPoll;
+pub use group_poll_vote::Entity as GroupPollVote;
+pub use group_request::Entity as GroupRequest;
+pub use message_reaction::Entity as MessageReaction;
+pub use pending_revision::Entity as PendingRevision;
+pub use shared_folder::Entity as SharedFolder;
+pub use shared_folder_access::Entity as SharedFolderAccess;
+pub use transfer_history::Entity as TransferHistory;
 pub use user::Entity as User;
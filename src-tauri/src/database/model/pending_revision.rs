@@ -0,0 +1,39 @@
+// src-tauri/src/database/model/pending_revision.rs
+//
+//! SeaORM 实体模型 - 待补发修订通知表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 待补发修订通知表实体（对端离线时排队，每个对端每条消息只保留最新一次修订）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pending_revision")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 接收通知的对端用户 ID
+    pub target_uid: i64,
+
+    /// 被修订消息的编号
+    pub msg_no: String,
+
+    /// 操作 (1-编辑, 2-撤回)
+    pub action: i8,
+
+    /// 修订后的内容（撤回时为空）
+    #[sea_orm(column_type = "Text")]
+    pub content: String,
+
+    /// 撤回时的撤回时限（秒，0 表示不限制）
+    pub window_secs: i64,
+
+    /// 修订时间，补发的撤回包沿用该时间
+    pub revise_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+// src-tauri/src/database/model/shared_folder.rs
+//
+//! SeaORM 实体模型 - 共享文件夹表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 共享文件夹表实体
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "shared_folder")]
+pub struct Model {
+    /// 共享 ID
+    #[sea_orm(primary_key)]
+    pub share_id: i64,
+
+    /// 共享名（对端浏览时看到的名称，唯一）
+    #[sea_orm(column_type = "Text")]
+    pub name: String,
+
+    /// 本地目录路径
+    #[sea_orm(column_type = "Text")]
+    pub path: String,
+
+    /// 创建时间
+    pub create_time: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
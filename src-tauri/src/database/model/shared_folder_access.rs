@@ -0,0 +1,42 @@
+// src-tauri/src/database/model/shared_folder_access.rs
+//
+//! SeaORM 实体模型 - 共享文件夹访问列表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 共享文件夹访问列表实体
+///
+/// 共享没有访问记录时对所有联系人开放
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "shared_folder_access")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 共享 ID
+    pub share_id: i64,
+
+    /// 允许访问的联系人用户 ID
+    pub contact_uid: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::shared_folder::Entity",
+        from = "Column::ShareId",
+        to = "super::shared_folder::Column::ShareId"
+    )]
+    SharedFolder,
+
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::ContactUid",
+        to = "super::user::Column::Uid"
+    )]
+    Contact,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
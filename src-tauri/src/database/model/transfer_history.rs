@@ -0,0 +1,61 @@
+// src-tauri/src/database/model/transfer_history.rs
+//
+//! SeaORM 实体模型 - 传输历史表
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// 传输历史表实体（每次完成的传输一条）
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "transfer_history")]
+pub struct Model {
+    /// 记录 ID
+    #[sea_orm(primary_key)]
+    pub id: i64,
+
+    /// 传输 ID (关联 transfer_state 表)
+    pub tid: i64,
+
+    /// 文件 ID
+    pub file_id: i64,
+
+    /// 文件名
+    #[sea_orm(column_type = "Text")]
+    pub file_name: String,
+
+    /// 文件大小
+    pub file_size: i64,
+
+    /// 传输方向 (0=下载, 1=上传)
+    pub direction: i8,
+
+    /// 对端用户 ID（未知时为 0）
+    pub peer_uid: i64,
+
+    /// 对端 IP
+    #[sea_orm(column_type = "Text")]
+    pub peer_ip: String,
+
+    /// 本次实际传输的字节数（断点续传时小于文件大小）
+    pub bytes: i64,
+
+    /// 耗时（毫秒）
+    pub duration_ms: i64,
+
+    /// 平均速率（字节/秒）
+    pub avg_speed: i64,
+
+    /// 重传的数据块数
+    pub retries: i32,
+
+    /// 开始时间
+    pub started_at: DateTime,
+
+    /// 完成时间
+    pub finished_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
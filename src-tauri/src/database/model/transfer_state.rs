@@ -30,7 +30,7 @@ pub struct Model {
     /// 文件总大小
     pub file_size: i64,
 
-    /// 传输状态 (0=等待中, 1=传输中, 2=已完成, -1=失败, -2=已取消)
+    /// 传输状态 (0=等待中, 1=传输中, 2=已完成, 3=已暂停, -1=失败, -2=已取消)
     pub status: i8,
 
     /// 数据包编号 (用于恢复传输)
@@ -48,6 +48,9 @@ pub struct Model {
     /// 错误信息 (失败时记录)
     pub error_message: Option<String>,
 
+    /// 单个传输的限速 (字节/秒, 0=不限)
+    pub rate_limit: i64,
+
     /// 更新时间
     pub update_time: String,
 
@@ -37,6 +37,10 @@ pub enum AppError {
     /// 已存在
     #[error("记录已存在: {0}")]
     AlreadyExists(String),
+
+    /// 权限不足
+    #[error("权限不足: {0}")]
+    Permission(String),
 }
 
 /// 应用结果类型
@@ -1,9 +1,21 @@
 use sea_orm::DbConn;
-use tracing::{error, info};
+use tauri::{AppHandle, Emitter};
+use tracing::{error, info, warn};
 
+use crate::core::chat::receipt::ReceiptHandler;
+use crate::core::chat::receiver::MessageReceiver;
+use crate::core::chat::revision::{EDIT_STATE_EDITED, EDIT_STATE_RECALLED};
+use crate::core::chat::{MessageRevisionService, PollService, ReactionService, TypingService};
+use crate::core::contact::discovery::{mark_online, remove_online_user, send_ansentry};
+use crate::core::file::policy::FilePolicyService;
+use crate::core::file::resume::resume_interrupted_transfers;
 use crate::core::file::FileTransferHandler;
+use crate::core::group::{GroupService, GroupSyncService};
 use crate::database::handler::{ContactHandler, UserHandler};
 use crate::event::model::{NetworkEvent, UiEvent};
+use crate::network::feiq::model::{
+    FileAttachment, GroupEnvelope, GroupRequest, GroupSync, GroupSyncOp, PollResult, PollSpec, Reaction,
+};
 
 pub async fn handle_network_event(event: NetworkEvent, db: &DbConn) {
     match event {
@@ -28,25 +40,213 @@ pub async fn handle_network_event(event: NetworkEvent, db: &DbConn) {
             content,
             msg_no,
             needs_receipt,
+            reply_to,
+            quote,
         } => {
-            handle_message_received(
+            MessageReceiver::handle_message_received(
+                db,
                 sender_ip,
                 sender_port,
                 sender_nickname,
                 content,
                 msg_no,
                 needs_receipt,
+                reply_to,
+                quote,
+                None,
+                None,
             )
             .await
         }
-        NetworkEvent::MessageReceiptReceived { msg_no } => {
-            handle_message_receipt(msg_no).await
+        NetworkEvent::GroupMessageReceived {
+            sender_ip,
+            sender_port,
+            sender_nickname,
+            group_no,
+            group_msg_no,
+            group_sender,
+            mentions,
+            content,
+            msg_no,
+            reply_to,
+            quote,
+            poll,
+        } => {
+            let poll = poll.and_then(|poll| serde_json::from_str::<PollSpec>(&poll).ok());
+            let envelope = GroupEnvelope {
+                group_no,
+                group_msg_no,
+                sender: group_sender,
+                mentions,
+            };
+            MessageReceiver::handle_message_received(
+                db,
+                sender_ip,
+                sender_port,
+                sender_nickname,
+                content,
+                msg_no,
+                true,
+                reply_to,
+                quote,
+                Some(envelope),
+                poll,
+            )
+            .await
+        }
+        NetworkEvent::MessageReceiptReceived {
+            sender_ip,
+            sender_port,
+            msg_no,
+        } => ReceiptHandler::handle_recvmsg(db, &sender_ip, sender_port, &msg_no).await,
+        NetworkEvent::MessageRead {
+            sender_ip,
+            sender_port,
+            msg_no,
+        } => ReceiptHandler::handle_readmsg(db, &sender_ip, sender_port, &msg_no).await,
+        // DELMSG 只能撤回该发送者自己发出的消息，并记入修订历史
+        NetworkEvent::MessageDeleted {
+            sender_ip,
+            sender_port,
+            msg_no,
+            sent_at,
+        } => {
+            let content = String::new();
+            MessageRevisionService::apply_remote(
+                db,
+                &sender_ip,
+                sender_port,
+                &msg_no,
+                EDIT_STATE_RECALLED,
+                content,
+                sent_at,
+                None,
+            )
+            .await
+        }
+        // 撤回包携带发送方的撤回时限
+        NetworkEvent::MessageRecalled {
+            sender_ip,
+            sender_port,
+            msg_no,
+            sent_at,
+            window_secs,
+        } => {
+            let content = String::new();
+            MessageRevisionService::apply_remote(
+                db,
+                &sender_ip,
+                sender_port,
+                &msg_no,
+                EDIT_STATE_RECALLED,
+                content,
+                sent_at,
+                window_secs,
+            )
+            .await
+        }
+        NetworkEvent::MessageEdited {
+            sender_ip,
+            sender_port,
+            msg_no,
+            content,
+        } => {
+            let now = chrono::Utc::now().timestamp();
+            let edit_state = EDIT_STATE_EDITED;
+            MessageRevisionService::apply_remote(db, &sender_ip, sender_port, &msg_no, edit_state, content, now, None)
+                .await
+        }
+        NetworkEvent::GroupSyncReceived {
+            sender_ip,
+            sender_port,
+            group_no,
+            ops,
+            count,
+            head,
+        } => handle_group_sync(db, sender_ip, sender_port, group_no, ops, count, head).await,
+        NetworkEvent::GroupRequestReceived {
+            sender_ip,
+            sender_port,
+            request_no,
+            action,
+            group_no,
+            expire_at,
+            group_name,
+        } => {
+            let request = GroupRequest {
+                request_no,
+                action,
+                group_no,
+                expire_at,
+                group_name,
+            };
+            if let Err(e) = GroupService::handle_request(db, &sender_ip, sender_port, request).await {
+                error!("处理群请求失败: {}", e);
+            }
+        }
+        NetworkEvent::PollVoteReceived {
+            sender_ip,
+            sender_port,
+            poll_no,
+            choices,
+        } => {
+            if let Err(e) = PollService::handle_vote(db, &sender_ip, sender_port, &poll_no, &choices).await {
+                error!("处理投票失败: poll_no={}, {}", poll_no, e);
+            }
         }
-        NetworkEvent::MessageRead { msg_no } => handle_message_read(msg_no).await,
-        NetworkEvent::MessageDeleted { msg_no } => handle_message_deleted(msg_no).await,
-        NetworkEvent::FileRequestReceived { from_ip, files } => {
-            handle_file_request(from_ip, files).await
+        NetworkEvent::PollVoteAcked {
+            sender_ip,
+            sender_port,
+            poll_no,
+            accepted,
+            choices,
+        } => PollService::handle_vote_ack(&sender_ip, sender_port, &poll_no, accepted, &choices),
+        NetworkEvent::PollResultReceived {
+            sender_ip,
+            sender_port,
+            result,
+        } => match serde_json::from_str::<PollResult>(&result) {
+            Ok(result) => {
+                if let Err(e) = PollService::apply_result(db, &sender_ip, sender_port, result).await {
+                    error!("处理投票结果失败: {}", e);
+                }
+            }
+            Err(e) => error!("解析投票结果失败: {}", e),
+        },
+        NetworkEvent::ReactionReceived {
+            sender_ip,
+            sender_port,
+            msg_no,
+            emoji,
+            group_no,
+            msg_sender,
+        } => {
+            let reaction = Reaction {
+                msg_no,
+                emoji,
+                group_no,
+                msg_sender,
+            };
+            if let Err(e) = ReactionService::apply_remote(db, &sender_ip, sender_port, reaction).await {
+                error!("处理消息回应失败: {}", e);
+            }
         }
+        NetworkEvent::TypingReceived {
+            sender_ip,
+            sender_port,
+            typing,
+            group_no,
+        } => {
+            if let Err(e) = TypingService::handle_remote(db, &sender_ip, sender_port, typing, group_no).await {
+                error!("处理输入状态失败: {}", e);
+            }
+        }
+        NetworkEvent::FileRequestReceived {
+            from_ip,
+            from_port,
+            packet_no,
+            files,
+        } => handle_file_request(db, from_ip, from_port, packet_no, files).await,
         NetworkEvent::UserUpdated { user } => handle_user_updated(user).await,
         NetworkEvent::FileDataRequest {
             from_ip,
@@ -60,20 +260,30 @@ pub async fn handle_network_event(event: NetworkEvent, db: &DbConn) {
             file_id,
             offset,
             data,
-        } => handle_file_data_received(db, from_ip, packet_no, file_id, offset, data).await,
-        NetworkEvent::FileRelease { from_ip, packet_no } => {
-            handle_file_release(db, from_ip, packet_no).await
-        }
+            compressed,
+        } => handle_file_data_received(db, from_ip, packet_no, file_id, offset, data, compressed).await,
+        NetworkEvent::FileDataAck {
+            from_ip,
+            packet_no,
+            file_id,
+            offset,
+        } => handle_file_data_ack(from_ip, packet_no, file_id, offset).await,
+        NetworkEvent::FileRelease {
+            from_ip,
+            packet_no,
+            reason,
+        } => handle_file_release(db, from_ip, packet_no, reason).await,
         _ => {
             info!("收到未处理的事件类型");
         }
     }
 }
 
+/// 处理用户上线事件：登记到在线列表并回复 ANSENTRY
 async fn handle_user_online(
-    ip: String,
+    ip: &str,
     port: u16,
-    nickname: String,
+    nickname: &str,
     hostname: Option<String>,
     mac_addr: Option<String>,
 ) {
@@ -84,13 +294,20 @@ async fn handle_user_online(
     if let Some(m) = mac_addr {
         info!("  MAC: {}", m);
     }
+
+    mark_online(ip, port, nickname);
+    if let Err(e) = send_ansentry(&format!("{}:{}", ip, port)).await {
+        error!("发送 ANSENTRY 失败: {}", e);
+    }
 }
 
 /// 处理用户上线事件（带数据库操作）
 ///
-/// 收到 BR_ENTRY 消息后：
+/// 收到 BR_ENTRY 消息后登记在线并回复 ANSENTRY，然后：
 /// 1. 更新或创建 user 表记录
 /// 2. 确保 contact 表中存在与当前用户的联系人关系
+/// 3. 继续与该用户之间中断的传输
+/// 4. 补发该用户所在群组的群状态
 async fn handle_user_online_with_db(
     db: &DbConn,
     ip: String,
@@ -99,13 +316,7 @@ async fn handle_user_online_with_db(
     hostname: Option<String>,
     mac_addr: Option<String>,
 ) {
-    info!("用户上线事件: {} ({}:{})", nickname, ip, port);
-    if let Some(h) = hostname {
-        info!("  主机名: {}", h);
-    }
-    if let Some(m) = mac_addr {
-        info!("  MAC: {}", m);
-    }
+    handle_user_online(&ip, port, &nickname, hostname, mac_addr).await;
 
     let machine_id = format!("{}:{}", ip, port);
 
@@ -129,6 +340,26 @@ async fn handle_user_online_with_db(
                     }
                 }
             }
+
+            // 4. 继续与该用户之间中断的传输
+            if let Err(e) = resume_interrupted_transfers(db, Some(&ip)).await {
+                error!("恢复与 {} 的传输失败: {}", ip, e);
+            }
+
+            // 5. 补发该用户离线期间错过的群状态
+            if let Err(e) = GroupSyncService::catch_up_peer(db, &machine_id).await {
+                error!("向 {} 补发群状态失败: {}", machine_id, e);
+            }
+
+            // 6. 发送该用户离线期间待发的群邀请和入群申请
+            if let Err(e) = GroupService::resend_pending_requests(db, &machine_id).await {
+                error!("向 {} 发送群请求失败: {}", machine_id, e);
+            }
+
+            // 7. 补发该用户离线期间的消息撤回/编辑通知
+            if let Err(e) = MessageRevisionService::send_pending(db, &machine_id).await {
+                error!("向 {} 补发消息修订失败: {}", machine_id, e);
+            }
         }
         Err(e) => {
             error!("❌ 更新/创建用户失败: {}", e);
@@ -138,25 +369,26 @@ async fn handle_user_online_with_db(
 
 async fn handle_user_offline(ip: String) {
     info!("用户离线事件: {}", ip);
+    remove_online_user(&ip);
 }
 
-async fn handle_user_presence(
-    ip: String,
-    port: u16,
-    nickname: String,
-    hostname: Option<String>,
-) {
+/// 处理用户在线应答：登记到在线列表
+async fn handle_user_presence(ip: &str, port: u16, nickname: &str, hostname: Option<String>) {
     info!("用户在线应答: {} ({}:{})", nickname, ip, port);
     if let Some(h) = hostname {
         info!("  主机名: {}", h);
     }
+
+    mark_online(ip, port, nickname);
 }
 
 /// 处理用户在线应答（带数据库操作）
 ///
-/// 收到 ANSENTRY 消息后：
+/// 收到 ANSENTRY 消息后登记在线，然后：
 /// 1. 更新或创建 user 表记录
 /// 2. 确保 contact 表中存在与当前用户的联系人关系
+/// 3. 继续与该用户之间中断的传输
+/// 4. 补发该用户所在群组的群状态
 pub async fn handle_user_presence_with_db(
     db: &DbConn,
     ip: String,
@@ -164,10 +396,7 @@ pub async fn handle_user_presence_with_db(
     nickname: String,
     hostname: Option<String>,
 ) {
-    info!("用户在线应答: {} ({}:{})", nickname, ip, port);
-    if let Some(h) = hostname {
-        info!("  主机名: {}", h);
-    }
+    handle_user_presence(&ip, port, &nickname, hostname).await;
 
     let machine_id = format!("{}:{}", ip, port);
 
@@ -191,6 +420,26 @@ pub async fn handle_user_presence_with_db(
                     }
                 }
             }
+
+            // 4. 继续与该用户之间中断的传输
+            if let Err(e) = resume_interrupted_transfers(db, Some(&ip)).await {
+                error!("恢复与 {} 的传输失败: {}", ip, e);
+            }
+
+            // 5. 补发该用户离线期间错过的群状态
+            if let Err(e) = GroupSyncService::catch_up_peer(db, &machine_id).await {
+                error!("向 {} 补发群状态失败: {}", machine_id, e);
+            }
+
+            // 6. 发送该用户离线期间待发的群邀请和入群申请
+            if let Err(e) = GroupService::resend_pending_requests(db, &machine_id).await {
+                error!("向 {} 发送群请求失败: {}", machine_id, e);
+            }
+
+            // 7. 补发该用户离线期间的消息撤回/编辑通知
+            if let Err(e) = MessageRevisionService::send_pending(db, &machine_id).await {
+                error!("向 {} 补发消息修订失败: {}", machine_id, e);
+            }
         }
         Err(e) => {
             error!("❌ 更新/创建用户失败: {}", e);
@@ -198,35 +447,50 @@ pub async fn handle_user_presence_with_db(
     }
 }
 
-async fn handle_message_received(
+/// 处理群状态同步：合并群操作并更新本地群组
+async fn handle_group_sync(
+    db: &DbConn,
     sender_ip: String,
     sender_port: u16,
-    sender_nickname: String,
-    content: String,
-    msg_no: String,
-    needs_receipt: bool,
+    group_no: String,
+    ops: String,
+    count: usize,
+    head: i64,
 ) {
-    info!("收到消息: {} from {}:{}", msg_no, sender_ip, sender_port);
-    info!("  发送者: {}", sender_nickname);
-    info!("  内容: {}", content);
-    info!("  需要确认: {}", needs_receipt);
-}
+    info!("收到群状态同步: group_no={} from {}:{}", group_no, sender_ip, sender_port);
 
-async fn handle_message_receipt(msg_no: String) {
-    info!("收到消息确认: {}", msg_no);
+    let ops: Vec<GroupSyncOp> = match serde_json::from_str(&ops) {
+        Ok(ops) => ops,
+        Err(e) => {
+            error!("解析群状态同步失败: {}", e);
+            return;
+        }
+    };
+    let sync = GroupSync {
+        group_no,
+        ops,
+        count,
+        head,
+    };
+    if let Err(e) = GroupSyncService::apply_remote(db, &sender_ip, sender_port, sync).await {
+        error!("合并群状态失败: {}", e);
+    }
 }
 
-async fn handle_message_read(msg_no: String) {
-    info!("消息已读: {}", msg_no);
-}
+async fn handle_file_request(db: &DbConn, from_ip: String, from_port: u16, packet_no: String, files: String) {
+    info!("收到文件请求: from {}, packet_no={}", from_ip, packet_no);
 
-async fn handle_message_deleted(msg_no: String) {
-    info!("消息已删除: {}", msg_no);
-}
+    let files: Vec<FileAttachment> = match serde_json::from_str(&files) {
+        Ok(files) => files,
+        Err(e) => {
+            error!("解析文件请求失败: {}", e);
+            return;
+        }
+    };
 
-async fn handle_file_request(from_ip: String, files: String) {
-    info!("收到文件请求: from {}", from_ip);
-    info!("  文件信息: {}", files);
+    if let Err(e) = FilePolicyService::handle_offer(db, &from_ip, from_port, &packet_no, files).await {
+        error!("处理文件请求失败: {}", e);
+    }
 }
 
 async fn handle_user_updated(user: String) {
@@ -255,9 +519,10 @@ async fn handle_file_data_received(
     file_id: u64,
     offset: u64,
     data: String,
+    compressed: bool,
 ) {
     if let Err(e) = FileTransferHandler::handle_file_data_received(
-        db, &from_ip, &packet_no, file_id, offset, &data,
+        db, &from_ip, &packet_no, file_id, offset, &data, compressed,
     )
     .await
     {
@@ -265,32 +530,54 @@ async fn handle_file_data_received(
     }
 }
 
-async fn handle_file_release(db: &DbConn, from_ip: String, packet_no: String) {
-    if let Err(e) = FileTransferHandler::handle_file_release(db, &from_ip, &packet_no).await {
+async fn handle_file_data_ack(from_ip: String, packet_no: String, file_id: u64, offset: u64) {
+    FileTransferHandler::handle_file_data_ack(&from_ip, &packet_no, file_id, offset);
+}
+
+async fn handle_file_release(db: &DbConn, from_ip: String, packet_no: String, reason: Option<String>) {
+    if let Err(e) = FileTransferHandler::handle_file_release(db, &from_ip, &packet_no, reason).await {
         error!("处理文件释放失败: {}", e);
     }
 }
 
-pub async fn handle_ui_event(_event: UiEvent) {}
+/// 前端监听的 UI 事件名
+pub const UI_EVENT: &str = "ui-event";
+
+/// 把 UI 事件转发给前端窗口
+///
+/// 事件按 serde 默认格式序列化为 `{ 变体名: 字段 }`，前端通过 `UI_EVENT` 监听
+pub async fn handle_ui_event(app_handle: &AppHandle, event: UiEvent) {
+    if let Err(e) = app_handle.emit(UI_EVENT, &event) {
+        warn!("转发 UI 事件失败: {}", e);
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::contact::discovery::find_user_by_ip;
 
     #[tokio::test]
     async fn test_handle_user_online() {
+        // 上线处理会回复 ANSENTRY，需要全局套接字
+        crate::network::udp::init_udp_socket().await.unwrap();
         handle_user_online(
-            "192.168.1.100".to_string(),
+            "192.168.1.100",
             2425,
-            "测试用户".to_string(),
+            "测试用户",
             Some("test-host".to_string()),
             Some("00:11:22:33:44:55".to_string()),
         )
         .await;
+        assert!(find_user_by_ip("192.168.1.100").is_some());
     }
 
     #[tokio::test]
     async fn test_handle_user_offline() {
-        handle_user_offline("192.168.1.100".to_string()).await;
+        handle_user_presence("192.168.1.101", 2425, "测试用户", None).await;
+        assert!(find_user_by_ip("192.168.1.101").is_some());
+
+        handle_user_offline("192.168.1.101".to_string()).await;
+        assert!(find_user_by_ip("192.168.1.101").is_none());
     }
 }
@@ -59,21 +59,137 @@ pub enum NetworkEvent {
         content: String,
         msg_no: String,
         needs_receipt: bool,
+        reply_to: Option<String>, // 引用回复的原消息编号
+        quote: Option<String>,    // 引用摘要
     },
 
     /// 收到确认（IPMSG_RECVMSG）
-    MessageReceiptReceived { msg_no: String },
+    MessageReceiptReceived {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+    },
 
     /// 消息已读（IPMSG_READMSG）
-    MessageRead { msg_no: String },
+    MessageRead {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+    },
+
+    /// 消息删除（IPMSG_DELMSG），按撤回处理
+    MessageDeleted {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+        /// 撤回包的发送时间（秒级时间戳，0 表示未知），用于判断撤回时限
+        sent_at: i64,
+    },
+
+    /// 消息撤回（FEIQ_RECALLMSG）
+    MessageRecalled {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+        /// 撤回包的发送时间（秒级时间戳，0 表示未知），用于判断撤回时限
+        sent_at: i64,
+        /// 发送方的撤回时限（秒，0 表示不限制；旧版本的撤回包不携带）
+        window_secs: Option<u64>,
+    },
+
+    /// 消息编辑（FEIQ_EDITMSG）
+    MessageEdited {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+        content: String,
+    },
+
+    /// 收到群消息（FEIQ_GROUPMSG）
+    GroupMessageReceived {
+        sender_ip: String,
+        sender_port: u16,
+        sender_nickname: String,
+        group_no: String,
+        group_msg_no: i64,
+        group_sender: String,  // 信封中的发送者机器标识
+        mentions: Vec<String>, // 被提及成员的机器标识
+        content: String,
+        msg_no: String,
+        reply_to: Option<String>,
+        quote: Option<String>,
+        poll: Option<String>, // 投票消息的 PollSpec JSON
+    },
+
+    /// 收到群状态同步（FEIQ_GROUPSYNC）
+    GroupSyncReceived {
+        sender_ip: String,
+        sender_port: u16,
+        group_no: String,
+        ops: String, // Vec<GroupSyncOp> JSON
+        count: usize,
+        head: i64,
+    },
 
-    /// 消息删除（IPMSG_DELMSG）
-    MessageDeleted { msg_no: String },
+    /// 收到群邀请/入群申请及其应答（FEIQ_GROUPREQ）
+    GroupRequestReceived {
+        sender_ip: String,
+        sender_port: u16,
+        request_no: String,
+        action: String,
+        group_no: String,
+        expire_at: i64,
+        group_name: String,
+    },
+
+    /// 收到投票（FEIQ_POLLMSG vote，本机是投票创建者）
+    PollVoteReceived {
+        sender_ip: String,
+        sender_port: u16,
+        poll_no: String,
+        choices: Vec<usize>,
+    },
+
+    /// 投票创建者确认收到投票（FEIQ_POLLMSG ack）
+    PollVoteAcked {
+        sender_ip: String,
+        sender_port: u16,
+        poll_no: String,
+        accepted: bool, // 投票已结束或选择无效时为 false
+        choices: Vec<usize>,
+    },
+
+    /// 收到投票结果（FEIQ_POLLMSG result）
+    PollResultReceived {
+        sender_ip: String,
+        sender_port: u16,
+        result: String, // PollResult JSON
+    },
+
+    /// 收到表情回应（FEIQ_REACTMSG）
+    ReactionReceived {
+        sender_ip: String,
+        sender_port: u16,
+        msg_no: String,
+        emoji: Option<String>,      // 取消回应时为 None
+        group_no: Option<String>,   // 单聊时为 None
+        msg_sender: Option<String>, // 被回应群消息的发送者机器标识，单聊时为 None
+    },
+
+    /// 收到输入状态（FEIQ_TYPINGMSG）
+    TypingReceived {
+        sender_ip: String,
+        sender_port: u16,
+        typing: bool,
+        group_no: Option<String>, // 单聊时为 None
+    },
 
     /// 文件请求（IPMSG_FILEATTACHOPT）
     FileRequestReceived {
         from_ip: String,
-        files: String, // Vec<FileInfo> JSON
+        from_port: u16,
+        packet_no: String,
+        files: String, // Vec<FileAttachment> JSON
     },
 
     /// 文件数据请求（IPMSG_GETFILEDATA）
@@ -90,11 +206,24 @@ pub enum NetworkEvent {
         packet_no: String,
         file_id: u64,
         offset: u64,
-        data: String, // Base64 encoded file chunk
+        data: String,     // Base64 encoded file chunk
+        compressed: bool, // 数据块经过 deflate 压缩
+    },
+
+    /// 文件数据块确认（滑动窗口 ACK）
+    FileDataAck {
+        from_ip: String,
+        packet_no: String,
+        file_id: u64,
+        offset: u64,
     },
 
     /// 文件释放（取消文件传输）
-    FileRelease { from_ip: String, packet_no: String },
+    FileRelease {
+        from_ip: String,
+        packet_no: String,
+        reason: Option<String>, // 对方给出的拒绝原因
+    },
 
     /// 用户更新信息
     UserUpdated {
@@ -156,6 +285,12 @@ pub enum UiEvent {
     /// 更新消息状态
     UpdateMessageStatus { msg_id: i64, status: i8 },
 
+    /// 消息已撤回
+    MessageRecalled { msg_id: i64 },
+
+    /// 消息已编辑
+    MessageEdited { msg_id: i64, content: String },
+
     /// 更新未读计数
     UpdateUnreadCount {
         session_type: i8,
@@ -163,6 +298,12 @@ pub enum UiEvent {
         count: i32,
     },
 
+    /// 群消息提及了本机用户
+    Mentioned { gid: i64, msg_id: i64, mention_all: bool },
+
+    /// 群消息成员回执更新（1-已送达, 2-已读）
+    GroupReceiptUpdated { msg_id: i64, member_uid: i64, status: i8 },
+
     /// 文件传输进度更新
     FileTransferProgress { file_id: i64, progress: u64, total: u64 },
 
@@ -171,6 +312,49 @@ pub enum UiEvent {
 
     /// 文件传输失败
     FileTransferFailed { file_id: i64, error: String },
+
+    /// 收到文件请求（已按接收策略处理）
+    FileOfferReceived {
+        from_ip: String,
+        packet_no: String,
+        files: String, // Vec<FileAttachment> JSON
+        auto_accepted: bool,
+    },
+
+    /// 接收的文件已被隔离
+    FileQuarantined { file_id: i64, path: String, reason: String },
+
+    /// 收到群邀请或入群申请
+    GroupRequestReceived {
+        request: String, // GroupRequestInfo JSON
+    },
+
+    /// 群邀请或入群申请状态变化（已接受/已拒绝/已过期）
+    GroupRequestUpdated { request_id: i64, status: i8 },
+
+    /// 发现允许申请加入的群组
+    GroupDiscovered {
+        group_no: String,
+        group_name: String,
+        admin_uid: i64,
+    },
+
+    /// 群公告发布、编辑、置顶或确认情况变化
+    GroupAnnouncementUpdated { gid: i64, announcement_id: i64 },
+
+    /// 投票结果更新（收到投票或创建者广播的结果）
+    PollUpdated { gid: i64, msg_id: i64, poll_id: i64 },
+
+    /// 消息的表情回应变化
+    ReactionUpdated { msg_id: i64 },
+
+    /// 对端输入状态变化（正在输入提示超时未刷新时自动变为停止输入）
+    TypingChanged {
+        session_type: i8,
+        target_id: i64,
+        uid: i64,
+        typing: bool,
+    },
 }
 
 // ============================================================
@@ -208,7 +392,14 @@ pub enum FileEvent {
     TransferCancelled { file_id: i64 },
 
     /// 文件传输进度更新
-    TransferProgress { file_id: i64, progress: u64, total: u64 },
+    TransferProgress {
+        file_id: i64,
+        progress: u64,
+        total: u64,
+        rate: u64,              // 当前速率 (字节/秒)
+        compression_ratio: f64, // 压缩后/压缩前，1.0 表示未压缩（TCP 通道不压缩）
+        eta_secs: Option<u64>,  // 预计剩余时间（秒），速率未知时为 None
+    },
 }
 
 // ============================================================
@@ -298,6 +489,8 @@ mod event_tests {
             content: "Hello, World!".to_string(),
             msg_no: "12345".to_string(),
             needs_receipt: true,
+            reply_to: None,
+            quote: None,
         };
 
         // 测试序列化
@@ -362,18 +555,105 @@ mod event_tests {
                 content: "Test".to_string(),
                 msg_no: "0".to_string(),
                 needs_receipt: false,
+                reply_to: Some("1".to_string()),
+                quote: Some("Quoted".to_string()),
             },
             NetworkEvent::MessageReceiptReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
                 msg_no: "0".to_string(),
             },
             NetworkEvent::MessageRead {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
                 msg_no: "0".to_string(),
             },
             NetworkEvent::MessageDeleted {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                msg_no: "0".to_string(),
+                sent_at: 1700000000,
+            },
+            NetworkEvent::MessageRecalled {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
                 msg_no: "0".to_string(),
+                sent_at: 1700000000,
+                window_secs: Some(120),
+            },
+            NetworkEvent::MessageEdited {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                msg_no: "0".to_string(),
+                content: "Edited".to_string(),
+            },
+            NetworkEvent::GroupMessageReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                sender_nickname: "D".to_string(),
+                group_no: "4.4.4.4:4-1-1700000000".to_string(),
+                group_msg_no: 1,
+                group_sender: "4.4.4.4:4".to_string(),
+                mentions: vec!["5.5.5.5:5".to_string()],
+                content: "Hello group".to_string(),
+                msg_no: "0".to_string(),
+                reply_to: None,
+                quote: None,
+                poll: None,
+            },
+            NetworkEvent::GroupSyncReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                group_no: "4.4.4.4:4-1-1700000000".to_string(),
+                ops: "[]".to_string(),
+                count: 0,
+                head: 0,
+            },
+            NetworkEvent::GroupRequestReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                request_no: "4.4.4.4:4-1700000000000".to_string(),
+                action: "invite".to_string(),
+                group_no: "4.4.4.4:4-1-1700000000".to_string(),
+                expire_at: 1_700_259_200,
+                group_name: "项目组".to_string(),
+            },
+            NetworkEvent::PollVoteReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                poll_no: "4.4.4.4:4-1700000000000".to_string(),
+                choices: vec![0, 2],
+            },
+            NetworkEvent::PollVoteAcked {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                poll_no: "4.4.4.4:4-1700000000000".to_string(),
+                accepted: true,
+                choices: vec![0, 2],
+            },
+            NetworkEvent::PollResultReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                result: "{}".to_string(),
+            },
+            NetworkEvent::ReactionReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                msg_no: "1700000000".to_string(),
+                emoji: Some("👍".to_string()),
+                group_no: None,
+                msg_sender: None,
+            },
+            NetworkEvent::TypingReceived {
+                sender_ip: "4.4.4.4".to_string(),
+                sender_port: 4,
+                typing: true,
+                group_no: None,
             },
             NetworkEvent::FileRequestReceived {
                 from_ip: "5.5.5.5".to_string(),
+                from_port: 2425,
+                packet_no: "1700000000".to_string(),
                 files: "[]".to_string(),
             },
         ];
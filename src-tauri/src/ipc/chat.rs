@@ -1,8 +1,15 @@
 // src-tauri/src/ipc/chat.rs
 //
+use crate::core::chat::receipt::ReceiptHandler;
+use crate::core::chat::revision;
+use crate::core::chat::{MentionService, MessageRevisionService, PollService, ReactionService, TypingService};
 use crate::core::ChatService;
 use crate::database::handler::{ChatMessageHandler, UserHandler};
-use crate::types::{ChatMessage, ChatSession, MapErrToFrontend};
+use crate::database::model::chat_message_revision;
+use crate::types::{
+    ChatMessage, ChatSession, GroupReadMembers, GroupReadSummary, MapErrToFrontend, MentionInfo, PollInfo,
+    ReactionSummary,
+};
 use sea_orm::DbConn;
 use tauri::State;
 
@@ -37,6 +44,189 @@ pub async fn send_text_message_handler(
         .map_err_to_frontend()
 }
 
+/// 发送引用回复
+#[tauri::command]
+pub async fn send_reply_message_handler(
+    session_type: i8,
+    target_id: i64,
+    content: String,
+    owner_uid: i64,
+    reply_to_mid: i64,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    ChatService::send_reply(db.inner(), session_type, target_id, owner_uid, content, reply_to_mid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取回复了指定消息的所有消息
+#[tauri::command]
+pub async fn get_message_replies_handler(mid: i64, db: State<'_, DbConn>) -> Result<Vec<ChatMessage>, String> {
+    ChatService::get_replies(db.inner(), mid).await.map_err_to_frontend()
+}
+
+/// 发送带 @ 提及的群消息
+#[tauri::command]
+pub async fn send_mention_message_handler(
+    gid: i64,
+    content: String,
+    owner_uid: i64,
+    mention_uids: Vec<i64>,
+    mention_all: bool,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    ChatService::send_with_mentions(db.inner(), gid, owner_uid, content, mention_uids, mention_all)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 在群聊中发起投票
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_poll_handler(
+    gid: i64,
+    owner_uid: i64,
+    question: String,
+    options: Vec<String>,
+    multiple: bool,
+    anonymous: bool,
+    deadline: Option<i64>,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    ChatService::send_poll(db.inner(), gid, owner_uid, question, options, multiple, anonymous, deadline)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 投票
+#[tauri::command]
+pub async fn vote_poll_handler(
+    poll_id: i64,
+    owner_uid: i64,
+    choices: Vec<usize>,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    PollService::vote(db.inner(), poll_id, owner_uid, &choices)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 提前结束投票
+#[tauri::command]
+pub async fn close_poll_handler(poll_id: i64, owner_uid: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    PollService::close(db.inner(), poll_id, owner_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取投票
+#[tauri::command]
+pub async fn get_poll_handler(poll_id: i64, db: State<'_, DbConn>) -> Result<PollInfo, String> {
+    PollService::get_poll(db.inner(), poll_id).await.map_err_to_frontend()
+}
+
+/// 用表情回应消息（`emoji` 为空时取消回应）
+#[tauri::command]
+pub async fn react_message_handler(
+    mid: i64,
+    owner_uid: i64,
+    emoji: Option<String>,
+    db: State<'_, DbConn>,
+) -> Result<Vec<ReactionSummary>, String> {
+    ReactionService::react(db.inner(), mid, owner_uid, emoji)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 通知会话对端本端的输入状态（限流，不产生消息记录）
+#[tauri::command]
+pub async fn set_typing_handler(
+    session_type: i8,
+    target_id: i64,
+    owner_uid: i64,
+    typing: bool,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    TypingService::set_typing(db.inner(), session_type, target_id, owner_uid, typing)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取群消息的已读统计
+#[tauri::command]
+pub async fn get_group_read_summary_handler(mid: i64, db: State<'_, DbConn>) -> Result<GroupReadSummary, String> {
+    ReceiptHandler::group_read_summary(db.inner(), mid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取群消息已读和未读的成员
+#[tauri::command]
+pub async fn get_group_read_members_handler(mid: i64, db: State<'_, DbConn>) -> Result<GroupReadMembers, String> {
+    ReceiptHandler::group_read_members(db.inner(), mid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取提及用户的群消息
+#[tauri::command]
+pub async fn get_mentions_handler(owner_uid: i64, db: State<'_, DbConn>) -> Result<Vec<MentionInfo>, String> {
+    MentionService::list_mentions(db.inner(), owner_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 撤回自己发送的消息
+#[tauri::command]
+pub async fn recall_message_handler(
+    mid: i64,
+    owner_uid: i64,
+    db: State<'_, DbConn>,
+) -> Result<ChatMessage, String> {
+    MessageRevisionService::recall_message(db.inner(), mid, owner_uid)
+        .await
+        .map(ChatService::to_frontend_message)
+        .map_err_to_frontend()
+}
+
+/// 编辑自己发送的文本消息
+#[tauri::command]
+pub async fn edit_message_handler(
+    mid: i64,
+    owner_uid: i64,
+    content: String,
+    db: State<'_, DbConn>,
+) -> Result<ChatMessage, String> {
+    MessageRevisionService::edit_message(db.inner(), mid, owner_uid, content)
+        .await
+        .map(ChatService::to_frontend_message)
+        .map_err_to_frontend()
+}
+
+/// 获取消息的编辑/撤回历史
+#[tauri::command]
+pub async fn get_message_revisions_handler(
+    mid: i64,
+    db: State<'_, DbConn>,
+) -> Result<Vec<chat_message_revision::Model>, String> {
+    MessageRevisionService::get_history(db.inner(), mid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取消息撤回时限（秒，0 表示不限制）
+#[tauri::command]
+pub async fn get_recall_window_handler() -> Result<u64, String> {
+    Ok(revision::recall_window_secs())
+}
+
+/// 设置消息撤回时限（秒，0 表示不限制）
+#[tauri::command]
+pub async fn set_recall_window_handler(secs: u64) -> Result<(), String> {
+    revision::set_recall_window_secs(secs);
+    Ok(())
+}
+
 /// 获取会话列表
 #[tauri::command]
 pub async fn get_session_list_handler(owner_uid: i64, db: State<'_, DbConn>) -> Result<Vec<ChatSession>, String> {
@@ -123,14 +313,23 @@ pub async fn retry_send_message(
     use crate::network::feiq::model::FeiQPacket;
     use crate::network::udp::sender;
 
-    // Get target user's IP from database
-    let target_user = UserHandler::find_by_id(db, target_id).await.map_err_to_frontend()?;
-
     let packet = FeiQPacket::make_feiq_message_packet(&message.content, None);
-    let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
+    ChatMessageHandler::set_msg_no(db, mid, &packet.ext_info.unique_id)
+        .await
+        .map_err_to_frontend()?;
 
-    // 发送消息
-    sender::send_packet(&addr, &packet).await.map_err_to_frontend()?;
+    if message.session_type == 1 {
+        // 群消息沿用原群内消息序号，接收方不会重复存储
+        use crate::core::group::GroupEnvelopeService;
+        GroupEnvelopeService::broadcast(db, mid, &packet).await.map_err_to_frontend()?;
+    } else {
+        // Get target user's IP from database
+        let target_user = UserHandler::find_by_id(db, target_id).await.map_err_to_frontend()?;
+        let addr = format!("{}:{}", target_user.feiq_ip, target_user.feiq_port);
+
+        // 发送消息
+        sender::send_packet(&addr, &packet).await.map_err_to_frontend()?;
+    }
 
     // 发送成功后更新状态
     ChatMessageHandler::update_status(db, mid, 1).await.map_err_to_frontend()?;
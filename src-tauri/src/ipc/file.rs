@@ -1,13 +1,20 @@
 // src-tauri/src/ipc/file.rs
 //
 /// 文件相关 IPC 接口（薄层 - 只做参数转换和错误映射）
+use crate::core::file::history::TransferHistoryService;
+use crate::core::file::policy::FilePolicyService;
 use crate::core::file::service::FileService;
-use crate::core::file::transfer::FileSender;
-use crate::database::handler::{FileStorageHandler, TransferStateHandler, UserHandler};
-use crate::types::{MapErrToFrontend, PendingTransfer};
+use crate::core::file::share::ShareService;
+use crate::core::file::storage;
+use crate::core::file::thumbnail;
+use crate::database::handler::FileStorageHandler;
+use crate::database::model::{file_accept_policy, file_offer, transfer_history};
+use crate::types::{
+    FileGcReport, FileOfferProgress, MapErrToFrontend, PeerTransferTotals, PendingTransfer, RateLimitSettings,
+    RemoteShareEntry, SharedFolderInfo, TransferQueue,
+};
 use sea_orm::DbConn;
 use tauri::State;
-use tracing::error;
 
 /// 发送文件请求
 #[tauri::command]
@@ -22,6 +29,128 @@ pub async fn send_file_request_handler(
         .map_err_to_frontend()
 }
 
+/// 一次向多个用户发送文件
+#[tauri::command]
+pub async fn send_file_to_users_handler(
+    file_paths: Vec<String>,
+    target_ips: Vec<String>,
+    owner_uid: i64,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    FileService::send_file_to_users(db.inner(), file_paths, target_ips, owner_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 向群组发送文件
+#[tauri::command]
+pub async fn send_group_file_handler(
+    file_paths: Vec<String>,
+    gid: i64,
+    owner_uid: i64,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    FileService::send_group_file(db.inner(), file_paths, gid, owner_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取一次文件发送按接收方汇总的进度
+#[tauri::command]
+pub async fn get_file_offer_progress_handler(
+    packet_no: String,
+    db: State<'_, DbConn>,
+) -> Result<FileOfferProgress, String> {
+    FileService::get_file_offer_progress(db.inner(), packet_no)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取对方图片附件的缩略图，返回本地缓存路径
+#[tauri::command]
+pub async fn get_file_thumbnail_handler(packet_no: String, file_id: i64, target_ip: String) -> Result<String, String> {
+    thumbnail::fetch_remote_thumbnail(&target_ip, &packet_no, file_id as u64)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 回收不再被引用的接收文件
+#[tauri::command]
+pub async fn collect_file_garbage_handler(db: State<'_, DbConn>) -> Result<FileGcReport, String> {
+    storage::collect_garbage(db.inner()).await.map_err_to_frontend()
+}
+
+/// 获取传输历史（可按联系人过滤）
+#[tauri::command]
+pub async fn get_transfer_history_handler(
+    peer_uid: Option<i64>,
+    limit: Option<u64>,
+    db: State<'_, DbConn>,
+) -> Result<Vec<transfer_history::Model>, String> {
+    TransferHistoryService::list(db.inner(), peer_uid, limit)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取按联系人汇总的传输统计
+#[tauri::command]
+pub async fn get_transfer_totals_handler(db: State<'_, DbConn>) -> Result<Vec<PeerTransferTotals>, String> {
+    TransferHistoryService::peer_totals(db.inner()).await.map_err_to_frontend()
+}
+
+/// 获取文件接收策略
+#[tauri::command]
+pub async fn get_file_policies_handler(db: State<'_, DbConn>) -> Result<Vec<file_accept_policy::Model>, String> {
+    FilePolicyService::list_policies(db.inner()).await.map_err_to_frontend()
+}
+
+/// 添加文件接收策略
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn add_file_policy_handler(
+    name: String,
+    priority: i32,
+    action: i8,
+    contact_tag: Option<String>,
+    extensions: Option<String>,
+    min_size: Option<i64>,
+    max_size: Option<i64>,
+    db: State<'_, DbConn>,
+) -> Result<file_accept_policy::Model, String> {
+    FilePolicyService::add_policy(
+        db.inner(),
+        name,
+        priority,
+        action,
+        contact_tag,
+        extensions,
+        min_size,
+        max_size,
+    )
+    .await
+    .map_err_to_frontend()
+}
+
+/// 启用或停用文件接收策略
+#[tauri::command]
+pub async fn set_file_policy_enabled_handler(id: i64, enabled: bool, db: State<'_, DbConn>) -> Result<(), String> {
+    FilePolicyService::set_policy_enabled(db.inner(), id, enabled)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 删除文件接收策略
+#[tauri::command]
+pub async fn delete_file_policy_handler(id: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    FilePolicyService::remove_policy(db.inner(), id).await.map_err_to_frontend()
+}
+
+/// 获取被隔离的文件
+#[tauri::command]
+pub async fn get_quarantined_files_handler(db: State<'_, DbConn>) -> Result<Vec<file_offer::Model>, String> {
+    FilePolicyService::list_quarantined(db.inner()).await.map_err_to_frontend()
+}
+
 /// 接收文件请求（响应）
 #[tauri::command]
 pub async fn accept_file_request_handler(
@@ -36,14 +165,29 @@ pub async fn accept_file_request_handler(
         .map_err_to_frontend()
 }
 
+/// 接收目录请求（仅 TCP 通道）
+#[tauri::command]
+pub async fn accept_directory_request_handler(
+    packet_no: String,
+    file_id: u64,
+    target_ip: String,
+    dest_dir: Option<String>,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    FileService::accept_directory(db.inner(), packet_no, file_id, target_ip, dest_dir)
+        .await
+        .map_err_to_frontend()
+}
+
 /// 拒绝文件请求
 #[tauri::command]
 pub async fn reject_file_request_handler(
     packet_no: String,
     target_ip: String,
+    reason: Option<String>,
     db: State<'_, DbConn>,
 ) -> Result<(), String> {
-    FileService::reject_file(db.inner(), packet_no, target_ip)
+    FileService::reject_file(db.inner(), packet_no, target_ip, reason)
         .await
         .map_err_to_frontend()
 }
@@ -69,78 +213,153 @@ pub async fn get_pending_transfers_handler(db: State<'_, DbConn>) -> Result<Vec<
     FileService::get_pending_transfers(db.inner()).await.map_err_to_frontend()
 }
 
-/// 恢复传输
+/// 恢复传输（从保存的偏移量重新排队）
 #[tauri::command]
 pub async fn resume_transfer_handler(tid: i64, db: State<'_, DbConn>) -> Result<(), String> {
-    // 获取传输信息
-    let transfer = TransferStateHandler::find_by_id(db.inner(), tid)
-        .await
-        .map_err_to_frontend()?
-        .ok_or_else(|| {
-            // 手动构造 NotFound 错误
-            use crate::types::ErrorCode;
-            use crate::types::FrontendError;
-            FrontendError {
-                code: ErrorCode::NotFound,
-                message: format!("传输不存在: {}", tid),
-                details: None,
-            }
-            .to_json()
-        })?;
-
-    // 检查传输状态
-    if transfer.status != 0 && transfer.status != 1 {
-        return Err({
-            use crate::types::ErrorCode;
-            use crate::types::FrontendError;
-            FrontendError {
-                code: ErrorCode::Business,
-                message: "传输状态无法恢复".to_string(),
-                details: Some(format!("当前状态: {}", transfer.status)),
-            }
-            .to_json()
-        });
-    }
-
-    // 获取目标用户信息
-    let _target_user = UserHandler::find_by_id(db.inner(), transfer.target_id)
-        .await
-        .map_err_to_frontend()?;
-
-    // 获取文件存储信息
-    let file_storage = FileStorageHandler::find_by_id(db.inner(), transfer.file_id)
-        .await
-        .map_err_to_frontend()?;
-
-    if transfer.direction == 1 {
-        let sender = FileSender::new(
-            file_storage.file_path.clone(),
-            transfer.file_id as u64,
-            format!("{}:{}", transfer.target_ip, transfer.target_port),
-            transfer.packet_no,
-        );
-
-        tokio::spawn(async move {
-            let file_id = transfer.file_id as u64;
-            let result = sender
-                .send_with_callback(move |progress| {
-                    use crate::event::bus::EVENT_SENDER;
-                    use crate::event::model::{AppEvent, FileEvent};
-                    let _ = EVENT_SENDER.send(AppEvent::File(FileEvent::TransferProgress {
-                        file_id: file_id as i64,
-                        progress: progress.offset,
-                        total: progress.total,
-                    }));
-                })
-                .await;
-
-            if let Err(e) = result {
-                error!("文件发送失败: {}", e);
-            }
-        });
-    } else {
-        tracing::info!("文件接收通过事件系统处理: {}", file_storage.file_path);
-    }
+    FileService::resume_transfer(db.inner(), tid).await.map_err_to_frontend()
+}
+
+/// 设置全局传输限速（字节/秒，0 表示不限速）
+#[tauri::command]
+pub async fn set_global_rate_limit_handler(bytes_per_sec: u64) -> Result<(), String> {
+    FileService::set_global_rate_limit(bytes_per_sec);
+    Ok(())
+}
 
+/// 设置对端传输限速（字节/秒，0 表示取消）
+#[tauri::command]
+pub async fn set_peer_rate_limit_handler(peer_ip: String, bytes_per_sec: u64) -> Result<(), String> {
+    FileService::set_peer_rate_limit(&peer_ip, bytes_per_sec);
     Ok(())
 }
+
+/// 设置单个传输限速（字节/秒，0 表示取消）
+#[tauri::command]
+pub async fn set_transfer_rate_limit_handler(
+    tid: i64,
+    bytes_per_sec: u64,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    FileService::set_transfer_rate_limit(db.inner(), tid, bytes_per_sec)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取当前限速设置
+#[tauri::command]
+pub async fn get_rate_limits_handler() -> Result<RateLimitSettings, String> {
+    Ok(FileService::get_rate_limits())
+}
+
+/// 将传输加入调度队列
+#[tauri::command]
+pub async fn enqueue_transfer_handler(tid: i64, priority: i32, db: State<'_, DbConn>) -> Result<(), String> {
+    FileService::enqueue_transfer(db.inner(), tid, priority)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 暂停传输
+#[tauri::command]
+pub async fn pause_transfer_handler(tid: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    FileService::pause_transfer(db.inner(), tid).await.map_err_to_frontend()
+}
+
+/// 重试失败的传输
+#[tauri::command]
+pub async fn retry_transfer_handler(tid: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    FileService::retry_transfer(db.inner(), tid).await.map_err_to_frontend()
+}
+
+/// 调整传输优先级（数值大者优先）
+#[tauri::command]
+pub async fn set_transfer_priority_handler(tid: i64, priority: i32) -> Result<(), String> {
+    FileService::set_transfer_priority(tid, priority).map_err_to_frontend()
+}
+
+/// 设置最大并发传输数
+#[tauri::command]
+pub async fn set_max_concurrent_transfers_handler(max_concurrent: usize, db: State<'_, DbConn>) -> Result<(), String> {
+    FileService::set_max_concurrent_transfers(db.inner(), max_concurrent);
+    Ok(())
+}
+
+/// 获取传输队列状态
+#[tauri::command]
+pub async fn get_transfer_queue_handler(db: State<'_, DbConn>) -> Result<TransferQueue, String> {
+    FileService::get_transfer_queue(db.inner()).await.map_err_to_frontend()
+}
+
+/// 发布共享文件夹（contact_uids 为空表示对所有联系人开放）
+#[tauri::command]
+pub async fn add_shared_folder_handler(
+    name: String,
+    path: String,
+    contact_uids: Vec<i64>,
+    db: State<'_, DbConn>,
+) -> Result<SharedFolderInfo, String> {
+    ShareService::add_share(db.inner(), name, path, contact_uids)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 取消共享文件夹
+#[tauri::command]
+pub async fn remove_shared_folder_handler(share_id: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    ShareService::remove_share(db.inner(), share_id).await.map_err_to_frontend()
+}
+
+/// 更新共享文件夹的访问列表
+#[tauri::command]
+pub async fn set_shared_folder_access_handler(
+    share_id: i64,
+    contact_uids: Vec<i64>,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    ShareService::set_share_access(db.inner(), share_id, contact_uids)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取本端发布的共享文件夹
+#[tauri::command]
+pub async fn get_shared_folders_handler(db: State<'_, DbConn>) -> Result<Vec<SharedFolderInfo>, String> {
+    ShareService::list_local_shares(db.inner()).await.map_err_to_frontend()
+}
+
+/// 浏览对端的共享文件夹
+#[tauri::command]
+pub async fn browse_remote_shares_handler(
+    target_ip: String,
+    db: State<'_, DbConn>,
+) -> Result<Vec<RemoteShareEntry>, String> {
+    ShareService::browse_remote_shares(db.inner(), &target_ip)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 浏览对端共享中的目录
+#[tauri::command]
+pub async fn browse_remote_share_dir_handler(
+    target_ip: String,
+    share_path: String,
+    db: State<'_, DbConn>,
+) -> Result<Vec<RemoteShareEntry>, String> {
+    ShareService::browse_remote_dir(db.inner(), &target_ip, &share_path)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 把对端共享中的文件加入下载队列
+#[tauri::command]
+pub async fn download_shared_file_handler(
+    target_ip: String,
+    share_path: String,
+    file_size: i64,
+    priority: i32,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    ShareService::queue_download(db.inner(), &target_ip, &share_path, file_size, priority)
+        .await
+        .map_err_to_frontend()
+}
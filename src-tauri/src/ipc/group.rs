@@ -1,10 +1,16 @@
 // src-tauri/src/ipc/group.rs
 //
 /// 群组相关 IPC 接口（薄层 - 只做参数转换和错误映射）
-use crate::core::group::GroupService;
+///
+/// 修改群组的操作以本机用户为操作者，由 GroupService 检查权限；
+/// 添加成员改为发送群邀请，对方接受后才加入群组
+use crate::core::group::{GroupAnnouncementService, GroupService};
 use crate::database::handler::group::{GroupHandler, GroupMemberHandler};
 use crate::database::handler::user::UserHandler;
-use crate::types::{GroupInfo, GroupMember, GroupRole, MapErrToFrontend};
+use crate::types::{
+    GroupAnnouncementAckInfo, GroupAnnouncementInfo, GroupInfo, GroupMember, GroupRequestInfo, GroupRole,
+    MapErrToFrontend,
+};
 use sea_orm::DbConn;
 use tauri::State;
 
@@ -19,10 +25,10 @@ pub async fn create_group_handler(
         .await
         .map_err_to_frontend()?;
 
-    // 添加成员（创建者已自动添加）
+    // 邀请成员（创建者已自动添加）
     for member_uid in member_uids {
         if member_uid != creator_uid {
-            GroupService::add_member(db.inner(), gid, member_uid, String::new())
+            GroupService::invite_member(db.inner(), gid, creator_uid, member_uid)
                 .await
                 .ok();
         }
@@ -45,6 +51,7 @@ pub async fn get_group_info_handler(gid: i64, db: State<'_, DbConn>) -> Result<G
         avatar: group.avatar,
         creator_uid: group.creator_uid,
         desc: group.description,
+        discoverable: group.discoverable != 0,
         create_time: group.create_time.to_string(),
     })
 }
@@ -82,7 +89,7 @@ pub async fn get_group_members_handler(gid: i64, db: State<'_, DbConn>) -> Resul
     Ok(result)
 }
 
-/// 添加群成员
+/// 添加群成员（发送群邀请）
 #[tauri::command]
 pub async fn add_group_member_handler(
     gid: i64,
@@ -90,7 +97,10 @@ pub async fn add_group_member_handler(
     _role: i8,
     db: State<'_, DbConn>,
 ) -> Result<(), String> {
-    GroupService::add_member(db.inner(), gid, member_uid, String::new())
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::invite_member(db.inner(), gid, operator_uid, member_uid)
         .await
         .map_err_to_frontend()?;
     Ok(())
@@ -99,7 +109,10 @@ pub async fn add_group_member_handler(
 /// 移除群成员
 #[tauri::command]
 pub async fn remove_group_member_handler(gid: i64, member_uid: i64, db: State<'_, DbConn>) -> Result<(), String> {
-    GroupService::remove_member(db.inner(), gid, member_uid)
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::remove_member(db.inner(), gid, operator_uid, member_uid)
         .await
         .map_err_to_frontend()?;
     Ok(())
@@ -113,7 +126,10 @@ pub async fn update_member_role_handler(
     role: i8,
     db: State<'_, DbConn>,
 ) -> Result<(), String> {
-    GroupMemberHandler::update_role(db.inner(), gid, member_uid, role)
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::update_member_role(db.inner(), gid, operator_uid, member_uid, role)
         .await
         .map_err_to_frontend()?;
     Ok(())
@@ -134,6 +150,7 @@ pub async fn get_user_groups_handler(user_uid: i64, db: State<'_, DbConn>) -> Re
             avatar: group.avatar,
             creator_uid: group.creator_uid,
             desc: group.description,
+            discoverable: group.discoverable != 0,
             create_time: group.create_time.to_string(),
         })
         .collect();
@@ -149,9 +166,13 @@ pub async fn update_group_info_handler(
     desc: String,
     db: State<'_, DbConn>,
 ) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
     GroupService::update_group(
         db.inner(),
         gid,
+        operator_uid,
         Some(group_name),
         if desc.is_empty() { None } else { Some(desc) },
         None,
@@ -161,11 +182,165 @@ pub async fn update_group_info_handler(
     Ok(())
 }
 
+/// 获取群公告（置顶在前）
+#[tauri::command]
+pub async fn get_group_announcements_handler(
+    gid: i64,
+    db: State<'_, DbConn>,
+) -> Result<Vec<GroupAnnouncementInfo>, String> {
+    let current_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupAnnouncementService::list(db.inner(), gid, current_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 发布群公告
+#[tauri::command]
+pub async fn create_group_announcement_handler(
+    gid: i64,
+    content: String,
+    pinned: bool,
+    db: State<'_, DbConn>,
+) -> Result<GroupAnnouncementInfo, String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupAnnouncementService::create(db.inner(), gid, operator_uid, &content, pinned)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 编辑群公告
+#[tauri::command]
+pub async fn update_group_announcement_handler(
+    announcement_id: i64,
+    content: String,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupAnnouncementService::edit(db.inner(), announcement_id, operator_uid, &content)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 置顶或取消置顶群公告
+#[tauri::command]
+pub async fn pin_group_announcement_handler(
+    announcement_id: i64,
+    pinned: bool,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupAnnouncementService::set_pinned(db.inner(), announcement_id, operator_uid, pinned)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 确认已读群公告
+#[tauri::command]
+pub async fn ack_group_announcement_handler(announcement_id: i64, db: State<'_, DbConn>) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupAnnouncementService::acknowledge(db.inner(), announcement_id, operator_uid)
+        .await
+        .map_err_to_frontend()
+}
+
+/// 获取群公告的成员确认情况
+#[tauri::command]
+pub async fn get_group_announcement_acks_handler(
+    announcement_id: i64,
+    db: State<'_, DbConn>,
+) -> Result<Vec<GroupAnnouncementAckInfo>, String> {
+    GroupAnnouncementService::list_acks(db.inner(), announcement_id)
+        .await
+        .map_err_to_frontend()
+}
+
 /// 删除群组
 #[tauri::command]
 pub async fn delete_group_handler(gid: i64, db: State<'_, DbConn>) -> Result<(), String> {
-    GroupService::delete_group(db.inner(), gid)
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::delete_group(db.inner(), gid, operator_uid)
+        .await
+        .map_err_to_frontend()?;
+    Ok(())
+}
+
+/// 获取待处理的群邀请和入群申请
+#[tauri::command]
+pub async fn get_group_requests_handler(db: State<'_, DbConn>) -> Result<Vec<GroupRequestInfo>, String> {
+    GroupService::list_requests(db.inner()).await.map_err_to_frontend()
+}
+
+/// 接受或拒绝群邀请
+#[tauri::command]
+pub async fn respond_group_invite_handler(request_id: i64, accept: bool, db: State<'_, DbConn>) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::respond_invitation(db.inner(), request_id, operator_uid, accept)
+        .await
+        .map_err_to_frontend()?;
+    Ok(())
+}
+
+/// 申请加入公开的群组
+#[tauri::command]
+pub async fn request_join_group_handler(
+    group_no: String,
+    group_name: String,
+    admin_uid: i64,
+    db: State<'_, DbConn>,
+) -> Result<i64, String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    let request = GroupService::request_join(db.inner(), operator_uid, &group_no, &group_name, admin_uid)
+        .await
+        .map_err_to_frontend()?;
+    Ok(request.id)
+}
+
+/// 审核入群申请
+#[tauri::command]
+pub async fn review_join_request_handler(request_id: i64, approve: bool, db: State<'_, DbConn>) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::review_join_request(db.inner(), request_id, operator_uid, approve)
+        .await
+        .map_err_to_frontend()?;
+    Ok(())
+}
+
+/// 设置群组是否允许申请加入
+#[tauri::command]
+pub async fn set_group_discoverable_handler(
+    gid: i64,
+    discoverable: bool,
+    db: State<'_, DbConn>,
+) -> Result<(), String> {
+    let operator_uid = UserHandler::get_current_user_id(db.inner())
+        .await
+        .map_err_to_frontend()?;
+    GroupService::set_discoverable(db.inner(), gid, operator_uid, discoverable)
         .await
         .map_err_to_frontend()?;
     Ok(())
 }
+
+/// 查询局域网内允许申请加入的群组（结果通过 GroupDiscovered 界面事件返回）
+#[tauri::command]
+pub async fn discover_groups_handler(db: State<'_, DbConn>) -> Result<usize, String> {
+    GroupService::discover_groups(db.inner()).await.map_err_to_frontend()
+}
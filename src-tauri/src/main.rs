@@ -26,15 +26,62 @@ async fn main() {
             ipc::chat::mark_messages_read_handler,
             ipc::chat::mark_message_read_and_send_receipt,
             ipc::chat::retry_send_message,
+            ipc::chat::recall_message_handler,
+            ipc::chat::edit_message_handler,
+            ipc::chat::get_message_revisions_handler,
+            ipc::chat::get_recall_window_handler,
+            ipc::chat::set_recall_window_handler,
+            ipc::chat::send_reply_message_handler,
+            ipc::chat::get_message_replies_handler,
+            ipc::chat::send_mention_message_handler,
+            ipc::chat::get_mentions_handler,
+            ipc::chat::get_group_read_summary_handler,
+            ipc::chat::get_group_read_members_handler,
+            ipc::chat::send_poll_handler,
+            ipc::chat::vote_poll_handler,
+            ipc::chat::close_poll_handler,
+            ipc::chat::get_poll_handler,
+            ipc::chat::react_message_handler,
+            ipc::chat::set_typing_handler,
             ipc::contact::get_contact_list_handler,
             ipc::contact::get_online_users_handler,
             ipc::file::send_file_request_handler,
+            ipc::file::send_file_to_users_handler,
+            ipc::file::send_group_file_handler,
+            ipc::file::get_file_offer_progress_handler,
+            ipc::file::collect_file_garbage_handler,
+            ipc::file::get_file_thumbnail_handler,
+            ipc::file::get_file_policies_handler,
+            ipc::file::add_file_policy_handler,
+            ipc::file::set_file_policy_enabled_handler,
+            ipc::file::delete_file_policy_handler,
+            ipc::file::get_quarantined_files_handler,
+            ipc::file::get_transfer_history_handler,
+            ipc::file::get_transfer_totals_handler,
             ipc::file::accept_file_request_handler,
+            ipc::file::accept_directory_request_handler,
             ipc::file::reject_file_request_handler,
             ipc::file::get_file_handler,
             ipc::file::cancel_upload_handler,
             ipc::file::get_pending_transfers_handler,
             ipc::file::resume_transfer_handler,
+            ipc::file::set_global_rate_limit_handler,
+            ipc::file::set_peer_rate_limit_handler,
+            ipc::file::set_transfer_rate_limit_handler,
+            ipc::file::get_rate_limits_handler,
+            ipc::file::enqueue_transfer_handler,
+            ipc::file::pause_transfer_handler,
+            ipc::file::retry_transfer_handler,
+            ipc::file::set_transfer_priority_handler,
+            ipc::file::set_max_concurrent_transfers_handler,
+            ipc::file::get_transfer_queue_handler,
+            ipc::file::add_shared_folder_handler,
+            ipc::file::remove_shared_folder_handler,
+            ipc::file::set_shared_folder_access_handler,
+            ipc::file::get_shared_folders_handler,
+            ipc::file::browse_remote_shares_handler,
+            ipc::file::browse_remote_share_dir_handler,
+            ipc::file::download_shared_file_handler,
             ipc::group::create_group_handler,
             ipc::group::get_group_info_handler,
             ipc::group::get_group_members_handler,
@@ -43,7 +90,19 @@ async fn main() {
             ipc::group::update_member_role_handler,
             ipc::group::get_user_groups_handler,
             ipc::group::update_group_info_handler,
+            ipc::group::get_group_announcements_handler,
+            ipc::group::create_group_announcement_handler,
+            ipc::group::update_group_announcement_handler,
+            ipc::group::pin_group_announcement_handler,
+            ipc::group::ack_group_announcement_handler,
+            ipc::group::get_group_announcement_acks_handler,
             ipc::group::delete_group_handler,
+            ipc::group::get_group_requests_handler,
+            ipc::group::respond_group_invite_handler,
+            ipc::group::request_join_group_handler,
+            ipc::group::review_join_request_handler,
+            ipc::group::set_group_discoverable_handler,
+            ipc::group::discover_groups_handler,
         ])
         .setup(|app| {
             let handle = app.handle().clone();
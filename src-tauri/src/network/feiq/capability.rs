@@ -0,0 +1,67 @@
+// src-tauri/src/network/feiq/capability.rs
+//
+//! 对端扩展能力
+//!
+//! 本端在上线广播、在线应答、文件附件、数据请求和数据确认包的附加标志位中声明自身能力，
+//! 收到对端带能力标志的包时记录下来。对端的上线广播/在线应答不带某能力时视为不再支持，
+//! 标准飞秋/飞鸽客户端从不设置这些标志，因此不会被误判。
+
+use crate::network::feiq::constants::FEIQ_LOCAL_CAPABILITIES;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+fn peer_capabilities() -> &'static Mutex<HashMap<String, u32>> {
+    static CAPABILITIES: OnceLock<Mutex<HashMap<String, u32>>> = OnceLock::new();
+    CAPABILITIES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 根据收到的包记录对端能力
+///
+/// # 参数
+/// * `ip` - 对端 IP
+/// * `msg_sub_type` - 包类型
+/// * `flags` - 包头附加标志位
+pub fn observe_peer_flags(ip: &str, msg_sub_type: u8, flags: u32) {
+    let capabilities = flags & FEIQ_LOCAL_CAPABILITIES;
+    let mut peers = peer_capabilities().lock().unwrap_or_else(|e| e.into_inner());
+
+    if capabilities != 0 {
+        *peers.entry(ip.to_string()).or_insert(0) |= capabilities;
+    } else if msg_sub_type == 9 || msg_sub_type == 10 {
+        // 上线广播/在线应答总是携带完整能力，不带即表示不支持
+        peers.remove(ip);
+    }
+}
+
+/// 对端是否支持指定能力
+pub fn peer_supports(ip: &str, capability: u32) -> bool {
+    peer_capabilities()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(ip)
+        .is_some_and(|caps| caps & capability != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::feiq::constants::{FEIQ_CAP_COMPRESS, FEIQ_FLAG_COMPRESSED};
+
+    #[test]
+    fn test_observe_peer_flags() {
+        let ip = "192.168.254.1";
+        assert!(!peer_supports(ip, FEIQ_CAP_COMPRESS));
+
+        observe_peer_flags(ip, 0x60, FEIQ_CAP_COMPRESS);
+        assert!(peer_supports(ip, FEIQ_CAP_COMPRESS));
+
+        // 普通包和数据块标志不影响已记录的能力
+        observe_peer_flags(ip, 0x20, 0);
+        observe_peer_flags(ip, 0x61, FEIQ_FLAG_COMPRESSED);
+        assert!(peer_supports(ip, FEIQ_CAP_COMPRESS));
+
+        // 上线广播不带能力标志：对端已换成不支持的客户端
+        observe_peer_flags(ip, 9, 0);
+        assert!(!peer_supports(ip, FEIQ_CAP_COMPRESS));
+    }
+}
@@ -183,3 +183,95 @@ pub const IPMSG_FILE_CREATETIME: u32 = 0x00000001;
 /// 文件修改时间
 #[allow(dead_code)]
 pub const IPMSG_FILE_MTIME: u32 = 0x00000002;
+
+// ============================================================
+// 扩展能力（包头附加标志位，非标准扩展）
+// ============================================================
+
+/// 支持文件数据块压缩
+pub const FEIQ_CAP_COMPRESS: u32 = 0x00000001;
+
+/// 文件数据块已压缩（仅出现在文件数据包中）
+pub const FEIQ_FLAG_COMPRESSED: u32 = 0x00000002;
+
+/// 文件附件请求（仅出现在文件附件包中，用于与普通消息区分）
+pub const FEIQ_FLAG_FILEATTACH: u32 = 0x00000004;
+
+/// 引用回复（仅出现在消息包中，remark 首行为 ">> 原消息编号 引用摘要"）
+pub const FEIQ_FLAG_REPLY: u32 = 0x00000010;
+
+/// 投票（仅出现在群消息包中，remark 首行为 "## 投票编号 选项标志 截止时间"）
+pub const FEIQ_FLAG_POLL: u32 = 0x00000040;
+
+/// 支持消息撤回与编辑
+pub const FEIQ_CAP_MSGEDIT: u32 = 0x00000008;
+
+/// 支持群消息信封与群状态同步
+pub const FEIQ_CAP_GROUP: u32 = 0x00000020;
+
+/// 支持消息表情回应
+pub const FEIQ_CAP_REACTION: u32 = 0x00000080;
+
+/// 支持正在输入提示
+pub const FEIQ_CAP_TYPING: u32 = 0x00000100;
+
+/// 本端声明的扩展能力
+pub const FEIQ_LOCAL_CAPABILITIES: u32 =
+    FEIQ_CAP_COMPRESS | FEIQ_CAP_MSGEDIT | FEIQ_CAP_GROUP | FEIQ_CAP_REACTION | FEIQ_CAP_TYPING;
+
+// ============================================================
+// 消息撤回与编辑（非标准扩展，仅发给声明 FEIQ_CAP_MSGEDIT 的对端）
+// ============================================================
+
+/// 撤回消息，remark 为原消息编号
+pub const FEIQ_RECALLMSG: u32 = 0x00000033;
+
+/// 编辑消息，remark 为 "原消息编号:新内容"
+pub const FEIQ_EDITMSG: u32 = 0x00000034;
+
+// ============================================================
+// 群聊（非标准扩展，仅发给声明 FEIQ_CAP_GROUP 的对端）
+// ============================================================
+
+/// 群消息，remark 首行为 "群编号 群内消息序号 发送者机器标识"，其后为消息内容
+pub const FEIQ_GROUPMSG: u32 = 0x00000035;
+
+/// 群状态同步，remark 首行为群编号，其后每行一个群操作 "时钟 来源 类型 成员 值"
+pub const FEIQ_GROUPSYNC: u32 = 0x00000036;
+
+/// 群邀请与入群申请，remark 为 "请求编号 动作 群编号 过期时间\n群名称"
+pub const FEIQ_GROUPREQ: u32 = 0x00000037;
+
+/// 投票、投票确认和投票结果，remark 首行为 "投票编号 动作"（vote/ack/result），见 `make_feiq_poll_vote_packet`
+pub const FEIQ_POLLMSG: u32 = 0x00000038;
+
+// ============================================================
+// 表情回应（非标准扩展，仅发给声明 FEIQ_CAP_REACTION 的对端）
+// ============================================================
+
+/// 表情回应，remark 为 "原消息编号 表情 群编号"（取消回应时表情为 "-"，单聊时群编号为 "-"）
+pub const FEIQ_REACTMSG: u32 = 0x00000039;
+
+// ============================================================
+// 正在输入（非标准扩展，仅发给声明 FEIQ_CAP_TYPING 的对端）
+// ============================================================
+
+/// 输入状态，remark 为 "状态 群编号"（1=正在输入, 0=停止输入；单聊时群编号为 "-"）
+pub const FEIQ_TYPINGMSG: u32 = 0x0000003A;
+
+// ============================================================
+// 共享文件夹（非标准扩展，仅 TCP 通道）
+// ============================================================
+
+/// 列出对端向本端开放的共享文件夹
+pub const FEIQ_LISTSHARES: u32 = 0x00000070;
+
+/// 列出共享文件夹中的一个目录
+pub const FEIQ_LISTDIR: u32 = 0x00000071;
+
+// ============================================================
+// 图片缩略图（非标准扩展，仅 TCP 通道）
+// ============================================================
+
+/// 获取图片附件的缩略图
+pub const FEIQ_GETTHUMB: u32 = 0x00000072;
@@ -1,6 +1,7 @@
 // src-tauri/src/network/feiq/mod.rs
 //
 //! 飞秋协议模块
+pub mod capability;
 pub mod constants;
 pub mod model;
 pub mod packer;
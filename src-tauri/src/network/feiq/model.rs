@@ -5,7 +5,9 @@
 use serde::{Deserialize, Serialize};
 
 // 导入常量
-use crate::network::feiq::constants::{IPMSG_FILEATTACHOPT, IPMSG_SENDCHECKOPT, IPMSG_UTF8OPT};
+use crate::network::feiq::constants::{
+    FEIQ_FLAG_FILEATTACH, FEIQ_FLAG_POLL, FEIQ_FLAG_REPLY, IPMSG_FILEATTACHOPT, IPMSG_SENDCHECKOPT, IPMSG_UTF8OPT,
+};
 // 导入工具函数
 use crate::network::feiq::utils::{format_mac_addr, timestamp_to_local};
 
@@ -24,13 +26,19 @@ pub struct FileAttachment {
     pub mtime: u64,
     /// 文件属性 (1=普通文件, 2=目录)
     pub attr: u32,
+    /// 图片尺寸 (宽, 高)，非图片文件为 None
+    #[serde(default)]
+    pub image_size: Option<(u32, u32)>,
+    /// 发送方的文件ID（请求文件数据时使用），未携带时为 0
+    #[serde(default)]
+    pub file_id: u64,
 }
 
 impl FileAttachment {
     /// 从 IPMsg 文件头字符串解析
     ///
-    /// 格式: `文件名:大小:修改时间:属性`
-    /// 多个文件用 \x07 分隔
+    /// 格式: `文件名:大小:修改时间:属性[:宽x高][:#文件ID]`
+    /// 多个文件用 \x07 分隔，扩展字段可选
     #[allow(dead_code)]
     pub fn from_ipmsg_header(s: &str) -> Result<Vec<Self>, String> {
         let mut files = Vec::new();
@@ -45,12 +53,16 @@ impl FileAttachment {
             let mtime = parts[2].parse::<u64>().map_err(|_| "Invalid mtime".to_string())?;
             let attr = parts[3].parse::<u32>().map_err(|_| "Invalid file attr".to_string())?;
 
-            files.push(FileAttachment {
+            let mut file = FileAttachment {
                 file_name,
                 file_size,
                 mtime,
                 attr,
-            });
+                image_size: None,
+                file_id: 0,
+            };
+            file.apply_extensions(&parts[4..]);
+            files.push(file);
         }
         Ok(files)
     }
@@ -58,7 +70,35 @@ impl FileAttachment {
     /// 转换为 IPMsg 文件头字符串
     #[allow(dead_code)]
     pub fn to_ipmsg_header(&self) -> String {
-        format!("{}:{}:{}:{}", self.file_name, self.file_size, self.mtime, self.attr)
+        let mut header = format!("{}:{}:{}:{}", self.file_name, self.file_size, self.mtime, self.attr);
+        if let Some((width, height)) = self.image_size {
+            header.push_str(&format!(":{}x{}", width, height));
+        }
+        if self.file_id != 0 {
+            header.push_str(&format!(":#{}", self.file_id));
+        }
+        header
+    }
+
+    /// 解析属性之后的扩展字段
+    ///
+    /// 不认识的扩展字段直接忽略，保持对其他客户端的兼容
+    pub fn apply_extensions(&mut self, fields: &[&str]) {
+        for field in fields {
+            if let Some(id) = field.strip_prefix('#') {
+                self.file_id = id.parse::<u64>().unwrap_or(0);
+            } else if let Some(size) = Self::parse_image_size(field) {
+                self.image_size = Some(size);
+            }
+        }
+    }
+
+    /// 解析图片尺寸字段 `宽x高`
+    pub fn parse_image_size(s: &str) -> Option<(u32, u32)> {
+        let (width, height) = s.split_once('x')?;
+        let width = width.parse::<u32>().ok()?;
+        let height = height.parse::<u32>().ok()?;
+        (width > 0 && height > 0).then_some((width, height))
     }
 
     /// 检查是否为目录
@@ -68,6 +108,161 @@ impl FileAttachment {
     }
 }
 
+// ============================================================
+// 群聊相关
+// ============================================================
+
+/// 群消息信封中表示 @所有人 的提及
+pub const MENTION_ALL: &str = "all";
+
+/// 群消息信封（FEIQ_GROUPMSG 的 remark 首行）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupEnvelope {
+    /// 群编号（创建者生成，各端一致）
+    pub group_no: String,
+
+    /// 群内消息序号（按发送者递增，重发时不变）
+    pub group_msg_no: i64,
+
+    /// 发送者机器标识（"ip:port"）
+    pub sender: String,
+
+    /// 被提及成员的机器标识（@所有人 为 `MENTION_ALL`）
+    pub mentions: Vec<String>,
+}
+
+impl GroupEnvelope {
+    /// 解析发送者机器标识，返回 (ip, port)
+    pub fn sender_addr(&self) -> Option<(String, u16)> {
+        let (ip, port) = self.sender.rsplit_once(':')?;
+        let port = port.parse::<u16>().ok()?;
+        (!ip.is_empty()).then(|| (ip.to_string(), port))
+    }
+}
+
+/// 群操作（群状态同步包中的一行）
+///
+/// 同一群组内按 (时钟, 来源) 排序，各端按相同规则合并后得到一致的群状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupSyncOp {
+    /// Lamport 时钟
+    pub clock: i64,
+
+    /// 发起操作的成员机器标识（"ip:port"）
+    pub origin: String,
+
+    /// 操作类型（create/invite/join/leave/kick/rename/describe/role）
+    pub kind: String,
+
+    /// 操作涉及的成员机器标识
+    pub member: Option<String>,
+
+    /// 操作值（群名称、群描述或角色）
+    pub value: Option<String>,
+}
+
+/// 群状态同步（FEIQ_GROUPSYNC 的 remark）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupSync {
+    /// 群编号
+    pub group_no: String,
+
+    /// 群操作（完整记录或其中一段）
+    pub ops: Vec<GroupSyncOp>,
+
+    /// 发送者压缩后的完整记录中的操作数
+    #[serde(default)]
+    pub count: usize,
+
+    /// 发送者压缩后的完整记录中的最大时钟
+    #[serde(default)]
+    pub head: i64,
+}
+
+/// 群邀请/入群申请（FEIQ_GROUPREQ 的 remark）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupRequest {
+    /// 请求编号（发起方生成，应答时原样带回）
+    pub request_no: String,
+
+    /// 动作（invite/join/accept/decline/discover/listing）
+    pub action: String,
+
+    /// 群编号（群组发现请求为 "-"）
+    pub group_no: String,
+
+    /// 过期时间（Unix 时间戳，秒）
+    pub expire_at: i64,
+
+    /// 群名称
+    pub group_name: String,
+}
+
+/// 投票选项标志：多选
+pub const POLL_FLAG_MULTIPLE: u8 = 0x01;
+
+/// 投票选项标志：匿名
+pub const POLL_FLAG_ANONYMOUS: u8 = 0x02;
+
+/// 投票（带 FEIQ_FLAG_POLL 的群消息）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollSpec {
+    /// 投票编号（创建者生成，各端一致）
+    pub poll_no: String,
+
+    /// 问题
+    pub question: String,
+
+    /// 选项
+    pub options: Vec<String>,
+
+    /// 是否多选
+    pub multiple: bool,
+
+    /// 是否匿名（结果中不带投票人）
+    pub anonymous: bool,
+
+    /// 截止时间（Unix 时间戳，秒）
+    pub deadline: Option<i64>,
+}
+
+/// 投票结果（创建者汇总后广播，FEIQ_POLLMSG 的 result）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PollResult {
+    /// 投票编号
+    pub poll_no: String,
+
+    /// 是否已结束
+    pub closed: bool,
+
+    /// 投票人数
+    pub voter_count: i64,
+
+    /// 每个选项的票数
+    pub counts: Vec<i64>,
+
+    /// 每个选项的投票人机器标识（匿名投票为空）
+    pub voters: Vec<Vec<String>>,
+}
+
+/// 表情回应（FEIQ_REACTMSG 的 remark）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reaction {
+    /// 被回应消息的消息编号
+    pub msg_no: String,
+
+    /// 表情（取消回应时为 None）
+    pub emoji: Option<String>,
+
+    /// 群编号（单聊时为 None）
+    pub group_no: Option<String>,
+
+    /// 被回应消息的发送者机器标识（单聊时为 None）
+    ///
+    /// 群消息的消息编号由各发送者各自生成，需要和发送者一起定位消息
+    pub msg_sender: Option<String>,
+}
+
 // ============================================================
 // 协议类型枚举
 // ============================================================
@@ -155,6 +350,306 @@ impl FeiQPacket {
     pub fn local_timestamp(&self) -> String {
         self.ext_info.timestamp_local.clone()
     }
+
+    /// 解析文件释放包的 remark，返回 (packet_no, 拒绝原因)
+    ///
+    /// remark 格式: "packet_no[:原因]"
+    pub fn release_info(&self) -> (String, Option<String>) {
+        let remark = &self.ext_info.remark;
+        match remark.split_once(':') {
+            Some((packet_no, reason)) if !reason.is_empty() => (packet_no.to_string(), Some(reason.to_string())),
+            Some((packet_no, _)) => (packet_no.to_string(), None),
+            None => (remark.clone(), None),
+        }
+    }
+
+    /// 检查命令字中是否包含某个 IPMsg 选项标志
+    pub fn has_option(&self, flag: u32) -> bool {
+        self.client_version & flag != 0
+    }
+
+    /// 是否为文件附件请求
+    ///
+    /// 飞秋扩展用 FEIQ_FLAG_FILEATTACH 标记，标准 IPMsg 客户端在命令字中带 IPMSG_FILEATTACHOPT
+    pub fn is_file_offer(&self) -> bool {
+        self.extra_flag & FEIQ_FLAG_FILEATTACH != 0 || self.has_option(IPMSG_FILEATTACHOPT)
+    }
+
+    /// 解析引用回复消息，返回 (原消息编号, 引用摘要, 回复内容)
+    ///
+    /// 只有带 FEIQ_FLAG_REPLY 标志且首行为 ">> 原消息编号 引用摘要" 的消息包才是引用回复
+    pub fn reply_info(&self) -> Option<(String, String, String)> {
+        if self.extra_flag & FEIQ_FLAG_REPLY == 0 {
+            return None;
+        }
+
+        let (header, content) = self.ext_info.remark.split_once('\n')?;
+        let header = header.strip_prefix(">> ")?;
+        let (msg_no, quote) = header.split_once(' ').unwrap_or((header, ""));
+        Some((msg_no.to_string(), quote.to_string(), content.to_string()))
+    }
+
+    /// 拆开群消息包，返回信封和其中的普通消息包
+    ///
+    /// 内层消息包的 remark 为信封之后的内容，引用回复等标志保持不变
+    pub fn open_group_envelope(&self) -> Option<(GroupEnvelope, FeiQPacket)> {
+        let (header, content) = self.ext_info.remark.split_once('\n')?;
+        let mut fields = header.split(' ');
+        let group_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        let group_msg_no = fields.next()?.parse::<i64>().ok()?;
+        let sender = fields.next()?.to_string();
+        let mentions = fields
+            .next()
+            .map(|s| s.split(',').filter(|m| !m.is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+
+        let mut inner = self.clone();
+        inner.ext_info.msg_sub_type = 0x20;
+        inner.ext_info.remark = content.to_string();
+
+        let envelope = GroupEnvelope {
+            group_no,
+            group_msg_no,
+            sender,
+            mentions,
+        };
+        Some((envelope, inner))
+    }
+
+    /// 解析群状态同步包的 remark
+    ///
+    /// 格式不正确的操作行会被忽略
+    pub fn group_sync_info(&self) -> Option<GroupSync> {
+        let mut lines = self.ext_info.remark.split('\n');
+        let mut header = lines.next()?.split_whitespace();
+        let group_no = header.next()?.to_string();
+        let count = header.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let head = header.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let ops = lines
+            .filter_map(|line| {
+                let mut fields = line.splitn(5, ' ');
+                let clock = fields.next()?.parse::<i64>().ok()?;
+                let origin = fields.next()?.to_string();
+                let kind = fields.next()?.to_string();
+                let member = fields.next().filter(|s| *s != "-").map(|s| s.to_string());
+                let value = fields.next().filter(|s| !s.is_empty()).map(|s| s.to_string());
+                Some(GroupSyncOp {
+                    clock,
+                    origin,
+                    kind,
+                    member,
+                    value,
+                })
+            })
+            .collect();
+
+        Some(GroupSync {
+            group_no,
+            ops,
+            count,
+            head,
+        })
+    }
+
+    /// 解析群邀请/入群申请包的 remark
+    ///
+    /// remark 格式: "请求编号 动作 群编号 过期时间\n群名称"
+    pub fn group_request_info(&self) -> Option<GroupRequest> {
+        let (header, group_name) = self
+            .ext_info
+            .remark
+            .split_once('\n')
+            .unwrap_or((self.ext_info.remark.as_str(), ""));
+        let mut fields = header.split(' ');
+        let request_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        let action = fields.next()?.to_string();
+        let group_no = fields.next()?.to_string();
+        let expire_at = fields.next()?.parse::<i64>().ok()?;
+
+        Some(GroupRequest {
+            request_no,
+            action,
+            group_no,
+            expire_at,
+            group_name: group_name.to_string(),
+        })
+    }
+
+    /// 解析表情回应包的 remark
+    ///
+    /// remark 格式: "原消息编号 表情 群编号 原消息发送者"
+    /// （取消回应时表情为 "-"，单聊时群编号和原消息发送者为 "-"）
+    pub fn reaction_info(&self) -> Option<Reaction> {
+        let mut fields = self.ext_info.remark.split(' ');
+        let msg_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        let emoji = fields.next().filter(|s| !s.is_empty())?;
+        let group_no = fields.next().filter(|s| !s.is_empty())?;
+        let msg_sender = fields.next().filter(|s| !s.is_empty())?;
+        if fields.next().is_some() {
+            return None;
+        }
+
+        Some(Reaction {
+            msg_no,
+            emoji: (emoji != "-").then(|| emoji.to_string()),
+            group_no: (group_no != "-").then(|| group_no.to_string()),
+            msg_sender: (msg_sender != "-").then(|| msg_sender.to_string()),
+        })
+    }
+
+    /// 解析输入状态包的 remark，返回 (是否正在输入, 群编号)
+    ///
+    /// remark 格式: "状态 群编号"（1=正在输入, 0=停止输入；单聊时群编号为 "-"）
+    pub fn typing_info(&self) -> Option<(bool, Option<String>)> {
+        let (state, group_no) = self.ext_info.remark.split_once(' ')?;
+        let typing = match state {
+            "1" => true,
+            "0" => false,
+            _ => return None,
+        };
+        if group_no.is_empty() || group_no.contains(' ') {
+            return None;
+        }
+        Some((typing, (group_no != "-").then(|| group_no.to_string())))
+    }
+
+    /// 解析投票消息
+    ///
+    /// 只有带 FEIQ_FLAG_POLL 标志的消息才是投票，remark 格式:
+    /// "## 投票编号 选项标志 截止时间\n问题\n1. 选项\n2. 选项..."（没有截止时间为 0）
+    pub fn poll_info(&self) -> Option<PollSpec> {
+        if self.extra_flag & FEIQ_FLAG_POLL == 0 {
+            return None;
+        }
+
+        let mut lines = self.ext_info.remark.split('\n');
+        let header = lines.next()?.strip_prefix("## ")?;
+        let mut fields = header.split(' ');
+        let poll_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        let flags = fields.next()?.parse::<u8>().ok()?;
+        let deadline = fields.next()?.parse::<i64>().ok()?;
+        let question = lines.next()?.to_string();
+        let options: Vec<String> = lines
+            .enumerate()
+            .map(|(i, line)| {
+                let prefix = format!("{}. ", i + 1);
+                line.strip_prefix(prefix.as_str()).unwrap_or(line).to_string()
+            })
+            .collect();
+        if options.len() < 2 {
+            return None;
+        }
+
+        Some(PollSpec {
+            poll_no,
+            question,
+            options,
+            multiple: flags & POLL_FLAG_MULTIPLE != 0,
+            anonymous: flags & POLL_FLAG_ANONYMOUS != 0,
+            deadline: (deadline > 0).then_some(deadline),
+        })
+    }
+
+    /// 解析投票包的 remark，返回 (投票编号, 所选选项序号)
+    ///
+    /// remark 格式: "投票编号 vote 选项序号,选项序号"
+    pub fn poll_vote_info(&self) -> Option<(String, Vec<usize>)> {
+        let mut fields = self.ext_info.remark.split(' ');
+        let poll_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        if fields.next()? != "vote" {
+            return None;
+        }
+        let choices = fields
+            .next()?
+            .split(',')
+            .map(|c| c.parse::<usize>().ok())
+            .collect::<Option<Vec<usize>>>()?;
+        Some((poll_no, choices))
+    }
+
+    /// 解析投票确认包的 remark
+    ///
+    /// remark 格式: "投票编号 ack 是否计入 选项序号,选项序号"，返回 (投票编号, 是否计入, 所确认的选项)
+    pub fn poll_vote_ack_info(&self) -> Option<(String, bool, Vec<usize>)> {
+        let mut fields = self.ext_info.remark.split(' ');
+        let poll_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        if fields.next()? != "ack" {
+            return None;
+        }
+        let accepted = fields.next()? == "1";
+        let choices = fields
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|c| !c.is_empty())
+            .map(|c| c.parse::<usize>().ok())
+            .collect::<Option<Vec<usize>>>()?;
+        Some((poll_no, accepted, choices))
+    }
+
+    /// 解析投票结果包的 remark
+    ///
+    /// remark 格式: "投票编号 result 是否结束 投票人数"，其后每行一个选项 "票数 投票人,投票人"
+    pub fn poll_result_info(&self) -> Option<PollResult> {
+        let mut lines = self.ext_info.remark.split('\n');
+        let mut fields = lines.next()?.split(' ');
+        let poll_no = fields.next().filter(|s| !s.is_empty())?.to_string();
+        if fields.next()? != "result" {
+            return None;
+        }
+        let closed = fields.next()? == "1";
+        let voter_count = fields.next()?.parse::<i64>().ok()?;
+
+        let mut counts = Vec::new();
+        let mut voters = Vec::new();
+        for line in lines {
+            let (count, option_voters) = line.split_once(' ').unwrap_or((line, ""));
+            counts.push(count.parse::<i64>().ok()?);
+            voters.push(
+                option_voters
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+
+        Some(PollResult {
+            poll_no,
+            closed,
+            voter_count,
+            counts,
+            voters,
+        })
+    }
+
+    /// 解析撤回/编辑包的 remark，返回 (原消息编号, 新内容)
+    ///
+    /// remark 格式: "msg_no[:新内容]"，撤回包没有新内容
+    pub fn revision_info(&self) -> (String, String) {
+        match self.ext_info.remark.split_once(':') {
+            Some((msg_no, content)) => (msg_no.to_string(), content.to_string()),
+            None => (self.ext_info.remark.clone(), String::new()),
+        }
+    }
+
+    /// 解析撤回包的 remark，返回 (原消息编号, 发送方的撤回时限秒数)
+    ///
+    /// remark 格式: "msg_no[:撤回时限]"，旧版本的撤回包没有撤回时限
+    pub fn recall_info(&self) -> (String, Option<u64>) {
+        let (msg_no, window) = self.revision_info();
+        (msg_no, window.trim().parse().ok())
+    }
+
+    /// 接收确认/已读回执包所确认的消息编号
+    ///
+    /// 消息编号在 remark 中，remark 为空时使用包编号
+    pub fn receipt_msg_no(&self) -> String {
+        match self.ext_info.remark.trim() {
+            "" => self.ext_info.unique_id.clone(),
+            msg_no => msg_no.to_string(),
+        }
+    }
 }
 
 /// 飞秋协议数据包（支持 IPMsg 和 FeiQ 两种格式）
@@ -357,6 +852,56 @@ impl ProtocolPacket {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_file_attachment_image_size() {
+        let file = FileAttachment {
+            file_name: "shot.png".to_string(),
+            file_size: 2048,
+            mtime: 1700000000,
+            attr: 1,
+            image_size: Some((1920, 1080)),
+            file_id: 0,
+        };
+        let header = file.to_ipmsg_header();
+        assert_eq!(header, "shot.png:2048:1700000000:1:1920x1080");
+        assert_eq!(FileAttachment::from_ipmsg_header(&header).unwrap(), vec![file]);
+
+        // 其他客户端的扩展字段不影响解析
+        let files = FileAttachment::from_ipmsg_header("a.txt:10:0:1:abc").unwrap();
+        assert_eq!(files[0].image_size, None);
+    }
+
+    #[test]
+    fn test_file_attachment_file_id() {
+        let file = FileAttachment {
+            file_name: "report.pdf".to_string(),
+            file_size: 4096,
+            mtime: 0,
+            attr: 1,
+            image_size: None,
+            file_id: 17,
+        };
+        let header = file.to_ipmsg_header();
+        assert_eq!(header, "report.pdf:4096:0:1:#17");
+        assert_eq!(FileAttachment::from_ipmsg_header(&header).unwrap(), vec![file]);
+    }
+
+    #[test]
+    fn test_feiq_file_offer_detection() {
+        let mut packet = FeiQPacket {
+            client_version: 4001,
+            ..Default::default()
+        };
+        assert!(!packet.is_file_offer());
+
+        packet.client_version = 0x20 | IPMSG_FILEATTACHOPT;
+        assert!(packet.is_file_offer());
+
+        packet.client_version = 4001;
+        packet.extra_flag = FEIQ_FLAG_FILEATTACH;
+        assert!(packet.is_file_offer());
+    }
+
     // ProtocolPacket tests commented out - protocol unification in progress
     // TODO: Remove ProtocolPacket struct entirely once all migration is complete
 
@@ -1,8 +1,17 @@
 // src-tauri/src/network/feiq/packer.rs
 //
 /// 飞秋协议封装器
-use crate::network::feiq::model::{FeiQExtInfo, FeiQPacket};
+use crate::network::feiq::constants::{
+    FEIQ_EDITMSG, FEIQ_FLAG_FILEATTACH, FEIQ_FLAG_POLL, FEIQ_FLAG_REPLY, FEIQ_GETTHUMB, FEIQ_GROUPMSG, FEIQ_GROUPREQ,
+    FEIQ_GROUPSYNC, FEIQ_LISTDIR, FEIQ_LISTSHARES, FEIQ_LOCAL_CAPABILITIES, FEIQ_POLLMSG, FEIQ_REACTMSG,
+    FEIQ_RECALLMSG, FEIQ_TYPINGMSG,
+};
+use crate::network::feiq::model::{
+    FeiQExtInfo, FeiQPacket, GroupEnvelope, GroupRequest, GroupSync, PollResult, PollSpec, Reaction,
+    POLL_FLAG_ANONYMOUS, POLL_FLAG_MULTIPLE,
+};
 use crate::network::feiq::utils::{format_mac_addr, timestamp_to_local};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// 获取当前用户信息
@@ -38,9 +47,13 @@ fn get_mac_address() -> String {
 }
 
 /// 生成唯一包 ID
+///
+/// 格式: "T" + 10 位秒级时间戳 + 4 位序号，同一秒内发出的多个包（如撤回紧跟原消息）不会重号
 fn generate_packet_id() -> String {
+    static SEQUENCE: AtomicU32 = AtomicU32::new(0);
     let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
-    format!("T{:010}", timestamp % 10000000000)
+    let seq = SEQUENCE.fetch_add(1, Ordering::Relaxed) % 10000;
+    format!("T{:010}{:04}", timestamp % 10000000000, seq)
 }
 
 // ============================================================
@@ -54,7 +67,7 @@ impl FeiQPacket {
 
     /// 创建 FeiQ 格式的在线广播包
     ///
-    /// 格式: 1_lbt6_0#128#MAC#端口#0#0#4001#9:时间戳:包ID:主机名:用户ID:备注
+    /// 格式: 1_lbt6_0#128#MAC#端口#0#能力标志#4001#9:时间戳:包ID:主机名:用户ID:备注
     pub fn make_feiq_entry_packet(nickname: Option<&str>) -> FeiQPacket {
         let (username, hostname, _ip, _port) = get_system_user_info();
         let mac_addr = get_mac_address();
@@ -73,7 +86,7 @@ impl FeiQPacket {
             mac_addr_formatted: mac_formatted,
             udp_port: 2425,
             file_transfer_id: 0,
-            extra_flag: 0,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES,
             client_version: 0x4001,
             ext_info: FeiQExtInfo {
                 msg_sub_type: 9, // 在线广播
@@ -135,6 +148,128 @@ impl FeiQPacket {
         }
     }
 
+    /// 创建引用回复消息包
+    ///
+    /// remark 首行为 ">> 原消息编号 引用摘要"，其后为回复内容；
+    /// 不识别 FEIQ_FLAG_REPLY 的客户端会把引用行当作正文显示，仍能看到上下文
+    pub fn make_feiq_reply_packet(
+        content: &str,
+        reply_msg_no: &str,
+        quote: &str,
+        nickname: Option<&str>,
+    ) -> FeiQPacket {
+        let remark = format!(">> {} {}\n{}", reply_msg_no, quote, content);
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.extra_flag |= FEIQ_FLAG_REPLY;
+        packet
+    }
+
+    /// 创建投票消息包（之后加上群消息信封发送）
+    ///
+    /// remark 首行为 "## 投票编号 选项标志 截止时间"，其后为问题和编号的选项；
+    /// 不识别 FEIQ_FLAG_POLL 的客户端会把投票当作普通消息显示
+    pub fn make_feiq_poll_packet(poll: &PollSpec, nickname: Option<&str>) -> FeiQPacket {
+        let mut flags = 0;
+        if poll.multiple {
+            flags |= POLL_FLAG_MULTIPLE;
+        }
+        if poll.anonymous {
+            flags |= POLL_FLAG_ANONYMOUS;
+        }
+        let mut remark = format!(
+            "## {} {} {}\n{}",
+            poll.poll_no,
+            flags,
+            poll.deadline.unwrap_or(0),
+            poll.question.replace(['\n', '\r'], " ")
+        );
+        for (i, option) in poll.options.iter().enumerate() {
+            remark.push_str(&format!("\n{}. {}", i + 1, option.replace(['\n', '\r'], " ")));
+        }
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.extra_flag |= FEIQ_FLAG_POLL;
+        packet
+    }
+
+    /// 创建投票包（发给投票创建者）
+    ///
+    /// 格式: remark 为 "投票编号 vote 选项序号,选项序号"
+    pub fn make_feiq_poll_vote_packet(poll_no: &str, choices: &[usize], nickname: Option<&str>) -> FeiQPacket {
+        let choices: Vec<String> = choices.iter().map(|c| c.to_string()).collect();
+        let remark = format!("{} vote {}", poll_no, choices.join(","));
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_POLLMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建投票确认包（创建者收到投票后回复投票人）
+    ///
+    /// 格式: remark 为 "投票编号 ack 是否计入 选项序号,选项序号"（投票已结束或选择无效时为 0）
+    pub fn make_feiq_poll_vote_ack_packet(
+        poll_no: &str,
+        accepted: bool,
+        choices: &[usize],
+        nickname: Option<&str>,
+    ) -> FeiQPacket {
+        let choices: Vec<String> = choices.iter().map(|c| c.to_string()).collect();
+        let remark = format!("{} ack {} {}", poll_no, accepted as u8, choices.join(","));
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_POLLMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建投票结果包（创建者广播给群成员）
+    ///
+    /// 格式: remark 首行为 "投票编号 result 是否结束 投票人数"，其后每行一个选项 "票数 投票人,投票人"
+    pub fn make_feiq_poll_result_packet(result: &PollResult, nickname: Option<&str>) -> FeiQPacket {
+        let mut remark = format!("{} result {} {}", result.poll_no, result.closed as u8, result.voter_count);
+        for (i, count) in result.counts.iter().enumerate() {
+            let voters = result.voters.get(i).map(|v| v.join(",")).unwrap_or_default();
+            remark.push_str(&format!("\n{} {}", count, voters));
+        }
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_POLLMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建表情回应包
+    ///
+    /// 格式: remark 为 "原消息编号 表情 群编号 原消息发送者"
+    /// （取消回应时表情为 "-"，单聊时群编号和原消息发送者为 "-"）
+    pub fn make_feiq_reaction_packet(reaction: &Reaction, nickname: Option<&str>) -> FeiQPacket {
+        let remark = format!(
+            "{} {} {} {}",
+            reaction.msg_no,
+            reaction.emoji.as_deref().unwrap_or("-"),
+            reaction.group_no.as_deref().unwrap_or("-"),
+            reaction.msg_sender.as_deref().unwrap_or("-")
+        );
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_REACTMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建输入状态包
+    ///
+    /// 格式: remark 为 "状态 群编号"（1=正在输入, 0=停止输入；单聊时群编号为 "-"）
+    pub fn make_feiq_typing_packet(typing: bool, group_no: Option<&str>, nickname: Option<&str>) -> FeiQPacket {
+        let remark = format!("{} {}", typing as u8, group_no.unwrap_or("-"));
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_TYPINGMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
     /// 创建 FeiQ 格式的接收确认包 (RECVMSG)
     pub fn make_feiq_recv_packet(msg_no: &str) -> FeiQPacket {
         let (username, hostname, _ip, _port) = get_system_user_info();
@@ -179,6 +314,77 @@ impl FeiQPacket {
         packet
     }
 
+    /// 创建撤回消息包
+    ///
+    /// 格式: remark 为 "被撤回消息的编号:撤回时限秒数"（0 表示不限制），
+    /// 接收方按发送方的撤回时限判断撤回是否有效
+    pub fn make_feiq_recall_packet(msg_no: &str, window_secs: u64, nickname: Option<&str>) -> FeiQPacket {
+        let mut packet = Self::make_feiq_message_packet(&format!("{}:{}", msg_no, window_secs), nickname);
+        packet.ext_info.msg_sub_type = FEIQ_RECALLMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建编辑消息包
+    ///
+    /// 格式: remark 为 "原消息编号:新内容"（新内容中可以包含冒号）
+    pub fn make_feiq_edit_packet(msg_no: &str, content: &str, nickname: Option<&str>) -> FeiQPacket {
+        let mut packet = Self::make_feiq_message_packet(&format!("{}:{}", msg_no, content), nickname);
+        packet.ext_info.msg_sub_type = FEIQ_EDITMSG as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 为消息包加上群消息信封
+    ///
+    /// 格式: remark 首行为 "群编号 群内消息序号 发送者机器标识 [提及]"，其后为原消息包的 remark。
+    /// 提及为逗号分隔的机器标识，没有提及时省略
+    pub fn make_feiq_group_packet(inner: &FeiQPacket, envelope: &GroupEnvelope) -> FeiQPacket {
+        let mut packet = inner.clone();
+        packet.ext_info.msg_sub_type = FEIQ_GROUPMSG as u8;
+        let mut header = format!("{} {} {}", envelope.group_no, envelope.group_msg_no, envelope.sender);
+        if !envelope.mentions.is_empty() {
+            header.push(' ');
+            header.push_str(&envelope.mentions.join(","));
+        }
+        packet.ext_info.remark = format!("{}\n{}", header, inner.ext_info.remark);
+        packet.extra_flag |= FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建群状态同步包
+    ///
+    /// 格式: remark 首行为 "群编号 操作数 最大时钟"，其后每行一个群操作 "时钟 来源 类型 成员 值"（没有成员时为 "-"）
+    pub fn make_feiq_group_sync_packet(sync: &GroupSync, nickname: Option<&str>) -> FeiQPacket {
+        let mut remark = format!("{} {} {}", sync.group_no, sync.count, sync.head);
+        for op in &sync.ops {
+            let value = op.value.as_deref().unwrap_or_default().replace(['\n', '\r'], " ");
+            let member = op.member.as_deref().unwrap_or("-");
+            remark.push_str(&format!("\n{} {} {} {} {}", op.clock, op.origin, op.kind, member, value));
+        }
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_GROUPSYNC as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
+    /// 创建群邀请/入群申请包
+    ///
+    /// 格式: remark 为 "请求编号 动作 群编号 过期时间\n群名称"
+    pub fn make_feiq_group_request_packet(request: &GroupRequest, nickname: Option<&str>) -> FeiQPacket {
+        let group_name = request.group_name.replace(['\n', '\r'], " ");
+        let remark = format!(
+            "{} {} {} {}\n{}",
+            request.request_no, request.action, request.group_no, request.expire_at, group_name
+        );
+
+        let mut packet = Self::make_feiq_message_packet(&remark, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_GROUPREQ as u8;
+        packet.extra_flag = FEIQ_LOCAL_CAPABILITIES;
+        packet
+    }
+
     // ============================================================
     // 文件传输相关数据包 (FeiQ 格式)
     // ============================================================
@@ -200,10 +406,8 @@ impl FeiQPacket {
 
         // 构建文件附件信息: 多个文件用 \a (0x07) 分隔
         // 格式: "filename1:size1:mtime1:attr1\afilename2:size2:mtime2:attr2"
-        let files_info: Vec<String> = files
-            .iter()
-            .map(|f| format!("{}:{}:{}:{}", f.file_name, f.file_size, f.mtime, f.attr))
-            .collect();
+        // 图片附件追加 ":宽x高"，接收方据此预留预览区域；":#文件ID" 为请求数据时使用的文件ID
+        let files_info: Vec<String> = files.iter().map(|f| f.to_ipmsg_header()).collect();
         let remark = files_info.join("\x07");
 
         FeiQPacket {
@@ -213,7 +417,7 @@ impl FeiQPacket {
             mac_addr_formatted: mac_formatted,
             udp_port: 2425,
             file_transfer_id: 0,
-            extra_flag: 0,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES | FEIQ_FLAG_FILEATTACH,
             client_version: 0x4001,
             ext_info: FeiQExtInfo {
                 msg_sub_type: 0x20, // SENDMSG
@@ -254,7 +458,7 @@ impl FeiQPacket {
             mac_addr_formatted: mac_formatted,
             udp_port: 2425,
             file_transfer_id: file_id as u32,
-            extra_flag: 0,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES,
             client_version: 0x4001,
             ext_info: FeiQExtInfo {
                 msg_sub_type: 0x60, // GETFILEDATA
@@ -268,6 +472,97 @@ impl FeiQPacket {
         }
     }
 
+    /// 创建目录文件请求包 (GETDIRFILES)
+    ///
+    /// 仅用于 TCP 文件通道，接收方请求整个目录的数据流
+    pub fn make_feiq_get_dir_files_packet(
+        packet_no: &str,
+        file_id: u64,
+        nickname: Option<&str>,
+    ) -> FeiQPacket {
+        let (username, hostname, _ip, _port) = get_system_user_info();
+        let mac_addr = get_mac_address();
+        let mac_formatted = format_mac_addr(&mac_addr).unwrap_or_else(|_| mac_addr.clone());
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let packet_id = generate_packet_id();
+        let nickname = nickname.unwrap_or(&username).to_string();
+
+        // remark 字段: "packet_no:file_id"
+        let remark = format!("{}:{}", packet_no, file_id);
+
+        FeiQPacket {
+            pkg_type: "1_lbt6_0".to_string(),
+            func_flag: 128,
+            mac_addr_raw: mac_addr,
+            mac_addr_formatted: mac_formatted,
+            udp_port: 2425,
+            file_transfer_id: file_id as u32,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES,
+            client_version: 0x4001,
+            ext_info: FeiQExtInfo {
+                msg_sub_type: 0x62, // GETDIRFILES (仅 TCP 通道)
+                timestamp,
+                timestamp_local: timestamp_to_local(timestamp),
+                unique_id: packet_id,
+                hostname: hostname.clone(),
+                nickname,
+                remark,
+            },
+        }
+    }
+
+    /// 创建共享列表请求包 (LISTSHARES)
+    ///
+    /// 仅用于 TCP 通道，remark 为空
+    pub fn make_feiq_list_shares_packet(nickname: Option<&str>) -> FeiQPacket {
+        let mut packet = Self::make_feiq_list_dir_packet("", nickname);
+        packet.ext_info.msg_sub_type = FEIQ_LISTSHARES as u8;
+        packet
+    }
+
+    /// 创建缩略图请求包 (GETTHUMB)
+    ///
+    /// 仅用于 TCP 通道，remark 字段: "packet_no:file_id"
+    pub fn make_feiq_get_thumbnail_packet(packet_no: &str, file_id: u64, nickname: Option<&str>) -> FeiQPacket {
+        let mut packet = Self::make_feiq_get_dir_files_packet(packet_no, file_id, nickname);
+        packet.ext_info.msg_sub_type = FEIQ_GETTHUMB as u8;
+        packet
+    }
+
+    /// 创建共享目录列表请求包 (LISTDIR)
+    ///
+    /// 仅用于 TCP 通道，remark 字段: "共享名/相对路径"
+    pub fn make_feiq_list_dir_packet(share_path: &str, nickname: Option<&str>) -> FeiQPacket {
+        let (username, hostname, _ip, _port) = get_system_user_info();
+        let mac_addr = get_mac_address();
+        let mac_formatted = format_mac_addr(&mac_addr).unwrap_or_else(|_| mac_addr.clone());
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let packet_id = generate_packet_id();
+        let nickname = nickname.unwrap_or(&username).to_string();
+
+        FeiQPacket {
+            pkg_type: "1_lbt6_0".to_string(),
+            func_flag: 128,
+            mac_addr_raw: mac_addr,
+            mac_addr_formatted: mac_formatted,
+            udp_port: 2425,
+            file_transfer_id: 0,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES,
+            client_version: 0x4001,
+            ext_info: FeiQExtInfo {
+                msg_sub_type: FEIQ_LISTDIR as u8,
+                timestamp,
+                timestamp_local: timestamp_to_local(timestamp),
+                unique_id: packet_id,
+                hostname: hostname.clone(),
+                nickname,
+                remark: share_path.to_string(),
+            },
+        }
+    }
+
     /// 创建文件数据包 (用于发送文件数据块)
     ///
     /// 用于发送方响应文件数据请求
@@ -312,6 +607,47 @@ impl FeiQPacket {
         }
     }
 
+    /// 创建文件数据块确认包
+    ///
+    /// 接收方每写入一个数据块即回复确认，供发送方滑动窗口使用
+    pub fn make_feiq_file_data_ack_packet(
+        packet_no: &str,
+        file_id: u64,
+        offset: u64,
+        nickname: Option<&str>,
+    ) -> FeiQPacket {
+        let (username, hostname, _ip, _port) = get_system_user_info();
+        let mac_addr = get_mac_address();
+        let mac_formatted = format_mac_addr(&mac_addr).unwrap_or_else(|_| mac_addr.clone());
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+        let packet_id = generate_packet_id();
+        let nickname = nickname.unwrap_or(&username).to_string();
+
+        // remark 字段: "packet_no:file_id:offset"
+        let remark = format!("{}:{}:{}", packet_no, file_id, offset);
+
+        FeiQPacket {
+            pkg_type: "1_lbt6_0".to_string(),
+            func_flag: 128,
+            mac_addr_raw: mac_addr,
+            mac_addr_formatted: mac_formatted,
+            udp_port: 2425,
+            file_transfer_id: file_id as u32,
+            extra_flag: FEIQ_LOCAL_CAPABILITIES,
+            client_version: 0x4001,
+            ext_info: FeiQExtInfo {
+                msg_sub_type: 0x63, // File data chunk ACK
+                timestamp,
+                timestamp_local: timestamp_to_local(timestamp),
+                unique_id: packet_id,
+                hostname: hostname.clone(),
+                nickname,
+                remark,
+            },
+        }
+    }
+
     /// 创建文件释放包 (RELEASEFILES)
     ///
     /// 用于取消文件传输或通知发送方释放文件资源
@@ -350,7 +686,9 @@ impl FeiQPacket {
 
     /// 序列化为 FeiQ 协议字符串
     ///
-    /// 格式: 版本号#长度#MAC#端口#标志1#标志2#命令#类型:时间戳:包ID:主机名:用户ID:备注
+    /// 格式: 版本号#长度#MAC#端口#标志1#标志2#命令#类型:类型:时间戳:包ID:主机名:用户ID:备注
+    ///
+    /// 即扩展段的 7 字段格式（第二个字段位于计数器的位置），解析后包ID仍为 unique_id
     pub fn to_feiq_string(&self) -> String {
         // 计算数据段长度
         let data_section = format!(
@@ -384,6 +722,7 @@ impl FeiQPacket {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::feiq::parser::parse_feiq_packet;
 
     #[test]
     fn test_make_feiq_entry_packet_format() {
@@ -502,4 +841,241 @@ mod tests {
         assert!(packet.ext_info.nickname.contains(nickname), "Should preserve nickname");
         assert_eq!(packet.client_version, 0x4001, "Should have correct client version");
     }
+
+    #[test]
+    fn test_feiq_recall_and_edit_packets() {
+        use crate::network::feiq::constants::FEIQ_CAP_MSGEDIT;
+
+        let recall = FeiQPacket::make_feiq_recall_packet("T0000000042", 300, Some("testuser"));
+        assert_eq!(recall.ext_info.msg_sub_type, 0x33, "Recall should have msg_sub_type 0x33");
+        assert_ne!(recall.extra_flag & FEIQ_CAP_MSGEDIT, 0, "Recall should declare the capability");
+        assert_eq!(recall.recall_info(), ("T0000000042".to_string(), Some(300)));
+
+        // 旧版本的撤回包只有消息编号，没有撤回时限
+        let legacy = FeiQPacket::make_feiq_message_packet("T0000000042", Some("testuser"));
+        assert_eq!(legacy.recall_info(), ("T0000000042".to_string(), None));
+
+        let edit = FeiQPacket::make_feiq_edit_packet("T0000000042", "改为 10:30 开会", Some("testuser"));
+        assert_eq!(edit.ext_info.msg_sub_type, 0x34, "Edit should have msg_sub_type 0x34");
+        assert_eq!(
+            edit.revision_info(),
+            ("T0000000042".to_string(), "改为 10:30 开会".to_string()),
+            "Content may contain colons"
+        );
+    }
+
+    #[test]
+    fn test_feiq_receipt_packets() {
+        let recv = FeiQPacket::make_feiq_recv_packet("T0000000042");
+        assert_eq!(recv.receipt_msg_no(), "T0000000042", "Receipt refers to the original message");
+        assert_ne!(recv.ext_info.unique_id, "T0000000042");
+
+        let read = FeiQPacket::make_feiq_read_packet("T0000000042");
+        assert_eq!(read.ext_info.msg_sub_type, 0x30);
+        assert_eq!(read.receipt_msg_no(), "T0000000042");
+
+        let mut legacy = FeiQPacket::make_feiq_recv_packet("");
+        legacy.ext_info.unique_id = "T0000000043".to_string();
+        assert_eq!(legacy.receipt_msg_no(), "T0000000043");
+    }
+
+    #[test]
+    fn test_feiq_reply_packet() {
+        let packet = FeiQPacket::make_feiq_reply_packet("好的\n收到", "T0000000042", "明天 10 点开会", Some("testuser"));
+        assert_eq!(packet.ext_info.msg_sub_type, 0x20, "Reply is an ordinary message");
+        assert!(packet.ext_info.remark.starts_with(">> T0000000042 明天 10 点开会\n"));
+        assert_eq!(
+            packet.reply_info(),
+            Some((
+                "T0000000042".to_string(),
+                "明天 10 点开会".to_string(),
+                "好的\n收到".to_string()
+            ))
+        );
+
+        // 不带引用标志的普通消息即使以 ">> " 开头也不是引用回复
+        let plain = FeiQPacket::make_feiq_message_packet(">> T0000000042 x\ny", Some("testuser"));
+        assert_eq!(plain.reply_info(), None);
+    }
+
+    #[test]
+    fn test_feiq_group_packets() {
+        use crate::network::feiq::model::{GroupSyncOp, MENTION_ALL};
+
+        let envelope = GroupEnvelope {
+            group_no: "192.168.1.2:2425-3-1700000000".to_string(),
+            group_msg_no: 7,
+            sender: "192.168.1.2:2425".to_string(),
+            mentions: Vec::new(),
+        };
+        let inner = FeiQPacket::make_feiq_reply_packet("好的", "T0000000042", "开会", Some("testuser"));
+        let packet = FeiQPacket::make_feiq_group_packet(&inner, &envelope);
+        assert_eq!(packet.ext_info.msg_sub_type, FEIQ_GROUPMSG as u8);
+
+        let (opened, message) = packet.open_group_envelope().unwrap();
+        assert_eq!(opened, envelope);
+        assert_eq!(opened.sender_addr(), Some(("192.168.1.2".to_string(), 2425)));
+        assert_eq!(message.ext_info.msg_sub_type, 0x20);
+        assert_eq!(message.reply_info().unwrap().2, "好的", "Reply survives the envelope");
+
+        let mentioned = GroupEnvelope {
+            mentions: vec!["192.168.1.3:2425".to_string(), MENTION_ALL.to_string()],
+            ..envelope.clone()
+        };
+        let (opened, message) = FeiQPacket::make_feiq_group_packet(&inner, &mentioned)
+            .open_group_envelope()
+            .unwrap();
+        assert_eq!(opened, mentioned);
+        assert_eq!(message.reply_info().unwrap().2, "好的");
+
+        let sync = GroupSync {
+            group_no: envelope.group_no.clone(),
+            ops: vec![
+                GroupSyncOp {
+                    clock: 1,
+                    origin: "192.168.1.2:2425".to_string(),
+                    kind: "create".to_string(),
+                    member: Some("192.168.1.2:2425".to_string()),
+                    value: Some("项目\n组 A".to_string()),
+                },
+                GroupSyncOp {
+                    clock: 2,
+                    origin: "192.168.1.2:2425".to_string(),
+                    kind: "leave".to_string(),
+                    member: None,
+                    value: None,
+                },
+            ],
+            count: 5,
+            head: 7,
+        };
+        let packet = FeiQPacket::make_feiq_group_sync_packet(&sync, Some("testuser"));
+        assert_eq!(packet.ext_info.msg_sub_type, FEIQ_GROUPSYNC as u8);
+        let parsed = packet.group_sync_info().unwrap();
+        assert_eq!(parsed.group_no, sync.group_no);
+        assert_eq!(parsed.ops[0].value.as_deref(), Some("项目 组 A"), "Values keep inner spaces");
+        assert_eq!(parsed.ops[1], sync.ops[1]);
+        assert_eq!((parsed.count, parsed.head), (5, 7));
+
+        let request = GroupRequest {
+            request_no: "192.168.1.2:2425-1700000000000".to_string(),
+            action: "invite".to_string(),
+            group_no: envelope.group_no.clone(),
+            expire_at: 1_700_259_200,
+            group_name: "项目组 A".to_string(),
+        };
+        let packet = FeiQPacket::make_feiq_group_request_packet(&request, Some("testuser"));
+        assert_eq!(packet.ext_info.msg_sub_type, FEIQ_GROUPREQ as u8);
+        assert_eq!(packet.group_request_info(), Some(request));
+    }
+
+    #[test]
+    fn test_feiq_poll_packets() {
+        let poll = PollSpec {
+            poll_no: "192.168.1.2:2425-1700000000000".to_string(),
+            question: "午饭吃什么".to_string(),
+            options: vec!["面条".to_string(), "1. 米饭".to_string()],
+            multiple: true,
+            anonymous: false,
+            deadline: Some(1_700_003_600),
+        };
+        let envelope = GroupEnvelope {
+            group_no: "192.168.1.2:2425-1-1700000000".to_string(),
+            group_msg_no: 3,
+            sender: "192.168.1.2:2425".to_string(),
+            mentions: Vec::new(),
+        };
+        let packet = FeiQPacket::make_feiq_group_packet(&FeiQPacket::make_feiq_poll_packet(&poll, None), &envelope);
+        let (_, message) = packet.open_group_envelope().unwrap();
+        assert_eq!(message.poll_info(), Some(poll.clone()), "Option text may look like a numbering");
+        assert_eq!(FeiQPacket::make_feiq_message_packet("## 1 0 0\n问题\n1. a\n2. b", None).poll_info(), None);
+
+        let vote = FeiQPacket::make_feiq_poll_vote_packet(&poll.poll_no, &[0, 1], None);
+        assert_eq!(vote.ext_info.msg_sub_type, FEIQ_POLLMSG as u8);
+        assert_eq!(vote.poll_vote_info(), Some((poll.poll_no.clone(), vec![0, 1])));
+        assert_eq!(vote.poll_result_info(), None);
+        assert_eq!(vote.poll_vote_ack_info(), None);
+
+        let ack = FeiQPacket::make_feiq_poll_vote_ack_packet(&poll.poll_no, true, &[0, 1], None);
+        assert_eq!(ack.poll_vote_ack_info(), Some((poll.poll_no.clone(), true, vec![0, 1])));
+        assert_eq!(ack.poll_vote_info(), None);
+
+        let result = PollResult {
+            poll_no: poll.poll_no.clone(),
+            closed: true,
+            voter_count: 2,
+            counts: vec![2, 0],
+            voters: vec![vec!["192.168.1.3:2425".to_string(), "192.168.1.4:2425".to_string()], Vec::new()],
+        };
+        let packet = FeiQPacket::make_feiq_poll_result_packet(&result, None);
+        assert_eq!(packet.poll_result_info(), Some(result));
+        assert_eq!(packet.poll_vote_info(), None);
+    }
+
+    #[test]
+    fn test_feiq_reaction_packet() {
+        let reaction = Reaction {
+            msg_no: "1700000000123".to_string(),
+            emoji: Some("👍".to_string()),
+            group_no: Some("192.168.1.2:2425-1-1700000000".to_string()),
+            msg_sender: Some("192.168.1.3:2425".to_string()),
+        };
+        let packet = FeiQPacket::make_feiq_reaction_packet(&reaction, None);
+        assert_eq!(packet.ext_info.msg_sub_type, FEIQ_REACTMSG as u8);
+        assert_eq!(packet.reaction_info(), Some(reaction));
+
+        let cleared = Reaction {
+            msg_no: "1700000000123".to_string(),
+            emoji: None,
+            group_no: None,
+            msg_sender: None,
+        };
+        let packet = FeiQPacket::make_feiq_reaction_packet(&cleared, None);
+        assert_eq!(packet.ext_info.remark, "1700000000123 - - -");
+        assert_eq!(packet.reaction_info(), Some(cleared));
+        assert_eq!(FeiQPacket::make_feiq_message_packet("1700000000123", None).reaction_info(), None);
+    }
+
+    #[test]
+    fn test_feiq_typing_packet() {
+        let group_no = "192.168.1.2:2425-1-1700000000";
+        let packet = FeiQPacket::make_feiq_typing_packet(true, Some(group_no), None);
+        assert_eq!(packet.ext_info.msg_sub_type, FEIQ_TYPINGMSG as u8);
+        assert_eq!(packet.typing_info(), Some((true, Some(group_no.to_string()))));
+
+        let packet = FeiQPacket::make_feiq_typing_packet(false, None, None);
+        assert_eq!(packet.ext_info.remark, "0 -");
+        assert_eq!(packet.typing_info(), Some((false, None)));
+        assert_eq!(FeiQPacket::make_feiq_message_packet("1 2 3", None).typing_info(), None);
+        assert_eq!(FeiQPacket::make_feiq_message_packet("hello", None).typing_info(), None);
+    }
+
+    #[test]
+    fn test_packet_ids_unique_within_second() {
+        let message = FeiQPacket::make_feiq_message_packet("hello", None);
+        let recall = FeiQPacket::make_feiq_recall_packet(&message.ext_info.unique_id, 120, None);
+        assert_ne!(message.ext_info.unique_id, recall.ext_info.unique_id);
+        assert!(message.ext_info.unique_id.starts_with('T'));
+
+        // 对方按序列化后的包ID记录消息，撤回包引用的是同一个编号
+        let received = parse_feiq_packet(&message.to_feiq_string()).unwrap();
+        let received_recall = parse_feiq_packet(&recall.to_feiq_string()).unwrap();
+        assert_eq!(received_recall.recall_info().0, received.ext_info.unique_id);
+    }
+
+    #[test]
+    fn test_packet_round_trip_keeps_unique_id() {
+        let packets = [
+            FeiQPacket::make_feiq_message_packet("hello: world", Some("testuser")),
+            FeiQPacket::make_feiq_recall_packet("T0000000042", 120, Some("testuser")),
+            FeiQPacket::make_feiq_recv_packet("T0000000042"),
+        ];
+        for packet in packets {
+            let parsed = parse_feiq_packet(&packet.to_feiq_string()).unwrap();
+            assert_eq!(parsed.ext_info.unique_id, packet.ext_info.unique_id);
+            assert_eq!(parsed.ext_info.nickname, packet.ext_info.nickname);
+            assert_eq!(parsed.ext_info.hostname, packet.ext_info.hostname);
+            assert_eq!(parsed.ext_info.remark, packet.ext_info.remark);
+        }
+    }
 }
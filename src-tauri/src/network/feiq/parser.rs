@@ -133,16 +133,16 @@ pub fn parse_feiq_packet_detail(packet_str: &str) -> Result<FeiQPacket, ParseErr
 
     // 根据字段数量处理不同的格式
     // 标准6字段格式: msg_sub_type:timestamp:unique_id:hostname:nickname:remark
-    // 扩展7字段格式: msg_sub_type:counter:timestamp:packet_id:hostname:unique_id:remark
+    // 扩展7字段格式: msg_sub_type:counter:timestamp:packet_id:hostname:user_id:remark
+    // 两种格式的包ID都作为 unique_id（消息编号），7字段格式的用户ID对应 6 字段格式的 nickname 位置
     let (timestamp, unique_id, hostname, nickname, remark) = if ext_fields.len() >= 7 {
-        // 7+ 字段格式（新版本 FeiQ）- 格式: msg_sub_type:counter:timestamp:packet_id:hostname:unique_id:remark
+        // 7+ 字段格式（新版本 FeiQ，本端 to_feiq_string 也输出此格式）
         let ts = ext_fields[2]
             .parse::<i64>()
             .map_err(|e| ParseError::InvalidFormat(format!("时间戳解析失败：{}", e)))?;
-        let _packet_id = ext_fields[3].to_string(); // 某种包 ID
+        let uid = ext_fields[3].to_string(); // 包 ID
         let host = ext_fields[4].to_string();
-        let uid = ext_fields[5].to_string(); // unique_id
-        let nick = String::new(); // 7字段格式通常没有 nickname 字段
+        let nick = ext_fields[5].to_string(); // 用户ID
         let rem = if ext_fields.len() > 6 {
             ext_fields[6..].join(":")
         } else {
@@ -272,7 +272,7 @@ mod tests {
     #[test]
     fn test_parse_feiq_7_field_format() {
         // 实际的飞秋数据包（7字段格式）
-        // 格式: msg_sub_type:counter:timestamp:packet_id:hostname:unique_id:remark
+        // 格式: msg_sub_type:counter:timestamp:packet_id:hostname:user_id:remark
         let input = "1_lbt6_0#128#5C60BA7361C6#2425#0#0#4001#9:9:1769669929:T1769669929:shikunsh-n:T0220165:";
         let detail = parse_feiq_packet_detail(input).unwrap();
 
@@ -283,9 +283,9 @@ mod tests {
         assert_eq!(detail.client_version, 0x4001); // 命令字（十六进制：4001）
         assert_eq!(detail.ext_info.msg_sub_type, 9);
         assert_eq!(detail.ext_info.timestamp, 1769669929);
-        assert_eq!(detail.ext_info.unique_id, "T0220165");
+        assert_eq!(detail.ext_info.unique_id, "T1769669929"); // 包 ID
         assert_eq!(detail.ext_info.hostname, "shikunsh-n");
-        assert_eq!(detail.ext_info.nickname, ""); // 7字段格式没有 nickname
+        assert_eq!(detail.ext_info.nickname, "T0220165"); // 用户ID
         assert_eq!(detail.ext_info.remark, "");
     }
 
@@ -2,5 +2,6 @@
 //
 /// 网络通信层模块
 pub mod feiq;
+pub mod tcp;
 pub mod udp;
 pub mod utils;
@@ -0,0 +1,262 @@
+// src-tauri/src/network/tcp/client.rs
+//
+/// TCP 文件通道客户端
+///
+/// 接收方主动连接发送方，发送请求包后读取原始字节流
+use super::protocol::{
+    is_safe_entry_name, DirEntryHeader, TcpFileRequest, HEADER_SIZE_LEN, MAX_THUMBNAIL_BYTES, SHARE_STATUS_OK,
+};
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::constants::{IPMSG_FILE_DIR, IPMSG_FILE_REGULAR, IPMSG_FILE_RETPARENT};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{timeout, Duration};
+use tracing::info;
+
+/// 建立连接的超时时间（超时视为对方不支持 TCP 通道）
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 单次读取的超时时间（对方停止发送时放弃连接）
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 接收目录文件时的读缓冲区大小
+const RECEIVE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 同名文件已存在时最多尝试的重命名次数
+const MAX_RENAME_ATTEMPTS: u32 = 1000;
+
+/// TCP 文件通道客户端
+pub struct TcpFileClient {
+    stream: TcpStream,
+}
+
+impl TcpFileClient {
+    /// 连接发送方的 TCP 文件通道
+    ///
+    /// # 参数
+    /// * `addr` - 目标地址 (格式: "IP:PORT")
+    pub async fn connect(addr: &str) -> AppResult<Self> {
+        let stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+            .await
+            .map_err(|_| AppError::Network(format!("TCP connect to {} timed out", addr)))?
+            .map_err(|e| AppError::Network(format!("Failed to connect TCP {}: {}", addr, e)))?;
+
+        stream
+            .set_nodelay(true)
+            .map_err(|e| AppError::Network(format!("Failed to set TCP_NODELAY: {}", e)))?;
+
+        Ok(Self { stream })
+    }
+
+    /// 请求单个文件数据
+    ///
+    /// 返回已发送请求的连接，调用方从中读取原始文件字节直至 EOF
+    pub async fn request_file_data(mut self, packet_no: &str, file_id: u64, offset: u64) -> AppResult<TcpStream> {
+        let request = TcpFileRequest::file_data(packet_no, file_id, offset);
+        self.stream.write_all(&request.to_wire()).await.map_err(AppError::Io)?;
+
+        info!(
+            "📤 [TCP GETFILEDATA] packet_no={}, file_id={}, offset={}",
+            packet_no, file_id, offset
+        );
+        Ok(self.stream)
+    }
+
+    /// 请求目录数据并写入本地目录
+    ///
+    /// # 参数
+    /// * `packet_no` - 数据包编号
+    /// * `file_id` - 文件ID
+    /// * `dest_dir` - 本地保存目录（远端目录会在其下创建，已有的同名文件不会被覆盖）
+    /// * `on_progress` - 进度回调，参数为已接收的文件字节数
+    ///
+    /// # 返回
+    /// 返回接收的文件字节总数
+    pub async fn fetch_dir_files<F>(
+        mut self,
+        packet_no: &str,
+        file_id: u64,
+        dest_dir: &Path,
+        mut on_progress: F,
+    ) -> AppResult<u64>
+    where
+        F: FnMut(u64),
+    {
+        let request = TcpFileRequest::dir_files(packet_no, file_id);
+        self.stream.write_all(&request.to_wire()).await.map_err(AppError::Io)?;
+
+        info!("📤 [TCP GETDIRFILES] packet_no={}, file_id={}", packet_no, file_id);
+
+        let mut current: PathBuf = dest_dir.to_path_buf();
+        let mut depth = 0usize;
+        let mut received = 0u64;
+        let mut buffer = vec![0u8; RECEIVE_BUFFER_SIZE];
+
+        while let Some(header) = read_dir_header(&mut self.stream).await? {
+            match header.attr & 0xff {
+                IPMSG_FILE_RETPARENT => {
+                    if depth == 0 {
+                        return Err(AppError::Protocol("目录数据流层级错误".to_string()));
+                    }
+                    depth -= 1;
+                    current.pop();
+                }
+                IPMSG_FILE_DIR => {
+                    if !is_safe_entry_name(&header.name) {
+                        return Err(AppError::Protocol(format!("非法目录名: {}", header.name)));
+                    }
+                    current.push(&header.name);
+                    depth += 1;
+                    tokio::fs::create_dir_all(&current).await.map_err(AppError::Io)?;
+                }
+                IPMSG_FILE_REGULAR => {
+                    if !is_safe_entry_name(&header.name) {
+                        return Err(AppError::Protocol(format!("非法文件名: {}", header.name)));
+                    }
+                    let mut file = create_unique_file(&current, &header.name).await?;
+                    let mut copied = 0u64;
+                    while copied < header.size {
+                        let want = (header.size - copied).min(buffer.len() as u64) as usize;
+                        let n = read_with_timeout(self.stream.read(&mut buffer[..want])).await?;
+                        if n == 0 {
+                            return Err(AppError::Network(format!(
+                                "文件数据不完整: {} ({}/{})",
+                                header.name, copied, header.size
+                            )));
+                        }
+                        file.write_all(&buffer[..n]).await.map_err(AppError::Io)?;
+                        copied += n as u64;
+                    }
+                    file.flush().await.map_err(AppError::Io)?;
+                    received += copied;
+                    on_progress(received);
+                }
+                other => {
+                    return Err(AppError::Protocol(format!("未知的文件属性: {:#x}", other)));
+                }
+            }
+        }
+
+        Ok(received)
+    }
+
+    /// 列出对方向本端开放的共享文件夹
+    pub async fn list_shares(self) -> AppResult<Vec<DirEntryHeader>> {
+        self.request_listing(TcpFileRequest::list_shares()).await
+    }
+
+    /// 列出对方共享文件夹中的目录
+    ///
+    /// # 参数
+    /// * `share_path` - "共享名/相对路径"，只有共享名时列出共享根目录
+    pub async fn list_dir(self, share_path: &str) -> AppResult<Vec<DirEntryHeader>> {
+        self.request_listing(TcpFileRequest::list_dir(share_path)).await
+    }
+
+    /// 获取图片附件的缩略图
+    ///
+    /// # 参数
+    /// * `packet_no` - 文件请求包编号
+    /// * `file_id` - 文件ID
+    pub async fn fetch_thumbnail(mut self, packet_no: &str, file_id: u64) -> AppResult<Vec<u8>> {
+        self.send_request(TcpFileRequest::thumbnail(packet_no, file_id)).await?;
+
+        let mut data = Vec::new();
+        read_with_timeout((&mut self.stream).take(MAX_THUMBNAIL_BYTES + 1).read_to_end(&mut data)).await?;
+        if data.len() as u64 > MAX_THUMBNAIL_BYTES {
+            return Err(AppError::Protocol("缩略图过大".to_string()));
+        }
+
+        Ok(data)
+    }
+
+    /// 发送列表请求，读取状态头部和条目头部
+    async fn request_listing(mut self, request: TcpFileRequest) -> AppResult<Vec<DirEntryHeader>> {
+        self.send_request(request).await?;
+
+        let mut entries = Vec::new();
+        while let Some(header) = read_dir_header(&mut self.stream).await? {
+            if !is_safe_entry_name(&header.name) {
+                return Err(AppError::Protocol(format!("非法文件名: {}", header.name)));
+            }
+            entries.push(header);
+        }
+
+        Ok(entries)
+    }
+
+    /// 发送扩展请求并读取状态头部，状态不是成功时返回对方给出的错误信息
+    async fn send_request(&mut self, request: TcpFileRequest) -> AppResult<()> {
+        self.stream.write_all(&request.to_wire()).await.map_err(AppError::Io)?;
+
+        let status = read_dir_header(&mut self.stream)
+            .await?
+            .filter(DirEntryHeader::is_status)
+            .ok_or_else(|| AppError::Protocol("响应缺少状态".to_string()))?;
+        if status.size != SHARE_STATUS_OK {
+            return Err(AppError::Business(status.name));
+        }
+
+        Ok(())
+    }
+}
+
+/// 带超时的读取
+async fn read_with_timeout<F, T>(read: F) -> AppResult<T>
+where
+    F: std::future::Future<Output = std::io::Result<T>>,
+{
+    timeout(READ_TIMEOUT, read)
+        .await
+        .map_err(|_| AppError::Network("TCP 读取超时".to_string()))?
+        .map_err(AppError::Io)
+}
+
+/// 在目录中新建文件，同名文件已存在时改名为 "名称 (n).扩展名"
+async fn create_unique_file(dir: &Path, name: &str) -> AppResult<tokio::fs::File> {
+    let original = Path::new(name);
+    let stem = original.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = original.extension().map(|e| format!(".{}", e.to_string_lossy()));
+
+    for attempt in 0..MAX_RENAME_ATTEMPTS {
+        let candidate = match attempt {
+            0 => name.to_string(),
+            n => format!("{} ({}){}", stem, n, extension.as_deref().unwrap_or("")),
+        };
+        match tokio::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(&candidate))
+            .await
+        {
+            Ok(file) => return Ok(file),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(AppError::Io(e)),
+        }
+    }
+
+    Err(AppError::Business(format!("同名文件过多: {}", name)))
+}
+
+/// 读取一个目录条目头部，流结束时返回 None
+async fn read_dir_header(stream: &mut TcpStream) -> AppResult<Option<DirEntryHeader>> {
+    let mut prefix = [0u8; HEADER_SIZE_LEN];
+    let mut filled = 0;
+    while filled < HEADER_SIZE_LEN {
+        let n = read_with_timeout(stream.read(&mut prefix[filled..])).await?;
+        if n == 0 {
+            if filled == 0 {
+                return Ok(None);
+            }
+            return Err(AppError::Protocol("目录头部被截断".to_string()));
+        }
+        filled += n;
+    }
+
+    let size = DirEntryHeader::parse_header_size(&prefix)?;
+    let mut body = vec![0u8; size - HEADER_SIZE_LEN];
+    read_with_timeout(stream.read_exact(&mut body)).await?;
+
+    DirEntryHeader::decode_body(&body).map(Some)
+}
@@ -0,0 +1,321 @@
+// src-tauri/src/network/tcp/listener.rs
+//
+/// TCP 文件通道监听器
+///
+/// 监听 TCP 2425 端口，响应 GETFILEDATA / GETDIRFILES / GETTHUMB 请求，
+/// 直接以原始字节流回写文件内容（无 base64 开销，由 TCP 负责流控）
+use super::protocol::{
+    DirEntryHeader, TcpFileRequest, TcpRequestKind, MAX_REQUEST_LEN, REQUEST_TERMINATOR, SHARE_STATUS_ERROR,
+    SHARE_STATUS_OK,
+};
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::constants::{IPMSG_FILE_DIR, IPMSG_FILE_REGULAR, IPMSG_FILE_RETPARENT};
+use crate::network::feiq::parser::{decode_gbk, parse_feiq_packet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{timeout, Duration, Instant};
+use tracing::{error, info, warn};
+
+/// 读取请求包的超时时间
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 发送缓冲区大小
+const SEND_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 文件来源
+///
+/// 由上层（core::file）实现，根据请求方、packet_no 和 file_id 找到本地文件路径
+#[async_trait::async_trait]
+pub trait FileSource: Send + Sync + 'static {
+    /// 解析本地文件路径
+    async fn resolve(&self, peer_ip: &str, packet_no: &str, file_id: u64) -> AppResult<PathBuf>;
+
+    /// 发送数据前的限速等待（默认不限速）
+    async fn throttle(&self, _peer_ip: &str, _request: &TcpFileRequest, _bytes: usize) {}
+
+    /// 数据发送完毕后的回调（默认不做处理）
+    async fn on_finished(&self, _peer_ip: &str, _request: &TcpFileRequest, _sent: u64, _elapsed: Duration) {}
+
+    /// 列出向请求方开放的共享文件夹（默认没有共享）
+    async fn list_shares(&self, _peer_ip: &str) -> AppResult<Vec<DirEntryHeader>> {
+        Ok(Vec::new())
+    }
+
+    /// 列出共享文件夹中的目录（默认没有共享）
+    async fn list_dir(&self, _peer_ip: &str, path: &str) -> AppResult<Vec<DirEntryHeader>> {
+        Err(AppError::NotFound(format!("共享路径 {}", path)))
+    }
+
+    /// 读取图片附件的缩略图（默认没有缩略图）
+    async fn thumbnail(&self, _peer_ip: &str, packet_no: &str, file_id: u64) -> AppResult<Vec<u8>> {
+        Err(AppError::NotFound(format!("缩略图 packet_no={}, file_id={}", packet_no, file_id)))
+    }
+}
+
+/// 启动 TCP 文件通道监听器
+///
+/// 绑定到 0.0.0.0:2425，每个连接独立处理
+pub async fn start_tcp_listener(source: Arc<dyn FileSource>) -> AppResult<()> {
+    let listener = TcpListener::bind("0.0.0.0:2425")
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to bind TCP listener to 0.0.0.0:2425: {}", e)))?;
+
+    info!("TCP 文件通道已监听 0.0.0.0:2425");
+
+    serve_tcp_listener(listener, source).await
+}
+
+/// 在已绑定的监听器上处理 TCP 文件请求
+pub async fn serve_tcp_listener(listener: TcpListener, source: Arc<dyn FileSource>) -> AppResult<()> {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                let source = source.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, addr.ip().to_string(), source).await {
+                        warn!("TCP 文件请求处理失败 ({}): {}", addr, e);
+                    }
+                });
+            }
+            Err(e) => {
+                error!("❌ [TCP ACCEPT ERROR] {}", e);
+            }
+        }
+    }
+}
+
+/// 处理单个 TCP 连接
+async fn handle_connection(mut stream: TcpStream, peer_ip: String, source: Arc<dyn FileSource>) -> AppResult<()> {
+    let request = timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .map_err(|_| AppError::Network("读取 TCP 请求超时".to_string()))??;
+
+    info!(
+        "收到 TCP 文件请求: kind={:?}, packet_no={}, file_id={}, offset={}",
+        request.kind, request.packet_no, request.file_id, request.offset
+    );
+
+    match request.kind {
+        TcpRequestKind::ListShares => send_listing(&mut stream, source.list_shares(&peer_ip).await).await,
+        TcpRequestKind::ListDir => send_listing(&mut stream, source.list_dir(&peer_ip, &request.path).await).await,
+        TcpRequestKind::Thumbnail => {
+            let thumbnail = source.thumbnail(&peer_ip, &request.packet_no, request.file_id).await;
+            send_thumbnail(&mut stream, thumbnail).await
+        }
+        TcpRequestKind::FileData => send_files(&mut stream, &peer_ip, source.as_ref(), &request, false).await,
+        TcpRequestKind::DirFiles => send_files(&mut stream, &peer_ip, source.as_ref(), &request, true).await,
+    }
+}
+
+/// 发送文件或目录数据，发送完毕后回调文件来源
+async fn send_files(
+    stream: &mut TcpStream,
+    peer_ip: &str,
+    source: &dyn FileSource,
+    request: &TcpFileRequest,
+    directory: bool,
+) -> AppResult<()> {
+    let path = source.resolve(peer_ip, &request.packet_no, request.file_id).await?;
+
+    let started = Instant::now();
+    let sender = Throttled {
+        source,
+        peer_ip,
+        request,
+    };
+    let sent = if directory {
+        send_dir_files(stream, &path, &sender).await?
+    } else {
+        send_file_data(stream, &path, request.offset, &sender).await?
+    };
+
+    stream.shutdown().await.map_err(AppError::Io)?;
+    source.on_finished(peer_ip, request, sent, started.elapsed()).await;
+
+    info!("TCP 文件数据发送完成: file_id={}, bytes={}", request.file_id, sent);
+    Ok(())
+}
+
+/// 发送共享列表响应：状态头部 + 条目头部
+async fn send_listing(stream: &mut TcpStream, listing: AppResult<Vec<DirEntryHeader>>) -> AppResult<()> {
+    match listing {
+        Ok(entries) => {
+            let mut bytes = DirEntryHeader::status(SHARE_STATUS_OK, "ok").encode();
+            for entry in &entries {
+                bytes.extend(entry.encode());
+            }
+            stream.write_all(&bytes).await.map_err(AppError::Io)?;
+        }
+        Err(e) => {
+            warn!("共享列表请求被拒绝: {}", e);
+            let header = DirEntryHeader::status(SHARE_STATUS_ERROR, &e.to_string());
+            stream.write_all(&header.encode()).await.map_err(AppError::Io)?;
+        }
+    }
+
+    stream.shutdown().await.map_err(AppError::Io)
+}
+
+/// 发送缩略图响应：状态头部 + 缩略图字节
+async fn send_thumbnail(stream: &mut TcpStream, thumbnail: AppResult<Vec<u8>>) -> AppResult<()> {
+    match thumbnail {
+        Ok(data) => {
+            let mut bytes = DirEntryHeader::status(SHARE_STATUS_OK, "ok").encode();
+            bytes.extend(data);
+            stream.write_all(&bytes).await.map_err(AppError::Io)?;
+        }
+        Err(e) => {
+            let header = DirEntryHeader::status(SHARE_STATUS_ERROR, &e.to_string());
+            stream.write_all(&header.encode()).await.map_err(AppError::Io)?;
+        }
+    }
+
+    stream.shutdown().await.map_err(AppError::Io)
+}
+
+/// 读取以结束符结尾的请求包
+async fn read_request(stream: &mut TcpStream) -> AppResult<TcpFileRequest> {
+    let mut buf = Vec::with_capacity(256);
+    let mut byte = [0u8; 1];
+
+    loop {
+        let n = stream.read(&mut byte).await.map_err(AppError::Io)?;
+        if n == 0 || byte[0] == REQUEST_TERMINATOR {
+            break;
+        }
+        buf.push(byte[0]);
+        if buf.len() > MAX_REQUEST_LEN {
+            return Err(AppError::Protocol("TCP 请求包过长".to_string()));
+        }
+    }
+
+    let decoded = decode_gbk(&buf).unwrap_or_else(|_| String::from_utf8_lossy(&buf).to_string());
+    let packet = parse_feiq_packet(&decoded).map_err(|e| AppError::Protocol(e.to_string()))?;
+
+    TcpFileRequest::from_packet(&packet)
+}
+
+/// 带限速的数据拷贝
+struct Throttled<'a> {
+    source: &'a dyn FileSource,
+    peer_ip: &'a str,
+    request: &'a TcpFileRequest,
+}
+
+impl Throttled<'_> {
+    /// 拷贝最多 `limit` 字节，每个缓冲区发送前先经过限速
+    async fn copy<R>(&self, reader: &mut R, stream: &mut TcpStream, limit: u64) -> AppResult<u64>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        let mut buffer = vec![0u8; SEND_BUFFER_SIZE];
+        let mut sent = 0u64;
+
+        while sent < limit {
+            let want = (limit - sent).min(SEND_BUFFER_SIZE as u64) as usize;
+            let n = reader.read(&mut buffer[..want]).await.map_err(AppError::Io)?;
+            if n == 0 {
+                break;
+            }
+
+            self.source.throttle(self.peer_ip, self.request, n).await;
+            stream.write_all(&buffer[..n]).await.map_err(AppError::Io)?;
+            sent += n as u64;
+        }
+
+        Ok(sent)
+    }
+}
+
+/// 从偏移量开始发送单个文件的原始字节
+async fn send_file_data(stream: &mut TcpStream, path: &Path, offset: u64, sender: &Throttled<'_>) -> AppResult<u64> {
+    let mut file = tokio::fs::File::open(path).await.map_err(AppError::Io)?;
+    let len = file.metadata().await.map_err(AppError::Io)?.len();
+    file.seek(SeekFrom::Start(offset)).await.map_err(AppError::Io)?;
+
+    sender.copy(&mut file, stream, len.saturating_sub(offset)).await
+}
+
+/// 按 IPMsg 目录格式发送整个目录
+///
+/// 进入目录时发送 DIR 头部，离开时发送 RETPARENT 头部。
+/// 目录中的符号链接不跟随（避免循环或发送目录以外的文件）
+async fn send_dir_files(stream: &mut TcpStream, root: &Path, sender: &Throttled<'_>) -> AppResult<u64> {
+    enum Step {
+        Enter(PathBuf),
+        Leave,
+    }
+
+    let mut sent = 0u64;
+    let mut stack = vec![Step::Enter(root.to_path_buf())];
+
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Enter(path) => {
+                // 共享根目录由文件来源解析，其下的条目不跟随符号链接
+                let metadata = if path == root {
+                    tokio::fs::metadata(&path).await
+                } else {
+                    tokio::fs::symlink_metadata(&path).await
+                }
+                .map_err(AppError::Io)?;
+                if metadata.file_type().is_symlink() {
+                    warn!("跳过目录中的符号链接: {}", path.display());
+                    continue;
+                }
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+
+                if metadata.is_dir() {
+                    let header = DirEntryHeader {
+                        name,
+                        size: 0,
+                        attr: IPMSG_FILE_DIR,
+                    };
+                    stream.write_all(&header.encode()).await.map_err(AppError::Io)?;
+                    stack.push(Step::Leave);
+
+                    let mut children = Vec::new();
+                    let mut entries = tokio::fs::read_dir(&path).await.map_err(AppError::Io)?;
+                    while let Some(entry) = entries.next_entry().await.map_err(AppError::Io)? {
+                        children.push(entry.path());
+                    }
+                    // 逆序入栈，保证按名称顺序发送
+                    children.sort();
+                    for child in children.into_iter().rev() {
+                        stack.push(Step::Enter(child));
+                    }
+                } else {
+                    let header = DirEntryHeader {
+                        name,
+                        size: metadata.len(),
+                        attr: IPMSG_FILE_REGULAR,
+                    };
+                    stream.write_all(&header.encode()).await.map_err(AppError::Io)?;
+
+                    let mut file = tokio::fs::File::open(&path).await.map_err(AppError::Io)?;
+                    let copied = sender.copy(&mut file, stream, metadata.len()).await?;
+                    // 文件在发送中变短时后续条目的头部会错位，只能中止
+                    if copied != metadata.len() {
+                        return Err(AppError::Io(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            format!("文件在发送中被改动: {} ({}/{})", path.display(), copied, metadata.len()),
+                        )));
+                    }
+                    sent += copied;
+                }
+            }
+            Step::Leave => {
+                let header = DirEntryHeader {
+                    name: ".".to_string(),
+                    size: 0,
+                    attr: IPMSG_FILE_RETPARENT,
+                };
+                stream.write_all(&header.encode()).await.map_err(AppError::Io)?;
+            }
+        }
+    }
+
+    Ok(sent)
+}
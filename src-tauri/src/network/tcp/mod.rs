@@ -0,0 +1,12 @@
+// src-tauri/src/network/tcp/mod.rs
+//
+/// TCP 文件数据通道模块
+///
+/// 与经典 IPMsg 一致，文件内容通过同端口的 TCP 连接以原始字节传输；
+/// 对方不支持 TCP 时仍回退到 UDP base64 分块
+pub mod client;
+pub mod listener;
+pub mod protocol;
+
+pub use client::TcpFileClient;
+pub use listener::{serve_tcp_listener, start_tcp_listener, FileSource};
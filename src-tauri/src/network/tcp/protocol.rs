@@ -0,0 +1,345 @@
+// src-tauri/src/network/tcp/protocol.rs
+//
+/// TCP 文件通道协议定义
+///
+/// 请求: 接收方连接发送方的 TCP 2425 端口，发送一个以 `\0` 结尾的
+/// GETFILEDATA (0x60) 或 GETDIRFILES (0x62) 飞秋数据包
+///
+/// 响应:
+/// - GETFILEDATA: 从请求偏移量开始的原始文件字节，发送完毕后关闭连接
+/// - GETDIRFILES: 按 IPMsg 目录格式依次发送 `头部 + 文件内容`，
+///   头部格式为 `header-size:filename:file-size:fileattr:`（数值均为十六进制）
+/// - LISTSHARES / LISTDIR (共享文件夹扩展): 先发送一个状态头部（属性为 0，
+///   大小字段为状态码，文件名字段为错误信息），成功时随后是每个条目的目录头部（不含文件内容）
+/// - GETTHUMB (缩略图扩展): 同样先发送状态头部，成功时随后是缩略图原始字节，发送完毕后关闭连接
+use crate::error::{AppError, AppResult};
+use crate::network::feiq::constants::{
+    FEIQ_GETTHUMB, FEIQ_LISTDIR, FEIQ_LISTSHARES, IPMSG_GETDIRFILES, IPMSG_GETFILEDATA,
+};
+use crate::network::feiq::model::FeiQPacket;
+
+/// 请求包结束符
+pub const REQUEST_TERMINATOR: u8 = 0;
+
+/// 请求包最大长度
+pub const MAX_REQUEST_LEN: usize = 2048;
+
+/// 目录头部长度字段的字节数 (4 位十六进制)
+pub const HEADER_SIZE_LEN: usize = 4;
+
+/// 共享列表响应状态：成功
+pub const SHARE_STATUS_OK: u64 = 0;
+
+/// 共享列表响应状态：共享不存在或无权访问
+pub const SHARE_STATUS_ERROR: u64 = 1;
+
+/// 缩略图响应的最大字节数
+pub const MAX_THUMBNAIL_BYTES: u64 = 256 * 1024;
+
+/// TCP 请求类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpRequestKind {
+    /// 请求单个文件数据 (GETFILEDATA)
+    FileData,
+    /// 请求目录数据 (GETDIRFILES)
+    DirFiles,
+    /// 列出共享文件夹 (LISTSHARES)
+    ListShares,
+    /// 列出共享目录内容 (LISTDIR)
+    ListDir,
+    /// 获取图片缩略图 (GETTHUMB)
+    Thumbnail,
+}
+
+/// TCP 文件通道请求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TcpFileRequest {
+    pub kind: TcpRequestKind,
+    pub packet_no: String,
+    pub file_id: u64,
+    pub offset: u64,
+    /// 共享路径 "共享名/相对路径"（仅 LISTDIR）
+    pub path: String,
+}
+
+impl TcpFileRequest {
+    /// 创建文件数据请求
+    pub fn file_data(packet_no: &str, file_id: u64, offset: u64) -> Self {
+        Self {
+            kind: TcpRequestKind::FileData,
+            packet_no: packet_no.to_string(),
+            file_id,
+            offset,
+            path: String::new(),
+        }
+    }
+
+    /// 创建目录数据请求
+    pub fn dir_files(packet_no: &str, file_id: u64) -> Self {
+        Self {
+            kind: TcpRequestKind::DirFiles,
+            packet_no: packet_no.to_string(),
+            file_id,
+            offset: 0,
+            path: String::new(),
+        }
+    }
+
+    /// 创建缩略图请求
+    pub fn thumbnail(packet_no: &str, file_id: u64) -> Self {
+        Self {
+            kind: TcpRequestKind::Thumbnail,
+            packet_no: packet_no.to_string(),
+            file_id,
+            offset: 0,
+            path: String::new(),
+        }
+    }
+
+    /// 创建共享列表请求
+    pub fn list_shares() -> Self {
+        Self {
+            kind: TcpRequestKind::ListShares,
+            packet_no: String::new(),
+            file_id: 0,
+            offset: 0,
+            path: String::new(),
+        }
+    }
+
+    /// 创建共享目录列表请求
+    pub fn list_dir(share_path: &str) -> Self {
+        Self {
+            kind: TcpRequestKind::ListDir,
+            packet_no: String::new(),
+            file_id: 0,
+            offset: 0,
+            path: share_path.to_string(),
+        }
+    }
+
+    /// 从飞秋数据包解析请求
+    ///
+    /// remark 格式:
+    /// - GETFILEDATA: "packet_no:file_id:offset"
+    /// - GETDIRFILES / GETTHUMB: "packet_no:file_id"
+    /// - LISTSHARES: 空
+    /// - LISTDIR: "共享名/相对路径"
+    pub fn from_packet(packet: &FeiQPacket) -> AppResult<Self> {
+        let kind = match packet.ext_info.msg_sub_type as u32 {
+            IPMSG_GETFILEDATA => TcpRequestKind::FileData,
+            IPMSG_GETDIRFILES => TcpRequestKind::DirFiles,
+            FEIQ_GETTHUMB => TcpRequestKind::Thumbnail,
+            FEIQ_LISTSHARES => return Ok(Self::list_shares()),
+            FEIQ_LISTDIR => return Ok(Self::list_dir(&packet.ext_info.remark)),
+            other => {
+                return Err(AppError::Protocol(format!("不支持的 TCP 请求类型: {}", other)));
+            }
+        };
+
+        let remark = &packet.ext_info.remark;
+        let parts: Vec<&str> = remark.split(':').collect();
+        let min_parts = if kind == TcpRequestKind::FileData { 3 } else { 2 };
+        if parts.len() < min_parts {
+            return Err(AppError::Protocol(format!("无效的 TCP 请求格式: {}", remark)));
+        }
+
+        let file_id = parts[1]
+            .parse::<u64>()
+            .map_err(|_| AppError::Protocol(format!("无效的文件ID: {}", parts[1])))?;
+        let offset = if kind == TcpRequestKind::FileData {
+            parts[2]
+                .parse::<u64>()
+                .map_err(|_| AppError::Protocol(format!("无效的偏移量: {}", parts[2])))?
+        } else {
+            0
+        };
+
+        Ok(Self {
+            kind,
+            packet_no: parts[0].to_string(),
+            file_id,
+            offset,
+            path: String::new(),
+        })
+    }
+
+    /// 转换为飞秋数据包
+    pub fn to_packet(&self) -> FeiQPacket {
+        match self.kind {
+            TcpRequestKind::FileData => {
+                FeiQPacket::make_feiq_get_file_data_packet(&self.packet_no, self.file_id, self.offset, None)
+            }
+            TcpRequestKind::DirFiles => FeiQPacket::make_feiq_get_dir_files_packet(&self.packet_no, self.file_id, None),
+            TcpRequestKind::ListShares => FeiQPacket::make_feiq_list_shares_packet(None),
+            TcpRequestKind::ListDir => FeiQPacket::make_feiq_list_dir_packet(&self.path, None),
+            TcpRequestKind::Thumbnail => {
+                FeiQPacket::make_feiq_get_thumbnail_packet(&self.packet_no, self.file_id, None)
+            }
+        }
+    }
+
+    /// 编码为线上字节 (数据包字符串 + 结束符)
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut bytes = self.to_packet().to_feiq_string().into_bytes();
+        bytes.push(REQUEST_TERMINATOR);
+        bytes
+    }
+}
+
+/// 目录数据流中的条目头部
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntryHeader {
+    pub name: String,
+    pub size: u64,
+    pub attr: u32,
+}
+
+impl DirEntryHeader {
+    /// 编码头部
+    ///
+    /// 文件名中的 ':' 按 IPMsg 约定转义为 "::"
+    pub fn encode(&self) -> Vec<u8> {
+        let body = format!(":{}:{:x}:{:x}:", self.name.replace(':', "::"), self.size, self.attr);
+        let total = HEADER_SIZE_LEN + body.len();
+        format!("{:04x}{}", total, body).into_bytes()
+    }
+
+    /// 共享列表响应的状态头部
+    pub fn status(code: u64, message: &str) -> Self {
+        Self {
+            name: message.to_string(),
+            size: code,
+            attr: 0,
+        }
+    }
+
+    /// 是否为状态头部
+    pub fn is_status(&self) -> bool {
+        self.attr == 0
+    }
+
+    /// 解析头部长度字段
+    ///
+    /// 返回整个头部的字节数（包含长度字段本身）
+    pub fn parse_header_size(prefix: &[u8]) -> AppResult<usize> {
+        let text =
+            std::str::from_utf8(prefix).map_err(|_| AppError::Protocol("目录头部长度不是有效文本".to_string()))?;
+        let size =
+            usize::from_str_radix(text, 16).map_err(|_| AppError::Protocol(format!("无效的目录头部长度: {}", text)))?;
+        if size <= HEADER_SIZE_LEN {
+            return Err(AppError::Protocol(format!("目录头部长度过短: {}", size)));
+        }
+        Ok(size)
+    }
+
+    /// 解析头部剩余部分（长度字段之后的内容）
+    pub fn decode_body(body: &[u8]) -> AppResult<Self> {
+        let text = std::str::from_utf8(body).map_err(|_| AppError::Protocol("目录头部不是有效文本".to_string()))?;
+
+        // 跳过开头的 ':'，连续两个 ':' 表示文件名中的转义冒号
+        let mut fields: Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut chars = text.strip_prefix(':').unwrap_or(text).chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != ':' {
+                current.push(c);
+            } else if chars.peek() == Some(&':') {
+                chars.next();
+                current.push(':');
+            } else {
+                fields.push(std::mem::take(&mut current));
+            }
+        }
+
+        if fields.len() < 3 {
+            return Err(AppError::Protocol(format!("无效的目录头部: {}", text)));
+        }
+
+        let size = u64::from_str_radix(&fields[1], 16)
+            .map_err(|_| AppError::Protocol(format!("无效的文件大小: {}", fields[1])))?;
+        let attr = u32::from_str_radix(&fields[2], 16)
+            .map_err(|_| AppError::Protocol(format!("无效的文件属性: {}", fields[2])))?;
+
+        Ok(Self {
+            name: fields[0].clone(),
+            size,
+            attr,
+        })
+    }
+}
+
+/// 校验远端提供的文件名，防止路径穿越
+pub fn is_safe_entry_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains('/') && !name.contains('\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::feiq::parser::parse_feiq_packet;
+
+    #[test]
+    fn test_file_data_request_roundtrip() {
+        let request = TcpFileRequest::file_data("1700000000", 3, 65536);
+        let wire = request.to_wire();
+        assert_eq!(*wire.last().unwrap(), REQUEST_TERMINATOR);
+
+        let text = String::from_utf8(wire[..wire.len() - 1].to_vec()).unwrap();
+        let packet = parse_feiq_packet(&text).unwrap();
+        assert_eq!(TcpFileRequest::from_packet(&packet).unwrap(), request);
+    }
+
+    #[test]
+    fn test_dir_files_request_roundtrip() {
+        let request = TcpFileRequest::dir_files("1700000000", 7);
+        let text = request.to_packet().to_feiq_string();
+        let packet = parse_feiq_packet(&text).unwrap();
+        assert_eq!(TcpFileRequest::from_packet(&packet).unwrap(), request);
+    }
+
+    #[test]
+    fn test_list_dir_request_roundtrip() {
+        let request = TcpFileRequest::list_dir("builds/2026:10/app.zip");
+        let text = request.to_packet().to_feiq_string();
+        let packet = parse_feiq_packet(&text).unwrap();
+        assert_eq!(TcpFileRequest::from_packet(&packet).unwrap(), request);
+
+        let request = TcpFileRequest::list_shares();
+        let text = request.to_packet().to_feiq_string();
+        let packet = parse_feiq_packet(&text).unwrap();
+        assert_eq!(TcpFileRequest::from_packet(&packet).unwrap(), request);
+    }
+
+    #[test]
+    fn test_thumbnail_request_roundtrip() {
+        let request = TcpFileRequest::thumbnail("1700000000", 2);
+        let text = request.to_packet().to_feiq_string();
+        let packet = parse_feiq_packet(&text).unwrap();
+        assert_eq!(TcpFileRequest::from_packet(&packet).unwrap(), request);
+    }
+
+    #[test]
+    fn test_dir_entry_header_roundtrip() {
+        let header = DirEntryHeader {
+            name: ":a:b.txt".to_string(),
+            size: 1024,
+            attr: 1,
+        };
+        let encoded = header.encode();
+
+        let size = DirEntryHeader::parse_header_size(&encoded[..4]).unwrap();
+        assert_eq!(size, encoded.len());
+
+        let decoded = DirEntryHeader::decode_body(&encoded[4..]).unwrap();
+        assert_eq!(decoded, header);
+    }
+
+    #[test]
+    fn test_is_safe_entry_name() {
+        assert!(is_safe_entry_name("report.txt"));
+        assert!(!is_safe_entry_name(".."));
+        assert!(!is_safe_entry_name("../etc/passwd"));
+        assert!(!is_safe_entry_name("a\\b"));
+    }
+}
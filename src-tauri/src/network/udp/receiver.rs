@@ -3,6 +3,9 @@
 /// UDP 接收器 - 使用全局共享的 UDP 套接字
 use crate::event::bus::EVENT_SENDER;
 use crate::event::model::{AppEvent, NetworkEvent};
+use crate::network::feiq::capability::observe_peer_flags;
+use crate::network::feiq::constants::FEIQ_FLAG_COMPRESSED;
+use crate::network::feiq::model::FileAttachment;
 use crate::network::feiq::parser::{decode_gbk, parse_feiq_packet};
 use tracing::{debug, error, info, warn};
 
@@ -18,6 +21,8 @@ fn publish_event_from_packet(
     let mac_addr = Some(packet.mac_addr_formatted.clone());
 
     let msg_sub_type = packet.ext_info.msg_sub_type;
+    observe_peer_flags(&sender_ip, msg_sub_type, packet.extra_flag);
+
     let event = match msg_sub_type {
         9 => {
             AppEvent::Network(NetworkEvent::UserOnline {
@@ -39,8 +44,27 @@ fn publish_event_from_packet(
                 hostname: Some(hostname),
             })
         }
+        0x20 if packet.is_file_offer() => {
+            // 文件附件请求: "filename:size:mtime:attr[:扩展字段]\a..."
+            let files = match FileAttachment::from_ipmsg_header(&packet.ext_info.remark) {
+                Ok(files) => files,
+                Err(e) => {
+                    warn!("Invalid file attachment format: {}", e);
+                    return Ok(());
+                }
+            };
+            AppEvent::Network(NetworkEvent::FileRequestReceived {
+                from_ip: sender_ip,
+                from_port: sender_port,
+                packet_no: packet.ext_info.unique_id.clone(),
+                files: serde_json::to_string(&files).map_err(|e| e.to_string())?,
+            })
+        }
         0x20 => {
-            let content = packet.ext_info.remark.clone();
+            let (content, reply_to, quote) = match packet.reply_info() {
+                Some((reply_msg_no, quote, content)) => (content, Some(reply_msg_no), Some(quote)),
+                None => (packet.ext_info.remark.clone(), None, None),
+            };
             let msg_no = packet.ext_info.unique_id.clone();
             let needs_receipt = true;
             AppEvent::Network(NetworkEvent::MessageReceived {
@@ -50,15 +74,184 @@ fn publish_event_from_packet(
                 content,
                 msg_no,
                 needs_receipt,
+                reply_to,
+                quote,
             })
         }
-        0x21 => {
-            let msg_no = packet.ext_info.unique_id.clone();
-            AppEvent::Network(NetworkEvent::MessageReceiptReceived { msg_no })
+        0x21 => AppEvent::Network(NetworkEvent::MessageReceiptReceived {
+            sender_ip,
+            sender_port,
+            msg_no: packet.receipt_msg_no(),
+        }),
+        0x30 => AppEvent::Network(NetworkEvent::MessageRead {
+            sender_ip,
+            sender_port,
+            msg_no: packet.receipt_msg_no(),
+        }),
+        0x31 => {
+            // DELMSG: 被删除消息的编号在 remark 中
+            let msg_no = packet.ext_info.remark.clone();
+            AppEvent::Network(NetworkEvent::MessageDeleted {
+                sender_ip,
+                sender_port,
+                msg_no,
+                sent_at: packet.ext_info.timestamp,
+            })
         }
-        0x30 => {
-            let msg_no = packet.ext_info.unique_id.clone();
-            AppEvent::Network(NetworkEvent::MessageRead { msg_no })
+        0x33 => {
+            // Recall message: "msg_no[:window_secs]"
+            let (msg_no, window_secs) = packet.recall_info();
+            AppEvent::Network(NetworkEvent::MessageRecalled {
+                sender_ip,
+                sender_port,
+                msg_no,
+                sent_at: packet.ext_info.timestamp,
+                window_secs,
+            })
+        }
+        0x34 => {
+            // Edit message: "msg_no:content"
+            let (msg_no, content) = packet.revision_info();
+            AppEvent::Network(NetworkEvent::MessageEdited {
+                sender_ip,
+                sender_port,
+                msg_no,
+                content,
+            })
+        }
+        0x35 => {
+            // Group message: "group_no group_msg_no sender\ncontent"
+            let (envelope, message) = match packet.open_group_envelope() {
+                Some(opened) => opened,
+                None => {
+                    warn!("Invalid group message format: {}", packet.ext_info.remark);
+                    return Ok(());
+                }
+            };
+            let poll = message.poll_info();
+            let (content, reply_to, quote) = match (&poll, message.reply_info()) {
+                (Some(poll), _) => (poll.question.clone(), None, None),
+                (None, Some((reply_msg_no, quote, content))) => (content, Some(reply_msg_no), Some(quote)),
+                (None, None) => (message.ext_info.remark.clone(), None, None),
+            };
+            let poll = match poll {
+                Some(poll) => Some(serde_json::to_string(&poll).map_err(|e| e.to_string())?),
+                None => None,
+            };
+            AppEvent::Network(NetworkEvent::GroupMessageReceived {
+                sender_ip,
+                sender_port,
+                sender_nickname,
+                group_no: envelope.group_no,
+                group_msg_no: envelope.group_msg_no,
+                group_sender: envelope.sender,
+                mentions: envelope.mentions,
+                content,
+                msg_no: packet.ext_info.unique_id.clone(),
+                reply_to,
+                quote,
+                poll,
+            })
+        }
+        0x36 => {
+            // Group sync: "group_no count head\nclock origin kind member value..."
+            let sync = match packet.group_sync_info() {
+                Some(sync) => sync,
+                None => {
+                    warn!("Invalid group sync format: {}", packet.ext_info.remark);
+                    return Ok(());
+                }
+            };
+            AppEvent::Network(NetworkEvent::GroupSyncReceived {
+                sender_ip,
+                sender_port,
+                group_no: sync.group_no,
+                ops: serde_json::to_string(&sync.ops).map_err(|e| e.to_string())?,
+                count: sync.count,
+                head: sync.head,
+            })
+        }
+        0x37 => {
+            // Group request: "request_no action group_no expire_at\ngroup_name"
+            let request = match packet.group_request_info() {
+                Some(request) => request,
+                None => {
+                    warn!("Invalid group request format: {}", packet.ext_info.remark);
+                    return Ok(());
+                }
+            };
+            AppEvent::Network(NetworkEvent::GroupRequestReceived {
+                sender_ip,
+                sender_port,
+                request_no: request.request_no,
+                action: request.action,
+                group_no: request.group_no,
+                expire_at: request.expire_at,
+                group_name: request.group_name,
+            })
+        }
+        0x38 => {
+            // Poll vote: "poll_no vote choices" / vote ack: "poll_no ack accepted choices"
+            // poll result: "poll_no result closed voter_count\ncount voters..."
+            if let Some((poll_no, choices)) = packet.poll_vote_info() {
+                AppEvent::Network(NetworkEvent::PollVoteReceived {
+                    sender_ip,
+                    sender_port,
+                    poll_no,
+                    choices,
+                })
+            } else if let Some((poll_no, accepted, choices)) = packet.poll_vote_ack_info() {
+                AppEvent::Network(NetworkEvent::PollVoteAcked {
+                    sender_ip,
+                    sender_port,
+                    poll_no,
+                    accepted,
+                    choices,
+                })
+            } else if let Some(result) = packet.poll_result_info() {
+                AppEvent::Network(NetworkEvent::PollResultReceived {
+                    sender_ip,
+                    sender_port,
+                    result: serde_json::to_string(&result).map_err(|e| e.to_string())?,
+                })
+            } else {
+                warn!("Invalid poll message format: {}", packet.ext_info.remark);
+                return Ok(());
+            }
+        }
+        0x39 => {
+            // Reaction: "msg_no emoji group_no msg_sender"
+            let reaction = match packet.reaction_info() {
+                Some(reaction) => reaction,
+                None => {
+                    warn!("Invalid reaction format: {}", packet.ext_info.remark);
+                    return Ok(());
+                }
+            };
+            AppEvent::Network(NetworkEvent::ReactionReceived {
+                sender_ip,
+                sender_port,
+                msg_no: reaction.msg_no,
+                emoji: reaction.emoji,
+                group_no: reaction.group_no,
+                msg_sender: reaction.msg_sender,
+            })
+        }
+        0x3A => {
+            // Typing state: "state group_no"
+            let (typing, group_no) = match packet.typing_info() {
+                Some(info) => info,
+                None => {
+                    debug!("Invalid typing state format: {}", packet.ext_info.remark);
+                    return Ok(());
+                }
+            };
+            AppEvent::Network(NetworkEvent::TypingReceived {
+                sender_ip,
+                sender_port,
+                typing,
+                group_no,
+            })
         }
         0x60 => {
             // File data request: "packet_no:file_id:offset"
@@ -66,7 +259,7 @@ fn publish_event_from_packet(
             let parts: Vec<&str> = remark.split(':').collect();
             if parts.len() >= 3 {
                 let packet_no = parts[0].to_string();
-                let file_id = parts[1].parse::<u32>().unwrap_or(0) as u64;
+                let file_id = parts[1].parse::<u64>().unwrap_or(0);
                 let offset = parts[2].parse::<u64>().unwrap_or(0);
                 AppEvent::Network(NetworkEvent::FileDataRequest {
                     from_ip: sender_ip,
@@ -85,7 +278,7 @@ fn publish_event_from_packet(
             let parts: Vec<&str> = remark.split(':').collect();
             if parts.len() >= 4 {
                 let packet_no = parts[0].to_string();
-                let file_id = parts[1].parse::<u32>().unwrap_or(0) as u64;
+                let file_id = parts[1].parse::<u64>().unwrap_or(0);
                 let offset = parts[2].parse::<u64>().unwrap_or(0);
                 let data = parts[3].to_string();
                 AppEvent::Network(NetworkEvent::FileDataReceived {
@@ -94,18 +287,39 @@ fn publish_event_from_packet(
                     file_id,
                     offset,
                     data,
+                    compressed: packet.extra_flag & FEIQ_FLAG_COMPRESSED != 0,
                 })
             } else {
                 warn!("Invalid file data format: {}", remark);
                 return Ok(());
             }
         }
+        0x63 => {
+            // File data ACK: "packet_no:file_id:offset"
+            let remark = &packet.ext_info.remark;
+            let parts: Vec<&str> = remark.split(':').collect();
+            if parts.len() >= 3 {
+                let packet_no = parts[0].to_string();
+                let file_id = parts[1].parse::<u64>().unwrap_or(0);
+                let offset = parts[2].parse::<u64>().unwrap_or(0);
+                AppEvent::Network(NetworkEvent::FileDataAck {
+                    from_ip: sender_ip,
+                    packet_no,
+                    file_id,
+                    offset,
+                })
+            } else {
+                warn!("Invalid file data ack format: {}", remark);
+                return Ok(());
+            }
+        }
         0x62 => {
-            // File release: "packet_no"
-            let packet_no = packet.ext_info.remark.clone();
+            // File release: "packet_no[:reason]"
+            let (packet_no, reason) = packet.release_info();
             AppEvent::Network(NetworkEvent::FileRelease {
                 from_ip: sender_ip,
                 packet_no,
+                reason,
             })
         }
         _ => {
@@ -126,7 +340,8 @@ pub async fn start_udp_receiver() -> Result<(), Box<dyn std::error::Error>> {
     let socket = super::socket::get_udp_socket();
     info!("UDP 接收器已启动，使用全局共享套接字监听端口 2425");
 
-    let mut buf = [0u8; 2048];
+    // 文件数据块可能超过 MTU (依赖 IP 分片)，按 UDP 最大报文分配缓冲区
+    let mut buf = vec![0u8; 65536];
 
     loop {
         match socket.recv_from(&mut buf).await {
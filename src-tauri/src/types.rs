@@ -102,6 +102,11 @@ impl From<crate::error::AppError> for FrontendError {
                 message: format!("{} 已存在", what),
                 details: None,
             },
+            crate::error::AppError::Permission(reason) => FrontendError {
+                code: ErrorCode::Permission,
+                message: reason,
+                details: None,
+            },
         }
     }
 }
@@ -204,6 +209,7 @@ pub enum MessageType {
     Text = 0,  // 文字消息
     File = 1,  // 文件消息
     Emoji = 2, // Emoji 消息
+    Poll = 6,  // 投票消息（3-5 为前端保留的图片/语音/视频）
 }
 
 /// 消息状态
@@ -226,6 +232,54 @@ pub struct ChatMessage {
     pub content: String,
     pub send_time: String,
     pub status: MessageStatus,
+    pub edited: bool,     // 内容被发送者修改过
+    pub is_revoked: bool, // 已被发送者撤回
+    pub reply_to: Option<QuotedMessage>,
+    pub poll: Option<PollInfo>,          // 投票消息中的投票
+    pub reactions: Vec<ReactionSummary>, // 按表情汇总的回应
+}
+
+/// 投票消息中的投票
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollInfo {
+    pub poll_id: i64,
+    pub mid: i64,
+    pub gid: i64,
+    pub creator_uid: i64,
+    pub question: String,
+    pub options: Vec<PollOptionInfo>,
+    pub multiple: bool,
+    pub anonymous: bool,
+    pub deadline: Option<String>,
+    pub closed: bool, // 已被创建者结束或已过截止时间
+    pub voter_count: i64,
+    pub my_choices: Vec<usize>, // 当前用户所选的选项序号
+}
+
+/// 投票选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOptionInfo {
+    pub text: String,
+    pub count: i64,
+    pub voters: Vec<i64>, // 投票人（匿名投票为空）
+}
+
+/// 消息的一种表情回应
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReactionSummary {
+    pub emoji: String,
+    pub count: i64,
+    pub reactors: Vec<i64>, // 回应者（按回应时间正序）
+    pub reacted_by_me: bool,
+}
+
+/// 引用回复所引用的消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotedMessage {
+    pub mid: Option<i64>,        // 原消息 ID（本地找不到原消息时为空）
+    pub sender_uid: Option<i64>, // 原消息发送者
+    pub content: String,         // 原消息内容，找不到原消息时为引用摘要
+    pub is_revoked: bool,        // 原消息已被撤回
 }
 
 /// 聊天会话
@@ -237,9 +291,49 @@ pub struct ChatSession {
     pub target_id: i64,
     pub last_msg_id: Option<i64>,
     pub unread_count: i32,
+    pub mention_count: i32, // 提及本机用户的未读消息数
     pub update_time: String,
 }
 
+/// 群消息已读统计（"已读 5/12"）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupReadSummary {
+    pub mid: i64,
+    pub total: usize,     // 接收成员数
+    pub delivered: usize, // 已送达（包括已读）
+    pub read: usize,
+}
+
+/// 群消息已读和未读的成员
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupReadMembers {
+    pub mid: i64,
+    pub read: Vec<GroupReceiptMember>,
+    pub unread: Vec<GroupReceiptMember>,
+}
+
+/// 群消息接收成员的回执
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupReceiptMember {
+    pub member_uid: i64,
+    pub nickname: String,
+    pub status: i8, // 0-未送达, 1-已送达, 2-已读
+    pub read_time: Option<String>,
+}
+
+/// 提及当前用户的群消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MentionInfo {
+    pub mid: i64,
+    pub gid: i64,
+    pub group_name: String,
+    pub sender_uid: i64,
+    pub sender_nickname: String,
+    pub content: String,
+    pub mention_all: bool, // 通过 @所有人 提及
+    pub send_time: String,
+}
+
 // ============================================================
 // 通讯录相关
 // ============================================================
@@ -266,6 +360,7 @@ pub struct GroupInfo {
     pub avatar: Option<String>,
     pub creator_uid: i64,
     pub desc: Option<String>,
+    pub discoverable: bool, // 是否允许申请加入
     pub create_time: String,
 }
 
@@ -288,6 +383,49 @@ pub struct GroupMember {
     pub join_time: String,
 }
 
+/// 群邀请/入群申请
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRequestInfo {
+    pub id: i64,
+    pub kind: i8,      // 0-群邀请, 1-入群申请
+    pub direction: i8, // 0-收到, 1-发出
+    pub gid: Option<i64>,
+    pub group_no: String,
+    pub group_name: String,
+    pub from_uid: i64,
+    pub from_nickname: String, // 邀请者或申请者昵称
+    pub to_uid: i64,
+    pub status: i8, // 0-待处理, 1-已接受, 2-已拒绝, 3-已过期
+    pub expire_time: String,
+    pub create_time: String,
+}
+
+/// 群公告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupAnnouncementInfo {
+    pub id: i64,
+    pub gid: i64,
+    pub content: String,
+    pub author_uid: i64,
+    pub author_nickname: String,
+    pub editor_uid: i64,
+    pub pinned: bool,
+    pub ack_count: usize,    // 已确认的当前成员数
+    pub member_count: usize, // 当前成员数
+    pub acknowledged: bool,  // 当前用户是否已确认
+    pub create_time: String,
+    pub update_time: String,
+}
+
+/// 群公告成员确认情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupAnnouncementAckInfo {
+    pub member_uid: i64,
+    pub nickname: String,
+    pub acknowledged: bool,
+    pub ack_time: Option<String>,
+}
+
 // ============================================================
 // 文件相关
 // ============================================================
@@ -311,10 +449,44 @@ pub enum TransferStatus {
     Pending = 0,      // 等待中
     Transferring = 1, // 传输中
     Completed = 2,    // 已完成
+    Paused = 3,       // 已暂停
     Failed = -1,      // 失败
     Cancelled = -2,   // 已取消
 }
 
+impl TransferStatus {
+    /// 由数据库中的状态码转换
+    pub fn from_code(code: i8) -> Self {
+        match code {
+            0 => TransferStatus::Pending,
+            1 => TransferStatus::Transferring,
+            2 => TransferStatus::Completed,
+            3 => TransferStatus::Paused,
+            -1 => TransferStatus::Failed,
+            _ => TransferStatus::Cancelled,
+        }
+    }
+}
+
+/// 文件接收策略动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilePolicyAction {
+    Ask = 0,    // 询问用户
+    Accept = 1, // 自动接收
+    Reject = 2, // 自动拒绝
+}
+
+impl FilePolicyAction {
+    /// 由数据库中的动作码转换
+    pub fn from_code(code: i8) -> Self {
+        match code {
+            1 => FilePolicyAction::Accept,
+            2 => FilePolicyAction::Reject,
+            _ => FilePolicyAction::Ask,
+        }
+    }
+}
+
 /// 文件传输进度
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
@@ -323,6 +495,24 @@ pub struct TransferProgress {
     pub progress: u64,
     pub total: u64,
     pub status: TransferStatus,
+    pub rate: u64,              // 当前速率 (字节/秒)
+    pub compression_ratio: f64, // 压缩后/压缩前，1.0 表示未压缩（TCP 通道不压缩）
+    pub eta_secs: Option<u64>,  // 预计剩余时间（秒）
+}
+
+/// 与某个联系人之间的传输汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerTransferTotals {
+    pub peer_uid: i64, // 0 表示未知用户，按 IP 汇总
+    pub peer_ip: String,
+    pub nickname: Option<String>,
+    pub sent_count: u64,
+    pub sent_bytes: u64,
+    pub received_count: u64,
+    pub received_bytes: u64,
+    pub total_duration_ms: u64,
+    pub avg_speed: u64, // 平均速率 (字节/秒)
+    pub last_transfer_time: String,
 }
 
 /// 待恢复的传输信息
@@ -338,3 +528,109 @@ pub struct PendingTransfer {
     pub target_ip: String,
     pub direction: i8, // 0=下载, 1=上传
 }
+
+/// 对端限速
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerRateLimit {
+    pub peer_ip: String,
+    pub bytes_per_sec: u64,
+}
+
+/// 单个传输限速
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRateLimit {
+    pub packet_no: String,
+    pub file_id: u64,
+    pub bytes_per_sec: u64,
+}
+
+/// 传输限速设置（字节/秒，0 表示不限速）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitSettings {
+    pub global: u64,
+    pub per_peer: Vec<PeerRateLimit>,
+    pub per_transfer: Vec<TransferRateLimit>,
+}
+
+/// 传输在调度队列中的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueueItemState {
+    Queued,  // 排队中
+    Running, // 运行中
+    Paused,  // 已暂停
+    Failed,  // 失败（可重试）
+}
+
+/// 调度队列中的一项传输
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTransfer {
+    pub tid: i64,
+    pub file_id: i64,
+    pub file_name: String,
+    pub direction: i8, // 0=下载, 1=上传
+    pub target_ip: String,
+    pub state: QueueItemState,
+    pub priority: i32,
+    pub position: Option<usize>, // 排队位置 (从 1 开始，仅排队中的传输有)
+    pub retries: u32,
+    pub transferred: i64,
+    pub file_size: i64,
+    pub error_message: Option<String>,
+}
+
+/// 传输调度队列状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferQueue {
+    pub max_concurrent: usize,
+    pub running: usize,
+    pub items: Vec<QueuedTransfer>,
+}
+
+/// 文件垃圾回收结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileGcReport {
+    pub removed_files: usize,
+    pub freed_bytes: u64,
+}
+
+/// 本端发布的共享文件夹
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedFolderInfo {
+    pub share_id: i64,
+    pub name: String,
+    pub path: String,
+    pub contact_uids: Vec<i64>, // 允许访问的联系人，为空表示所有联系人
+}
+
+/// 对端共享中的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteShareEntry {
+    pub name: String,
+    pub path: String, // 共享路径 "共享名/相对路径"，用于继续浏览或下载
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// 单个接收方的文件发送进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipientProgress {
+    pub target_ip: String,
+    pub target_uid: Option<i64>,
+    pub status: TransferStatus,
+    pub file_count: usize,
+    pub completed_files: usize,
+    pub transferred: i64,
+    pub total: i64,
+}
+
+/// 一次文件发送（单聊/多人/群组）的汇总进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOfferProgress {
+    pub packet_no: String,
+    pub session_type: i8, // 0=单聊, 1=群聊
+    pub target_id: i64,   // 单聊为首个接收方 ID，群聊为群组 ID
+    pub recipients: Vec<RecipientProgress>,
+    pub completed_recipients: usize,
+    pub transferred: i64,
+    pub total: i64,
+}
@@ -336,6 +336,8 @@ async fn test_file_attachment_creation() {
         file_size,
         mtime: 0,
         attr: 0,
+        image_size: None,
+        file_id: 0,
     };
 
     let packet = FeiQPacket::make_feiq_file_attach_packet(
@@ -388,6 +390,7 @@ async fn test_file_transfer_database_persistence() {
         target_port: sea_orm::ActiveValue::Set(2425),
         checksum: sea_orm::ActiveValue::Set("abc123".to_string()),
         error_message: sea_orm::ActiveValue::Set(None),
+        rate_limit: sea_orm::ActiveValue::Set(0),
         update_time: sea_orm::ActiveValue::Set(now.clone()),
         create_time: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc()),
     };
@@ -620,6 +623,7 @@ async fn test_file_transfer_cancellation() {
         target_port: sea_orm::ActiveValue::Set(2425),
         checksum: sea_orm::ActiveValue::Set("xyz789".to_string()),
         error_message: sea_orm::ActiveValue::Set(None),
+        rate_limit: sea_orm::ActiveValue::Set(0),
         update_time: sea_orm::ActiveValue::Set(now),
         create_time: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc()),
     };
@@ -664,6 +668,8 @@ async fn test_udp_file_transfer_flow() {
         file_size: 1024,
         mtime: 0,
         attr: 1,
+        image_size: None,
+        file_id: 0,
     }];
 
     let attach_packet = FeiQPacket::make_feiq_file_attach_packet(&files, None);
@@ -752,6 +758,8 @@ async fn test_udp_bidirectional_communication() {
         file_size: 2048,
         mtime: 0,
         attr: 1,
+        image_size: None,
+        file_id: 0,
     }];
     let attach_packet = FeiQPacket::make_feiq_file_attach_packet(&files1, None);
     let data1 = attach_packet.to_feiq_string();
@@ -762,6 +770,8 @@ async fn test_udp_bidirectional_communication() {
         file_size: 4096,
         mtime: 0,
         attr: 1,
+        image_size: None,
+        file_id: 0,
     }];
     let attach_packet2 = FeiQPacket::make_feiq_file_attach_packet(&files2, None);
     let data2 = attach_packet2.to_feiq_string();
@@ -825,3 +835,410 @@ async fn test_udp_packet_loss_simulation() {
 
     assert!(result.is_ok(), "Should receive packet within timeout");
 }
+
+// ============================================================
+// TCP 文件通道集成测试
+// ============================================================
+
+/// 按文件ID提供本地路径的测试文件来源
+struct TestFileSource {
+    files: std::collections::HashMap<u64, PathBuf>,
+}
+
+#[async_trait::async_trait]
+impl feiqiu_communication::network::tcp::FileSource for TestFileSource {
+    async fn resolve(
+        &self,
+        _peer_ip: &str,
+        _packet_no: &str,
+        file_id: u64,
+    ) -> feiqiu_communication::AppResult<PathBuf> {
+        self.files
+            .get(&file_id)
+            .cloned()
+            .ok_or_else(|| feiqiu_communication::AppError::NotFound(format!("file_id={}", file_id)))
+    }
+}
+
+/// 在回环地址的随机端口上启动 TCP 文件通道，返回监听地址
+async fn start_loopback_listener(files: std::collections::HashMap<u64, PathBuf>) -> String {
+    use feiqiu_communication::network::tcp::serve_tcp_listener;
+    use std::sync::Arc;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("Failed to bind TCP listener");
+    let addr = listener.local_addr().expect("Failed to get listener addr").to_string();
+    tokio::spawn(serve_tcp_listener(listener, Arc::new(TestFileSource { files })));
+    addr
+}
+
+#[tokio::test]
+async fn test_tcp_loopback_file_and_dir_transfer() {
+    use feiqiu_communication::network::tcp::TcpFileClient;
+    use tokio::io::AsyncReadExt;
+
+    // 大于发送缓冲区的文件，以及包含子目录的目录
+    let file_path = create_test_file("tcp_loopback.bin", 200 * 1024).await;
+    let file_data = fs::read(&file_path).unwrap();
+
+    let root = std::env::temp_dir().join(format!("feiqiu_test_tcp_loopback_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let share = root.join("share");
+    fs::create_dir_all(share.join("sub")).unwrap();
+    fs::write(share.join("a.txt"), b"hello").unwrap();
+    fs::write(share.join("sub").join("b.bin"), &file_data[..70 * 1024]).unwrap();
+    // 指向上级目录的符号链接不被跟随
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&root, share.join("loop")).unwrap();
+
+    let addr = start_loopback_listener(
+        [(1u64, file_path.clone()), (2u64, share.clone())].into_iter().collect(),
+    )
+    .await;
+
+    // GETFILEDATA：完整读取
+    let mut stream = TcpFileClient::connect(&addr)
+        .await
+        .unwrap()
+        .request_file_data("1000", 1, 0)
+        .await
+        .unwrap();
+    let mut received = Vec::new();
+    stream.read_to_end(&mut received).await.unwrap();
+    assert_eq!(calculate_checksum(&received), calculate_checksum(&file_data));
+
+    // GETFILEDATA：从偏移处续传
+    let offset = 123_456u64;
+    let mut stream = TcpFileClient::connect(&addr)
+        .await
+        .unwrap()
+        .request_file_data("1000", 1, offset)
+        .await
+        .unwrap();
+    let mut rest = Vec::new();
+    stream.read_to_end(&mut rest).await.unwrap();
+    assert_eq!(rest, &file_data[offset as usize..]);
+
+    // GETDIRFILES：目录结构和内容一致，进度累计到文件字节总数
+    let dest = root.join("dest");
+    fs::create_dir_all(&dest).unwrap();
+    let mut last_progress = 0u64;
+    let total = TcpFileClient::connect(&addr)
+        .await
+        .unwrap()
+        .fetch_dir_files("1000", 2, &dest, |received| last_progress = received)
+        .await
+        .unwrap();
+    assert_eq!(total, 5 + 70 * 1024);
+    assert_eq!(last_progress, total);
+    assert_eq!(fs::read(dest.join("share").join("a.txt")).unwrap(), b"hello");
+    assert_eq!(
+        fs::read(dest.join("share").join("sub").join("b.bin")).unwrap(),
+        &file_data[..70 * 1024]
+    );
+    assert!(!dest.join("share").join("loop").exists());
+
+    // 再次接收到同一目录：已有文件不被覆盖，新文件改名保存
+    fs::write(dest.join("share").join("a.txt"), b"local edit").unwrap();
+    TcpFileClient::connect(&addr)
+        .await
+        .unwrap()
+        .fetch_dir_files("1000", 2, &dest, |_| {})
+        .await
+        .unwrap();
+    assert_eq!(fs::read(dest.join("share").join("a.txt")).unwrap(), b"local edit");
+    assert_eq!(fs::read(dest.join("share").join("a (1).txt")).unwrap(), b"hello");
+
+    // 未知文件ID：连接被关闭，没有数据
+    let mut stream = TcpFileClient::connect(&addr)
+        .await
+        .unwrap()
+        .request_file_data("1000", 9, 0)
+        .await
+        .unwrap();
+    let mut nothing = Vec::new();
+    let _ = stream.read_to_end(&mut nothing).await;
+    assert!(nothing.is_empty());
+
+    let _ = fs::remove_dir_all(&root);
+    let _ = fs::remove_file(&file_path);
+}
+
+/// 创建一条传输记录
+async fn create_transfer(
+    db: &sea_orm::DbConn,
+    packet_no: &str,
+    file_id: i64,
+    direction: i8,
+    status: i8,
+    target_ip: &str,
+) -> transfer_state::Model {
+    let transfer_state = transfer_state::ActiveModel {
+        tid: sea_orm::ActiveValue::NotSet,
+        file_id: sea_orm::ActiveValue::Set(file_id),
+        session_type: sea_orm::ActiveValue::Set(0),
+        target_id: sea_orm::ActiveValue::Set(2),
+        direction: sea_orm::ActiveValue::Set(direction),
+        transferred: sea_orm::ActiveValue::Set(0),
+        file_size: sea_orm::ActiveValue::Set(1024),
+        status: sea_orm::ActiveValue::Set(status),
+        packet_no: sea_orm::ActiveValue::Set(packet_no.to_string()),
+        target_ip: sea_orm::ActiveValue::Set(target_ip.to_string()),
+        target_port: sea_orm::ActiveValue::Set(2425),
+        checksum: sea_orm::ActiveValue::Set(String::new()),
+        error_message: sea_orm::ActiveValue::Set(None),
+        rate_limit: sea_orm::ActiveValue::Set(0),
+        update_time: sea_orm::ActiveValue::Set(String::new()),
+        create_time: sea_orm::ActiveValue::Set(chrono::Utc::now().naive_utc()),
+    };
+
+    TransferStateHandler::create(db, transfer_state)
+        .await
+        .expect("Failed to create transfer state")
+}
+
+#[tokio::test]
+async fn test_file_release_only_cancels_releasing_peer() {
+    use feiqiu_communication::core::file::FileTransferHandler;
+
+    // 同一批文件发给了两个接收方，只有一方释放
+    let db = init_test_db().await;
+    let first = create_transfer(&db, "release_packet", 1, 1, 1, "192.168.1.10").await;
+    let second = create_transfer(&db, "release_packet", 1, 1, 1, "192.168.1.11").await;
+
+    FileTransferHandler::handle_file_release(&db, "192.168.1.10", "release_packet", None)
+        .await
+        .unwrap();
+    // 与该批文件无关的主机发来的释放包不影响任何接收方
+    FileTransferHandler::handle_file_release(&db, "192.168.1.99", "release_packet", None)
+        .await
+        .unwrap();
+
+    let first = TransferStateHandler::find_by_id(&db, first.tid).await.unwrap().unwrap();
+    let second = TransferStateHandler::find_by_id(&db, second.tid).await.unwrap().unwrap();
+    assert_eq!(first.status, -2);
+    assert_eq!(second.status, 1);
+}
+
+// ============================================================
+// 消息编号跨实例集成测试
+// ============================================================
+
+/// 把对方序列化发出的消息包按接收流程存入本地
+async fn receive_message(db: &sea_orm::DbConn, sent: &FeiQPacket) {
+    use feiqiu_communication::core::chat::receiver::MessageReceiver;
+
+    let packet = parse_feiq_packet(&sent.to_feiq_string()).expect("Failed to parse packet");
+    let (reply_to, quote, content) = match packet.reply_info() {
+        Some((msg_no, quote, content)) => (Some(msg_no), Some(quote), content),
+        None => (None, None, packet.ext_info.remark.clone()),
+    };
+    MessageReceiver::handle_message_received(
+        db,
+        "192.168.1.10".to_string(),
+        2425,
+        packet.ext_info.nickname.clone(),
+        content,
+        packet.ext_info.unique_id.clone(),
+        false,
+        reply_to,
+        quote,
+        None,
+        None,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_reply_resolves_across_serialized_packets() {
+    use feiqiu_communication::database::model::chat_message;
+    use sea_orm::{EntityTrait, QueryOrder};
+
+    // 对方先发一条消息，再引用回复自己的这条消息
+    let db = init_test_db().await;
+    let original = FeiQPacket::make_feiq_message_packet("明天 10 点开会", Some("alice"));
+    let other = FeiQPacket::make_feiq_message_packet("另一条消息", Some("alice"));
+    let reply = FeiQPacket::make_feiq_reply_packet("好的", &original.ext_info.unique_id, "明天 10 点开会", Some("alice"));
+
+    receive_message(&db, &original).await;
+    receive_message(&db, &other).await;
+    receive_message(&db, &reply).await;
+
+    let messages = chat_message::Entity::find()
+        .order_by_asc(chat_message::Column::Mid)
+        .all(&db)
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].msg_no.as_deref(), Some(original.ext_info.unique_id.as_str()));
+    assert_ne!(messages[0].msg_no, messages[1].msg_no, "Messages from one sender keep distinct numbers");
+    assert_eq!(messages[2].content, "好的");
+    assert_eq!(messages[2].reply_to_mid, Some(messages[0].mid));
+}
+
+#[tokio::test]
+async fn test_reaction_lands_on_reacted_message_across_instances() {
+    use feiqiu_communication::core::chat::ReactionService;
+    use feiqiu_communication::database::model::chat_message;
+    use feiqiu_communication::network::feiq::model::Reaction;
+    use sea_orm::{EntityTrait, QueryOrder};
+
+    // A（192.168.1.10）发出两条消息，本地按发出时的包ID记录
+    let db_a = init_test_db().await;
+    let peer_b = UserHandler::create(
+        &db_a,
+        user::Model {
+            uid: 0,
+            feiq_ip: "192.168.1.20".to_string(),
+            feiq_port: 2425,
+            feiq_machine_id: "192.168.1.20:2425".to_string(),
+            nickname: "bob".to_string(),
+            avatar: None,
+            status: 1,
+            create_time: chrono::Utc::now().naive_utc(),
+            update_time: chrono::Utc::now().naive_utc(),
+        },
+    )
+    .await
+    .unwrap();
+    let first = FeiQPacket::make_feiq_message_packet("第一条", Some("alice"));
+    let second = FeiQPacket::make_feiq_message_packet("第二条", Some("alice"));
+    let mut sent_mids = Vec::new();
+    for packet in [&first, &second] {
+        let message = ChatMessageHandler::create_with_msg_no(
+            &db_a,
+            0,
+            peer_b.uid,
+            1,
+            packet.ext_info.remark.clone(),
+            0,
+            Some(packet.ext_info.unique_id.clone()),
+        )
+        .await
+        .unwrap();
+        sent_mids.push(message.mid);
+    }
+
+    // B 收到这两条消息，回应第二条
+    let db_b = init_test_db().await;
+    receive_message(&db_b, &first).await;
+    receive_message(&db_b, &second).await;
+    let received = chat_message::Entity::find()
+        .order_by_asc(chat_message::Column::Mid)
+        .all(&db_b)
+        .await
+        .unwrap();
+    let reaction = Reaction {
+        msg_no: received[1].msg_no.clone().unwrap(),
+        emoji: Some("👍".to_string()),
+        group_no: None,
+        msg_sender: None,
+    };
+    let packet = FeiQPacket::make_feiq_reaction_packet(&reaction, Some("bob"));
+
+    // A 收到回应包后只有第二条消息带上回应
+    let parsed = parse_feiq_packet(&packet.to_feiq_string()).unwrap();
+    ReactionService::apply_remote(&db_a, "192.168.1.20", 2425, parsed.reaction_info().unwrap())
+        .await
+        .unwrap();
+    let first_reactions = ReactionService::get_reactions(&db_a, sent_mids[0], 1).await.unwrap();
+    let second_reactions = ReactionService::get_reactions(&db_a, sent_mids[1], 1).await.unwrap();
+    assert!(first_reactions.is_empty());
+    assert_eq!(second_reactions.len(), 1);
+    assert_eq!(second_reactions[0].emoji, "👍");
+}
+
+// ============================================================
+// 消息修订集成测试
+// ============================================================
+
+#[tokio::test]
+async fn test_recall_applies_locally_and_queues_for_offline_peer() {
+    use feiqiu_communication::core::chat::MessageRevisionService;
+    use feiqiu_communication::database::handler::PendingRevisionHandler;
+
+    let db = init_test_db().await;
+    let offline = UserHandler::create(
+        &db,
+        user::Model {
+            uid: 0,
+            feiq_ip: "192.168.1.20".to_string(),
+            feiq_port: 2425,
+            feiq_machine_id: "192.168.1.20:2425".to_string(),
+            nickname: "bob".to_string(),
+            avatar: None,
+            status: 0,
+            create_time: chrono::Utc::now().naive_utc(),
+            update_time: chrono::Utc::now().naive_utc(),
+        },
+    )
+    .await
+    .unwrap();
+    let message =
+        ChatMessageHandler::create_with_msg_no(&db, 0, offline.uid, 1, "hello".to_string(), 0, Some("T1".to_string()))
+            .await
+            .unwrap();
+
+    // 对方离线：本地先撤回，通知存入待补发表
+    let recalled = MessageRevisionService::recall_message(&db, message.mid, 1).await.unwrap();
+    assert_eq!(recalled.edit_state, 2);
+    let pending = PendingRevisionHandler::list_by_target(&db, offline.uid).await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!((pending[0].msg_no.as_str(), pending[0].action), ("T1", 2));
+    assert_eq!(pending[0].window_secs, 120);
+
+    // 会话对端不存在时通知无法发出，本地编辑仍然生效
+    let orphan =
+        ChatMessageHandler::create_with_msg_no(&db, 0, 999, 1, "draft".to_string(), 0, Some("T2".to_string()))
+            .await
+            .unwrap();
+    let edited = MessageRevisionService::edit_message(&db, orphan.mid, 1, "final".to_string()).await.unwrap();
+    assert_eq!((edited.content.as_str(), edited.edit_state), ("final", 1));
+}
+
+// ============================================================
+// 共享文件夹集成测试
+// ============================================================
+
+#[tokio::test]
+async fn test_share_access_matches_peer_by_ip() {
+    use feiqiu_communication::core::file::share::ShareService;
+    use feiqiu_communication::database::handler::ContactHandler;
+
+    let db = init_test_db().await;
+    let mut uids = Vec::new();
+    // 本地用户在前，对端使用非默认端口
+    for (ip, port) in [("127.0.0.1", 2425), ("192.168.1.30", 2426)] {
+        let created = UserHandler::create(
+            &db,
+            user::Model {
+                uid: 0,
+                feiq_ip: ip.to_string(),
+                feiq_port: port,
+                feiq_machine_id: format!("{}:{}", ip, port),
+                nickname: ip.to_string(),
+                avatar: None,
+                status: 1,
+                create_time: chrono::Utc::now().naive_utc(),
+                update_time: chrono::Utc::now().naive_utc(),
+            },
+        )
+        .await
+        .unwrap();
+        uids.push(created.uid);
+    }
+    ContactHandler::create(&db, uids[0], uids[1], None, None).await.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("feiqiu_share_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    ShareService::add_share(&db, "docs".to_string(), dir.to_string_lossy().to_string(), vec![uids[1]])
+        .await
+        .unwrap();
+
+    let shares = ShareService::list_shares_for_peer(&db, "192.168.1.30").await.unwrap();
+    assert_eq!(shares.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["docs"]);
+    assert!(ShareService::list_shares_for_peer(&db, "192.168.1.31").await.unwrap().is_empty());
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -224,10 +224,11 @@ export function useIPC() {
       }),
 
     /** 拒绝文件请求 */
-    rejectFileRequest: (packetNo: string, targetIp: string) =>
+    rejectFileRequest: (packetNo: string, targetIp: string, reason?: string) =>
       invokeCommand<void>('reject_file_request_handler', {
         packet_no: packetNo,
         target_ip: targetIp,
+        reason: reason ?? null,
       }),
 
     /** 取消文件传输 */
@@ -1,5 +1,14 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { ChatMessage, ChatSession } from '../types';
+import type {
+  ChatMessage,
+  ChatMessageRevision,
+  ChatSession,
+  GroupReadMembers,
+  GroupReadSummary,
+  MentionInfo,
+  PollInfo,
+  ReactionSummary,
+} from '../types';
 
 export const chatAPI = {
   /**
@@ -268,4 +277,129 @@ export const chatAPI = {
       ownerUid,
     });
   },
+
+  /** 发送引用回复，返回新消息 ID */
+  sendReply: async (
+    sessionType: number,
+    targetId: number,
+    content: string,
+    ownerUid: number,
+    replyToMid: number
+  ) => {
+    return await invoke<number>('send_reply_message_handler', {
+      sessionType,
+      targetId,
+      content,
+      ownerUid,
+      replyToMid,
+    });
+  },
+
+  /** 获取回复了指定消息的所有消息 */
+  getReplies: async (mid: number) => {
+    return await invoke<ChatMessage[]>('get_message_replies_handler', { mid });
+  },
+
+  /** 发送带 @ 提及的群消息（@所有人 需要群主或管理员权限），返回新消息 ID */
+  sendMention: async (
+    gid: number,
+    content: string,
+    ownerUid: number,
+    mentionUids: number[],
+    mentionAll: boolean
+  ) => {
+    return await invoke<number>('send_mention_message_handler', {
+      gid,
+      content,
+      ownerUid,
+      mentionUids,
+      mentionAll,
+    });
+  },
+
+  /** 获取群消息的已读统计 */
+  getGroupReadSummary: async (mid: number) => {
+    return await invoke<GroupReadSummary>('get_group_read_summary_handler', { mid });
+  },
+
+  /** 获取群消息已读和未读的成员 */
+  getGroupReadMembers: async (mid: number) => {
+    return await invoke<GroupReadMembers>('get_group_read_members_handler', { mid });
+  },
+
+  /** 在群聊中发起投票（deadline 为 Unix 时间戳，秒），返回新消息 ID */
+  sendPoll: async (
+    gid: number,
+    ownerUid: number,
+    question: string,
+    options: string[],
+    multiple: boolean,
+    anonymous: boolean,
+    deadline: number | null
+  ) => {
+    return await invoke<number>('send_poll_handler', {
+      gid,
+      ownerUid,
+      question,
+      options,
+      multiple,
+      anonymous,
+      deadline,
+    });
+  },
+
+  /** 投票（重新投票会覆盖之前的选择） */
+  votePoll: async (pollId: number, ownerUid: number, choices: number[]) => {
+    return await invoke<void>('vote_poll_handler', { pollId, ownerUid, choices });
+  },
+
+  /** 提前结束投票（只有创建者可以结束） */
+  closePoll: async (pollId: number, ownerUid: number) => {
+    return await invoke<void>('close_poll_handler', { pollId, ownerUid });
+  },
+
+  /** 获取投票 */
+  getPoll: async (pollId: number) => {
+    return await invoke<PollInfo>('get_poll_handler', { pollId });
+  },
+
+  /** 用表情回应消息（emoji 为 null 时取消回应），返回消息最新的回应汇总 */
+  reactMessage: async (mid: number, ownerUid: number, emoji: string | null) => {
+    return await invoke<ReactionSummary[]>('react_message_handler', { mid, ownerUid, emoji });
+  },
+
+  /** 通知会话对端本端的输入状态（后端限流，可在每次输入时调用） */
+  setTyping: async (sessionType: number, targetId: number, ownerUid: number, typing: boolean) => {
+    return await invoke<void>('set_typing_handler', { sessionType, targetId, ownerUid, typing });
+  },
+
+  /** 获取提及自己的群消息 */
+  getMentions: async (ownerUid: number) => {
+    return await invoke<MentionInfo[]>('get_mentions_handler', { ownerUid });
+  },
+
+  /** 撤回自己发送的消息（需在撤回时限内） */
+  recallMessage: async (mid: number, ownerUid: number) => {
+    return await invoke<ChatMessage>('recall_message_handler', { mid, ownerUid });
+  },
+
+  /** 编辑自己发送的文本消息 */
+  editMessage: async (mid: number, ownerUid: number, content: string) => {
+    return await invoke<ChatMessage>('edit_message_handler', { mid, ownerUid, content });
+  },
+
+  /** 获取消息的编辑/撤回历史 */
+  getMessageRevisions: async (mid: number) => {
+    return await invoke<ChatMessageRevision[]>('get_message_revisions_handler', { mid });
+  },
+
+  /** 获取消息撤回时限（秒，0 表示不限制） */
+  getRecallWindow: async () => {
+    return await invoke<number>('get_recall_window_handler');
+  },
+
+  /** 设置消息撤回时限（秒，0 表示不限制） */
+  setRecallWindow: async (secs: number) => {
+    return await invoke<void>('set_recall_window_handler', { secs });
+  },
 };
@@ -0,0 +1,14 @@
+// IPC 封装 - 后端推送的 UI 事件
+
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { UiEvent } from '../types';
+
+/** 后端转发 UI 事件使用的事件名 */
+export const UI_EVENT = 'ui-event';
+
+export const eventsAPI = {
+  /** 监听后端推送的 UI 事件（群邀请/入群申请、提及、输入状态等），返回取消监听函数 */
+  onUiEvent: async (handler: (event: UiEvent) => void): Promise<UnlistenFn> => {
+    return await listen<UiEvent>(UI_EVENT, (event) => handler(event.payload));
+  },
+};
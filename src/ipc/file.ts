@@ -1,6 +1,18 @@
 // IPC 封装 - 文件传输相关
 
 import { invoke } from '@tauri-apps/api/core';
+import type {
+  FileAcceptPolicy,
+  FileGcReport,
+  FileOffer,
+  FileOfferProgress,
+  PeerTransferTotals,
+  RateLimitSettings,
+  RemoteShareEntry,
+  SharedFolderInfo,
+  TransferHistoryEntry,
+  TransferQueue,
+} from '../types';
 
 export const fileAPI = {
   /** 发送文件请求 */
@@ -12,6 +24,39 @@ export const fileAPI = {
     });
   },
 
+  /** 一次向多个用户发送文件 */
+  sendFileToUsers: async (filePaths: string[], targetIps: string[], ownerUid: number) => {
+    return await invoke<number>('send_file_to_users_handler', {
+      filePaths,
+      targetIps,
+      ownerUid,
+    });
+  },
+
+  /** 向群组发送文件 */
+  sendGroupFile: async (filePaths: string[], gid: number, ownerUid: number) => {
+    return await invoke<number>('send_group_file_handler', {
+      filePaths,
+      gid,
+      ownerUid,
+    });
+  },
+
+  /** 获取文件发送按接收方汇总的进度 */
+  getFileOfferProgress: async (packetNo: string) => {
+    return await invoke<FileOfferProgress>('get_file_offer_progress_handler', { packetNo });
+  },
+
+  /** 获取对方图片附件的缩略图，返回本地缓存路径 */
+  getFileThumbnail: async (packetNo: string, fileId: number, targetIp: string) => {
+    return await invoke<string>('get_file_thumbnail_handler', { packetNo, fileId, targetIp });
+  },
+
+  /** 回收不再被引用的接收文件 */
+  collectFileGarbage: async () => {
+    return await invoke<FileGcReport>('collect_file_garbage_handler');
+  },
+
   /** 接受文件请求 */
   acceptFileRequest: async (packetNo: string, fileId: number, offset: number, targetIp: string) => {
     return await invoke<void>('accept_file_request_handler', {
@@ -22,14 +67,79 @@ export const fileAPI = {
     });
   },
 
+  /** 接受目录请求（TCP 通道），destDir 为空时保存到下载目录 */
+  acceptDirectoryRequest: async (packetNo: string, fileId: number, targetIp: string, destDir?: string) => {
+    return await invoke<void>('accept_directory_request_handler', {
+      packetNo,
+      fileId,
+      targetIp,
+      destDir: destDir ?? null,
+    });
+  },
+
   /** 拒绝文件请求 */
-  rejectFileRequest: async (packetNo: string, targetIp: string) => {
+  rejectFileRequest: async (packetNo: string, targetIp: string, reason?: string) => {
     return await invoke<void>('reject_file_request_handler', {
       packetNo,
       targetIp,
+      reason: reason ?? null,
+    });
+  },
+
+  /** 获取传输历史（peerUid 为空时返回全部） */
+  getTransferHistory: async (peerUid?: number, limit?: number) => {
+    return await invoke<TransferHistoryEntry[]>('get_transfer_history_handler', {
+      peerUid: peerUid ?? null,
+      limit: limit ?? null,
+    });
+  },
+
+  /** 获取按联系人汇总的传输统计 */
+  getTransferTotals: async () => {
+    return await invoke<PeerTransferTotals[]>('get_transfer_totals_handler');
+  },
+
+  /** 获取文件接收策略 */
+  getFilePolicies: async () => {
+    return await invoke<FileAcceptPolicy[]>('get_file_policies_handler');
+  },
+
+  /** 添加文件接收策略（action: 0=询问, 1=自动接收, 2=自动拒绝） */
+  addFilePolicy: async (
+    name: string,
+    priority: number,
+    action: number,
+    contactTag?: string,
+    extensions?: string,
+    minSize?: number,
+    maxSize?: number
+  ) => {
+    return await invoke<FileAcceptPolicy>('add_file_policy_handler', {
+      name,
+      priority,
+      action,
+      contactTag: contactTag ?? null,
+      extensions: extensions ?? null,
+      minSize: minSize ?? null,
+      maxSize: maxSize ?? null,
     });
   },
 
+  /** 启用或停用文件接收策略 */
+  setFilePolicyEnabled: async (id: number, enabled: boolean) => {
+    return await invoke<void>('set_file_policy_enabled_handler', { id, enabled });
+  },
+
+  /** 删除文件接收策略 */
+  deleteFilePolicy: async (id: number) => {
+    return await invoke<void>('delete_file_policy_handler', { id });
+  },
+
+  /** 获取被隔离的文件 */
+  getQuarantinedFiles: async () => {
+    return await invoke<FileOffer[]>('get_quarantined_files_handler');
+  },
+
   /** 获取文件 */
   getFile: async (fid: number) => {
     return await invoke<any>('get_file_handler', { fid });
@@ -49,4 +159,89 @@ export const fileAPI = {
   resumeTransfer: async (tid: number) => {
     return await invoke<void>('resume_transfer_handler', { tid });
   },
+
+  /** 设置全局限速（字节/秒，0 表示不限速） */
+  setGlobalRateLimit: async (bytesPerSec: number) => {
+    return await invoke<void>('set_global_rate_limit_handler', { bytesPerSec });
+  },
+
+  /** 设置对端限速（字节/秒，0 表示取消） */
+  setPeerRateLimit: async (peerIp: string, bytesPerSec: number) => {
+    return await invoke<void>('set_peer_rate_limit_handler', { peerIp, bytesPerSec });
+  },
+
+  /** 设置单个传输限速（字节/秒，0 表示取消） */
+  setTransferRateLimit: async (tid: number, bytesPerSec: number) => {
+    return await invoke<void>('set_transfer_rate_limit_handler', { tid, bytesPerSec });
+  },
+
+  /** 获取当前限速设置 */
+  getRateLimits: async () => {
+    return await invoke<RateLimitSettings>('get_rate_limits_handler');
+  },
+
+  /** 将传输加入调度队列（优先级数值大者优先） */
+  enqueueTransfer: async (tid: number, priority: number = 0) => {
+    return await invoke<void>('enqueue_transfer_handler', { tid, priority });
+  },
+
+  /** 暂停传输 */
+  pauseTransfer: async (tid: number) => {
+    return await invoke<void>('pause_transfer_handler', { tid });
+  },
+
+  /** 重试失败的传输 */
+  retryTransfer: async (tid: number) => {
+    return await invoke<void>('retry_transfer_handler', { tid });
+  },
+
+  /** 调整传输优先级 */
+  setTransferPriority: async (tid: number, priority: number) => {
+    return await invoke<void>('set_transfer_priority_handler', { tid, priority });
+  },
+
+  /** 设置最大并发传输数 */
+  setMaxConcurrentTransfers: async (maxConcurrent: number) => {
+    return await invoke<void>('set_max_concurrent_transfers_handler', { maxConcurrent });
+  },
+
+  /** 获取传输队列状态 */
+  getTransferQueue: async () => {
+    return await invoke<TransferQueue>('get_transfer_queue_handler');
+  },
+
+  /** 发布共享文件夹（contactUids 为空表示对所有联系人开放） */
+  addSharedFolder: async (name: string, path: string, contactUids: number[] = []) => {
+    return await invoke<SharedFolderInfo>('add_shared_folder_handler', { name, path, contactUids });
+  },
+
+  /** 取消共享文件夹 */
+  removeSharedFolder: async (shareId: number) => {
+    return await invoke<void>('remove_shared_folder_handler', { shareId });
+  },
+
+  /** 更新共享文件夹的访问列表 */
+  setSharedFolderAccess: async (shareId: number, contactUids: number[]) => {
+    return await invoke<void>('set_shared_folder_access_handler', { shareId, contactUids });
+  },
+
+  /** 获取本端发布的共享文件夹 */
+  getSharedFolders: async () => {
+    return await invoke<SharedFolderInfo[]>('get_shared_folders_handler');
+  },
+
+  /** 浏览对端的共享文件夹 */
+  browseRemoteShares: async (targetIp: string) => {
+    return await invoke<RemoteShareEntry[]>('browse_remote_shares_handler', { targetIp });
+  },
+
+  /** 浏览对端共享中的目录（sharePath: "共享名/相对路径"） */
+  browseRemoteShareDir: async (targetIp: string, sharePath: string) => {
+    return await invoke<RemoteShareEntry[]>('browse_remote_share_dir_handler', { targetIp, sharePath });
+  },
+
+  /** 把对端共享中的文件加入下载队列，返回传输 ID */
+  downloadSharedFile: async (targetIp: string, sharePath: string, fileSize: number, priority: number = 0) => {
+    return await invoke<number>('download_shared_file_handler', { targetIp, sharePath, fileSize, priority });
+  },
 };
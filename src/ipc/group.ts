@@ -2,7 +2,13 @@
 // Phase 7: 群聊功能
 
 import { invoke } from '@tauri-apps/api/core';
-import type { GroupInfo, GroupMember } from '../types';
+import type {
+  GroupAnnouncementAckInfo,
+  GroupAnnouncementInfo,
+  GroupInfo,
+  GroupMember,
+  GroupRequestInfo,
+} from '../types';
 
 export const groupAPI = {
   /** 创建群组 */
@@ -24,7 +30,7 @@ export const groupAPI = {
     return await invoke<GroupMember[]>('get_group_members_handler', { gid });
   },
 
-  /** 添加群成员 */
+  /** 添加群成员（发送群邀请） */
   addGroupMember: async (gid: number, memberUid: number, role: number) => {
     return await invoke<void>('add_group_member_handler', {
       gid,
@@ -64,8 +70,76 @@ export const groupAPI = {
     });
   },
 
+  /** 获取群公告（置顶在前） */
+  getGroupAnnouncements: async (gid: number) => {
+    return await invoke<GroupAnnouncementInfo[]>('get_group_announcements_handler', { gid });
+  },
+
+  /** 发布群公告 */
+  createGroupAnnouncement: async (gid: number, content: string, pinned: boolean) => {
+    return await invoke<GroupAnnouncementInfo>('create_group_announcement_handler', {
+      gid,
+      content,
+      pinned,
+    });
+  },
+
+  /** 编辑群公告（成员需要重新确认） */
+  updateGroupAnnouncement: async (announcementId: number, content: string) => {
+    return await invoke<void>('update_group_announcement_handler', { announcementId, content });
+  },
+
+  /** 置顶或取消置顶群公告 */
+  pinGroupAnnouncement: async (announcementId: number, pinned: boolean) => {
+    return await invoke<void>('pin_group_announcement_handler', { announcementId, pinned });
+  },
+
+  /** 确认已读群公告 */
+  ackGroupAnnouncement: async (announcementId: number) => {
+    return await invoke<void>('ack_group_announcement_handler', { announcementId });
+  },
+
+  /** 获取群公告的成员确认情况 */
+  getGroupAnnouncementAcks: async (announcementId: number) => {
+    return await invoke<GroupAnnouncementAckInfo[]>('get_group_announcement_acks_handler', { announcementId });
+  },
+
   /** 删除群组 */
   deleteGroup: async (gid: number) => {
     return await invoke<void>('delete_group_handler', { gid });
   },
+
+  /** 获取待处理的群邀请和入群申请 */
+  getGroupRequests: async () => {
+    return await invoke<GroupRequestInfo[]>('get_group_requests_handler');
+  },
+
+  /** 接受或拒绝群邀请 */
+  respondGroupInvite: async (requestId: number, accept: boolean) => {
+    return await invoke<void>('respond_group_invite_handler', { requestId, accept });
+  },
+
+  /** 申请加入公开的群组 */
+  requestJoinGroup: async (groupNo: string, groupName: string, adminUid: number) => {
+    return await invoke<number>('request_join_group_handler', {
+      groupNo,
+      groupName,
+      adminUid,
+    });
+  },
+
+  /** 审核入群申请 */
+  reviewJoinRequest: async (requestId: number, approve: boolean) => {
+    return await invoke<void>('review_join_request_handler', { requestId, approve });
+  },
+
+  /** 设置群组是否允许申请加入 */
+  setGroupDiscoverable: async (gid: number, discoverable: boolean) => {
+    return await invoke<void>('set_group_discoverable_handler', { gid, discoverable });
+  },
+
+  /** 查询局域网内允许申请加入的群组 */
+  discoverGroups: async () => {
+    return await invoke<number>('discover_groups_handler');
+  },
 };
@@ -1,5 +1,6 @@
 export { chatAPI } from './chat';
 export { contactAPI } from './contact';
+export { eventsAPI } from './events';
 export { fileAPI } from './file';
 
 // 群组相关 IPC 接口
@@ -22,6 +22,8 @@ export enum MessageType {
   Voice = 4,
   /** 视频消息 */
   Video = 5,
+  /** 投票消息 */
+  Poll = 6,
 }
 
 /** 消息状态 */
@@ -60,12 +62,80 @@ export interface ChatMessage {
   status: MessageStatus;
   /** 文件信息（当 msg_type = File 时） */
   file_info?: FileAttachment;
+  /** 是否被编辑过 */
+  edited?: boolean;
   /** 是否撤回 */
   is_revoked?: boolean;
+  /** 引用回复所引用的消息 */
+  reply_to?: QuotedMessage | null;
+  /** 投票（当 msg_type = Poll 时） */
+  poll?: PollInfo | null;
+  /** 按表情汇总的回应 */
+  reactions?: ReactionSummary[];
   /** 扩展数据 */
   extra?: Record<string, unknown>;
 }
 
+/** 引用回复所引用的消息 */
+export interface QuotedMessage {
+  /** 原消息 ID（本地找不到原消息时为 null） */
+  mid: number | null;
+  sender_uid: number | null;
+  /** 原消息内容，找不到原消息时为引用摘要 */
+  content: string;
+  /** 原消息已被撤回 */
+  is_revoked: boolean;
+}
+
+/** 消息的一种表情回应 */
+export interface ReactionSummary {
+  emoji: string;
+  count: number;
+  /** 回应者 UID（按回应时间正序） */
+  reactors: number[];
+  reacted_by_me: boolean;
+}
+
+/** 群投票 */
+export interface PollInfo {
+  poll_id: number;
+  mid: number;
+  gid: number;
+  creator_uid: number;
+  question: string;
+  options: PollOptionInfo[];
+  multiple: boolean;
+  anonymous: boolean;
+  /** 截止时间，没有截止时间为 null */
+  deadline: string | null;
+  /** 已结束（创建者结束或已过截止时间） */
+  closed: boolean;
+  voter_count: number;
+  /** 自己所选的选项序号 */
+  my_choices: number[];
+}
+
+/** 投票选项 */
+export interface PollOptionInfo {
+  text: string;
+  count: number;
+  /** 投票人 UID（匿名投票为空） */
+  voters: number[];
+}
+
+/** 消息的一次编辑/撤回记录 */
+export interface ChatMessageRevision {
+  id: number;
+  mid: number;
+  /** 1=编辑, 2=撤回 */
+  action: number;
+  old_content: string;
+  /** 撤回时为 null */
+  new_content: string | null;
+  actor_uid: number;
+  create_time: string;
+}
+
 /** 文件附件信息 */
 export interface FileAttachment {
   /** 文件 ID */
@@ -98,6 +168,8 @@ export interface ChatSession {
   last_msg_id: number | null;
   /** 未读数量 */
   unread_count: number;
+  /** 提及自己的未读数量 */
+  mention_count: number;
   /** 更新时间 */
   update_time: string;
   /** 会话名称（缓存的，用于显示） */
@@ -110,6 +182,68 @@ export interface ChatSession {
   last_message_time?: string;
 }
 
+/**
+ * 群消息已读统计（"已读 5/12"）
+ */
+export interface GroupReadSummary {
+  /** 消息 ID */
+  mid: number;
+  /** 接收成员数 */
+  total: number;
+  /** 已送达数（包括已读） */
+  delivered: number;
+  /** 已读数 */
+  read: number;
+}
+
+/**
+ * 群消息接收成员的回执
+ */
+export interface GroupReceiptMember {
+  /** 成员 UID */
+  member_uid: number;
+  /** 成员昵称 */
+  nickname: string;
+  /** 0-未送达, 1-已送达, 2-已读 */
+  status: number;
+  /** 已读时间 */
+  read_time: string | null;
+}
+
+/**
+ * 群消息已读和未读的成员
+ */
+export interface GroupReadMembers {
+  /** 消息 ID */
+  mid: number;
+  /** 已读成员 */
+  read: GroupReceiptMember[];
+  /** 未读成员 */
+  unread: GroupReceiptMember[];
+}
+
+/**
+ * 提及自己的群消息
+ */
+export interface MentionInfo {
+  /** 消息 ID */
+  mid: number;
+  /** 群组 ID */
+  gid: number;
+  /** 群名称 */
+  group_name: string;
+  /** 发送者 UID */
+  sender_uid: number;
+  /** 发送者昵称 */
+  sender_nickname: string;
+  /** 消息内容 */
+  content: string;
+  /** 是否通过 @所有人 提及 */
+  mention_all: boolean;
+  /** 发送时间 */
+  send_time: string;
+}
+
 /** 发送消息参数 */
 export interface SendMessageParams {
   /** 会话类型 */
@@ -11,6 +11,12 @@ export interface FileInfo {
   file_path: string;
   file_size: number;
   mime_type: string;
+  /** 图片宽度（非图片为空） */
+  image_width?: number;
+  /** 图片高度（非图片为空） */
+  image_height?: number;
+  /** 本地缓存的缩略图路径 */
+  thumbnail_path?: string;
 }
 
 /** 文件传输状态 */
@@ -21,6 +27,8 @@ export enum TransferStatus {
   Transferring = 1,
   /** 已完成 */
   Completed = 2,
+  /** 已暂停 */
+  Paused = 3,
   /** 已取消 */
   Cancelled = -2,
   /** 失败 */
@@ -35,6 +43,42 @@ export interface TransferProgress {
   speed: number;
   transferred: number;
   status?: TransferStatus;
+  /** 压缩率（压缩后/压缩前，1 表示未压缩；仅 UDP 推送的数据块会压缩） */
+  compression_ratio?: number;
+  /** 预计剩余时间（秒），速率未知时为 null */
+  eta_secs?: number | null;
+}
+
+/** 已完成传输的历史记录 */
+export interface TransferHistoryEntry {
+  id: number;
+  tid: number;
+  file_id: number;
+  file_name: string;
+  file_size: number;
+  direction: number; // 0=下载, 1=上传
+  peer_uid: number;
+  peer_ip: string;
+  bytes: number;
+  duration_ms: number;
+  avg_speed: number; // 字节/秒
+  retries: number;
+  started_at: string;
+  finished_at: string;
+}
+
+/** 与某个联系人之间的传输汇总 */
+export interface PeerTransferTotals {
+  peer_uid: number;
+  peer_ip: string;
+  nickname: string | null;
+  sent_count: number;
+  sent_bytes: number;
+  received_count: number;
+  received_bytes: number;
+  total_duration_ms: number;
+  avg_speed: number;
+  last_transfer_time: string;
 }
 
 /** 待恢复的传输信息 */
@@ -50,6 +94,113 @@ export interface PendingTransfer {
   direction: number; // 0=下载, 1=上传
 }
 
+/** 传输限速设置（字节/秒，0 表示不限速） */
+export interface RateLimitSettings {
+  global: number;
+  per_peer: { peer_ip: string; bytes_per_sec: number }[];
+  per_transfer: { packet_no: string; file_id: number; bytes_per_sec: number }[];
+}
+
+/** 传输在调度队列中的状态 */
+export type QueueItemState = 'Queued' | 'Running' | 'Paused' | 'Failed';
+
+/** 调度队列中的一项传输 */
+export interface QueuedTransfer {
+  tid: number;
+  file_id: number;
+  file_name: string;
+  direction: number; // 0=下载, 1=上传
+  target_ip: string;
+  state: QueueItemState;
+  priority: number;
+  position?: number; // 排队位置，仅排队中的传输有
+  retries: number;
+  transferred: number;
+  file_size: number;
+  error_message?: string;
+}
+
+/** 传输调度队列状态 */
+export interface TransferQueue {
+  max_concurrent: number;
+  running: number;
+  items: QueuedTransfer[];
+}
+
+/** 本端发布的共享文件夹 */
+export interface SharedFolderInfo {
+  share_id: number;
+  name: string;
+  path: string;
+  /** 允许访问的联系人，为空表示所有联系人 */
+  contact_uids: number[];
+}
+
+/** 对端共享中的条目 */
+export interface RemoteShareEntry {
+  name: string;
+  /** 共享路径 "共享名/相对路径"，用于继续浏览或下载 */
+  path: string;
+  size: number;
+  is_dir: boolean;
+}
+
+/** 单个接收方的文件发送进度 */
+export interface RecipientProgress {
+  target_ip: string;
+  target_uid?: number;
+  status: TransferStatus;
+  file_count: number;
+  completed_files: number;
+  transferred: number;
+  total: number;
+}
+
+/** 一次文件发送（单聊/多人/群组）的汇总进度 */
+export interface FileOfferProgress {
+  packet_no: string;
+  session_type: number; // 0=单聊, 1=群聊
+  target_id: number;
+  recipients: RecipientProgress[];
+  completed_recipients: number;
+  transferred: number;
+  total: number;
+}
+
+/** 文件垃圾回收结果 */
+export interface FileGcReport {
+  removed_files: number;
+  freed_bytes: number;
+}
+
+/** 文件接收策略 */
+export interface FileAcceptPolicy {
+  id: number;
+  name: string;
+  priority: number;
+  action: number; // 0=询问, 1=自动接收, 2=自动拒绝
+  contact_tag: string | null;
+  extensions: string | null;
+  min_size: number | null;
+  max_size: number | null;
+  enabled: number;
+  create_time: string;
+}
+
+/** 收到的文件请求记录 */
+export interface FileOffer {
+  id: number;
+  packet_no: string;
+  file_id: number;
+  from_ip: string;
+  file_name: string;
+  file_size: number;
+  decision: number; // 0=待定, 1=接收, 2=拒绝
+  reason: string | null;
+  quarantine_reason: string | null;
+  create_time: string;
+}
+
 /** 群组信息 */
 export interface GroupInfo {
   gid: number;
@@ -57,6 +208,8 @@ export interface GroupInfo {
   avatar?: string;
   creator_uid: number;
   desc?: string;
+  /** 是否允许申请加入 */
+  discoverable: boolean;
   create_time: string;
 }
 
@@ -77,5 +230,83 @@ export interface GroupMember {
   join_time: string;
 }
 
+/** 群邀请/入群申请 */
+export interface GroupRequestInfo {
+  id: number;
+  /** 0-群邀请, 1-入群申请 */
+  kind: number;
+  /** 0-收到, 1-发出 */
+  direction: number;
+  gid?: number;
+  group_no: string;
+  group_name: string;
+  from_uid: number;
+  from_nickname: string;
+  to_uid: number;
+  /** 0-待处理, 1-已接受, 2-已拒绝, 3-已过期 */
+  status: number;
+  expire_time: string;
+  create_time: string;
+}
+
+/** 群公告 */
+export interface GroupAnnouncementInfo {
+  id: number;
+  gid: number;
+  content: string;
+  author_uid: number;
+  author_nickname: string;
+  editor_uid: number;
+  pinned: boolean;
+  /** 已确认的当前成员数 */
+  ack_count: number;
+  member_count: number;
+  /** 当前用户是否已确认 */
+  acknowledged: boolean;
+  create_time: string;
+  update_time: string;
+}
+
+/** 群公告成员确认情况 */
+export interface GroupAnnouncementAckInfo {
+  member_uid: number;
+  nickname: string;
+  acknowledged: boolean;
+  ack_time?: string;
+}
+
 /** 联系人（从 contact 导出的别名） */
 export type Contact = ContactInfo;
+
+/**
+ * 后端转发给前端的 UI 事件（监听 "ui-event"）
+ *
+ * 与后端 `UiEvent` 的 serde 默认格式一致：`{ 变体名: 字段 }`
+ */
+export type UiEvent =
+  | { ShowNotification: { title: string; body: string } }
+  | { UpdateUserList: { users: string } }
+  | { AddUser: { user: string } }
+  | { RemoveUser: { ip: string } }
+  | { OpenChatWindow: { user_id: number } }
+  | { CloseChatWindow: { user_id: number } }
+  | { UpdateChatTitle: { user_id: number; title: string } }
+  | { DisplayMessage: { session_type: number; target_id: number; message: string } }
+  | { UpdateMessageStatus: { msg_id: number; status: number } }
+  | { MessageRecalled: { msg_id: number } }
+  | { MessageEdited: { msg_id: number; content: string } }
+  | { UpdateUnreadCount: { session_type: number; target_id: number; count: number } }
+  | { Mentioned: { gid: number; msg_id: number; mention_all: boolean } }
+  | { GroupReceiptUpdated: { msg_id: number; member_uid: number; status: number } }
+  | { FileTransferProgress: { file_id: number; progress: number; total: number } }
+  | { FileTransferComplete: { file_id: number } }
+  | { FileTransferFailed: { file_id: number; error: string } }
+  | { FileOfferReceived: { from_ip: string; packet_no: string; files: string; auto_accepted: boolean } }
+  | { FileQuarantined: { file_id: number; path: string; reason: string } }
+  | { GroupRequestReceived: { request: string } }
+  | { GroupRequestUpdated: { request_id: number; status: number } }
+  | { GroupDiscovered: { group_no: string; group_name: string; admin_uid: number } }
+  | { GroupAnnouncementUpdated: { gid: number; announcement_id: number } }
+  | { PollUpdated: { gid: number; msg_id: number; poll_id: number } }
+  | { ReactionUpdated: { msg_id: number } }
+  | { TypingChanged: { session_type: number; target_id: number; uid: number; typing: boolean } };